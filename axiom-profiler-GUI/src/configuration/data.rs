@@ -2,6 +2,7 @@ use fxhash::FxHashMap;
 use smt_log_parser::{
     display_with::{DisplayConfiguration, SymbolReplacement},
     formatter::TermDisplayContext,
+    items::Radix,
 };
 
 use crate::state::FileInfo;
@@ -57,6 +58,8 @@ impl Configuration {
             // Set manually elsewhere
             enode_char_limit: None,
             ast_depth_limit: None,
+            numeral_radix: Radix::AsLogged,
+            numeral_width: 0,
         }
     }
 }
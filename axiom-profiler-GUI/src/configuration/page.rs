@@ -1,18 +1,21 @@
 use std::rc::Rc;
 
-use smt_log_parser::display_with::SymbolReplacement;
+use smt_log_parser::{display_with::SymbolReplacement, items::Radix};
 use wasm_bindgen::JsCast;
 use yew::{function_component, use_context, use_effect_with_deps, Callback, Event, Html};
 
 use crate::configuration::{Configuration, ConfigurationProvider, TermDisplayFlag};
 
 macro_rules! flag_widget {
-    ($cfg:ident, $default:ident, $($access:ident).+, $title:expr, $description:expr, $($from:ident => $to:literal),+$(,)?) => {
+    ($cfg:ident, $default:ident, $($access:ident).+, $title:expr, $description:expr, $($from:tt => $to:literal),+$(,)?) => {
         {
             let id = stringify!(cfg.$($access).+);
             let curr = &(($cfg).config.$($access).+);
             let curr_to = match curr {
                 $($from => $to,)+
+                // Only reachable for fields which are not enums.
+                #[allow(unreachable_patterns)]
+                _ => "",
             };
             let effect = move |curr_to| {
                 let element = gloo::utils::document().get_element_by_id(id);
@@ -26,6 +29,8 @@ macro_rules! flag_widget {
             let default = $default.$($access).+;
             let default_to = match &default {
                 $($from => $to,)+
+                #[allow(unreachable_patterns)]
+                _ => "",
             };
             let cfg_update = $cfg.update.clone();
             let onchange = Callback::from(move |e: Event| {
@@ -88,6 +93,32 @@ pub fn Flags(_props: &()) -> Html {
         None => "Disabled",
     );
     use_effect_with_deps(move |deps| effect(deps), deps);
+    use Radix::*;
+    let (numeral_radix, effect, deps) = flag_widget!(
+        cfg,
+        default,
+        display.numeral_radix,
+        "Numerals",
+        "The radix in which integer, rational and bit-vector literals are displayed.",
+        AsLogged => "As logged",
+        Decimal => "Decimal",
+        Hexadecimal => "Hexadecimal",
+        Binary => "Binary",
+    );
+    use_effect_with_deps(move |deps| effect(deps), deps);
+    let (numeral_width, effect, deps) = flag_widget!(
+        cfg,
+        default,
+        display.numeral_width,
+        "Numeral width",
+        "The minimum number of digits in which integer, rational and bit-vector literals are displayed, padded with zeros.",
+        0 => "Unpadded",
+        4 => "4 digits",
+        8 => "8 digits",
+        16 => "16 digits",
+        32 => "32 digits",
+    );
+    use_effect_with_deps(move |deps| effect(deps), deps);
 
     yew::html! {
         <div class="flags-page"><div class="flags-content">
@@ -95,6 +126,8 @@ pub fn Flags(_props: &()) -> Html {
             <button onclick={reset}>{"Reset configuration"}</button>
             {display_term_ids}
             {replace_symbols}
+            {numeral_radix}
+            {numeral_width}
             <TermDisplayFlag cfg={cfg.clone()} />
         </div></div>
     }
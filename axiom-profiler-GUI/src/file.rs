@@ -1,9 +1,11 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, io::Read, rc::Rc};
 
-use gloo::file::File;
+use gloo::file::{Blob, File};
 use smt_log_parser::{
-    parsers::{AsyncBufferRead, ParseState},
-    LogParser, Z3Parser,
+    parsers::{
+        any::AnyParser, smt2::Smt2Problem, AsyncBufferRead, Compression, Decompressed, ParseState,
+    },
+    snapshot, LogParser, Z3Parser,
 };
 use wasm_bindgen::JsCast;
 use wasm_streams::ReadableStream;
@@ -13,7 +15,7 @@ use yew::{html::Scope, Callback, DragEvent};
 use crate::{
     global_callbacks::GlobalCallbacks,
     infobars::OmnibarMessage,
-    state::{FileInfo, StateContext},
+    state::{FileInfo, SourceFile, StateContext},
     CallbackRef, FileDataComponent, LoadingState, Msg, ParseProgress, PREVENT_DEFAULT_DRAG_OVER,
 };
 
@@ -107,16 +109,39 @@ impl FileDataComponent {
             *cancel.borrow_mut() = true;
         });
         let cancel = self.cancel.clone();
+        let state = link.get_state().unwrap();
+        state.set_raw_log(None);
+        state.set_source(None);
+        if file_name.ends_with(SNAPSHOT_EXTENSION) {
+            self.load_snapshot(file, link);
+            return changed;
+        }
         // Turn into stream
         let blob: &web_sys::Blob = file.as_ref();
         let stream = ReadableStream::from_raw(blob.stream().unchecked_into());
         match stream.try_into_async_read() {
             Ok(stream) => {
                 let link = link.clone();
+                let raw_log = Blob::clone(&file);
                 link.send_message(Msg::LoadingState(LoadingState::StartParsing));
-                let mut parser = Z3Parser::from_async(stream.buffer());
                 wasm_bindgen_futures::spawn_local(async move {
-                    log::info!("Parsing \"{file_name}\"");
+                    let reader = match stream.buffer_decompressed().await {
+                        Ok(reader) => reader,
+                        Err(err) => {
+                            link.send_message(Msg::FailedOpening(err.to_string()));
+                            return;
+                        }
+                    };
+                    log::info!("Parsing \"{file_name}\" ({:?})", reader.compression());
+                    // The raw log lines can only be shown (and so their
+                    // positions are only worth recording) if the file can be
+                    // sliced directly.
+                    let uncompressed = reader.compression() == Compression::None;
+                    if uncompressed {
+                        state.set_raw_log(Some(raw_log));
+                    }
+                    let mut parser = reader.into_async_parser::<AnyParser>();
+                    parser.set_provenance(uncompressed);
                     let finished = loop {
                         let mut lines_to_read = 100_000;
                         let finished = parser
@@ -159,7 +184,8 @@ impl FileDataComponent {
                         cancel,
                     )));
                     link.send_message(Msg::LoadedFile(
-                        Box::new(parser.take_parser()),
+                        Box::new(parser.take_parser().into()),
+                        None,
                         finished,
                         cancel,
                     ))
@@ -171,7 +197,10 @@ impl FileDataComponent {
                 let reader = gloo::file::callbacks::read_as_bytes(&file, move |res| {
                     log::info!("Loading to string \"{file_name}\"");
                     let res = res.and_then(|res| {
-                        String::from_utf8(res)
+                        let mut text = String::new();
+                        Decompressed::new(&res[..])
+                            .and_then(|mut reader| reader.read_to_string(&mut text))
+                            .map(|_| text)
                             .map_err(|err| gloo::file::FileReadError::NotReadable(err.to_string()))
                     });
                     let text_data = match res {
@@ -182,9 +211,11 @@ impl FileDataComponent {
                         }
                     };
                     log::info!("Parsing \"{file_name}\"");
+                    // Progress is reported relative to the decompressed text.
+                    let file_size = text_data.len() as u64;
                     link.send_message(Msg::LoadingState(LoadingState::StartParsing));
                     wasm_bindgen_futures::spawn_local(async move {
-                        let mut parser = Z3Parser::from_str(&text_data);
+                        let mut parser = AnyParser::from_str(&text_data);
                         let finished = loop {
                             let mut lines_to_read = 100_000;
                             let finished = parser.process_until(|_, state| {
@@ -223,7 +254,8 @@ impl FileDataComponent {
                             cancel,
                         )));
                         link.send_message(Msg::LoadedFile(
-                            Box::new(parser.take_parser()),
+                            Box::new(parser.take_parser().into()),
+                            None,
                             finished,
                             cancel,
                         ))
@@ -234,4 +266,120 @@ impl FileDataComponent {
         };
         changed
     }
+
+    /// Opens the SMT-LIB input of the currently loaded log, relating its
+    /// quantifiers to their source. Returns whether the view changed.
+    pub fn load_source_file(&mut self, file: File, link: &Scope<FileDataComponent>) -> bool {
+        let state = link.get_state().unwrap();
+        let Some(parser) = state.state.parser.clone() else {
+            let message = OmnibarMessage {
+                message: "Open a log before the SMT-LIB file it was created from".to_string(),
+                is_error: true,
+            };
+            self.set_message(link, message, 8000);
+            return true;
+        };
+        let name = file.name();
+        log::info!("Loading source \"{name}\"");
+        let link = link.clone();
+        let reader = gloo::file::callbacks::read_as_text(&file, move |res| {
+            let problem = res
+                .map_err(|err| err.to_string())
+                .and_then(|text| Smt2Problem::parse(&text).map_err(|err| format!("{err:?}")));
+            let problem = match problem {
+                Ok(problem) => problem,
+                Err(err) => {
+                    let message = OmnibarMessage {
+                        message: format!("Failed to parse \"{name}\": {err}"),
+                        is_error: true,
+                    };
+                    link.send_message(Msg::ShowMessage(message, 8000));
+                    return;
+                }
+            };
+            let map = problem.map_quantifiers(&parser.parser.borrow());
+            let found = map.quants.iter().filter(|q| q.is_some()).count();
+            let message = OmnibarMessage {
+                message: format!(
+                    "Found {found} of {} quantifiers in \"{name}\"",
+                    map.quants.len()
+                ),
+                is_error: false,
+            };
+            link.send_message(Msg::ShowMessage(message, 4000));
+            link.get_state().unwrap().set_source(Some(SourceFile {
+                name,
+                problem: Rc::new(problem),
+                map: Rc::new(map),
+            }));
+        });
+        self.source_reader = Some(reader);
+        false
+    }
+
+    /// Snapshots are small compared to the log they were created from, so
+    /// they are read in one go rather than streamed. An instantiation graph
+    /// stored in the snapshot is used instead of constructing a new one.
+    fn load_snapshot(&mut self, file: File, link: &Scope<FileDataComponent>) {
+        let link = link.clone();
+        link.send_message(Msg::LoadingState(LoadingState::ReadingToString));
+        let reader = gloo::file::callbacks::read_as_bytes(&file, move |res| {
+            let parser = res.map_err(|err| err.to_string()).and_then(|bytes| {
+                if !snapshot::is_snapshot(&bytes) {
+                    return Err("Not a snapshot file".to_string());
+                }
+                Z3Parser::load_snapshot_with_graph(&bytes[..]).map_err(|err| err.to_string())
+            });
+            match parser {
+                Ok((parser, graph)) => {
+                    link.send_message(Msg::LoadingState(LoadingState::DoneParsing(false, false)));
+                    link.send_message(Msg::LoadedFile(
+                        Box::new(parser),
+                        graph.map(Box::new),
+                        ParseState::Completed {
+                            end_of_stream: true,
+                        },
+                        false,
+                    ));
+                }
+                Err(err) => link.send_message(Msg::FailedOpening(err)),
+            }
+        });
+        self.reader = Some(reader);
+    }
+
+    /// Serializes the currently opened parser and offers it as a download.
+    pub fn save_snapshot(&self, link: &Scope<FileDataComponent>) -> Result<(), String> {
+        let state = link.get_state().unwrap();
+        let (Some(parser), Some(info)) = (&state.state.parser, &state.state.file_info) else {
+            return Err("No file opened".to_string());
+        };
+        let mut bytes = Vec::new();
+        parser
+            .parser
+            .borrow()
+            .save_snapshot(&mut bytes)
+            .map_err(|err| err.to_string())?;
+        let stem = info
+            .name
+            .rsplit_once('.')
+            .map_or(info.name.as_str(), |(stem, _)| stem);
+        let blob = gloo::file::Blob::new_with_options(&bytes[..], Some("application/octet-stream"));
+        let url = gloo::file::ObjectUrl::from(blob);
+        let document = gloo::utils::document();
+        let anchor = document
+            .create_element("a")
+            .map_err(|_| "Failed to create download link".to_string())?;
+        anchor.set_attribute("href", &url).ok();
+        anchor
+            .set_attribute("download", &format!("{stem}{SNAPSHOT_EXTENSION}"))
+            .ok();
+        if let Some(anchor) = anchor.dyn_ref::<web_sys::HtmlElement>() {
+            anchor.click();
+        }
+        Ok(())
+    }
 }
+
+pub const SNAPSHOT_EXTENSION: &str = ".slpsnap";
+pub const SMT2_EXTENSION: &str = ".smt2";
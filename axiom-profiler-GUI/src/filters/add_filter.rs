@@ -5,7 +5,10 @@ use petgraph::{
     visit::{Dfs, Walker},
     Direction,
 };
-use smt_log_parser::analysis::{raw::NodeKind, RawNodeIndex};
+use smt_log_parser::{
+    analysis::{raw::NodeKind, RawNodeIndex},
+    items::Value,
+};
 use yew::{function_component, html, use_context, Callback, Html, MouseEvent, Properties};
 
 use crate::{
@@ -44,6 +47,10 @@ pub fn AddFilterSidebar(props: &AddFilterSidebarProps) -> Html {
             vec![Filter::MaxBranching(DEFAULT_NODE_COUNT)],
             vec![Filter::MaxDepth(6)],
             vec![Filter::ShowNamedQuantifier("name".to_string())],
+            Value::parse_comparison("> 1000")
+                .map(|(ordering, value)| Filter::BoundValue(ordering, value))
+                .into_iter()
+                .collect(),
             mls,
             mls_all,
         ]
@@ -2,7 +2,7 @@ use std::rc::Rc;
 
 use gloo::timers::callback::Timeout;
 use material_yew::icon::MatIcon;
-use smt_log_parser::items::QuantIdx;
+use smt_log_parser::items::{QuantIdx, Value};
 use web_sys::{Element, HtmlElement, HtmlInputElement};
 use yew::{
     function_component, html, use_context, Callback, Children, Component, Context, Html, NodeRef,
@@ -408,6 +408,10 @@ impl Filter {
             Filter::MaxDepth(_) => Filter::MaxDepth(new_data[0]),
             Filter::ShowLongestPath(old) => Filter::ShowLongestPath(*old),
             Filter::ShowNamedQuantifier(_) => Filter::ShowNamedQuantifier(new_strings[0].clone()),
+            Filter::BoundValue(..) => match Value::parse_comparison(&new_strings[0]) {
+                Some((ordering, value)) => Filter::BoundValue(ordering, value),
+                None => self.clone(),
+            },
             Filter::SelectNthMatchingLoop(_) => {
                 Filter::SelectNthMatchingLoop(new_data[0].max(1) - 1)
             }
@@ -1,13 +1,14 @@
 mod add_filter;
 mod manage_filter;
 
-use std::fmt::Display;
+use std::{cmp::Ordering, fmt::Display};
 
 use material_yew::icon::MatIcon;
 use petgraph::Direction;
-use smt_log_parser::analysis::{raw::NodeKind, RawNodeIndex};
+use smt_log_parser::analysis::{raw::NodeKind, CostModel, RawNodeIndex};
 use smt_log_parser::parsers::ParseState;
-use yew::{html, Callback, Component, Context, Html, MouseEvent, NodeRef, Properties};
+use wasm_bindgen::JsCast;
+use yew::{html, Callback, Component, Context, Event, Html, MouseEvent, NodeRef, Properties};
 
 use crate::{
     filters::{
@@ -46,7 +47,9 @@ pub enum Msg {
     EndEdit(usize, Filter),
     AddFilter(bool, Filter),
     ToggleDisabler(usize),
+    SetCostModel(CostModel),
     ToggleMlViewerMode,
+    ToggleQuantifierView,
 }
 
 pub struct FiltersState {
@@ -54,6 +57,9 @@ pub struct FiltersState {
     delete_node: NodeRef,
     will_delete: bool,
     disabler_chain: Vec<(Disabler, bool)>,
+    cost_model: CostModel,
+    /// Whether the graph of dependencies between quantifiers is shown.
+    quantifier_view: bool,
     filter_chain: Vec<Filter>,
     applied_filter_chain: Vec<Filter>,
     prev_filter_chain: Vec<Filter>,
@@ -110,6 +116,8 @@ impl Component for FiltersState {
         let applied_filter_chain = filter_chain.clone();
         let mut self_ = Self {
             disabler_chain,
+            cost_model: CostModel::default(),
+            quantifier_view: false,
             filter_chain,
             prev_filter_chain,
             applied_filter_chain,
@@ -227,6 +235,27 @@ impl Component for FiltersState {
                 self.reset_disabled(&ctx.props().file);
                 false
             }
+            Msg::SetCostModel(cost_model) => {
+                if self.cost_model == cost_model {
+                    return false;
+                }
+                self.cost_model = cost_model;
+                let msg = SVGMsg::SetCostModel(cost_model);
+                let msgs = self.rerender_msgs();
+                let quantifier_view = self
+                    .quantifier_view
+                    .then_some(SVGMsg::RenderQuantifierGraph(true));
+                ctx.props()
+                    .file
+                    .send_updates(std::iter::once(msg).chain(msgs).chain(quantifier_view));
+                true
+            }
+            Msg::ToggleQuantifierView => {
+                self.quantifier_view = !self.quantifier_view;
+                let msg = SVGMsg::RenderQuantifierGraph(self.quantifier_view);
+                ctx.props().file.send_updates(std::iter::once(msg));
+                true
+            }
             Msg::ToggleMlViewerMode => {
                 let state = ctx.link().get_state().unwrap();
                 let found_mls = &state.state.parser.as_ref().unwrap().found_mls;
@@ -281,6 +310,18 @@ impl Component for FiltersState {
                 <li><a draggable="false" href="#" onclick={toggle_ml_viewer_mode}><div class="material-icons"><MatIcon>{"loop"}</MatIcon></div>{"View likely matching loops"}</a></li>
             }
         };
+        let toggle_quantifier_view = ctx.link().callback(|ev: MouseEvent| {
+            ev.prevent_default();
+            Msg::ToggleQuantifierView
+        });
+        let (icon, text) = if self.quantifier_view {
+            ("close", "Hide quantifier dependencies")
+        } else {
+            ("account_tree", "View quantifier dependencies")
+        };
+        let quantifier_view = html! {
+            <li><a draggable="false" href="#" onclick={toggle_quantifier_view}><div class="material-icons"><MatIcon>{icon}</MatIcon></div>{text}</a></li>
+        };
         let reset = ctx.link().callback(|e: MouseEvent| {
             e.prevent_default();
             Msg::ResetOperations
@@ -351,6 +392,20 @@ impl Component for FiltersState {
                 <div class="material-icons"><MatIcon>{icon}</MatIcon></div>{action}{d.description()}
             </a> }
         });
+        // Cost model
+        let onchange = ctx.link().batch_callback(|e: Event| {
+            let target = e.target()?;
+            let target = target.dyn_into::<web_sys::HtmlSelectElement>().ok()?;
+            target.value().parse().ok().map(Msg::SetCostModel)
+        });
+        let cost_models = CostModel::ALL.iter().map(|model| {
+            let selected = *model == self.cost_model;
+            html! { <option value={model.name()} {selected}>{model.description()}</option> }
+        });
+        let cost_model = html! { <li><a draggable="false" class="cost-model" title="How the cost of an instantiation, used to find the most expensive ones, is measured">
+            <div class="material-icons"><MatIcon>{"attach_money"}</MatIcon></div>{"Cost "}
+            <select {onchange}>{for cost_models}</select>
+        </a></li> };
         let normal_mode = if ctx.link().get_state().unwrap().state.ml_viewer_mode {
             html! {}
         } else {
@@ -368,6 +423,7 @@ impl Component for FiltersState {
                 <li><a draggable="false" class="trace-file-name">{details}</a></li>
                 {normal_mode}
                 {ml_viewer_mode}
+                {quantifier_view}
             </ul></SidebarSectionHeader>
             {selected_nodes}
             <SidebarSectionHeader header_text={"Graph Operations"} collapsed_text={"Operations applied to the graph"}><ul>
@@ -381,6 +437,7 @@ impl Component for FiltersState {
             <ToggleList {toggle} {selected}>
                 {for disablers}
             </ToggleList>
+            {cost_model}
             </ul></SidebarSectionHeader>
         </>
         }
@@ -420,6 +477,7 @@ impl Filter {
             Filter::MaxDepth(_) => "link",
             Filter::ShowLongestPath(_) => "route",
             Filter::ShowNamedQuantifier(_) => "fingerprint",
+            Filter::BoundValue(..) => "pin",
             Filter::SelectNthMatchingLoop(_) => "repeat_one",
             Filter::ShowMatchingLoopSubgraph => "repeat",
         }
@@ -460,6 +518,9 @@ impl Filter {
             Self::ShowNamedQuantifier(name) => {
                 format!("Show quant \"{name}\"")
             }
+            Self::BoundValue(ordering, value) => {
+                format!("Show bound \"{} {value}\"", symbol(*ordering))
+            }
             Self::SelectNthMatchingLoop(n) => {
                 let ordinal = match n {
                     n if (n / 10) % 10 == 1 => "th",
@@ -552,6 +613,13 @@ impl Filter {
             Self::ShowNamedQuantifier(name) => {
                 format!("{show} nodes of quantifier \"{}\"", display(name, applied))
             }
+            Self::BoundValue(ordering, value) => {
+                let comparison = format!("{} {value}", symbol(*ordering));
+                format!(
+                    "{show} instantiations binding a numeral \"{}\"",
+                    display(comparison, applied)
+                )
+            }
             Self::SelectNthMatchingLoop(n) => {
                 let ordinal = match n {
                     0 => return "{show} only nodes in longest matching loop".to_string(),
@@ -573,6 +641,15 @@ impl Filter {
     }
 }
 
+/// The comparison operator of a [`Filter::BoundValue`].
+fn symbol(ordering: Ordering) -> char {
+    match ordering {
+        Ordering::Less => '<',
+        Ordering::Equal => '=',
+        Ordering::Greater => '>',
+    }
+}
+
 fn display<T: Display>(t: T, applied: bool) -> String {
     if applied {
         t.to_string()
@@ -0,0 +1,56 @@
+use material_yew::MatIcon;
+use smt_log_parser::parsers::diagnostics::Severity;
+use yew::{function_component, html, Html, Properties};
+
+use crate::{infobars::SidebarSectionHeader, RcParser};
+
+/// How many distinct problems to list, the remaining ones are only counted.
+const MAX_SHOWN: usize = 20;
+
+#[derive(Properties, PartialEq)]
+pub struct ParseDiagnosticsProps {
+    pub parser: RcParser,
+}
+
+/// Lists the problems encountered while parsing the log, such that users know
+/// how much of the log is missing from the profile.
+#[function_component]
+pub fn ParseDiagnostics(props: &ParseDiagnosticsProps) -> Html {
+    let parser = props.parser.parser.borrow();
+    let diagnostics = parser.diagnostics();
+    if diagnostics.is_empty() {
+        return html! {};
+    }
+    let header = format!("Parse Diagnostics ({})", diagnostics.lines_affected());
+    let collapsed_text = format!(
+        "{} lines could not be parsed, {} were skipped",
+        diagnostics.lines_affected(),
+        diagnostics.lines_skipped()
+    );
+    let entries = diagnostics.iter().take(MAX_SHOWN).map(|diagnostic| {
+        let icon = match diagnostic.severity {
+            Severity::Warning => "warning",
+            Severity::Error | Severity::Fatal => "error",
+        };
+        let title = format!(
+            "{}: line {} (byte {})\n{}",
+            diagnostic.severity, diagnostic.line_no, diagnostic.byte_offset, diagnostic.message
+        );
+        let text = format!(
+            "{} {} (x{})",
+            diagnostic.entry, diagnostic.kind, diagnostic.count
+        );
+        html! {
+            <li><a draggable="false" {title}><div class="material-icons"><MatIcon>{icon}</MatIcon></div>{text}</a></li>
+        }
+    });
+    let more = diagnostics.len().checked_sub(MAX_SHOWN).filter(|&n| n > 0).map(|n| {
+        html! { <li><a draggable="false" class="trace-file-name">{format!("{n} more")}</a></li> }
+    });
+    html! {
+        <SidebarSectionHeader header_text={header} {collapsed_text}><ul>
+            {for entries}
+            {more}
+        </ul></SidebarSectionHeader>
+    }
+}
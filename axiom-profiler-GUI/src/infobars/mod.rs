@@ -1,8 +1,10 @@
+mod diagnostics;
 mod ml_omnibox;
 mod omnibox;
 mod sidebar;
 mod topbar;
 
+pub use diagnostics::*;
 pub use omnibox::*;
 pub use sidebar::*;
 pub use topbar::*;
@@ -13,7 +13,7 @@ use results::graph_info;
 use results::svg_result::{
     Msg as SVGMsg, QuantIdxToColourMap, RenderedGraph, RenderingState, SVGResult,
 };
-use smt_log_parser::analysis::{InstGraph, RawNodeIndex, VisibleEdgeIndex};
+use smt_log_parser::analysis::{InstGraph, RawNodeIndex, TriggerAnalysis, VisibleEdgeIndex};
 use smt_log_parser::parsers::z3::z3parser::Z3Parser;
 use smt_log_parser::parsers::{ParseState, ReaderState};
 use wasm_bindgen::closure::Closure;
@@ -27,7 +27,9 @@ use crate::commands::CommandsProvider;
 use crate::configuration::{ConfigurationProvider, Flags};
 use crate::filters::FiltersState;
 
-use crate::infobars::{OmnibarMessage, SearchActionResult, SidebarSectionHeader, Topbar};
+use crate::infobars::{
+    OmnibarMessage, ParseDiagnostics, SearchActionResult, SidebarSectionHeader, Topbar,
+};
 use crate::results::filters::Filter;
 use crate::results::svg_result::GraphState;
 use crate::state::{StateContext, StateProviderContext};
@@ -70,7 +72,12 @@ pub static PREVENT_DEFAULT_DRAG_OVER: OnceLock<Mutex<bool>> = OnceLock::new();
 
 pub enum Msg {
     File(Option<File>),
-    LoadedFile(Box<Z3Parser>, ParseState<bool>, bool),
+    LoadedFile(
+        Box<Z3Parser>,
+        Option<Box<InstGraph>>,
+        ParseState<bool>,
+        bool,
+    ),
     LoadingState(LoadingState),
     RenderedGraph(RenderedGraph),
     FailedOpening(String),
@@ -81,6 +88,7 @@ pub enum Msg {
     KeyDown(KeyboardEvent),
     ShowHelpToggled(bool),
     SearchMatchingLoops,
+    SaveSnapshot,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -181,6 +189,7 @@ pub struct FileDataComponent {
     file_select: NodeRef,
     file: Option<OpenedFileInfo>,
     reader: Option<FileReader>,
+    source_reader: Option<FileReader>,
     pending_ops: usize,
     progress: LoadingState,
     message: Option<(Timeout, OmnibarMessage)>,
@@ -194,7 +203,7 @@ pub struct FileDataComponent {
     sidebar_button: NodeRef,
     flags_visible: SetVisibleCallback,
     _callback_refs: [CallbackRef; 6],
-    _command_refs: [CommandRef; 4],
+    _command_refs: [CommandRef; 5],
 }
 
 impl FileDataComponent {
@@ -302,11 +311,25 @@ impl Component for FileDataComponent {
             disabled: false,
         };
         let toggle_flags_cmd = (commands)(toggle_flags_cmd);
-        let _command_refs = [help_cmd, hide_sidebar_cmd, search_cmd, toggle_flags_cmd];
+        let save_snapshot_cmd = Command {
+            name: "Save snapshot of parsed file".to_string(),
+            execute: ctx.link().callback(|_| Msg::SaveSnapshot),
+            keyboard_shortcut: vec![],
+            disabled: false,
+        };
+        let save_snapshot_cmd = (commands)(save_snapshot_cmd);
+        let _command_refs = [
+            help_cmd,
+            hide_sidebar_cmd,
+            search_cmd,
+            toggle_flags_cmd,
+            save_snapshot_cmd,
+        ];
         Self {
             file_select: NodeRef::default(),
             file: None,
             reader: None,
+            source_reader: None,
             pending_ops: 0,
             progress: LoadingState::NoFileSelected,
             message: None,
@@ -330,6 +353,9 @@ impl Component for FileDataComponent {
                 let Some(file) = file else {
                     return false;
                 };
+                if file.name().ends_with(file::SMT2_EXTENSION) {
+                    return self.load_source_file(file, ctx.link());
+                }
                 // remove any old parser in the state
                 let state = ctx.link().get_state().unwrap();
                 state.update_parser(|p| p.take().is_some());
@@ -428,9 +454,10 @@ impl Component for FileDataComponent {
                 self.message.take();
                 true
             }
-            Msg::LoadedFile(parser, parser_state, parser_cancelled) => {
+            Msg::LoadedFile(parser, graph, parser_state, parser_cancelled) => {
                 drop(self.reader.take());
-                let parser = RcParser::new(*parser);
+                let mut parser = RcParser::new(*parser);
+                parser.graph = graph.map(|graph| Rc::new(RefCell::new(*graph)));
                 let state = ctx.link().get_state().unwrap();
                 state.update_parser(move |p| {
                     *p = Some(parser);
@@ -526,6 +553,17 @@ impl Component for FileDataComponent {
                 log::info!("Returning false");
                 false
             }
+            Msg::SaveSnapshot => {
+                if let Err(message) = self.save_snapshot(ctx.link()) {
+                    let message = OmnibarMessage {
+                        message,
+                        is_error: true,
+                    };
+                    self.set_message(ctx.link(), message, 5000);
+                    return true;
+                }
+                false
+            }
         }
     }
 
@@ -566,6 +604,9 @@ impl Component for FileDataComponent {
         let data = ctx.link().get_state().unwrap();
         let parser = data.state.parser.clone();
         let parser_ref = parser.clone();
+        let diagnostics = parser
+            .clone()
+            .map(|parser| html! { <ParseDiagnostics {parser} /> });
         let visible = self
             .file
             .as_ref()
@@ -669,12 +710,13 @@ impl Component for FileDataComponent {
         <>
             <nav class="sidebar" ref={sidebar}>
                 <header class={header_class}><img src="html/logo_side_small.png" class="brand"/><div ref={&self.sidebar_button} class="sidebar-button" onclick={hide_sidebar}><MatIconButton icon="menu"></MatIconButton></div></header>
-                <input type="file" ref={&self.file_select} class="trace_file" accept=".log" onchange={on_change} multiple=false/>
+                <input type="file" ref={&self.file_select} class="trace_file" accept=".log,.gz,.zst,.xz,.slpsnap,.smt2" onchange={on_change} multiple=false/>
                 <div class="sidebar-scroll"><div class="sidebar-scroll-container">
                     <SidebarSectionHeader header_text="Navigation" collapsed_text="Open a new trace" section={self.navigation_section.clone()}><ul>
                         <li><a href="#" draggable="false" id="open_trace_file"><div class="material-icons"><MatIcon>{"folder_open"}</MatIcon></div>{"Open trace file"}</a></li>
                     </ul></SidebarSectionHeader>
                     {current_trace}
+                    {diagnostics}
                     <SidebarSectionHeader header_text="Support" collapsed_text="Documentation & Bugs"><ul>
                         <li><a href="#" draggable="false" onclick={show_shortcuts} id="keyboard_shortcuts"><div class="material-icons"><MatIcon>{"help"}</MatIcon></div>{"Keyboard shortcuts"}</a></li>
                         <li><a href="https://github.com/viperproject/axiom-profiler-2/blob/main/README.md" target="_blank" id="documentation"><div class="material-icons"><MatIcon>{"find_in_page"}</MatIcon></div>{"Documentation"}</a></li>
@@ -735,6 +777,8 @@ pub struct RcParser {
     lookup: Rc<StringLookupZ3>,
     colour_map: QuantIdxToColourMap,
     graph: Option<Rc<RefCell<InstGraph>>>,
+    /// Calculated together with the graph since it needs the costs.
+    triggers: Option<Rc<TriggerAnalysis>>,
     found_mls: Option<usize>,
 }
 
@@ -745,6 +789,7 @@ impl Clone for RcParser {
             lookup: self.lookup.clone(),
             colour_map: self.colour_map,
             graph: self.graph.clone(),
+            triggers: self.triggers.clone(),
             found_mls: self.found_mls,
         }
     }
@@ -769,6 +814,7 @@ impl RcParser {
             lookup: Rc::new(lookup),
             colour_map,
             graph: None,
+            triggers: None,
             found_mls: None,
         }
     }
@@ -1,3 +1,5 @@
+use std::cmp::Ordering;
+
 use petgraph::{
     visit::{Dfs, Walker},
     Direction, Graph,
@@ -9,7 +11,7 @@ use smt_log_parser::{
         InstGraph, RawNodeIndex,
     },
     display_with::{DisplayCtxt, DisplayWithCtxt},
-    items::QuantIdx,
+    items::{QuantIdx, Value},
     Z3Parser,
 };
 
@@ -41,6 +43,7 @@ pub enum Filter {
     MaxDepth(usize),
     ShowLongestPath(RawNodeIndex),
     ShowNamedQuantifier(String),
+    BoundValue(Ordering, Value),
     SelectNthMatchingLoop(usize),
     ShowMatchingLoopSubgraph,
 }
@@ -128,6 +131,15 @@ impl Filter {
                         })
                     })
             }
+            Filter::BoundValue(ordering, value) => {
+                graph
+                    .raw
+                    .set_visibility_when(false, |_: RawNodeIndex, node: &Node| {
+                        node.kind()
+                            .inst()
+                            .is_some_and(|i| parser.binds_value(i, ordering, &value))
+                    })
+            }
             // TODO: implement
             Filter::SelectNthMatchingLoop(n) => {
                 graph.raw.reset_visibility_to(true);
@@ -184,7 +196,14 @@ impl Filter {
                         node.kind().inst().is_some() && !node.part_of_ml.contains(&n)
                     });
                 let dot_graph = graph.nth_matching_loop_graph(n);
-                return FilterOutput::MatchingLoopGraph(dot_graph);
+                let ctxt = config(parser);
+                let explanation = graph
+                    .analysis
+                    .matching_loop_explanations
+                    .get(n)
+                    .map(|explanation| explanation.sentences(&ctxt))
+                    .unwrap_or_default();
+                return FilterOutput::MatchingLoopGraph(dot_graph, explanation);
             }
             Filter::ShowMatchingLoopSubgraph => {
                 // graph.raw.reset_visibility_to(true);
@@ -220,8 +239,8 @@ impl Filter {
 
 pub enum FilterOutput {
     LongestPath(Vec<RawNodeIndex>),
-    MatchingLoopGeneralizedTerms(Vec<String>),
-    MatchingLoopGraph(Graph<MLGraphNode, ()>),
+    /// The graph of the matching loop and one sentence per step of it.
+    MatchingLoopGraph(Graph<MLGraphNode, ()>, Vec<String>),
     None,
 }
 
@@ -3,7 +3,6 @@ use std::rc::Rc;
 use crate::{state::StateProvider, utils::split_div::SplitDiv};
 use indexmap::map::{Entry, IndexMap};
 use material_yew::WeakComponentLink;
-// use smt_log_parser::parsers::z3::inst_graph::{EdgeType, NodeInfo};
 use smt_log_parser::analysis::{RawNodeIndex, VisibleEdgeIndex};
 use yew::prelude::*;
 
@@ -16,9 +15,11 @@ use super::{
 pub struct GraphInfo {
     selected_nodes: IndexMap<RawNodeIndex, bool>,
     selected_edges: IndexMap<VisibleEdgeIndex, bool>,
-    generalized_terms: Vec<String>,
+    matching_loop_explanation: Vec<String>,
     graph_container: WeakComponentLink<graph_container::GraphContainer>,
     displayed_matching_loop_graph: Option<AttrValue>,
+    /// The rendered quantifier graph and a description of each of its cycles.
+    displayed_quantifier_graph: Option<(AttrValue, Vec<String>)>,
     in_ml_viewer_mode: bool,
     _context_listener: ContextHandle<Rc<StateProvider>>,
 }
@@ -59,8 +60,9 @@ pub enum Msg {
     // SelectNodes(Vec<RawNodeIndex>),
     DeselectAll,
     SelectAll,
-    ShowGeneralizedTerms(Vec<String>),
+    ShowMatchingLoopExplanation(Vec<String>),
     ShowMatchingLoopGraph(AttrValue),
+    ShowQuantifierGraph(Option<(AttrValue, Vec<String>)>),
     ContextUpdated(Rc<StateProvider>),
 }
 
@@ -107,9 +109,10 @@ impl Component for GraphInfo {
                 .copied()
                 .map(|e| (e, false))
                 .collect(),
-            generalized_terms: Vec::new(),
+            matching_loop_explanation: Vec::new(),
             graph_container: WeakComponentLink::default(),
             displayed_matching_loop_graph: None,
+            displayed_quantifier_graph: None,
             in_ml_viewer_mode: state.state.ml_viewer_mode,
             _context_listener: context_listener,
         }
@@ -202,14 +205,18 @@ impl Component for GraphInfo {
             //     ctx.props().update_selected_nodes.emit(selected_nodes);
             //     true
             // }
-            Msg::ShowGeneralizedTerms(terms) => {
-                self.generalized_terms = terms;
+            Msg::ShowMatchingLoopExplanation(explanation) => {
+                self.matching_loop_explanation = explanation;
                 true
             }
             Msg::ShowMatchingLoopGraph(graph) => {
                 self.displayed_matching_loop_graph = Some(graph);
                 true
             }
+            Msg::ShowQuantifierGraph(graph) => {
+                self.displayed_quantifier_graph = graph;
+                true
+            }
             Msg::ScrollZoomSelection => {
                 let Some(graph_container) = &*self.graph_container.borrow() else {
                     return false;
@@ -247,9 +254,9 @@ impl Component for GraphInfo {
         let on_edge_select = ctx.link().callback(Msg::UserSelectedEdge);
         let deselect_all = ctx.link().callback(|_| Msg::DeselectAll);
         let select_all = ctx.link().callback(|_| Msg::SelectAll);
-        let _generalized_terms = self.generalized_terms.iter().map(|term| {
+        let explanation = self.matching_loop_explanation.iter().map(|sentence| {
             html! {
-                <li>{term}</li>
+                <li>{Html::from_html_unchecked(AttrValue::from(sentence.clone()))}</li>
             }
         });
         let outdated = ctx
@@ -258,7 +265,23 @@ impl Component for GraphInfo {
             .then(|| html! {<div class="outdated"></div>});
         let hide_right_bar = self.selected_nodes.is_empty()
             && self.selected_edges.is_empty()
+            && self.displayed_quantifier_graph.is_none()
             && !(self.in_ml_viewer_mode && self.displayed_matching_loop_graph.is_some());
+        let quantifier_graph = self.displayed_quantifier_graph.as_ref().map(|(graph, cycles)| {
+            let cycles = if cycles.is_empty() {
+                html! { <p>{"No quantifiers depend on themselves, not even indirectly."}</p> }
+            } else {
+                html! { <ul>{for cycles.iter().map(|cycle| html! { <li>{cycle}</li> })}</ul> }
+            };
+            html! {
+                <>
+                    <h2>{"Quantifier Dependencies"}</h2>
+                    <h3>{"Cycles"}</h3>
+                    {cycles}
+                    <div style="overflow-x: auto;">{Html::from_html_unchecked(graph.clone())}</div>
+                </>
+            }
+        });
         let left_bound = if hide_right_bar { 1.0 } else { 0.3 };
         html! {
             <>
@@ -277,11 +300,13 @@ impl Component for GraphInfo {
                 <div style="width:100%; height:100%; overflow-wrap:anywhere; overflow:clip auto;">
                     <SelectedNodesInfo selected_nodes={self.selected_nodes.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>()} on_click={on_node_click} />
                     <SelectedEdgesInfo selected_edges={self.selected_edges.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>()} rendered={ctx.props().rendered.clone()} on_click={on_edge_click} />
+                    {quantifier_graph}
                     { if let Some(graph) = &self.displayed_matching_loop_graph {
                         if self.in_ml_viewer_mode {
                             html!{
                                 <>
                                     <h2>{"Information on Displayed Matching Loop"}</h2>
+                                    <ul>{for explanation}</ul>
                                     <div style="overflow-x: auto;">{Html::from_html_unchecked(graph.clone())}</div>
                                 </>
                             }
@@ -291,11 +316,6 @@ impl Component for GraphInfo {
                     } else {
                         html!{}
                     }}
-                    // TODO: re-add matching loops
-                    // <h2>{"Information about displayed matching loop:"}</h2>
-                    // <div>
-                    //     <ul>{for generalized_terms}</ul>
-                    // </div>
                 </div>
             </SplitDiv>
             {outdated}
@@ -4,17 +4,22 @@ use smt_log_parser::{
     analysis::{
         raw::{EdgeKind, Node, NodeKind},
         visible::{VisibleEdge, VisibleEdgeKind},
-        InstGraph, RawNodeIndex, VisibleEdgeIndex,
+        InstGraph, RawNodeIndex, TriggerAnalysis, VisibleEdgeIndex,
     },
     display_with::{DisplayCtxt, DisplayWithCtxt},
     items::{MatchKind, VarNames},
+    parsers::{smt2::SourceQuantifier, z3::provenance::LinePos},
     NonMaxU32,
 };
 use yew::{
-    function_component, html, use_context, AttrValue, Callback, Html, MouseEvent, Properties,
+    function_component, html, use_context, use_effect_with_deps, use_state, AttrValue, Callback,
+    Html, MouseEvent, Properties,
 };
 
-use crate::{configuration::ConfigurationProvider, state::StateProvider};
+use crate::{
+    configuration::ConfigurationProvider,
+    state::{SourceFile, StateProvider},
+};
 
 use super::svg_result::RenderedGraph;
 
@@ -59,6 +64,7 @@ impl<'a, 'b> NodeInfo<'a, 'b> {
                     MatchKind::TheorySolving { .. } => "Theory Solving",
                     MatchKind::Axiom { .. } => "Axiom",
                     MatchKind::Quantifier { .. } => "Quantifier",
+                    MatchKind::Reported { .. } => "Reported",
                 }
             }
         }
@@ -95,7 +101,7 @@ impl<'a, 'b> NodeInfo<'a, 'b> {
                     format!("{namespace}[{id}]")
                 }
                 MatchKind::Axiom { axiom, .. } => ctxt.parser[*axiom].kind.with(&ctxt).to_string(),
-                MatchKind::Quantifier { quant, .. } => {
+                MatchKind::Quantifier { quant, .. } | MatchKind::Reported { quant, .. } => {
                     ctxt.parser[*quant].kind.with(&ctxt).to_string()
                 }
             },
@@ -111,6 +117,46 @@ impl<'a, 'b> NodeInfo<'a, 'b> {
             .quant_idx()?;
         Some(quant_idx.with(self.ctxt).to_string())
     }
+    pub fn quantifier_source<'s>(&self, source: &'s SourceFile) -> Option<&'s SourceQuantifier> {
+        let NodeKind::Instantiation(inst) = *self.node.kind() else {
+            return None;
+        };
+        let quant_idx = self.ctxt.parser[self.ctxt.parser[inst].match_]
+            .kind
+            .quant_idx()?;
+        source.get(quant_idx)
+    }
+    /// How the trigger of this instantiation fared across the whole log.
+    pub fn trigger_stats(&self, triggers: &TriggerAnalysis) -> Option<String> {
+        let NodeKind::Instantiation(inst) = *self.node.kind() else {
+            return None;
+        };
+        let match_ = &self.ctxt.parser[self.ctxt.parser[inst].match_].kind;
+        let (quant, pattern) = (match_.quant_idx()?, match_.pattern()?);
+        let infos = triggers.get(quant)?;
+        let info = infos.iter().find(|info| info.trigger == pattern)?;
+        let total_costs = triggers.total_costs();
+        let share = if total_costs > 0.0 {
+            100.0 * info.costs / total_costs
+        } else {
+            0.0
+        };
+        let symbols: Vec<_> = info
+            .top_symbols()
+            .into_iter()
+            .take(3)
+            .map(|(symbol, count)| format!("{} {count}", &self.ctxt.parser[symbol]))
+            .collect();
+        Some(format!(
+            "{} matches, {} instantiations, {share:.1}% of cost, {} via equalities, matched {}; trigger {} of {}",
+            info.matches,
+            info.insts,
+            info.equality_matches,
+            symbols.join(", "),
+            infos.iter().position(|i| i.trigger == pattern)? + 1,
+            infos.len(),
+        ))
+    }
     pub fn blame(&self) -> Option<Vec<(String, String, Vec<String>)>> {
         let NodeKind::Instantiation(inst) = *self.node.kind() else {
             return None;
@@ -183,6 +229,70 @@ impl<'a, 'b> NodeInfo<'a, 'b> {
                 .collect(),
         )
     }
+
+    /// The lines of the log which created the node.
+    pub fn raw_log(&self) -> Option<Vec<(&'static str, LinePos)>> {
+        let provenance = self.ctxt.parser.provenance()?;
+        let lines = match *self.node.kind() {
+            NodeKind::ENode(enode) => vec![("ENode", provenance.enode(enode)?)],
+            NodeKind::GivenEquality(eq, _) => vec![("Equality", provenance.eq_given(eq)?)],
+            NodeKind::TransEquality(_) => return None,
+            NodeKind::Instantiation(inst) => {
+                let match_ = self.ctxt.parser[inst].match_;
+                vec![
+                    ("Match", provenance.match_(match_)?),
+                    ("Instance", provenance.inst(inst)?),
+                ]
+            }
+        };
+        Some(lines)
+    }
+}
+
+/// The longest line prefix read from the log, the rest is cut off.
+const MAX_RAW_LINE_LEN: u64 = 4096;
+
+#[derive(Properties, PartialEq)]
+pub struct RawLogLineProps {
+    header: &'static str,
+    pos: LinePos,
+}
+
+/// Shows the line at `pos`, read from the log file if it is still available.
+#[function_component]
+pub fn RawLogLine(RawLogLineProps { header, pos }: &RawLogLineProps) -> Html {
+    let data = use_context::<Rc<StateProvider>>().unwrap();
+    let line = use_state(|| None::<String>);
+    {
+        let line = line.clone();
+        use_effect_with_deps(
+            move |(raw_log, pos)| {
+                line.set(None);
+                let reader = raw_log.as_ref().map(|raw_log| {
+                    let start = pos.byte_offset as u64;
+                    let end = (start + MAX_RAW_LINE_LEN).min(raw_log.size());
+                    let slice = raw_log.slice(start, end);
+                    gloo::file::callbacks::read_as_text(&slice, move |text| {
+                        if let Ok(text) = text {
+                            let text = text.lines().next().unwrap_or_default();
+                            line.set(Some(text.to_string()));
+                        }
+                    })
+                });
+                move || drop(reader)
+            },
+            (data.state.raw_log.clone(), *pos),
+        );
+    }
+    let position = format!("line {}, byte {}", pos.line_no, pos.byte_offset);
+    match &*line {
+        Some(line) => html! {
+            <li><h4 style="display: inline">{header}{" ("}{position}{"): "}</h4><code>{line}</code></li>
+        },
+        None => html! {
+            <li><h4 style="display: inline">{header}{": "}</h4>{position}</li>
+        },
+    }
 }
 
 #[derive(Properties, PartialEq)]
@@ -206,6 +316,7 @@ pub fn SelectedNodesInfo(
     }
     let parser = data.state.parser.as_ref().unwrap();
     let graph = parser.graph.as_ref().unwrap();
+    let triggers = parser.triggers.clone();
     let parser = &*parser.parser;
     let graph = graph.borrow();
     let ctxt = &DisplayCtxt {
@@ -234,6 +345,21 @@ pub fn SelectedNodesInfo(
             let quantifier_body = info.quantifier_body().map(|body| html! {
                 <><InfoLine header="Body" text={body} code=true /><hr/></>
             });
+            let quantifier_source = data.state.source.as_ref().and_then(|source| {
+                let quant = info.quantifier_source(source)?;
+                let start = quant.span.start;
+                let weight = quant.weight.map(|w| format!(", weight {w}")).unwrap_or_default();
+                let location = format!("{}:{}:{}{weight}", source.name, start.line, start.column);
+                Some(html! {
+                    <>
+                    <li><h4 style="display: inline">{"Source: "}</h4>{location}<pre><code>{&quant.text}</code></pre></li>
+                    <hr/>
+                    </>
+                })
+            });
+            let trigger_stats = triggers.as_ref().and_then(|triggers| info.trigger_stats(triggers)).map(|stats| html! {
+                <><InfoLine header="Trigger Statistics" text={stats} code=false /><hr/></>
+            });
             let blame: Option<Html> = info.blame().map(|blame| blame.into_iter().enumerate().map(|(idx, (trigger, enode, equalities))| {
                 let equalities: Html = equalities.into_iter().map(|equality| html! {
                     <InfoLine header="Equality" text={equality} code=true />
@@ -261,15 +387,24 @@ pub fn SelectedNodesInfo(
                 }).collect();
                 html! { <>{yields}<hr/></> }
             });
+            let raw_log = info.raw_log().map(|lines| {
+                let lines: Html = lines.into_iter().map(|(header, pos)| html! {
+                    <RawLogLine {header} {pos} />
+                }).collect();
+                html! { <>{lines}<hr/></> }
+            });
             html! {
                 <details {open}>
                 <summary {onclick}>{summary}{description}</summary>
                 <ul>
                     {quantifier_body}
+                    {quantifier_source}
+                    {trigger_stats}
                     {blame}
                     {bound_terms}
                     {resulting_term}
                     {yield_terms}
+                    {raw_log}
                     <InfoLine header="Cost" text={format!("{:.1}{}", info.node.cost, z3_gen.unwrap_or_default())} code=false />
                     <InfoLine header="To Root" text={format!("short {}, long {}", info.node.fwd_depth.min, info.node.fwd_depth.max)} code=false />
                     <InfoLine header="To Leaf" text={format!("short {}, long {}", info.node.bwd_depth.min, info.node.bwd_depth.max)} code=false />
@@ -24,8 +24,8 @@ use petgraph::{
 };
 use smt_log_parser::{
     analysis::{
-        analysis::matching_loop::MLGraphNode, raw::NodeKind, visible::VisibleInstGraph, InstGraph,
-        RawNodeIndex, VisibleEdgeIndex,
+        analysis::matching_loop::MLGraphNode, raw::NodeKind, visible::VisibleInstGraph, CostModel,
+        InstGraph, QuantifierAnalysis, RawNodeIndex, TriggerAnalysis, VisibleEdgeIndex,
     },
     display_with::{DisplayCtxt, DisplayWithCtxt},
     items::QuantIdx,
@@ -62,12 +62,15 @@ pub enum Msg {
     UpdateSvgText(AttrValue, VisibleInstGraph),
     SetPermission(GraphDimensions),
     SetDisabled(Vec<Disabler>),
+    SetCostModel(CostModel),
     RenderGraph,
     ApplyFilter(Filter),
     ResetGraph,
     UserPermission(WarningChoice),
     WorkerOutput(super::worker::WorkerOutput),
     RenderMLGraph(Graph<MLGraphNode, ()>),
+    /// Render (or hide) the graph of dependencies between quantifiers.
+    RenderQuantifierGraph(bool),
     // UpdateSelectedNodes(Vec<RawNodeIndex>),
     // SearchMatchingLoops,
     // SelectNthMatchingLoop(usize),
@@ -141,7 +144,13 @@ impl Component for SVGResult {
             gloo::timers::future::TimeoutFuture::new(10).await;
             let data = link.get_state().unwrap();
             let parser = data.state.parser.as_ref().unwrap();
-            let inst_graph = match InstGraph::new(&parser.parser.borrow()) {
+            // The graph may have been restored from a snapshot.
+            let inst_graph = match parser.graph.clone() {
+                Some(inst_graph) => Ok(inst_graph),
+                None => InstGraph::new(&parser.parser.borrow())
+                    .map(|inst_graph| Rc::new(RefCell::new(inst_graph))),
+            };
+            let inst_graph = match inst_graph {
                 Ok(inst_graph) => inst_graph,
                 Err(err) => {
                     log::error!("Failed constructing instantiation graph: {err:?}");
@@ -155,10 +164,12 @@ impl Component for SVGResult {
                     return;
                 }
             };
-            let inst_graph = Rc::new(RefCell::new(inst_graph));
+            let triggers =
+                TriggerAnalysis::new(&parser.parser.borrow(), Some(&inst_graph.borrow()));
             let inst_graph_ref = inst_graph.clone();
             data.update_graph(|p| {
                 p.graph = Some(inst_graph_ref);
+                p.triggers = Some(Rc::new(triggers));
                 true
             });
             link.send_message(Msg::ConstructedGraph(inst_graph));
@@ -234,16 +245,13 @@ impl Component for SVGResult {
                         //     .send_message(GraphInfoMsg::SelectNodes(path));
                         false
                     }
-                    FilterOutput::MatchingLoopGeneralizedTerms(gen_terms) => {
+                    FilterOutput::MatchingLoopGraph(graph, explanation) => {
                         ctx.props()
                             .insts_info_link
                             .borrow()
                             .as_ref()
                             .unwrap()
-                            .send_message(GraphInfoMsg::ShowGeneralizedTerms(gen_terms));
-                        false
-                    }
-                    FilterOutput::MatchingLoopGraph(graph) => {
+                            .send_message(GraphInfoMsg::ShowMatchingLoopExplanation(explanation));
                         ctx.link().send_message(Msg::RenderMLGraph(graph));
                         false
                     }
@@ -283,6 +291,16 @@ impl Component for SVGResult {
                 Disabler::apply(disablers.iter().copied(), inst_graph, &parser.borrow());
                 false
             }
+            Msg::SetCostModel(cost_model) => {
+                let parser = parser.borrow();
+                inst_graph.set_cost_model(&parser, cost_model);
+                let triggers = TriggerAnalysis::new(&parser, Some(inst_graph));
+                data.update_graph(|p| {
+                    p.triggers = Some(Rc::new(triggers));
+                    true
+                });
+                false
+            }
             Msg::RenderGraph => {
                 if self
                     .rendered
@@ -601,6 +619,51 @@ impl Component for SVGResult {
                 // only need to re-render once the new SVG has been set
                 true
             }
+            Msg::RenderQuantifierGraph(show) => {
+                let link = ctx.props().insts_info_link.borrow().clone().unwrap();
+                if !show {
+                    link.send_message(GraphInfoMsg::ShowQuantifierGraph(None));
+                    return false;
+                }
+                let parser = parser.borrow();
+                inst_graph.initialise_inst_succs_and_preds(&parser);
+                let qanalysis = QuantifierAnalysis::new(&parser, inst_graph);
+                let graph = qanalysis.graph();
+                let cfg = ctx.link().get_configuration().unwrap();
+                let ctxt = &DisplayCtxt {
+                    parser: &parser,
+                    term_display: &data.state.term_display,
+                    config: cfg.config.display.clone(),
+                };
+                let cycles: Vec<_> = graph
+                    .cycles()
+                    .into_iter()
+                    .map(|scc| {
+                        let costs: f64 = scc.iter().map(|&q| qanalysis[q].costs).sum();
+                        let names: Vec<_> = scc
+                            .iter()
+                            .map(|&q| parser[q].kind.with(ctxt).to_string())
+                            .collect();
+                        let percentage = graph.percentage(costs);
+                        format!("{} ({percentage:.1}% of cost)", names.join(", "))
+                    })
+                    .collect();
+                let dot_output = graph.to_dot(ctxt, |quant| {
+                    let hue = rc_parser.colour_map.get_graphviz_hue_for_quant_idx(&quant);
+                    format!("style=filled fillcolor=\"{hue} {NODE_COLOUR_SATURATION} {NODE_COLOUR_VALUE}\"")
+                });
+                wasm_bindgen_futures::spawn_local(async move {
+                    gloo_timers::future::TimeoutFuture::new(10).await;
+                    let graphviz = VizInstance::new().await;
+                    let options = viz_js::Options::default();
+                    let svg = graphviz
+                        .render_svg_element(dot_output, options)
+                        .expect("Could not render graphviz");
+                    let svg_text = AttrValue::from(svg.outer_html());
+                    link.send_message(GraphInfoMsg::ShowQuantifierGraph(Some((svg_text, cycles))));
+                });
+                false
+            }
         }
     }
 
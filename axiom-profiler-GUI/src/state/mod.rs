@@ -1,6 +1,11 @@
 use std::rc::Rc;
 
-use smt_log_parser::formatter::TermDisplayContext;
+use gloo::file::Blob;
+use smt_log_parser::{
+    formatter::TermDisplayContext,
+    items::QuantIdx,
+    parsers::smt2::{Smt2Problem, SourceMap, SourceQuantifier},
+};
 use yew::{
     html, Callback, Children, Component, Context, ContextHandle, ContextProvider, Html, Properties,
 };
@@ -19,6 +24,11 @@ pub struct State {
     /// Calculated automatically based on the set file_info.
     pub term_display: TermDisplayContext,
     pub parser: Option<RcParser>,
+    /// The opened log if it can be read from directly (i.e. is not
+    /// compressed), used to show the raw lines which created items.
+    pub raw_log: Option<Blob>,
+    /// The SMT-LIB input the opened log was created from, if also opened.
+    pub source: Option<SourceFile>,
     pub ml_viewer_mode: bool,
     pub overlay_visible: bool,
 }
@@ -31,6 +41,25 @@ pub struct FileInfo {
     pub size: u64,
 }
 
+#[derive(Clone)]
+pub struct SourceFile {
+    pub name: String,
+    pub problem: Rc<Smt2Problem>,
+    pub map: Rc<SourceMap>,
+}
+
+impl SourceFile {
+    pub fn get(&self, quant: QuantIdx) -> Option<&SourceQuantifier> {
+        self.map.get(&self.problem, quant)
+    }
+}
+
+impl PartialEq for SourceFile {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.problem, &other.problem) && Rc::ptr_eq(&self.map, &other.map)
+    }
+}
+
 #[derive(Clone, PartialEq)]
 pub struct StateProvider {
     pub state: State,
@@ -57,6 +86,22 @@ impl StateProvider {
         });
     }
 
+    pub fn set_raw_log(&self, raw_log: Option<Blob>) {
+        self.update.update(move |state| {
+            state.raw_log = raw_log;
+            Some(StateUpdateKind::Other)
+        });
+    }
+
+    pub fn set_source(&self, source: Option<SourceFile>) {
+        self.update.update(move |state| {
+            (state.source.is_some() || source.is_some()).then(|| {
+                state.source = source;
+                StateUpdateKind::Other
+            })
+        });
+    }
+
     pub fn set_ml_viewer_mode(&self, ml_viewer_mode: bool) {
         self.update.update(move |state| {
             (state.ml_viewer_mode != ml_viewer_mode).then(|| {
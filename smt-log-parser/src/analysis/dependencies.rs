@@ -1,8 +1,19 @@
-use std::{cmp::Ordering, ops::Deref};
+use std::ops::Deref;
 
 use fxhash::FxHashSet;
+use petgraph::{
+    algo::tarjan_scc,
+    dot::{Config, Dot},
+    graph::NodeIndex,
+    visit::EdgeRef,
+    Direction,
+};
 
-use crate::{items::QuantIdx, FxHashMap, TiVec, Z3Parser};
+use crate::{
+    display_with::{DisplayCtxt, DisplayWithCtxt},
+    items::{InstIdx, QuantIdx, QueryIdx},
+    DiGraph, FxHashMap, TiVec, Z3Parser,
+};
 
 use super::InstGraph;
 
@@ -14,9 +25,22 @@ pub struct QuantifierInfo {
     /// How much total cost did this quantifier accrue from individual
     /// instantiations.
     pub costs: f64,
+    /// How many instantiations of this quantifier there are.
+    pub insts: u32,
+    /// The instantiations of other quantifiers which instantiations of this
+    /// quantifier depend on.
+    pub direct_deps: FxHashMap<QuantIdx, QuantDependency>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct QuantDependency {
     /// How many times does an instantiation of this quantifier depend on an
     /// instantiation of the other quantifier.
-    pub direct_deps: FxHashMap<QuantIdx, u32>,
+    pub count: u32,
+    /// The share of the cost of this quantifier blamed on the other one. Each
+    /// instantiation splits its cost evenly between the parents it depends
+    /// on which are themselves quantifier instantiations.
+    pub costs: f64,
 }
 
 type TransQuantAnalaysis = TiVec<QuantIdx, FxHashSet<QuantIdx>>;
@@ -32,6 +56,19 @@ impl QuantifierAnalysis {
     /// Calculate the analysis. Make sure that you have run
     /// `initialise_inst_succs_and_preds` on the `inst_graph`!
     pub fn new(parser: &Z3Parser, inst_graph: &InstGraph) -> Self {
+        Self::new_for_insts(parser, inst_graph, parser.insts.insts.keys())
+    }
+    /// Calculate the analysis only for the instantiations of a single query.
+    /// Dependencies on instantiations from earlier queries are still counted.
+    pub fn new_for_query(parser: &Z3Parser, inst_graph: &InstGraph, query: QueryIdx) -> Self {
+        Self::new_for_insts(parser, inst_graph, parser.query_items(query).insts())
+    }
+
+    fn new_for_insts(
+        parser: &Z3Parser,
+        inst_graph: &InstGraph,
+        insts: impl Iterator<Item = InstIdx>,
+    ) -> Self {
         let mut self_ = Self(
             parser
                 .quantifiers
@@ -39,7 +76,8 @@ impl QuantifierAnalysis {
                 .map(|_| QuantifierInfo::default())
                 .collect(),
         );
-        for (iidx, inst) in parser.insts.insts.iter_enumerated() {
+        for iidx in insts {
+            let inst = &parser.insts[iidx];
             let match_ = &parser.insts[inst.match_];
             let Some(qidx) = match_.kind.quant_idx() else {
                 continue;
@@ -47,13 +85,18 @@ impl QuantifierAnalysis {
             let qinfo = &mut self_.0[qidx];
             let ginst = &inst_graph.raw[iidx];
             qinfo.costs += ginst.cost;
-            for &parent_iidx in &ginst.inst_parents.nodes {
-                let parent_inst = &parser.insts[parent_iidx];
-                let parent_match_ = &parser.insts[parent_inst.match_];
-                let Some(parent_qidx) = parent_match_.kind.quant_idx() else {
-                    continue;
-                };
-                *qinfo.direct_deps.entry(parent_qidx).or_default() += 1;
+            qinfo.insts += 1;
+            let parent_qidxs = || {
+                ginst.inst_parents.nodes.iter().filter_map(|&parent_iidx| {
+                    let parent_inst = &parser.insts[parent_iidx];
+                    parser.insts[parent_inst.match_].kind.quant_idx()
+                })
+            };
+            let share = ginst.cost / parent_qidxs().count() as f64;
+            for parent_qidx in parent_qidxs() {
+                let dep = qinfo.direct_deps.entry(parent_qidx).or_default();
+                dep.count += 1;
+                dep.costs += share;
             }
         }
         self_
@@ -63,41 +106,219 @@ impl QuantifierAnalysis {
         self.iter().map(|info| info.costs).sum()
     }
 
-    pub fn calculate_transitive(&self, mut steps: Option<u32>) -> TransQuantAnalaysis {
-        let mut initial = self
-            .iter()
-            .map(|info| info.direct_deps.keys().copied().collect())
-            .collect();
-        while !steps.is_some_and(|steps| steps == 0) {
-            if !self.calculate_transitive_one(&mut initial) {
+    /// The quantifiers which each quantifier transitively depends on. With
+    /// `Some(steps)` only those at most `steps + 1` dependencies away are
+    /// included. A quantifier depends on itself only if it is part of a
+    /// cycle.
+    pub fn calculate_transitive(&self, steps: Option<u32>) -> TransQuantAnalaysis {
+        match steps {
+            Some(steps) => self
+                .keys()
+                .map(|qidx| self.deps_within(qidx, steps.saturating_add(1)))
+                .collect(),
+            None => self.graph().transitive_deps(),
+        }
+    }
+    fn deps_within(&self, qidx: QuantIdx, distance: u32) -> FxHashSet<QuantIdx> {
+        let mut deps = FxHashSet::default();
+        let mut frontier = vec![qidx];
+        for _ in 0..distance {
+            let mut next = Vec::new();
+            for qidx in frontier {
+                let new = self[qidx]
+                    .direct_deps
+                    .keys()
+                    .filter(|&&dep| deps.insert(dep));
+                next.extend(new);
+            }
+            if next.is_empty() {
                 break;
             }
-            if let Some(steps) = &mut steps {
-                *steps -= 1;
+            frontier = next;
+        }
+        deps
+    }
+
+    /// The quantifier-level dependency graph.
+    pub fn graph(&self) -> QuantifierGraph {
+        QuantifierGraph::new(self)
+    }
+}
+
+/// A node of the [`QuantifierGraph`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuantNode {
+    pub quant: QuantIdx,
+    pub insts: u32,
+    pub costs: f64,
+}
+
+/// The [`QuantifierAnalysis`] as a graph with one node per quantifier. There
+/// is an edge from `a` to `b` if an instantiation of `b` depends on an
+/// instantiation of `a`, i.e. `a` feeds `b`. A strongly connected component
+/// is a group of quantifiers which (potentially) recursively instantiate each
+/// other.
+pub struct QuantifierGraph {
+    pub graph: DiGraph<QuantNode, QuantDependency>,
+    /// The node of each quantifier, `None` for those which were not
+    /// instantiated and which no instantiation depends on.
+    pub nodes: TiVec<QuantIdx, Option<NodeIndex>>,
+    /// The cost of all quantifiers, see [`QuantifierAnalysis::total_costs`].
+    pub total_costs: f64,
+}
+
+impl QuantifierGraph {
+    pub fn new(analysis: &QuantifierAnalysis) -> Self {
+        let mut self_ = Self {
+            graph: DiGraph::with_capacity(0, 0),
+            nodes: analysis.iter().map(|_| None).collect(),
+            total_costs: analysis.total_costs(),
+        };
+        for (qidx, info) in analysis.iter_enumerated() {
+            if info.insts == 0 && info.direct_deps.is_empty() {
+                continue;
+            }
+            let node = self_.node(analysis, qidx);
+            for (&dep, &weight) in info.direct_deps.iter() {
+                let dep = self_.node(analysis, dep);
+                self_.graph.add_edge(dep, node, weight);
             }
         }
-        initial
+        self_
+    }
+
+    fn node(&mut self, analysis: &QuantifierAnalysis, quant: QuantIdx) -> NodeIndex {
+        *self.nodes[quant].get_or_insert_with(|| {
+            let info = &analysis[quant];
+            self.graph.add_node(QuantNode {
+                quant,
+                insts: info.insts,
+                costs: info.costs,
+            })
+        })
+    }
+
+    /// The strongly connected components which contain at least one cycle,
+    /// i.e. those with several quantifiers or with a quantifier feeding
+    /// itself. The quantifiers of each are sorted, the components by
+    /// decreasing cost.
+    pub fn cycles(&self) -> Vec<Vec<QuantIdx>> {
+        let mut sccs = tarjan_scc(&*self.graph);
+        sccs.retain(|scc| match scc.as_slice() {
+            [node] => self.graph.find_edge(*node, *node).is_some(),
+            _ => true,
+        });
+        let mut cycles: Vec<_> = sccs
+            .into_iter()
+            .map(|scc| {
+                let costs: f64 = scc.iter().map(|&node| self.graph[node].costs).sum();
+                let mut quants: Vec<_> =
+                    scc.into_iter().map(|node| self.graph[node].quant).collect();
+                quants.sort_unstable();
+                (costs, quants)
+            })
+            .collect();
+        cycles.sort_by(|l, r| r.0.total_cmp(&l.0).then_with(|| l.1.cmp(&r.1)));
+        cycles.into_iter().map(|(_, quants)| quants).collect()
+    }
+
+    /// The share of the total cost taken up by `costs`, as a percentage. 0 if
+    /// the total is (e.g. with the proof cost model) zero.
+    pub fn percentage(&self, costs: f64) -> f64 {
+        percentage(costs, self.total_costs)
     }
-    fn calculate_transitive_one(&self, analysis: &mut TransQuantAnalaysis) -> bool {
-        let mut changed = false;
-        for (idx, info) in self.iter_enumerated() {
-            for &ddep in info.direct_deps.keys() {
-                let (curr, ddep) = match idx.cmp(&ddep) {
-                    Ordering::Less => {
-                        let (left, right) = analysis.split_at_mut(ddep);
-                        (&mut left[idx], right.first().unwrap())
+
+    /// The graph in the DOT format of graphviz. Quantifiers in a cycle, and
+    /// the edges between them, are drawn in red. Edges are labelled with the
+    /// number of dependencies and the share of the cost of the dependent
+    /// quantifier blamed on them. `node_style` gives further attributes of
+    /// the node of each quantifier, e.g. its fill colour.
+    pub fn to_dot(&self, ctxt: &DisplayCtxt, node_style: impl Fn(QuantIdx) -> String) -> String {
+        let mut cycle = vec![None; self.graph.node_count()];
+        for (n, scc) in self.cycles().into_iter().enumerate() {
+            for qidx in scc {
+                cycle[self.nodes[qidx].unwrap().index()] = Some(n);
+            }
+        }
+        let escape = |s: String| s.replace('\\', "\\\\").replace('"', "\\\"");
+        format!(
+            "{:?}",
+            Dot::with_attr_getters(
+                &*self.graph,
+                &[Config::EdgeNoLabel, Config::NodeNoLabel],
+                &|g, edge| {
+                    let dep = edge.weight();
+                    let percentage = percentage(dep.costs, g[edge.target()].costs);
+                    let in_cycle = cycle[edge.source().index()]
+                        .is_some_and(|n| cycle[edge.target().index()] == Some(n));
+                    let colour = if in_cycle { " color=red" } else { "" };
+                    format!(
+                        "label=\"{}, {percentage:.1}%\" tooltip=\"{} dependencies, {percentage:.1}% of the cost of the target\"{colour}",
+                        dep.count, dep.count
+                    )
+                },
+                &|_, (node, data)| {
+                    let name = escape(ctxt.parser[data.quant].kind.with(ctxt).to_string());
+                    let percentage = self.percentage(data.costs);
+                    let colour = if cycle[node.index()].is_some() {
+                        " color=red penwidth=2"
+                    } else {
+                        ""
+                    };
+                    let mut style = node_style(data.quant);
+                    if !style.is_empty() {
+                        style.insert(0, ' ');
                     }
-                    Ordering::Greater => {
-                        let (left, right) = analysis.split_at_mut(idx);
-                        (right.first_mut().unwrap(), &left[ddep])
+                    format!(
+                        "label=\"{name}\\n{} instantiations, {percentage:.1}%\" shape=box{style}{colour}",
+                        data.insts,
+                    )
+                },
+            )
+        )
+    }
+
+    /// The quantifiers which each quantifier transitively depends on,
+    /// calculated once per strongly connected component.
+    pub fn transitive_deps(&self) -> TransQuantAnalaysis {
+        let mut deps: TransQuantAnalaysis =
+            self.nodes.iter().map(|_| FxHashSet::default()).collect();
+        let sccs = tarjan_scc(&*self.graph);
+        let mut scc_of = vec![0; self.graph.node_count()];
+        for (idx, scc) in sccs.iter().enumerate() {
+            for node in scc {
+                scc_of[node.index()] = idx;
+            }
+        }
+        // The components are in reverse topological order, so walking them
+        // backwards visits all dependencies of a component before it.
+        for (idx, scc) in sccs.iter().enumerate().rev() {
+            let mut scc_deps = FxHashSet::default();
+            for &node in scc {
+                for dep in self.graph.neighbors_directed(node, Direction::Incoming) {
+                    let dep_scc = scc_of[dep.index()];
+                    if dep_scc == idx {
+                        scc_deps.extend(scc.iter().map(|&node| self.graph[node].quant));
+                    } else {
+                        scc_deps.insert(self.graph[dep].quant);
+                        scc_deps.extend(&deps[self.graph[dep].quant]);
                     }
-                    Ordering::Equal => continue,
-                };
-                let old_len = curr.len();
-                curr.extend(ddep);
-                changed |= old_len != curr.len();
+                }
+            }
+            for &node in scc {
+                deps[self.graph[node].quant].clone_from(&scc_deps);
             }
         }
-        changed
+        deps
+    }
+}
+
+/// The share of `total` taken up by `part`, as a percentage. 0 if the total
+/// is (e.g. with the proof cost model) zero.
+pub fn percentage(part: f64, total: f64) -> f64 {
+    if total > 0.0 {
+        100.0 * part / total
+    } else {
+        0.0
     }
 }
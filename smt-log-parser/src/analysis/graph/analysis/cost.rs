@@ -1,11 +1,17 @@
+use std::str::FromStr;
+
+#[cfg(feature = "mem_dbg")]
+use mem_dbg::{MemDbg, MemSize};
 use petgraph::Direction;
+use roaring::RoaringBitmap;
 
 use crate::{
     analysis::{
         raw::{Node, NodeKind},
-        RawNodeIndex,
+        InstGraph, InstUsage, ProofAnalysis, RawNodeIndex,
     },
-    Z3Parser,
+    items::{InstIdx, TermIdx},
+    FxHashMap, TiVec, Z3Parser,
 };
 
 use super::{Initialiser, TransferInitialiser};
@@ -87,3 +93,193 @@ impl CostInitialiser for DefaultCost {
         node.cost * incoming[idx] as f64 / total
     }
 }
+
+/// Like [`DefaultCost`] but an instantiation starts out with the weight
+/// returned by the function rather than 1.
+pub struct WeightedCost<W>(pub W);
+impl<W: FnMut(InstIdx, &Z3Parser) -> f64> CostInitialiser for WeightedCost<W> {
+    fn base(&mut self, node: &Node, parser: &Z3Parser) -> f64 {
+        match node.kind() {
+            NodeKind::Instantiation(iidx) if !node.disabled() => (self.0)(*iidx, parser),
+            _ => 0.0,
+        }
+    }
+    type Observed = usize;
+    fn observe(&mut self, node: &Node, parser: &Z3Parser) -> Self::Observed {
+        CostInitialiser::observe(&mut DefaultCost, node, parser)
+    }
+    fn transfer(
+        &mut self,
+        node: &Node,
+        from_idx: RawNodeIndex,
+        idx: usize,
+        incoming: &[Self::Observed],
+    ) -> f64 {
+        CostInitialiser::transfer(&mut DefaultCost, node, from_idx, idx, incoming)
+    }
+}
+
+/// Which notion of "expensive" the cost of a node reflects. In all models
+/// the cost of an instantiation is propagated to the nodes it depends on,
+/// the models differ in what an instantiation costs on its own.
+#[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum CostModel {
+    /// Every instantiation costs 1, see [`DefaultCost`].
+    #[default]
+    Uniform,
+    /// The AST size of the terms yielded by the instantiation.
+    TermSize,
+    /// The number of e-nodes created by the instantiation.
+    ENodes,
+    /// The number of distinct instantiations reachable from a node
+    /// (including itself), not split between the parents of an instantiation.
+    /// Found with the transitive closure of the subgraph rather than
+    /// propagated, such that a descendant reachable along several paths is
+    /// counted once.
+    Descendants,
+    /// One more than the z3 generation of the instantiation, so that
+    /// instantiations deep into a chain weigh more.
+    Generation,
+    /// 1 for instantiations used in the proof of an unsat query and 0 for
+    /// all others, see [`ProofAnalysis`]. All costs are 0 if the log has no
+    /// proof.
+    Proof,
+}
+
+impl CostModel {
+    pub const ALL: [Self; 6] = [
+        Self::Uniform,
+        Self::TermSize,
+        Self::ENodes,
+        Self::Descendants,
+        Self::Generation,
+        Self::Proof,
+    ];
+
+    /// The name used on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Uniform => "uniform",
+            Self::TermSize => "term-size",
+            Self::ENodes => "enodes",
+            Self::Descendants => "descendants",
+            Self::Generation => "generation",
+            Self::Proof => "proof",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Uniform => "instantiations",
+            Self::TermSize => "size of yielded terms",
+            Self::ENodes => "e-nodes created",
+            Self::Descendants => "descendants",
+            Self::Generation => "z3 generation",
+            Self::Proof => "used in proof",
+        }
+    }
+}
+
+impl FromStr for CostModel {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|model| model.name() == s)
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::ALL.iter().map(|model| model.name()).collect();
+                format!(
+                    "unknown cost model `{s}`, expected one of {}",
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// The AST size of terms, memoised.
+#[derive(Default)]
+struct TermSizes(FxHashMap<TermIdx, f64>);
+
+impl TermSizes {
+    /// Walks the term without recursion, yielded terms may be deep.
+    fn term(&mut self, parser: &Z3Parser, term: TermIdx) -> f64 {
+        let mut stack = vec![term];
+        while let Some(&next) = stack.last() {
+            let children = &parser[next].child_ids;
+            if let Some(&child) = children.iter().find(|c| !self.0.contains_key(c)) {
+                stack.push(child);
+                continue;
+            }
+            let size = 1.0 + children.iter().map(|c| self.0[c]).sum::<f64>();
+            self.0.insert(next, size);
+            stack.pop();
+        }
+        self.0[&term]
+    }
+}
+
+impl InstGraph {
+    /// Recalculates the cost of all nodes with `model`, which is also used
+    /// by any later [`InstGraph::initialise_default`].
+    pub fn set_cost_model(&mut self, parser: &Z3Parser, model: CostModel) {
+        self.cost_model = model;
+        self.initialise_cost(parser);
+        self.analyse();
+    }
+
+    pub(super) fn initialise_cost(&mut self, parser: &Z3Parser) {
+        match self.cost_model {
+            CostModel::Uniform => self.initialise_transfer(DefaultCost, parser),
+            CostModel::TermSize => {
+                let mut sizes = TermSizes::default();
+                let weight = |iidx: InstIdx, parser: &Z3Parser| {
+                    let yields = parser[iidx].yields_terms.iter();
+                    yields
+                        .map(|&enode| sizes.term(parser, parser[enode].owner))
+                        .sum()
+                };
+                self.initialise_transfer(WeightedCost(weight), parser)
+            }
+            CostModel::ENodes => {
+                let weight =
+                    |iidx: InstIdx, parser: &Z3Parser| parser[iidx].yields_terms.len() as f64;
+                self.initialise_transfer(WeightedCost(weight), parser)
+            }
+            CostModel::Descendants => self.initialise_descendants(parser),
+            CostModel::Generation => {
+                let weight = |iidx: InstIdx, parser: &Z3Parser| {
+                    parser[iidx].z3_generation.unwrap_or(0) as f64 + 1.0
+                };
+                self.initialise_transfer(WeightedCost(weight), parser)
+            }
+            CostModel::Proof => {
+                let usage: TiVec<InstIdx, InstUsage> = ProofAnalysis::new(parser).usage;
+                let weight = |iidx: InstIdx, _: &Z3Parser| {
+                    (usage[iidx] == InstUsage::UsedInProof) as u8 as f64
+                };
+                self.initialise_transfer(WeightedCost(weight), parser)
+            }
+        }
+    }
+
+    fn initialise_descendants(&mut self, parser: &Z3Parser) {
+        for node in self.raw.graph.node_weights_mut() {
+            node.cost = CostInitialiser::base(&mut DefaultCost, node, parser);
+        }
+        for subgraph in self.subgraphs.iter() {
+            let insts: RoaringBitmap = subgraph
+                .nodes
+                .iter()
+                .enumerate()
+                .filter(|(_, &node)| self.raw[node].cost > 0.0)
+                .map(|(idx, _)| idx as u32)
+                .collect();
+            for (idx, &node) in subgraph.nodes.iter().enumerate() {
+                let descendants = subgraph.reach_fwd.reachable_among(idx as u32, &insts);
+                self.raw.graph[node.0].cost = descendants as f64;
+            }
+        }
+    }
+}
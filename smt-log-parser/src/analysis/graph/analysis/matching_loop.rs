@@ -4,12 +4,17 @@ use std::{
 };
 
 use fxhash::{FxHashMap, FxHashSet};
-use petgraph::{graph::NodeIndex, visit::Dfs, Direction::Outgoing};
+#[cfg(feature = "mem_dbg")]
+use mem_dbg::{MemDbg, MemSize};
+use petgraph::{
+    graph::NodeIndex,
+    Direction::{Incoming, Outgoing},
+};
 
-use super::RawNodeIndex;
+use super::trigger_graph::{QuantTrigger, TriggerGraph};
 use crate::{
     analysis::{
-        raw::{Node, NodeKind, RawIx},
+        raw::{NodeKind, RawIx},
         visible::VisibleEdge,
         InstGraph,
     },
@@ -151,51 +156,9 @@ impl AbstractInst {
                 .insert(n, MlEquality::from(from, to, creators));
         }
     }
-    pub fn _to_string(&self, compact: bool, parser: &mut Z3Parser, ctxt: DisplayCtxt) -> String {
-        let generalised_pattern = parser
-            .terms
-            .generalise_pattern(&mut parser.strings, self.id.1);
-        let matched_terms = self
-            .matched_terms
-            .values()
-            .map(|mterm| {
-                format!(
-                    "{} created by {}",
-                    mterm.matched.with(&ctxt),
-                    parser[mterm.creator.0].kind.with(&ctxt)
-                )
-            })
-            .collect::<Vec<String>>()
-            .join(", ");
-        let equalities = self
-            .equalities
-            .values()
-            .map(|meq| {
-                format!(
-                    "{} = {} created by {}",
-                    meq.from.with(&ctxt),
-                    meq.to.with(&ctxt),
-                    meq.creators
-                        .iter()
-                        .map(|cr| format!("{}", parser[cr.0].kind.with(&ctxt)))
-                        .collect::<Vec<String>>()
-                        .join(", ")
-                )
-            })
-            .collect::<Vec<String>>()
-            .join(", ");
-        if compact {
-            format!(
-                "{}: {}",
-                parser[self.id.0].kind.with(&ctxt),
-                generalised_pattern.with(&ctxt)
-            )
-        } else {
-            format!("Abstract Instantiation ({}, {}) \n has matched terms \n {} \n and \n equalities {}", parser[self.id.0].kind.with(&ctxt), generalised_pattern.with(&ctxt), matched_terms, equalities)
-        }
-    }
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, Eq, Hash, PartialEq)]
 pub enum MLGraphNode {
     QI(QuantIdx, TermIdx),
@@ -203,112 +166,166 @@ pub enum MLGraphNode {
     Equality(TermIdx, TermIdx),
 }
 
+/// A cycle of triggers in the [`TriggerGraph`] which is confirmed by a chain
+/// of concrete instantiations along which the terms grow.
+#[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone)]
+pub struct MatchingLoop {
+    /// Sorted, a single trigger if the loop does not involve any others.
+    pub triggers: Vec<QuantTrigger>,
+    /// The instantiations of `triggers` which blame, or are blamed by, another
+    /// one of them, in order.
+    pub insts: Vec<InstIdx>,
+    /// The longest chain of instantiations, from first to last.
+    pub chain: Vec<InstIdx>,
+    /// The summed cost of `insts`.
+    pub cost: f64,
+    /// The depth of the deepest bound or matched term of the first
+    /// instantiation of `chain`.
+    pub start_depth: u32,
+    /// As `start_depth` but for the last instantiation, always larger.
+    pub end_depth: u32,
+}
+
 impl InstGraph {
+    /// Finds all matching loops, including those which alternate between
+    /// several quantifiers, and ranks them by cost. Every cycle in the
+    /// [`TriggerGraph`] is a candidate, it is confirmed if its longest chain
+    /// of instantiations has at least [`MIN_MATCHING_LOOP_LENGTH`] links and
+    /// the terms at its end are deeper than those at its start.
+    ///
+    /// The terms matched by the first instantiation of a loop may have been
+    /// yielded by MBQI, theory-solving or reported instantiations. These have
+    /// no pattern and are left out of the loop's graph and explanation.
+    ///
+    /// Returns the number of matching loops found, the `n`th one is stored in
+    /// `analysis.matching_loops[n]` and its instantiations have `n` in their
+    /// `part_of_ml`.
     pub fn search_matching_loops(&mut self, parser: &mut Z3Parser) -> usize {
-        let currently_disabled_nodes = self.disabled_nodes();
         self.initialise_inst_succs_and_preds(parser);
-        // disable all nodes that do not correspond to QIs
-        self.reset_disabled_to(parser, |nx, g| {
-            !matches!(g[nx].kind(), NodeKind::Instantiation(_))
-        });
-        let quants: FxHashSet<_> = self
-            .raw
-            .graph
-            .node_weights()
-            .filter_map(|node| {
-                if let NodeKind::Instantiation(inst) = node.kind() {
-                    Some(inst)
-                } else {
-                    None
-                }
-            })
-            .flat_map(|inst| parser[parser[*inst].match_].kind.quant_idx())
-            .collect();
-        let mut matching_loop_nodes_per_quant: Vec<FxHashSet<RawNodeIndex>> = Vec::new();
-        for quant in quants {
-            self.raw.reset_visibility_to(true);
-            self.raw
-                .set_visibility_when(false, |_: RawNodeIndex, node: &Node| {
-                    node.kind()
-                        .inst()
-                        .is_some_and(|i| parser[parser[i].match_].kind.quant_idx() == Some(quant))
-                });
-            let mut visible_graph = self.to_visible_simplified();
-            let matching_loops = visible_graph.find_end_nodes_of_longest_paths();
-            matching_loop_nodes_per_quant.push(matching_loops);
+        for node in self.raw.graph.node_weights_mut() {
+            node.part_of_ml.clear();
         }
-        self.raw.reset_visibility_to(true);
-        let ml_nodes = matching_loop_nodes_per_quant
-            .iter()
-            .flat_map(|ml| ml.iter().cloned());
-        self.raw.set_visibility_many(false, ml_nodes);
-        let mut matching_loop_subgraph = self.to_visible_simplified();
-        // for displaying the nth longest matching loop later on, we want to compute the end nodes of all the matching loops
-        // and sort them by max-depth in descending order
-        matching_loop_subgraph.compute_longest_distances_from_roots();
-        // compute end-nodes of matching loops
-        let mut matching_loop_end_nodes: Vec<_> = matching_loop_subgraph
-            .graph
-            .node_indices()
-            // only keep end-points of matching loops, i.e., nodes without any children in the matching loop subgraph
-            .filter(|nx| {
-                matching_loop_subgraph
-                    .graph
-                    .neighbors_directed(*nx, Outgoing)
-                    .count()
-                    == 0
-            })
-            .collect();
-        // sort the matching loop end-nodes by the max-depth
-        matching_loop_end_nodes.sort_unstable_by(|node_a, node_b| {
-            let max_depth_node_a = matching_loop_subgraph
-                .graph
-                .node_weight(*node_a)
-                .unwrap()
-                .max_depth;
-            let max_depth_node_b = matching_loop_subgraph
-                .graph
-                .node_weight(*node_b)
-                .unwrap()
-                .max_depth;
-            if max_depth_node_a < max_depth_node_b {
-                std::cmp::Ordering::Greater
-            } else {
-                std::cmp::Ordering::Less
+        let loops = match TriggerGraph::new(self, parser) {
+            Ok(triggers) => self.confirm_matching_loops(parser, &triggers),
+            Err(err) => {
+                debug_assert!(err.is_allocation(), "{err:?}");
+                Vec::new()
             }
-        });
-        // assign to each node in a matching loop which matching loops it belongs to, i.e., if a node is part of the
-        // i-th longest matching loop, it stores the index i-1. Do this, by doing a reverse-DFS from all ML end nodes
-        for (i, end_node) in matching_loop_end_nodes.iter().enumerate() {
-            let mut dfs = Dfs::new(
-                petgraph::visit::Reversed(&matching_loop_subgraph.graph),
-                *end_node,
-            );
-            while let Some(nx) = dfs.next(petgraph::visit::Reversed(&matching_loop_subgraph.graph))
-            {
-                let orig_nx = matching_loop_subgraph.graph[nx].idx.0;
-                self.raw.graph[orig_nx].part_of_ml.insert(i);
+        };
+        for (i, ml) in loops.iter().enumerate() {
+            for &inst in &ml.insts {
+                self.raw[inst].part_of_ml.insert(i);
             }
         }
-        // collect all matching loop end nodes
-        let matching_loop_end_nodes_raw_indices: Vec<RawNodeIndex> = matching_loop_end_nodes
+        let end_nodes = loops
             .iter()
-            .map(|nidx| matching_loop_subgraph.graph[*nidx].idx)
+            .map(|ml| {
+                self.raw
+                    .index(NodeKind::Instantiation(*ml.chain.last().unwrap()))
+            })
             .collect();
-        // return the total number of potential matching loops
-        let nr_matching_loop_end_nodes = matching_loop_end_nodes_raw_indices.len();
-        self.analysis.matching_loop_end_nodes = Some(matching_loop_end_nodes_raw_indices);
-
-        // compute the ML graphs for all the potential matching loops
-        // first enable all of them
-        self.reset_disabled_to(parser, |_, _| false);
-        self.analysis.matching_loop_graphs = (0..nr_matching_loop_end_nodes)
+        let nr_matching_loops = loops.len();
+        self.analysis.matching_loop_end_nodes = Some(end_nodes);
+        self.analysis.matching_loops = loops;
+        self.analysis.matching_loop_graphs = (0..nr_matching_loops)
             .map(|n| self.compute_nth_matching_loop_graph(n, parser))
             .collect();
+        self.analysis.matching_loop_explanations = (0..nr_matching_loops)
+            .map(|n| self.compute_nth_matching_loop_explanation(n, parser))
+            .collect();
+        nr_matching_loops
+    }
 
-        // make sure the enabled and disabled nodes stay the same as before calling the ML search
-        self.reset_disabled_to(parser, |nx, _| currently_disabled_nodes.contains(&nx));
-        nr_matching_loop_end_nodes
+    /// Checks each cycle of `triggers`, in time linear in the number of
+    /// instantiations and the edges between them.
+    fn confirm_matching_loops(
+        &self,
+        parser: &Z3Parser,
+        triggers: &TriggerGraph,
+    ) -> Vec<MatchingLoop> {
+        let cycles = triggers.cycles();
+        let mut cycle_of = vec![None; triggers.graph.node_count()];
+        for (idx, cycle) in cycles.iter().enumerate() {
+            for node in cycle {
+                cycle_of[node.index()] = Some(idx);
+            }
+        }
+        let mut cycle_insts = vec![Vec::new(); cycles.len()];
+        for (iidx, node) in triggers.inst_nodes.iter_enumerated() {
+            if let Some(idx) = node.and_then(|node| cycle_of[node.index()]) {
+                cycle_insts[idx].push(iidx);
+            }
+        }
+        let mut depths = TermDepths::default();
+        let mut loops: Vec<_> = cycles
+            .into_iter()
+            .zip(cycle_insts)
+            .filter_map(|(cycle, insts)| {
+                let mut ml = self.longest_chain(&insts)?;
+                ml.start_depth = depths.inst(parser, ml.chain[0]);
+                ml.end_depth = depths.inst(parser, *ml.chain.last().unwrap());
+                if ml.end_depth <= ml.start_depth {
+                    return None;
+                }
+                ml.triggers = cycle.iter().map(|&node| triggers.graph[node]).collect();
+                Some(ml)
+            })
+            .collect();
+        loops.sort_by(|a, b| {
+            b.cost
+                .total_cmp(&a.cost)
+                .then_with(|| b.chain.len().cmp(&a.chain.len()))
+                .then_with(|| a.triggers.cmp(&b.triggers))
+        });
+        loops
+    }
+
+    /// The longest chain through the instantiations of a cycle, these are
+    /// sorted and an instantiation is always created after those it blames.
+    fn longest_chain(&self, insts: &[InstIdx]) -> Option<MatchingLoop> {
+        // The length of the longest chain ending in each instantiation and
+        // the previous instantiation in that chain.
+        let mut chains: FxHashMap<InstIdx, (usize, Option<InstIdx>)> = HashMap::default();
+        let mut looping = FxHashSet::default();
+        let mut longest: Option<(usize, InstIdx)> = None;
+        for &iidx in insts {
+            let prev = self.raw[iidx]
+                .inst_parents
+                .nodes
+                .iter()
+                .filter_map(|parent| Some((chains.get(parent)?.0, *parent)))
+                .max();
+            if let Some((_, parent)) = prev {
+                looping.insert(parent);
+                looping.insert(iidx);
+            }
+            let len = prev.map_or(1, |(len, _)| len + 1);
+            chains.insert(iidx, (len, prev.map(|(_, parent)| parent)));
+            if longest.map_or(true, |(longest, _)| len > longest) {
+                longest = Some((len, iidx));
+            }
+        }
+        let (len, end) = longest.filter(|(len, _)| *len >= MIN_MATCHING_LOOP_LENGTH)?;
+        let mut chain = Vec::with_capacity(len);
+        let mut next = Some(end);
+        while let Some(iidx) = next {
+            chain.push(iidx);
+            next = chains[&iidx].1;
+        }
+        chain.reverse();
+        let mut insts: Vec<_> = looping.into_iter().collect();
+        insts.sort_unstable();
+        let cost = insts.iter().map(|&iidx| self.raw[iidx].cost).sum();
+        Some(MatchingLoop {
+            triggers: Vec::new(),
+            insts,
+            chain,
+            cost,
+            start_depth: 0,
+            end_depth: 0,
+        })
     }
 
     pub fn found_matching_loops(&self) -> Option<usize> {
@@ -326,6 +343,91 @@ impl InstGraph {
         }
     }
 
+    /// Summarises the `n`th matching loop graph link by link, see
+    /// [`MatchingLoopExplanation`].
+    pub fn compute_nth_matching_loop_explanation(
+        &self,
+        n: usize,
+        parser: &mut Z3Parser,
+    ) -> MatchingLoopExplanation {
+        let ml = &self.analysis.matching_loops[n];
+        let ml_graph = &self.analysis.matching_loop_graphs[n];
+        // The graph only has the generalised patterns, map those of the loop
+        // back to the original ones so that they can be displayed with the
+        // names of the quantified variables.
+        let original: FxHashMap<_, _> = ml
+            .triggers
+            .iter()
+            .map(|trigger| {
+                let generalised = parser
+                    .terms
+                    .generalise_pattern(&mut parser.strings, trigger.pattern);
+                ((trigger.quant, generalised), trigger.pattern)
+            })
+            .collect();
+        let trigger = |node: NodeIndex| match ml_graph[node] {
+            MLGraphNode::QI(quant, pattern) => Some(QuantTrigger {
+                quant,
+                pattern: original.get(&(quant, pattern)).copied().unwrap_or(pattern),
+            }),
+            _ => None,
+        };
+        let mut explanation = MatchingLoopExplanation {
+            triggers: ml.triggers.clone(),
+            ..Default::default()
+        };
+        for node in ml_graph.node_indices() {
+            let (matched, equality) = match ml_graph[node] {
+                MLGraphNode::QI(..) => continue,
+                MLGraphNode::ENode(matched) => {
+                    explanation.matched.push(matched);
+                    (Some(matched), None)
+                }
+                MLGraphNode::Equality(from, to) => {
+                    explanation.equalities.push(MlEqualityTerms { from, to });
+                    (None, Some(MlEqualityTerms { from, to }))
+                }
+            };
+            for creator in ml_graph.neighbors_directed(node, Incoming) {
+                let Some(from) = trigger(creator) else {
+                    continue;
+                };
+                for user in ml_graph.neighbors_directed(node, Outgoing) {
+                    let Some(to) = trigger(user) else {
+                        continue;
+                    };
+                    let steps = &mut explanation.steps;
+                    let idx = match steps
+                        .iter()
+                        .position(|s| s.from == from.quant && s.to == to)
+                    {
+                        Some(idx) => idx,
+                        None => {
+                            steps.push(MatchingLoopStep {
+                                from: from.quant,
+                                to,
+                                matched: Vec::new(),
+                                equalities: Vec::new(),
+                            });
+                            steps.len() - 1
+                        }
+                    };
+                    steps[idx].matched.extend(matched);
+                    steps[idx].equalities.extend(equality);
+                }
+            }
+        }
+        // Links within the loop first, in the order of its triggers.
+        let order = |quant: QuantIdx| {
+            let position = ml.triggers.iter().position(|t| t.quant == quant);
+            position.unwrap_or(usize::MAX)
+        };
+        explanation
+            .steps
+            .sort_by_key(|step| (order(step.to.quant).max(order(step.from)), order(step.from)));
+        explanation
+    }
+
     fn _get_blame_term(&self, edge: &VisibleEdge, parser: &Z3Parser) -> Option<TermIdx> {
         let kind = edge.kind(self);
         let node = &self.raw[self.raw.index(kind.blame(self))];
@@ -375,8 +477,13 @@ impl InstGraph {
                     let creator = parser[*matched_term].created_by;
                     if let Some(inst) = creator {
                         let match_ = &parser[parser[inst].match_];
-                        let creator_pattern = match_.kind.pattern().unwrap();
-                        let creator_quant = match_.kind.quant_idx().unwrap();
+                        // MBQI, theory-solving and reported instantiations
+                        // have no pattern to blame.
+                        let (Some(creator_pattern), Some(creator_quant)) =
+                            (match_.kind.pattern(), match_.kind.quant_idx())
+                        else {
+                            continue;
+                        };
                         let blame_term = parser[*matched_term].owner;
                         if let Some(abstract_inst) = abstract_insts.get_mut(&(quant, pattern)) {
                             abstract_inst.merge_nth_blame_term(
@@ -408,14 +515,8 @@ impl InstGraph {
                             .get_creator_insts(parser)
                             .iter()
                             .filter_map(|iidx| {
-                                if let Some(inst) = iidx {
-                                    let match_ = &parser[parser[*inst].match_];
-                                    let creator_pattern = match_.kind.pattern().unwrap();
-                                    let creator_quant = match_.kind.quant_idx().unwrap();
-                                    Some((creator_quant, creator_pattern))
-                                } else {
-                                    None
-                                }
+                                let match_ = &parser[parser[(*iidx)?].match_];
+                                Some((match_.kind.quant_idx()?, match_.kind.pattern()?))
                             })
                             .collect();
                         if !creator_insts.is_empty() {
@@ -569,6 +670,139 @@ impl InstGraph {
     }
 }
 
+/// The AST depth of terms, memoised.
+#[derive(Default)]
+struct TermDepths(FxHashMap<TermIdx, u32>);
+
+impl TermDepths {
+    /// The depth of the deepest bound or matched term of `inst`.
+    fn inst(&mut self, parser: &Z3Parser, inst: InstIdx) -> u32 {
+        let match_ = &parser[parser[inst].match_];
+        let bound = match_
+            .kind
+            .bound_terms(|enode| parser[enode].owner, |term| term);
+        let matched = match_
+            .trigger_matches()
+            .map(|blame| parser[blame.enode()].owner);
+        bound
+            .into_iter()
+            .chain(matched)
+            .map(|term| self.term(parser, term))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Walks the term without recursion, matching loops create deep terms.
+    fn term(&mut self, parser: &Z3Parser, term: TermIdx) -> u32 {
+        let mut stack = vec![term];
+        while let Some(&next) = stack.last() {
+            let children = &parser[next].child_ids;
+            if let Some(&child) = children.iter().find(|c| !self.0.contains_key(c)) {
+                stack.push(child);
+                continue;
+            }
+            let depth = 1 + children.iter().map(|c| self.0[c]).max().unwrap_or(0);
+            self.0.insert(next, depth);
+            stack.pop();
+        }
+        self.0[&term]
+    }
+}
+
+/// A generalised equality `from = to`.
+#[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MlEqualityTerms {
+    pub from: TermIdx,
+    pub to: TermIdx,
+}
+
+/// One link of a matching loop: instantiations of `from` yield the
+/// `matched` terms and `equalities` which let the trigger `to` match.
+#[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone)]
+pub struct MatchingLoopStep {
+    pub from: QuantIdx,
+    /// The pattern is only generalised if `to` is not one of the triggers of
+    /// the loop.
+    pub to: QuantTrigger,
+    /// Generalised, empty if the link is only through equalities.
+    pub matched: Vec<TermIdx>,
+    pub equalities: Vec<MlEqualityTerms>,
+}
+
+/// A human-readable summary of a matching loop graph.
+#[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Default)]
+pub struct MatchingLoopExplanation {
+    /// See [`MatchingLoop::triggers`].
+    pub triggers: Vec<QuantTrigger>,
+    /// The generalised terms matched by the triggers.
+    pub matched: Vec<TermIdx>,
+    /// The generalised equalities which the matches needed.
+    pub equalities: Vec<MlEqualityTerms>,
+    /// The links within the loop come first.
+    pub steps: Vec<MatchingLoopStep>,
+}
+
+impl MatchingLoopExplanation {
+    /// One sentence per step, e.g. "q1 yields `f(g(_))`, which matches
+    /// trigger `{ f(x) }` of q2".
+    pub fn sentences(&self, ctxt: &DisplayCtxt) -> Vec<String> {
+        let code = |s: String| {
+            if ctxt.config.html() {
+                format!("<code>{s}</code>")
+            } else {
+                format!("`{s}`")
+            }
+        };
+        let join = |items: Vec<String>| match items.split_last() {
+            Some((last, rest)) if !rest.is_empty() => format!("{} and {last}", rest.join(", ")),
+            _ => items.concat(),
+        };
+        self.steps
+            .iter()
+            .map(|step| {
+                let from = ctxt.parser[step.from].kind.with(ctxt);
+                let to = ctxt.parser[step.to.quant].kind.with(ctxt);
+                let mut quant = Some(step.to.quant);
+                let trigger = code(step.to.pattern.with_data(ctxt, &mut quant).to_string());
+                let equality = if step.equalities.len() == 1 {
+                    "equality"
+                } else {
+                    "equalities"
+                };
+                let equalities = step
+                    .equalities
+                    .iter()
+                    .map(|eq| code(format!("{} = {}", eq.from.with(ctxt), eq.to.with(ctxt))))
+                    .collect();
+                let equalities = join(equalities);
+                if step.matched.is_empty() {
+                    return format!(
+                        "{from} yields {equality} {equalities}, which lets trigger {trigger} of {to} match"
+                    );
+                }
+                let matched = step
+                    .matched
+                    .iter()
+                    .map(|term| code(term.with(ctxt).to_string()))
+                    .collect();
+                let matched = join(matched);
+                let via = if step.equalities.is_empty() {
+                    String::new()
+                } else {
+                    format!(" via {equality} {equalities}")
+                };
+                format!("{from} yields {matched}, which matches trigger {trigger} of {to}{via}")
+            })
+            .collect()
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum InstOrEquality {
     Inst(String, MatchKind),
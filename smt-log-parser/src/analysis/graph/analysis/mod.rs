@@ -2,6 +2,7 @@ pub mod cost;
 pub mod depth;
 pub mod matching_loop;
 pub mod next_insts;
+pub mod trigger_graph;
 
 #[cfg(feature = "mem_dbg")]
 use mem_dbg::{MemDbg, MemSize};
@@ -10,13 +11,15 @@ use petgraph::Direction;
 use crate::{Graph, Result, Z3Parser};
 
 use self::{
-    cost::DefaultCost, depth::DefaultDepth, matching_loop::MLGraphNode,
+    depth::DefaultDepth,
+    matching_loop::{MLGraphNode, MatchingLoop, MatchingLoopExplanation},
     next_insts::DefaultNextInsts,
 };
 
 use super::{raw::Node, InstGraph, RawNodeIndex};
 
 #[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Default)]
 pub struct Analysis {
     // Highest to lowest
@@ -28,7 +31,10 @@ pub struct Analysis {
     // // Most to least
     // pub(super) max_depth: Vec<RawNodeIndex>,
     pub matching_loop_end_nodes: Option<Vec<RawNodeIndex>>,
+    /// Sorted by decreasing cost, see [`InstGraph::search_matching_loops`].
+    pub matching_loops: Vec<MatchingLoop>,
     pub matching_loop_graphs: Vec<Graph<MLGraphNode, ()>>,
+    pub matching_loop_explanations: Vec<MatchingLoopExplanation>,
 }
 
 impl Analysis {
@@ -50,7 +56,9 @@ impl Analysis {
             children,
             fwd_depth_min,
             matching_loop_end_nodes: None,
+            matching_loops: Vec::new(),
             matching_loop_graphs: vec![],
+            matching_loop_explanations: Vec::new(),
         })
     }
 }
@@ -141,7 +149,7 @@ impl InstGraph {
         }
     }
     pub fn initialise_default(&mut self, parser: &Z3Parser) {
-        self.initialise_transfer(DefaultCost, parser);
+        self.initialise_cost(parser);
         self.initialise_collect(DefaultDepth::<true>, parser);
         self.initialise_collect(DefaultDepth::<false>, parser);
 
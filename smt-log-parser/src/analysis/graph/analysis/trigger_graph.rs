@@ -0,0 +1,89 @@
+#[cfg(feature = "mem_dbg")]
+use mem_dbg::{MemDbg, MemSize};
+use petgraph::{algo::tarjan_scc, graph::NodeIndex};
+
+use crate::{
+    analysis::InstGraph,
+    items::{InstIdx, QuantIdx, TermIdx},
+    DiGraph, FxHashMap, Result, TiVec, Z3Parser,
+};
+
+/// A quantifier together with one of its triggers. All instantiations
+/// through the same trigger are folded into a single node of the
+/// [`TriggerGraph`].
+#[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct QuantTrigger {
+    pub quant: QuantIdx,
+    pub pattern: TermIdx,
+}
+
+/// An abstraction of the instantiation graph with one node per
+/// [`QuantTrigger`]. There is an edge from `a` to `b` if an instantiation of
+/// `a` yielded a term (or equality) blamed by an instantiation of `b`, the
+/// weight is the number of such pairs of instantiations. A matching loop
+/// shows up as a cycle in this graph.
+pub struct TriggerGraph {
+    pub graph: DiGraph<QuantTrigger, u32>,
+    /// The node of each instantiation, `None` for those which do not match
+    /// a trigger (e.g. theory-solving or MBQI).
+    pub inst_nodes: TiVec<InstIdx, Option<NodeIndex>>,
+}
+
+impl TriggerGraph {
+    /// Requires the `inst_parents` of `graph` to have been initialised with
+    /// [`InstGraph::initialise_inst_succs_and_preds`].
+    pub fn new(graph: &InstGraph, parser: &Z3Parser) -> Result<Self> {
+        let mut self_ = Self {
+            graph: DiGraph::with_capacity(0, 0),
+            inst_nodes: TiVec::default(),
+        };
+        let mut nodes = FxHashMap::default();
+        self_.inst_nodes.raw.try_reserve(parser.insts.insts.len())?;
+        for inst in parser.insts.insts.iter() {
+            let kind = &parser[inst.match_].kind;
+            let node = kind
+                .quant_idx()
+                .zip(kind.pattern())
+                .map(|(quant, pattern)| {
+                    *nodes
+                        .entry(QuantTrigger { quant, pattern })
+                        .or_insert_with_key(|&trigger| self_.graph.add_node(trigger))
+                });
+            self_.inst_nodes.push(node);
+        }
+        for (iidx, node) in self_.inst_nodes.iter_enumerated() {
+            let Some(node) = *node else {
+                continue;
+            };
+            for &parent in &graph.raw[iidx].inst_parents.nodes {
+                let Some(parent) = self_.inst_nodes[parent] else {
+                    continue;
+                };
+                match self_.graph.find_edge(parent, node) {
+                    Some(edge) => self_.graph[edge] += 1,
+                    None => {
+                        self_.graph.add_edge(parent, node, 1);
+                    }
+                }
+            }
+        }
+        Ok(self_)
+    }
+
+    /// The strongly connected components which contain at least one cycle,
+    /// i.e. those with several triggers or with a trigger feeding itself.
+    /// The triggers of each are sorted.
+    pub fn cycles(&self) -> Vec<Vec<NodeIndex>> {
+        let mut sccs = tarjan_scc(&*self.graph);
+        sccs.retain(|scc| match scc.as_slice() {
+            [node] => self.graph.find_edge(*node, *node).is_some(),
+            _ => true,
+        });
+        for scc in &mut sccs {
+            scc.sort_unstable_by_key(|&node| self.graph[node]);
+        }
+        sccs
+    }
+}
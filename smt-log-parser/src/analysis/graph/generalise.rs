@@ -1,10 +1,25 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{ToPrimitive, Zero};
+
 use crate::{
-    items::{Meaning, Term, TermIdx, TermKind},
+    items::{Generalisation, Meaning, ProofOrApp, TermIdx, TermKind, Value},
     parsers::z3::terms::Terms,
-    IString, StringTable,
+    StringTable,
 };
 
+/// The longest chain of functions which [`Terms::generalise`] looks for
+/// repetitions of, e.g. `f(g(_))` has length 2.
+pub const MAX_ITERATED_CHAIN: usize = 4;
+
 impl Terms {
+    /// Anti-unifies the terms: the result agrees with all of them wherever
+    /// they agree and has a [`TermKind::Generalised`] wherever they differ.
+    /// Repeating structure is kept, e.g. `x`, `f(x)` and `f(f(x))` are
+    /// generalised to `f^k(x)` and `1`, `2` and `3` to `1 + k`, see
+    /// [`Generalisation`]. Generalised terms may be passed in again, so
+    /// that terms can be added one at a time. Returns `None` if the terms
+    /// have nothing in common.
     pub fn generalise(
         &mut self,
         strings: &mut StringTable,
@@ -27,9 +42,11 @@ impl Terms {
         }
 
         let mut next = terms;
-        let mut stack: Vec<(Vec<&'static Term>, Option<Meaning>, Vec<TermIdx>)> = vec![];
+        // The terms being generalised, their meaning and the generalised
+        // children so far. Terms are referred to by index since generalising
+        // creates new terms, which may move the existing ones.
+        let mut stack: Vec<(Vec<TermIdx>, Option<Meaning>, Vec<TermIdx>)> = vec![];
         loop {
-            let deref: Vec<&'static Term>;
             if check(next.iter(), |t1, t2| t1 == t2) {
                 // if terms are equal, no need to generalize
                 assert!(!next.is_empty(), "generalise called with empty terms");
@@ -37,49 +54,71 @@ impl Terms {
                     return Some(next[0]);
                 };
                 children.push(next[0]);
-            } else if check(next.iter().map(|t| self.meaning(*t)), |m1, m2| m1 == m2) && {
-                let d = next.iter().map(|t| &self[*t]).collect();
-                deref = unsafe { std::mem::transmute::<Vec<&Term>, Vec<&'static Term>>(d) };
-                check(deref.iter(), |t1, t2| {
-                    t1.kind == t2.kind && t1.child_ids.len() == t2.child_ids.len()
+            } else if check(next.iter().copied(), |t1, t2| self.same_meaning(t1, t2))
+                && check(next.iter().map(|t| &self[*t]), |t1, t2| {
+                    t1.kind == t2.kind
+                        && t1.child_ids.len() == t2.child_ids.len()
+                        && match t1.kind {
+                            // Only the bases of the same chain line up.
+                            TermKind::Generalised(Generalisation::Iterated) => {
+                                t1.child_ids[0] == t2.child_ids[0]
+                            }
+                            TermKind::Generalised(_) => false,
+                            _ => true,
+                        }
                 })
-            } {
+            {
                 // if neither term is generalized, check the meanings and kinds and recurse over children
                 let meaning = self.meaning(next[0]).copied();
-                stack.push((deref, meaning, vec![]));
+                stack.push((next, meaning, vec![]));
             } else {
                 // if meanings or kinds don't match up, need to generalize
-                let (_, _, children) = stack.last_mut()?;
-                let meaning = self.try_find_meaning(strings, &next);
-                let tidx = self.new_synthetic_term(
-                    TermKind::Generalised,
-                    next.into_boxed_slice(),
-                    meaning,
-                );
+                let repeating = self.find_repeating(strings, &next);
+                let Some((_, _, children)) = stack.last_mut() else {
+                    return repeating;
+                };
+                let tidx = repeating.unwrap_or_else(|| {
+                    let kind = TermKind::Generalised(Generalisation::Opaque);
+                    self.new_synthetic_term(kind, next.into_boxed_slice(), None)
+                });
                 children.push(tidx);
             }
 
-            let (mut deref, mut meaning, mut children) = stack.pop().unwrap();
-            while deref[0].child_ids.len() == children.len() {
-                let tidx =
-                    self.new_synthetic_term(deref[0].kind, children.into_boxed_slice(), meaning);
-                let Some((new_deref, new_meaning, new_children)) = stack.pop() else {
+            let (mut terms, mut meaning, mut children) = stack.pop().unwrap();
+            while self[terms[0]].child_ids.len() == children.len() {
+                let kind = self[terms[0]].kind;
+                let tidx = self.new_synthetic_term(kind, children.into_boxed_slice(), meaning);
+                let Some((new_terms, new_meaning, new_children)) = stack.pop() else {
                     return Some(tidx);
                 };
-                (deref, meaning, children) = (new_deref, new_meaning, new_children);
+                (terms, meaning, children) = (new_terms, new_meaning, new_children);
                 children.push(tidx);
             }
-            next = deref.iter().map(|t| t.child_ids[children.len()]).collect();
-            stack.push((deref, meaning, children));
+            next = terms
+                .iter()
+                .map(|t| self[*t].child_ids[children.len()])
+                .collect();
+            stack.push((terms, meaning, children));
+        }
+    }
+
+    /// Whether the terms have the same meaning, values which are written
+    /// differently (e.g. `-3` and `(- 3)`) but decode to the same value are
+    /// the same constant.
+    fn same_meaning(&self, t1: TermIdx, t2: TermIdx) -> bool {
+        match (self.value(t1), self.value(t2)) {
+            (Some(v1), Some(v2)) => v1 == v2,
+            _ => self.meaning(t1) == self.meaning(t2),
         }
     }
 
     pub fn generalise_pattern(&mut self, _strings: &mut StringTable, pattern: TermIdx) -> TermIdx {
         match self[pattern].kind {
             TermKind::Var(_) => {
-                self.new_synthetic_term(TermKind::Generalised, Default::default(), None)
+                let kind = TermKind::Generalised(Generalisation::Opaque);
+                self.new_synthetic_term(kind, Default::default(), None)
             }
-            TermKind::Generalised => pattern,
+            TermKind::Generalised(_) => pattern,
             _ => {
                 let children = Vec::from(self[pattern].child_ids.clone())
                     .into_iter()
@@ -94,18 +133,204 @@ impl Terms {
         }
     }
 
-    pub fn try_find_meaning(
-        &self,
+    /// Tries to generalise terms which differ at the root to a
+    /// [`Generalisation::Iterated`] or [`Generalisation::Progression`].
+    fn find_repeating(&mut self, strings: &mut StringTable, terms: &[TermIdx]) -> Option<TermIdx> {
+        self.find_iterated(strings, terms)
+            .or_else(|| self.find_progression(terms))
+    }
+
+    /// Each term is written as some number of repetitions of a chain of
+    /// unary functions applied to a base, if the numbers differ the result
+    /// is the chain iterated `k` times on the generalised bases. Chains of
+    /// up to [`MAX_ITERATED_CHAIN`] functions are tried, shortest first,
+    /// and the first one after which all bases are the same wins.
+    fn find_iterated(&mut self, strings: &mut StringTable, terms: &[TermIdx]) -> Option<TermIdx> {
+        let chains: Vec<Vec<ProofOrApp>> = match terms
+            .iter()
+            .find(|&&t| self[t].kind == TermKind::Generalised(Generalisation::Iterated))
+        {
+            Some(&iterated) => vec![self.unary_chain(self[iterated].child_ids[0])],
+            None => {
+                let longest = terms
+                    .iter()
+                    .map(|&t| self.unary_chain(t))
+                    .max_by_key(Vec::len)?;
+                (1..=longest.len().min(MAX_ITERATED_CHAIN))
+                    .map(|len| longest[..len].to_vec())
+                    .collect()
+            }
+        };
+        let mut fallback = None;
+        for chain in chains {
+            if chain.is_empty() {
+                continue;
+            }
+            let (counts, bases): (Vec<_>, Vec<_>) =
+                terms.iter().map(|&t| self.peel_chain(t, &chain)).unzip();
+            // The same number of repetitions everywhere is not a
+            // repetition, the terms differ below them.
+            if counts.iter().all(|&c| c.is_some() && c == counts[0]) {
+                continue;
+            }
+            if bases.iter().all(|&b| b == bases[0]) {
+                return Some(self.new_iterated(strings, &chain, bases));
+            }
+            fallback.get_or_insert((chain, bases));
+        }
+        let (chain, bases) = fallback?;
+        Some(self.new_iterated(strings, &chain, bases))
+    }
+
+    /// The unary applications at the root of the term, outermost first.
+    /// Stops at a [`Generalisation::Opaque`] so that this also gives the
+    /// chain of a [`Generalisation::Iterated`].
+    fn unary_chain(&self, mut term: TermIdx) -> Vec<ProofOrApp> {
+        let mut chain = Vec::new();
+        while let TermKind::ProofOrApp(f) = self[term].kind {
+            if self[term].child_ids.len() != 1 || self.meaning(term).is_some() {
+                break;
+            }
+            chain.push(f);
+            term = self[term].child_ids[0];
+        }
+        chain
+    }
+
+    /// Removes as many repetitions of `chain` as possible from the root of
+    /// `term`. Returns their number, `None` if it went through an
+    /// [`Generalisation::Iterated`] of the same chain and so stands for any
+    /// number, and the remaining base.
+    fn peel_chain(&self, mut term: TermIdx, chain: &[ProofOrApp]) -> (Option<u32>, TermIdx) {
+        let mut count = Some(0_u32);
+        loop {
+            if self[term].kind == TermKind::Generalised(Generalisation::Iterated) {
+                let children = &self[term].child_ids;
+                if self.unary_chain(children[0]) == chain {
+                    count = None;
+                    term = children[1];
+                    continue;
+                }
+            }
+            let mut inner = term;
+            for &f in chain {
+                match self[inner].kind {
+                    TermKind::ProofOrApp(g)
+                        if g == f
+                            && self[inner].child_ids.len() == 1
+                            && self.meaning(inner).is_none() =>
+                    {
+                        inner = self[inner].child_ids[0];
+                    }
+                    _ => return (count, term),
+                }
+            }
+            count = count.map(|c| c + 1);
+            term = inner;
+        }
+    }
+
+    fn new_iterated(
+        &mut self,
         strings: &mut StringTable,
-        _terms: &[TermIdx],
-    ) -> Option<Meaning> {
-        let value = String::from("_");
-        // TODO: it would be nice here to try and find the repeating pattern,
-        // e.g. if we have `x`, `f(x)`, `f(f(x))`, ... we could generalise to `f^*(c)`.
+        chain: &[ProofOrApp],
+        bases: Vec<TermIdx>,
+    ) -> TermIdx {
+        let base = self.generalise(strings, bases.clone()).unwrap_or_else(|| {
+            let kind = TermKind::Generalised(Generalisation::Opaque);
+            self.new_synthetic_term(kind, bases.into_boxed_slice(), None)
+        });
+        let kind = TermKind::Generalised(Generalisation::Opaque);
+        let hole = self.new_synthetic_term(kind, Default::default(), None);
+        let chain = chain.iter().rev().fold(hole, |inner, &f| {
+            self.new_synthetic_term(TermKind::ProofOrApp(f), Box::new([inner]), None)
+        });
+        let kind = TermKind::Generalised(Generalisation::Iterated);
+        self.new_synthetic_term(kind, Box::new([chain, base]), None)
+    }
 
-        (value != "_").then(|| Meaning {
-            theory: IString(strings.get_or_intern("generalisation")),
-            value: IString(strings.get_or_intern(value)),
-        })
+    /// Integers (or progressions found earlier) whose values together form
+    /// an arithmetic progression.
+    fn find_progression(&mut self, terms: &[TermIdx]) -> Option<TermIdx> {
+        // The first, second and last integer of each term, all the same for
+        // an integer.
+        let mut parts = Vec::new();
+        for &term in terms {
+            let part = match self[term].kind {
+                TermKind::Generalised(Generalisation::Progression) => {
+                    let &[first, second, last] = &*self[term].child_ids else {
+                        return None;
+                    };
+                    [first, second, last]
+                }
+                _ => [term; 3],
+            };
+            let part = part.map(|t| match self.value(t) {
+                Some(Value::Int(value)) => Some(((**value).clone(), t)),
+                _ => None,
+            });
+            let [Some(first), Some(second), Some(last)] = part else {
+                return None;
+            };
+            parts.push([first, second, last]);
+        }
+        parts.sort_by(|l, r| l[0].0.cmp(&r[0].0));
+
+        // Any progression containing all the parts has a step dividing
+        // their steps and the distances between them.
+        let start = &parts[0][0].0;
+        let step = parts
+            .iter()
+            .fold(BigInt::zero(), |step, [first, second, _]| {
+                step.gcd(&(&second.0 - &first.0)).gcd(&(&first.0 - start))
+            });
+        if step.is_zero() {
+            return None;
+        }
+        // The parts as the index of their first and last integer in the
+        // progression and their stride.
+        let index = |value: &BigInt| ((value - start) / &step).to_usize();
+        let lattice = parts
+            .iter()
+            .map(|[first, second, last]| {
+                let stride = index(&(start + &second.0 - &first.0))?;
+                Some((index(&first.0)?, stride.max(1), index(&last.0)?))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let len = lattice.iter().map(|&(_, _, last)| last).max()? + 1;
+        // Too few integers to fill the progression, this also bounds the
+        // work done below.
+        let counts = lattice
+            .iter()
+            .fold(0_usize, |counts, &(first, stride, last)| {
+                counts.saturating_add((last - first) / stride + 1)
+            });
+        if counts < len {
+            return None;
+        }
+        let mut covered = vec![false; len];
+        for &(first, stride, last) in &lattice {
+            (first..=last)
+                .step_by(stride)
+                .for_each(|idx| covered[idx] = true);
+        }
+        if !covered.iter().all(|&c| c) {
+            return None;
+        }
+
+        let second_value = start + &step;
+        let second = parts.iter().find_map(|[first, second, _]| {
+            let second = if first.0 == second_value {
+                first
+            } else {
+                second
+            };
+            (second.0 == second_value).then_some(second.1)
+        })?;
+        let end = start + &step * (len - 1);
+        let last = parts.iter().find(|part| part[2].0 == end)?[2].1;
+        let children = Box::new([parts[0][0].1, second, last]);
+        let kind = TermKind::Generalised(Generalisation::Progression);
+        Some(self.new_synthetic_term(kind, children, None))
     }
 }
@@ -5,8 +5,6 @@ use crate::{items::GraphIdx, Result, TiVec, Z3Parser};
 
 use self::{analysis::Analysis, raw::RawInstGraph, subgraph::Subgraph, visible::VisibleInstGraph};
 
-// TODO: once the ML algo is reimplemented, delete this
-// pub mod inst_graph;
 pub mod analysis;
 pub mod disable;
 pub mod generalise;
@@ -15,15 +13,19 @@ pub mod raw;
 pub mod subgraph;
 pub mod visible;
 
+pub use analysis::cost::CostModel;
 pub use raw::{RawEdgeIndex, RawNodeIndex};
 pub use visible::{VisibleEdgeIndex, VisibleNodeIndex};
 
 #[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug)]
 pub struct InstGraph {
     pub raw: RawInstGraph,
     pub subgraphs: TiVec<GraphIdx, Subgraph>,
     pub analysis: Analysis,
+    /// See [`InstGraph::set_cost_model`].
+    pub cost_model: CostModel,
 }
 
 impl InstGraph {
@@ -35,6 +37,7 @@ impl InstGraph {
             raw,
             subgraphs,
             analysis,
+            cost_model: CostModel::default(),
         };
         self_.initialise_default(parser);
         Ok(self_)
@@ -72,8 +75,10 @@ macro_rules! graph_idx {
                     Self::from(usize::MAX - 1)
                 }
             }
+            #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
             #[derive(Debug, Copy, Clone, Default, PartialEq, PartialOrd, Eq, Ord, Hash)]
             pub struct $node(pub petgraph::graph::NodeIndex<$inner>);
+            #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
             #[derive(Debug, Copy, Clone, Default, PartialEq, PartialOrd, Eq, Ord, Hash)]
             pub struct $edge(pub petgraph::graph::EdgeIndex<$inner>);
 
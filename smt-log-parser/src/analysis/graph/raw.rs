@@ -26,6 +26,7 @@ use super::subgraph::{Subgraph, VisitBox};
 graph_idx!(raw_idx, RawNodeIndex, RawEdgeIndex, RawIx);
 
 #[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug)]
 pub struct RawInstGraph {
     pub graph: DiGraph<Node, EdgeKind, RawIx>,
@@ -216,7 +217,10 @@ impl RawInstGraph {
     pub fn index(&self, kind: NodeKind) -> RawNodeIndex {
         match kind {
             NodeKind::ENode(enode) => enode.index(self),
-            NodeKind::GivenEquality(eq, use_) => (eq, use_).index(self),
+            // Written out in full as `typenum` (a dependency of the xz
+            // decoder) implements `std::ops::Index` for all tuples, which
+            // makes `(eq, use_).index(self)` ambiguous.
+            NodeKind::GivenEquality(eq, use_) => IndexesInstGraph::index(&(eq, use_), self),
             NodeKind::TransEquality(eq) => eq.index(self),
             NodeKind::Instantiation(inst) => inst.index(self),
         }
@@ -252,6 +256,7 @@ impl RawInstGraph {
 }
 
 #[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug)]
 pub struct GraphStats {
     pub hidden: u32,
@@ -282,6 +287,7 @@ impl GraphStats {
     }
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone)]
 pub struct Node {
     state: NodeState,
@@ -295,6 +301,7 @@ pub struct Node {
     pub part_of_ml: fxhash::FxHashSet<usize>,
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NodeState {
     Disabled,
@@ -302,6 +309,7 @@ pub enum NodeState {
     Visible,
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, Copy, Default)]
 pub struct Depth {
     /// What is the shortest path to a root/leaf
@@ -310,6 +318,7 @@ pub struct Depth {
     pub max: u32,
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, Default)]
 pub struct NextInsts {
     /// What are the immediate next instantiation nodes
@@ -351,6 +360,7 @@ impl Node {
     }
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, Copy)]
 pub enum NodeKind {
     /// Corresponds to `ENodeIdx`.
@@ -425,6 +435,7 @@ impl NodeKind {
     }
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, Copy)]
 pub enum EdgeKind {
     /// Instantiation -> ENode
@@ -12,6 +12,7 @@ use roaring::RoaringBitmap;
 use super::{raw::RawIx, InstGraph, RawNodeIndex};
 
 #[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug)]
 pub struct Subgraph {
     pub(super) nodes: Vec<RawNodeIndex>,
@@ -141,6 +142,7 @@ impl Subgraph {
     }
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug)]
 pub struct TransitiveClosure(Vec<RoaringBitmap>);
 impl TransitiveClosure {
@@ -159,6 +161,10 @@ impl TransitiveClosure {
         }
         reachable
     }
+    /// The number of nodes in `among` which can be reached from `from`.
+    pub fn reachable_among(&self, from: u32, among: &RoaringBitmap) -> u64 {
+        self.0[from as usize].intersection_len(among)
+    }
 
     #[cfg(feature = "mem_dbg")]
     pub(crate) fn inner(&self) -> &Vec<RoaringBitmap> {
@@ -0,0 +1,103 @@
+use crate::{
+    items::{QuantIdx, QuantKind},
+    qid::{QidDecoders, QidSource},
+    FxHashMap, TiVec, Z3Parser,
+};
+
+use super::QuantifierAnalysis;
+
+/// The instantiations of all quantifiers which originate from the same place
+/// in the front-end's input.
+#[derive(Debug, Clone)]
+pub struct Hotspot {
+    pub tool: String,
+    pub file: Option<String>,
+    /// `None` when aggregating per file.
+    pub line: Option<u32>,
+    pub insts: u32,
+    pub costs: f64,
+    pub quants: Vec<QuantIdx>,
+}
+
+impl Hotspot {
+    /// E.g. `test.dfy:12`, or the tool if the file is not known.
+    pub fn location(&self) -> String {
+        let file = self.file.as_deref().unwrap_or(&self.tool);
+        match self.line {
+            Some(line) => format!("{file}:{line}"),
+            None => file.to_string(),
+        }
+    }
+}
+
+/// The instantiation counts and costs of [`QuantifierAnalysis`] aggregated by
+/// the source file and line decoded from the quantifier names.
+pub struct HotspotAnalysis {
+    pub sources: TiVec<QuantIdx, Option<QidSource>>,
+    /// Sorted by decreasing cost.
+    pub files: Vec<Hotspot>,
+    /// Only quantifiers with a known line, sorted by decreasing cost.
+    pub lines: Vec<Hotspot>,
+}
+
+impl HotspotAnalysis {
+    pub fn new(parser: &Z3Parser, analysis: &QuantifierAnalysis, decoders: &QidDecoders) -> Self {
+        let sources: TiVec<QuantIdx, _> = parser
+            .quantifiers()
+            .iter()
+            .map(|quant| {
+                let name = match quant.kind {
+                    QuantKind::NamedQuant(name) | QuantKind::UnnamedQuant { name, .. } => name,
+                    QuantKind::Other(_) | QuantKind::Lambda => return None,
+                };
+                decoders.decode(&parser[name])
+            })
+            .collect();
+        let mut files = FxHashMap::default();
+        let mut lines = FxHashMap::default();
+        for (qidx, source) in sources.iter_enumerated() {
+            let Some(source) = source else {
+                continue;
+            };
+            let info = &analysis[qidx];
+            let add = |hotspots: &mut FxHashMap<_, Hotspot>, line| {
+                let key = (source.tool.as_str(), source.file.as_deref(), line);
+                let hotspot = hotspots.entry(key).or_insert_with(|| Hotspot {
+                    tool: source.tool.clone(),
+                    file: source.file.clone(),
+                    line,
+                    insts: 0,
+                    costs: 0.0,
+                    quants: Vec::new(),
+                });
+                hotspot.insts += info.insts;
+                hotspot.costs += info.costs;
+                hotspot.quants.push(qidx);
+            };
+            add(&mut files, None);
+            if source.line.is_some() {
+                add(&mut lines, source.line);
+            }
+        }
+        let sorted = |mut hotspots: FxHashMap<_, Hotspot>| {
+            let mut hotspots: Vec<_> = hotspots.drain().map(|(_, hotspot)| hotspot).collect();
+            hotspots.sort_by(|l, r| {
+                r.costs
+                    .total_cmp(&l.costs)
+                    .then_with(|| r.insts.cmp(&l.insts))
+                    .then_with(|| l.location().cmp(&r.location()))
+            });
+            hotspots
+        };
+        Self {
+            files: sorted(files),
+            lines: sorted(lines),
+            sources,
+        }
+    }
+
+    /// How many quantifiers had a name which could be decoded.
+    pub fn decoded(&self) -> usize {
+        self.sources.iter().filter(|s| s.is_some()).count()
+    }
+}
@@ -0,0 +1,473 @@
+use std::{
+    fmt,
+    ops::{Deref, Index, IndexMut},
+    str::FromStr,
+};
+
+use crate::{
+    display_with::{DisplayCtxt, DisplayWithCtxt},
+    items::{InstIdx, QuantIdx, TermIdx, TermKind},
+    FxHashMap, IString, TiVec, Z3Parser,
+};
+
+use super::InstGraph;
+
+/// The number of affected instantiations kept as evidence for each [`Lint`].
+pub const MAX_EVIDENCE: usize = 10;
+
+/// A check for a common cause of poor solver performance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LintRule {
+    /// A trigger which matched through long chains of equalities. The
+    /// threshold is the number of given equalities in a single chain.
+    EqualityChains,
+    /// A quantifier instantiated many times where nothing depends on any of
+    /// its instantiations. The threshold is the number of instantiations.
+    UnusedYields,
+    /// A trigger consisting of a single application of a function symbol to
+    /// variables, where that symbol is very common. The threshold is the
+    /// percentage of all e-nodes which are applications of the symbol.
+    CommonTrigger,
+    /// A quantifier only instantiated by model-based quantifier
+    /// instantiation, i.e. its triggers never matched. The threshold is the
+    /// number of instantiations.
+    MbqiOnly,
+    /// A quantifier instantiated repeatedly with the same bindings. The
+    /// threshold is the number of instantiations with the same bindings.
+    RepeatedBindings,
+}
+
+impl LintRule {
+    pub const ALL: [Self; 5] = [
+        Self::EqualityChains,
+        Self::UnusedYields,
+        Self::CommonTrigger,
+        Self::MbqiOnly,
+        Self::RepeatedBindings,
+    ];
+
+    /// The name used on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::EqualityChains => "equality-chains",
+            Self::UnusedYields => "unused-yields",
+            Self::CommonTrigger => "common-trigger",
+            Self::MbqiOnly => "mbqi-only",
+            Self::RepeatedBindings => "repeated-bindings",
+        }
+    }
+
+    pub fn default_config(self) -> RuleConfig {
+        let (level, threshold) = match self {
+            Self::EqualityChains => (LintLevel::Warning, 4.0),
+            Self::UnusedYields => (LintLevel::Warning, 1000.0),
+            Self::CommonTrigger => (LintLevel::Warning, 10.0),
+            Self::MbqiOnly => (LintLevel::Info, 1.0),
+            Self::RepeatedBindings => (LintLevel::Warning, 3.0),
+        };
+        RuleConfig { level, threshold }
+    }
+}
+
+impl FromStr for LintRule {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|rule| rule.name() == s)
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::ALL.iter().map(|rule| rule.name()).collect();
+                format!(
+                    "unknown lint rule `{s}`, expected one of {}",
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// How seriously the findings of a rule are taken, `Allow` disables it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LintLevel {
+    Allow,
+    Info,
+    Warning,
+    Error,
+}
+
+impl LintLevel {
+    pub const ALL: [Self; 4] = [Self::Allow, Self::Info, Self::Warning, Self::Error];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Allow => "allow",
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+impl fmt::Display for LintLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for LintLevel {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|level| level.name() == s)
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::ALL.iter().map(|level| level.name()).collect();
+                format!(
+                    "unknown lint level `{s}`, expected one of {}",
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RuleConfig {
+    pub level: LintLevel,
+    /// See the documentation of each [`LintRule`] for what this measures.
+    pub threshold: f64,
+}
+
+/// The configuration of each [`LintRule`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintConfig([RuleConfig; LintRule::ALL.len()]);
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self(LintRule::ALL.map(LintRule::default_config))
+    }
+}
+
+impl Index<LintRule> for LintConfig {
+    type Output = RuleConfig;
+    fn index(&self, rule: LintRule) -> &Self::Output {
+        &self.0[rule as usize]
+    }
+}
+
+impl IndexMut<LintRule> for LintConfig {
+    fn index_mut(&mut self, rule: LintRule) -> &mut Self::Output {
+        &mut self.0[rule as usize]
+    }
+}
+
+/// A finding of a [`LintRule`] about a quantifier (or one of its triggers).
+#[derive(Debug, Clone)]
+pub struct Lint {
+    pub rule: LintRule,
+    pub level: LintLevel,
+    pub quant: QuantIdx,
+    pub trigger: Option<TermIdx>,
+    /// The value which reached the threshold of the rule, e.g. the length
+    /// of the longest equality chain.
+    pub value: f64,
+    /// How many instantiations (or, for [`LintRule::RepeatedBindings`], sets
+    /// of bindings) are affected.
+    pub count: u64,
+    /// Some of the affected instantiations, at most [`MAX_EVIDENCE`].
+    pub evidence: Vec<InstIdx>,
+}
+
+impl Lint {
+    pub fn message(&self, ctxt: &DisplayCtxt) -> String {
+        let quant = ctxt.parser[self.quant].kind.with(ctxt);
+        let trigger = self.trigger.map(|trigger| {
+            let mut quant = Some(self.quant);
+            trigger.with_data(ctxt, &mut quant).to_string()
+        });
+        let trigger = trigger.unwrap_or_default();
+        let value = self.value;
+        let count = |word: &str| match self.count {
+            1 => format!("1 {word}"),
+            n => format!("{n} {word}s"),
+        };
+        match self.rule {
+            LintRule::EqualityChains => format!(
+                "trigger `{trigger}` of {quant} matched {} through chains of up to {value} equalities",
+                count("time")
+            ),
+            LintRule::UnusedYields => format!(
+                "{quant} was instantiated {} but nothing depends on any of these instantiations",
+                count("time")
+            ),
+            LintRule::CommonTrigger => {
+                let symbol = self
+                    .trigger
+                    .and_then(|trigger| single_application(ctxt.parser, trigger))
+                    .map(|name| &ctxt.parser[name])
+                    .unwrap_or_default();
+                format!(
+                    "trigger `{trigger}` of {quant} matches every application of `{symbol}`, {value:.1}% of all e-nodes ({})",
+                    count("instantiation")
+                )
+            }
+            LintRule::MbqiOnly => format!(
+                "{quant} was only instantiated by MBQI ({}), its triggers never matched",
+                count("time")
+            ),
+            LintRule::RepeatedBindings => format!(
+                "{quant} was instantiated up to {value} times with the same bindings ({} repeated)",
+                count("binding")
+            ),
+        }
+    }
+}
+
+/// The function symbol of a trigger which consists of a single application
+/// of that symbol to variables, e.g. `{ f(x, y) }`.
+fn single_application(parser: &Z3Parser, trigger: TermIdx) -> Option<IString> {
+    let [term] = &*parser[trigger].child_ids else {
+        return None;
+    };
+    let term = &parser[*term];
+    let all_vars = !term.child_ids.is_empty()
+        && term
+            .child_ids
+            .iter()
+            .all(|&child| matches!(parser[child].kind, TermKind::Var(_)));
+    all_vars.then_some(term.kind.app_name()).flatten()
+}
+
+/// The findings of all enabled [`LintRule`]s, most severe first.
+pub struct LintAnalysis(Vec<Lint>);
+
+impl Deref for LintAnalysis {
+    type Target = Vec<Lint>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl LintAnalysis {
+    /// Make sure that you have run `initialise_inst_succs_and_preds` on the
+    /// `inst_graph`!
+    pub fn new(parser: &Z3Parser, inst_graph: &InstGraph, config: &LintConfig) -> Self {
+        let mut self_ = Self(Vec::new());
+        let enabled = |rule| config[rule].level != LintLevel::Allow;
+        let threshold = |rule: LintRule| config[rule].threshold;
+        if enabled(LintRule::EqualityChains) {
+            self_.equality_chains(parser, threshold(LintRule::EqualityChains));
+        }
+        if enabled(LintRule::UnusedYields) {
+            self_.unused_yields(parser, inst_graph, threshold(LintRule::UnusedYields));
+        }
+        if enabled(LintRule::CommonTrigger) {
+            self_.common_trigger(parser, threshold(LintRule::CommonTrigger));
+        }
+        if enabled(LintRule::MbqiOnly) {
+            self_.mbqi_only(parser, threshold(LintRule::MbqiOnly));
+        }
+        if enabled(LintRule::RepeatedBindings) {
+            self_.repeated_bindings(parser, threshold(LintRule::RepeatedBindings));
+        }
+        for lint in &mut self_.0 {
+            lint.level = config[lint.rule].level;
+        }
+        self_.0.sort_by(|l, r| {
+            r.level
+                .cmp(&l.level)
+                .then_with(|| l.rule.cmp(&r.rule))
+                .then_with(|| r.value.total_cmp(&l.value))
+                .then_with(|| l.quant.cmp(&r.quant))
+                .then_with(|| l.trigger.cmp(&r.trigger))
+        });
+        self_
+    }
+
+    fn push(
+        &mut self,
+        rule: LintRule,
+        quant: QuantIdx,
+        trigger: Option<TermIdx>,
+        value: f64,
+        count: u64,
+        evidence: impl IntoIterator<Item = InstIdx>,
+    ) {
+        self.0.push(Lint {
+            rule,
+            level: rule.default_config().level,
+            quant,
+            trigger,
+            value,
+            count,
+            evidence: evidence.into_iter().take(MAX_EVIDENCE).collect(),
+        });
+    }
+
+    fn equality_chains(&mut self, parser: &Z3Parser, threshold: f64) {
+        let mut triggers: FxHashMap<_, Vec<_>> = FxHashMap::default();
+        for (iidx, inst) in parser.insts.insts.iter_enumerated() {
+            let match_ = &parser[inst.match_];
+            let (Some(quant), Some(pattern)) = (match_.kind.quant_idx(), match_.kind.pattern())
+            else {
+                continue;
+            };
+            let longest = match_
+                .trigger_matches()
+                .flat_map(|blame| blame.equalities())
+                .map(|eq| parser[eq].given_len)
+                .max()
+                .unwrap_or_default();
+            if (longest as f64) < threshold {
+                continue;
+            }
+            triggers
+                .entry((quant, pattern))
+                .or_default()
+                .push((longest, iidx));
+        }
+        let mut triggers: Vec<_> = triggers.drain().collect();
+        triggers.sort_unstable_by_key(|(trigger, _)| *trigger);
+        for ((quant, pattern), mut insts) in triggers {
+            insts.sort_unstable_by(|l, r| r.0.cmp(&l.0).then(l.1.cmp(&r.1)));
+            let longest = insts[0].0 as f64;
+            let count = insts.len() as u64;
+            let evidence = insts.into_iter().map(|(_, iidx)| iidx);
+            self.push(
+                LintRule::EqualityChains,
+                quant,
+                Some(pattern),
+                longest,
+                count,
+                evidence,
+            );
+        }
+    }
+
+    fn unused_yields(&mut self, parser: &Z3Parser, inst_graph: &InstGraph, threshold: f64) {
+        let mut quants: TiVec<QuantIdx, (u64, bool, Vec<InstIdx>)> = parser
+            .quantifiers
+            .iter()
+            .map(|_| (0, false, Vec::new()))
+            .collect();
+        for (iidx, inst) in parser.insts.insts.iter_enumerated() {
+            let Some(quant) = parser[inst.match_].kind.quant_idx() else {
+                continue;
+            };
+            let (insts, used, evidence) = &mut quants[quant];
+            *insts += 1;
+            *used |= !inst_graph.raw[iidx].inst_children.nodes.is_empty();
+            if evidence.len() < MAX_EVIDENCE {
+                evidence.push(iidx);
+            }
+        }
+        for (quant, (insts, used, evidence)) in quants.drain_enumerated(..) {
+            if !used && insts > 0 && insts as f64 >= threshold {
+                let value = insts as f64;
+                self.push(LintRule::UnusedYields, quant, None, value, insts, evidence);
+            }
+        }
+    }
+
+    fn common_trigger(&mut self, parser: &Z3Parser, threshold: f64) {
+        let mut symbols: FxHashMap<IString, u64> = FxHashMap::default();
+        for enode in parser.egraph.enodes.iter() {
+            if let Some(name) = parser[enode.owner].kind.app_name() {
+                *symbols.entry(name).or_default() += 1;
+            }
+        }
+        let enodes = parser.egraph.enodes.len().max(1) as f64;
+        let mut common = FxHashMap::default();
+        for (quant, quantifier) in parser.quantifiers.iter_enumerated() {
+            for &trigger in quantifier.triggers.iter() {
+                let Some(name) = single_application(parser, trigger) else {
+                    continue;
+                };
+                let share = 100.0 * symbols.get(&name).copied().unwrap_or_default() as f64 / enodes;
+                if share >= threshold {
+                    common.insert((quant, trigger), (share, Vec::new()));
+                }
+            }
+        }
+        for (iidx, inst) in parser.insts.insts.iter_enumerated() {
+            let kind = &parser[inst.match_].kind;
+            let trigger = kind.quant_idx().zip(kind.pattern());
+            if let Some((_, insts)) = trigger.and_then(|trigger| common.get_mut(&trigger)) {
+                insts.push(iidx);
+            }
+        }
+        let mut common: Vec<_> = common.drain().collect();
+        common.sort_unstable_by_key(|(trigger, _)| *trigger);
+        for ((quant, trigger), (share, insts)) in common {
+            let count = insts.len() as u64;
+            self.push(
+                LintRule::CommonTrigger,
+                quant,
+                Some(trigger),
+                share,
+                count,
+                insts,
+            );
+        }
+    }
+
+    fn mbqi_only(&mut self, parser: &Z3Parser, threshold: f64) {
+        let mut quants: TiVec<QuantIdx, (u64, bool, Vec<InstIdx>)> = parser
+            .quantifiers
+            .iter()
+            .map(|_| (0, true, Vec::new()))
+            .collect();
+        for (iidx, inst) in parser.insts.insts.iter_enumerated() {
+            let kind = &parser[inst.match_].kind;
+            let Some(quant) = kind.quant_idx() else {
+                continue;
+            };
+            let (insts, only_mbqi, evidence) = &mut quants[quant];
+            *insts += 1;
+            *only_mbqi &= kind.is_mbqi();
+            if evidence.len() < MAX_EVIDENCE {
+                evidence.push(iidx);
+            }
+        }
+        for (quant, (insts, only_mbqi, evidence)) in quants.drain_enumerated(..) {
+            if only_mbqi && insts > 0 && insts as f64 >= threshold {
+                let value = insts as f64;
+                self.push(LintRule::MbqiOnly, quant, None, value, insts, evidence);
+            }
+        }
+    }
+
+    fn repeated_bindings(&mut self, parser: &Z3Parser, threshold: f64) {
+        let mut bindings: FxHashMap<_, Vec<InstIdx>> = FxHashMap::default();
+        for (iidx, inst) in parser.insts.insts.iter_enumerated() {
+            let kind = &parser[inst.match_].kind;
+            let Some(quant) = kind.quant_idx() else {
+                continue;
+            };
+            let bound = kind.bound_terms(|enode| parser[enode].owner, |term| term);
+            bindings.entry((quant, bound)).or_default().push(iidx);
+        }
+        let mut quants: FxHashMap<QuantIdx, (u64, Vec<InstIdx>)> = FxHashMap::default();
+        for ((quant, _), insts) in bindings.drain() {
+            if (insts.len() as f64) < threshold {
+                continue;
+            }
+            let (repeated, most) = quants.entry(quant).or_default();
+            *repeated += 1;
+            if insts.len() > most.len() || (insts.len() == most.len() && insts[0] < most[0]) {
+                *most = insts;
+            }
+        }
+        let mut quants: Vec<_> = quants.drain().collect();
+        quants.sort_unstable_by_key(|(quant, _)| *quant);
+        for (quant, (repeated, most)) in quants {
+            let value = most.len() as f64;
+            self.push(
+                LintRule::RepeatedBindings,
+                quant,
+                None,
+                value,
+                repeated,
+                most,
+            );
+        }
+    }
+}
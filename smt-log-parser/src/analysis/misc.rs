@@ -1,9 +1,13 @@
-use crate::{items::QuantIdx, TiVec, Z3Parser};
+use crate::{
+    items::{ItemRanges, QuantIdx, QueryIdx},
+    TiVec, Z3Parser,
+};
 
 pub struct LogInfo {
     pub match_: MatchesInfo,
     pub inst: InstsInfo,
     pub quants: QuantsInfo,
+    pub cdcl: CdclInfo,
 }
 
 #[derive(Default)]
@@ -31,14 +35,42 @@ impl InstsInfo {
     }
 }
 
+#[derive(Default)]
+/// Counts of the boolean search events of the SAT core.
+pub struct CdclInfo {
+    pub decisions: u64,
+    pub propagations: u64,
+    pub conflicts: u64,
+    /// Total number of literals in all learned clauses.
+    pub learned_literals: u64,
+    /// How many literals were assigned while an instantiation was being
+    /// processed.
+    pub inst_assignments: u64,
+    /// How many decisions were on a literal produced by an instantiation.
+    pub inst_decisions: u64,
+    /// How many conflicts involved a literal produced by an instantiation.
+    pub inst_conflicts: u64,
+    /// How many conflicts involved a literal produced by an instantiation of
+    /// each quantifier.
+    pub conflict_quants: TiVec<QuantIdx, u64>,
+}
+
 /// How many times each quantifier was instantiated
 pub struct QuantsInfo(pub TiVec<QuantIdx, u64>);
 
 impl LogInfo {
     pub fn new(parser: &Z3Parser) -> Self {
+        Self::new_for_items(parser, parser.all_items())
+    }
+    /// Calculate the info only for the items of a single query.
+    pub fn new_for_query(parser: &Z3Parser, query: QueryIdx) -> Self {
+        Self::new_for_items(parser, parser.query_items(query))
+    }
+
+    fn new_for_items(parser: &Z3Parser, items: ItemRanges) -> Self {
         let mut quants = QuantsInfo(parser.quantifiers.iter().map(|_| 0).collect());
         let mut match_ = MatchesInfo::default();
-        for inst in parser.instantiations().iter() {
+        for inst in &parser.insts.insts.raw[items.insts.clone()] {
             let match_i = &parser[inst.match_];
             if let Some(qidx) = match_i.kind.quant_idx() {
                 quants.0[qidx] += 1;
@@ -48,21 +80,50 @@ impl LogInfo {
                 MBQI { .. } => match_.mbqi += 1,
                 TheorySolving { .. } => match_.theory_solving += 1,
                 Axiom { .. } => match_.axioms += 1,
-                Quantifier { .. } => match_.quantifiers += 1,
+                Quantifier { .. } | Reported { .. } => match_.quantifiers += 1,
             }
         }
 
         let inst = InstsInfo {
-            insts: parser.insts.insts.len() as u64,
-            enodes: parser.egraph.enodes.len() as u64,
-            geqs: parser.egraph.equalities.given.len() as u64,
-            treqs: parser.egraph.equalities.transitive.len() as u64,
+            insts: items.insts.len() as u64,
+            enodes: items.enodes.len() as u64,
+            geqs: items.given_eqs.len() as u64,
+            treqs: items.trans_eqs.len() as u64,
         };
 
+        let mut cdcl = CdclInfo {
+            decisions: items.decisions.len() as u64,
+            conflicts: items.conflicts.len() as u64,
+            conflict_quants: parser.quantifiers.iter().map(|_| 0).collect(),
+            ..Default::default()
+        };
+        for decision in &parser.cdcl.decisions.raw[items.decisions] {
+            cdcl.inst_decisions += decision.produced_by.is_some() as u64;
+        }
+        for assign in &parser.cdcl.assignments.raw[items.assignments] {
+            cdcl.propagations += !assign.decision as u64;
+            cdcl.inst_assignments += assign.created_by.is_some() as u64;
+        }
+        for conflict in &parser.cdcl.conflicts.raw[items.conflicts] {
+            cdcl.learned_literals += conflict.clause.len() as u64;
+            cdcl.inst_conflicts += !conflict.produced_by.is_empty() as u64;
+            let mut quants: Vec<_> = conflict
+                .produced_by
+                .iter()
+                .filter_map(|&iidx| parser[parser[iidx].match_].kind.quant_idx())
+                .collect();
+            quants.sort_unstable();
+            quants.dedup();
+            for qidx in quants {
+                cdcl.conflict_quants[qidx] += 1;
+            }
+        }
+
         Self {
             match_,
             inst,
             quants,
+            cdcl,
         }
     }
 }
@@ -1,7 +1,17 @@
 mod dependencies;
 mod graph;
+mod hotspots;
+mod lint;
 mod misc;
+mod pops;
+mod proof;
+mod triggers;
 
 pub use dependencies::*;
 pub use graph::*;
+pub use hotspots::*;
+pub use lint::*;
 pub use misc::*;
+pub use pops::*;
+pub use proof::*;
+pub use triggers::*;
@@ -0,0 +1,87 @@
+use crate::{
+    items::{QuantIdx, StackIdx},
+    TiVec, Z3Parser,
+};
+
+/// Work done within stack frames which were later popped, z3 throws away all
+/// such instantiations and e-nodes.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LostWork {
+    pub insts: u64,
+    pub enodes: u64,
+}
+
+/// How many instantiations of a quantifier were made, and how many of those
+/// were lost to pops.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct QuantPopInfo {
+    pub insts: u64,
+    pub lost: u64,
+}
+
+/// How much work was lost to `[pop]`s, per quantifier and per stack frame.
+pub struct PopAnalysis {
+    pub quants: TiVec<QuantIdx, QuantPopInfo>,
+    /// The work done directly within each frame (i.e. not within a frame
+    /// pushed on top of it), only non-zero for popped frames.
+    pub frames: TiVec<StackIdx, LostWork>,
+    pub total: LostWork,
+    /// The part of `total` done within frames which were popped without a
+    /// `[pop]` being logged.
+    pub implicit: LostWork,
+}
+
+impl PopAnalysis {
+    pub fn new(parser: &Z3Parser) -> Self {
+        let frames = parser.stack_frames();
+        let is_lost = |frame: Option<StackIdx>| frame.is_some_and(|f| !frames[f].active);
+        let is_implicit = |frame: Option<StackIdx>| frame.is_some_and(|f| frames[f].implicit_pop);
+
+        let mut quants: TiVec<QuantIdx, QuantPopInfo> = parser
+            .quantifiers
+            .iter()
+            .map(|_| Default::default())
+            .collect();
+        let mut lost: TiVec<StackIdx, LostWork> =
+            frames.iter().map(|_| Default::default()).collect();
+        let mut total = LostWork::default();
+        let mut implicit = LostWork::default();
+        for inst in parser.instantiations() {
+            let frame_lost = is_lost(inst.frame);
+            if let Some(qidx) = parser[inst.match_].kind.quant_idx() {
+                quants[qidx].insts += 1;
+                quants[qidx].lost += frame_lost as u64;
+            }
+            if frame_lost {
+                lost[inst.frame.unwrap()].insts += 1;
+                total.insts += 1;
+            }
+            implicit.insts += is_implicit(inst.frame) as u64;
+        }
+        for enode in parser.egraph.enodes.iter() {
+            if is_lost(enode.frame()) {
+                lost[enode.frame().unwrap()].enodes += 1;
+                total.enodes += 1;
+            }
+            implicit.enodes += is_implicit(enode.frame()) as u64;
+        }
+        Self {
+            quants,
+            frames: lost,
+            total,
+            implicit,
+        }
+    }
+
+    /// The popped frames which lost the most instantiations, most first.
+    pub fn worst_frames(&self) -> Vec<(StackIdx, LostWork)> {
+        let mut frames: Vec<_> = self
+            .frames
+            .iter_enumerated()
+            .filter(|(_, lost)| lost.insts > 0 || lost.enodes > 0)
+            .map(|(idx, lost)| (idx, *lost))
+            .collect();
+        frames.sort_by(|(_, l), (_, r)| (r.insts, r.enodes).cmp(&(l.insts, l.enodes)));
+        frames
+    }
+}
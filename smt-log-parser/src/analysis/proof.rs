@@ -0,0 +1,220 @@
+use fxhash::FxHashSet;
+
+use crate::{
+    items::{InstIdx, ProofOrApp, QuantIdx, QueryIdx, TermIdx, TermKind},
+    FxHashMap, IString, TiVec, Z3Parser,
+};
+
+/// A single `[mk-proof]` step.
+#[derive(Debug, Clone)]
+pub struct ProofStep {
+    pub term: TermIdx,
+    /// The proof rule, e.g. `quant-inst`, `mp` or `unit-resolution`.
+    pub rule: IString,
+    /// The proof steps this step was derived from.
+    pub premises: Vec<TermIdx>,
+    /// The formula this step proves.
+    pub conclusion: Option<TermIdx>,
+}
+
+/// Whether an instantiation contributed to a refutation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstUsage {
+    /// The `quant-inst` step of the instantiation is reachable from a final
+    /// refutation of its query.
+    UsedInProof,
+    /// The query of the instantiation was refuted, but the instantiation is
+    /// not part of the refutation.
+    Wasted,
+    /// The query of the instantiation has no refutation (e.g. it was not
+    /// unsat or z3 was not run with `proof=true`), or there is no
+    /// `quant-inst` step for the instantiation.
+    Unknown,
+}
+
+/// How many instantiations of a quantifier were used in the proof.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct QuantProofInfo {
+    pub used: u64,
+    pub wasted: u64,
+    pub unknown: u64,
+}
+
+/// The proof DAG built from the `[mk-proof]` terms and which instantiations
+/// are needed for the final refutation(s) of unsat queries. An instantiation
+/// is only compared against the refutations of its own query, or of the
+/// entire log if it has no queries.
+pub struct ProofAnalysis {
+    /// All proof steps, by their term.
+    pub steps: FxHashMap<TermIdx, ProofStep>,
+    /// The proof steps concluding `false` which are not a premise of any
+    /// other step, together with the query they were created in.
+    pub refutations: Vec<(Option<QueryIdx>, TermIdx)>,
+    /// The `quant-inst` step of each instantiation, if found.
+    pub inst_steps: TiVec<InstIdx, Option<TermIdx>>,
+    pub usage: TiVec<InstIdx, InstUsage>,
+    pub quants: TiVec<QuantIdx, QuantProofInfo>,
+}
+
+impl ProofAnalysis {
+    pub fn new(parser: &Z3Parser) -> Self {
+        let mut steps = FxHashMap::default();
+        let mut is_premise = FxHashSet::default();
+        // The `quant-inst` steps by their conclusion.
+        let mut quant_inst_steps = FxHashMap::default();
+        for term in (0..parser.terms.len()).map(TermIdx::from) {
+            let TermKind::ProofOrApp(ProofOrApp {
+                is_proof: true,
+                name,
+            }) = parser[term].kind
+            else {
+                continue;
+            };
+            let children = &parser[term].child_ids;
+            let (premises, conclusion) = match children.split_last() {
+                Some((&last, rest)) if !is_proof(parser, last) => (rest, Some(last)),
+                _ => (&children[..], None),
+            };
+            let premises: Vec<_> = premises
+                .iter()
+                .copied()
+                .filter(|&p| is_proof(parser, p))
+                .collect();
+            is_premise.extend(premises.iter().copied());
+            if &parser[name] == "quant-inst" {
+                if let Some(conclusion) = conclusion {
+                    quant_inst_steps.entry(conclusion).or_insert(term);
+                }
+            }
+            let step = ProofStep {
+                term,
+                rule: name,
+                premises,
+                conclusion,
+            };
+            steps.insert(term, step);
+        }
+
+        let mut refutations: Vec<_> = steps
+            .values()
+            .filter(|step| !is_premise.contains(&step.term))
+            .filter(|step| step.conclusion.is_some_and(|c| is_false(parser, c)))
+            .map(|step| (parser.term_query(step.term), step.term))
+            .collect();
+        refutations.sort_unstable();
+
+        // The conclusions of all `quant-inst` steps reachable from a
+        // refutation of each query, z3 may create several steps for the same
+        // lemma.
+        let mut used_lemmas = FxHashSet::default();
+        for query in refutations.chunk_by(|(l, _), (r, _)| l == r) {
+            let mut seen = FxHashSet::default();
+            let mut todo: Vec<_> = query.iter().map(|&(_, term)| term).collect();
+            while let Some(term) = todo.pop() {
+                if !seen.insert(term) {
+                    continue;
+                }
+                let step = &steps[&term];
+                if &parser[step.rule] == "quant-inst" {
+                    used_lemmas.extend(step.conclusion.map(|c| (query[0].0, c)));
+                }
+                todo.extend(step.premises.iter().copied());
+            }
+        }
+
+        let refuted: FxHashSet<_> = refutations.iter().map(|&(query, _)| query).collect();
+
+        let inst_steps: TiVec<InstIdx, _> = parser
+            .instantiations()
+            .iter()
+            .map(|inst| {
+                let proof = inst.get_resulting_term()?;
+                if steps.contains_key(&proof) {
+                    Some(proof)
+                } else {
+                    // Without proofs the instance refers to the lemma itself.
+                    quant_inst_steps.get(&proof).copied()
+                }
+            })
+            .collect();
+        let mut quants: TiVec<QuantIdx, QuantProofInfo> = parser
+            .quantifiers()
+            .iter()
+            .map(|_| Default::default())
+            .collect();
+        let usage: TiVec<InstIdx, _> = inst_steps
+            .iter_enumerated()
+            .map(|(iidx, step)| {
+                let lemma = step.and_then(|step| steps[&step].conclusion);
+                let query = parser.inst_query(iidx);
+                let usage = match lemma {
+                    Some(lemma) if refuted.contains(&query) => {
+                        if used_lemmas.contains(&(query, lemma)) {
+                            InstUsage::UsedInProof
+                        } else {
+                            InstUsage::Wasted
+                        }
+                    }
+                    _ => InstUsage::Unknown,
+                };
+                if let Some(qidx) = parser[parser[iidx].match_].kind.quant_idx() {
+                    let info = &mut quants[qidx];
+                    match usage {
+                        InstUsage::UsedInProof => info.used += 1,
+                        InstUsage::Wasted => info.wasted += 1,
+                        InstUsage::Unknown => info.unknown += 1,
+                    }
+                }
+                usage
+            })
+            .collect();
+        Self {
+            steps,
+            refutations,
+            inst_steps,
+            usage,
+            quants,
+        }
+    }
+
+    /// Whether any refutation was found, otherwise all instantiations are
+    /// [`InstUsage::Unknown`].
+    pub fn has_refutation(&self) -> bool {
+        !self.refutations.is_empty()
+    }
+
+    /// The number of distinct queries (counting a log without queries as
+    /// one) with a refutation.
+    pub fn refuted_queries(&self) -> usize {
+        self.refutations.chunk_by(|(l, _), (r, _)| l == r).count()
+    }
+
+    /// The number of used, wasted and unknown instantiations.
+    pub fn total(&self) -> QuantProofInfo {
+        let mut total = QuantProofInfo::default();
+        for usage in self.usage.iter() {
+            match usage {
+                InstUsage::UsedInProof => total.used += 1,
+                InstUsage::Wasted => total.wasted += 1,
+                InstUsage::Unknown => total.unknown += 1,
+            }
+        }
+        total
+    }
+}
+
+fn is_proof(parser: &Z3Parser, term: TermIdx) -> bool {
+    matches!(
+        parser[term].kind,
+        TermKind::ProofOrApp(ProofOrApp { is_proof: true, .. })
+    )
+}
+
+fn is_false(parser: &Z3Parser, term: TermIdx) -> bool {
+    let term = &parser[term];
+    term.child_ids.is_empty()
+        && term
+            .kind
+            .app_name()
+            .is_some_and(|name| &parser[name] == "false")
+}
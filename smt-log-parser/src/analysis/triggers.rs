@@ -0,0 +1,128 @@
+use crate::{
+    items::{MatchKind, QuantIdx, TermIdx},
+    FxHashMap, IString, TiVec, Z3Parser,
+};
+
+use super::InstGraph;
+
+/// Statistics for a single trigger of a quantifier.
+#[derive(Debug, Clone)]
+pub struct TriggerInfo {
+    /// The `pattern` term.
+    pub trigger: TermIdx,
+    /// How many times the trigger matched, including matches which were
+    /// never instantiated.
+    pub matches: u64,
+    pub insts: u64,
+    /// The cost of all instantiations, zero if no graph was given.
+    pub costs: f64,
+    /// How many matches needed at least one equality to match some term of
+    /// the trigger.
+    pub equality_matches: u64,
+    /// How often the matched e-nodes had each function symbol, counting all
+    /// terms of a multi-pattern.
+    pub matched_symbols: FxHashMap<IString, u64>,
+}
+
+impl TriggerInfo {
+    fn new(trigger: TermIdx) -> Self {
+        Self {
+            trigger,
+            matches: 0,
+            insts: 0,
+            costs: 0.0,
+            equality_matches: 0,
+            matched_symbols: FxHashMap::default(),
+        }
+    }
+
+    /// The matched function symbols, most frequent first.
+    pub fn top_symbols(&self) -> Vec<(IString, u64)> {
+        let mut symbols: Vec<_> = self.matched_symbols.iter().map(|(s, c)| (*s, *c)).collect();
+        symbols.sort_by(|l, r| r.1.cmp(&l.1).then(l.0.cmp(&r.0)));
+        symbols
+    }
+}
+
+/// Matches and instantiations per trigger of each quantifier. The triggers
+/// are in the order of [`Quantifier::triggers`](crate::items::Quantifier),
+/// followed by any pattern which was matched but not part of the
+/// quantifier.
+pub struct TriggerAnalysis(TiVec<QuantIdx, Vec<TriggerInfo>>);
+
+impl std::ops::Deref for TriggerAnalysis {
+    type Target = TiVec<QuantIdx, Vec<TriggerInfo>>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl TriggerAnalysis {
+    /// Costs are only calculated if an `inst_graph` is given.
+    pub fn new(parser: &Z3Parser, inst_graph: Option<&InstGraph>) -> Self {
+        let mut self_ = Self(
+            parser
+                .quantifiers
+                .iter()
+                .map(|quant| {
+                    quant
+                        .triggers
+                        .iter()
+                        .map(|&t| TriggerInfo::new(t))
+                        .collect()
+                })
+                .collect(),
+        );
+        for match_ in parser.insts.matches.iter() {
+            let (MatchKind::Quantifier { quant, pattern, .. }
+            | MatchKind::Axiom {
+                axiom: quant,
+                pattern,
+                ..
+            }) = &match_.kind
+            else {
+                continue;
+            };
+            let info = self_.get_or_insert(*quant, *pattern);
+            info.matches += 1;
+            let mut needed_equality = false;
+            for blame in match_.trigger_matches() {
+                needed_equality |= blame.equalities_len() > 0;
+                let owner = parser[blame.enode()].owner;
+                if let Some(name) = parser[owner].kind.app_name() {
+                    *info.matched_symbols.entry(name).or_default() += 1;
+                }
+            }
+            info.equality_matches += needed_equality as u64;
+        }
+        for (iidx, inst) in parser.insts.insts.iter_enumerated() {
+            let match_ = &parser[inst.match_].kind;
+            let (Some(quant), Some(pattern)) = (match_.quant_idx(), match_.pattern()) else {
+                continue;
+            };
+            let info = self_.get_or_insert(quant, pattern);
+            info.insts += 1;
+            if let Some(inst_graph) = inst_graph {
+                info.costs += inst_graph.raw[iidx].cost;
+            }
+        }
+        self_
+    }
+
+    fn get_or_insert(&mut self, quant: QuantIdx, pattern: TermIdx) -> &mut TriggerInfo {
+        let triggers = &mut self.0[quant];
+        let idx = match triggers.iter().position(|t| t.trigger == pattern) {
+            Some(idx) => idx,
+            None => {
+                triggers.push(TriggerInfo::new(pattern));
+                triggers.len() - 1
+            }
+        };
+        &mut triggers[idx]
+    }
+
+    /// The cost of all instantiations of all triggers.
+    pub fn total_costs(&self) -> f64 {
+        self.iter().flatten().map(|info| info.costs).sum()
+    }
+}
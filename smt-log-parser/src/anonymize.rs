@@ -0,0 +1,392 @@
+use std::io::{self, BufRead, Write};
+
+use fxhash::FxHashSet;
+
+use crate::FxHashMap;
+
+/// How many arguments a theory symbol is applied to.
+#[derive(Debug, Clone, Copy)]
+enum Arity {
+    Exactly(usize),
+    AtLeast(usize),
+}
+use Arity::{AtLeast, Exactly};
+
+impl Arity {
+    fn allows(self, arity: usize) -> bool {
+        match self {
+            Exactly(n) => arity == n,
+            AtLeast(n) => arity >= n,
+        }
+    }
+}
+
+/// Function symbols of the SMT-LIB theories and z3 internals, these are kept
+/// as is when applied to the right number of arguments unless theory symbols
+/// are anonymized as well. z3's array extensions `const`, `map` and
+/// `default` are common user names and so are not kept.
+const THEORY_SYMBOLS: &[(&str, Arity)] = &[
+    // Core
+    ("true", Exactly(0)),
+    ("false", Exactly(0)),
+    ("not", Exactly(1)),
+    ("and", AtLeast(2)),
+    ("or", AtLeast(2)),
+    ("xor", Exactly(2)),
+    ("=>", Exactly(2)),
+    ("=", AtLeast(2)),
+    ("distinct", AtLeast(2)),
+    ("ite", Exactly(3)),
+    ("if", Exactly(3)),
+    ("iff", Exactly(2)),
+    ("implies", Exactly(2)),
+    ("pattern", AtLeast(1)),
+    ("lblpos", Exactly(1)),
+    ("lblneg", Exactly(1)),
+    ("oeq", Exactly(2)),
+    ("<null>", AtLeast(0)),
+    // Arithmetic
+    ("+", AtLeast(2)),
+    ("-", AtLeast(1)),
+    ("*", AtLeast(2)),
+    ("/", Exactly(2)),
+    ("div", Exactly(2)),
+    ("mod", Exactly(2)),
+    ("rem", Exactly(2)),
+    ("abs", Exactly(1)),
+    ("<=", Exactly(2)),
+    ("<", Exactly(2)),
+    (">=", Exactly(2)),
+    (">", Exactly(2)),
+    ("~", Exactly(1)),
+    ("to_real", Exactly(1)),
+    ("to_int", Exactly(1)),
+    ("is_int", Exactly(1)),
+    ("/0", AtLeast(1)),
+    ("div0", AtLeast(1)),
+    ("mod0", AtLeast(1)),
+    ("^", Exactly(2)),
+    // Arrays
+    ("select", AtLeast(2)),
+    ("store", AtLeast(3)),
+    ("as-array", Exactly(0)),
+    // Bit-vectors
+    ("concat", AtLeast(2)),
+    ("extract", Exactly(1)),
+    ("zero_extend", Exactly(1)),
+    ("sign_extend", Exactly(1)),
+    ("repeat", Exactly(1)),
+    ("rotate_left", Exactly(1)),
+    ("rotate_right", Exactly(1)),
+    ("bv2int", Exactly(1)),
+    ("int2bv", Exactly(1)),
+    ("bvadd", AtLeast(2)),
+    ("bvsub", Exactly(2)),
+    ("bvmul", AtLeast(2)),
+    ("bvudiv", Exactly(2)),
+    ("bvsdiv", Exactly(2)),
+    ("bvurem", Exactly(2)),
+    ("bvsrem", Exactly(2)),
+    ("bvsmod", Exactly(2)),
+    ("bvneg", Exactly(1)),
+    ("bvand", AtLeast(2)),
+    ("bvor", AtLeast(2)),
+    ("bvnot", Exactly(1)),
+    ("bvxor", AtLeast(2)),
+    ("bvnand", Exactly(2)),
+    ("bvnor", Exactly(2)),
+    ("bvxnor", Exactly(2)),
+    ("bvshl", Exactly(2)),
+    ("bvlshr", Exactly(2)),
+    ("bvashr", Exactly(2)),
+    ("bvult", Exactly(2)),
+    ("bvule", Exactly(2)),
+    ("bvugt", Exactly(2)),
+    ("bvuge", Exactly(2)),
+    ("bvslt", Exactly(2)),
+    ("bvsle", Exactly(2)),
+    ("bvsgt", Exactly(2)),
+    ("bvsge", Exactly(2)),
+    ("bvcomp", Exactly(2)),
+];
+/// Prefixes of namespaced theory symbols, e.g. `str.len`.
+const THEORY_PREFIXES: &[&str] = &["fp.", "str.", "seq.", "re.", "to_fp"];
+/// Sorts of the SMT-LIB theories, kept as is unless theory symbols are
+/// anonymized as well.
+const THEORY_SORTS: &[&str] = &[
+    "Bool",
+    "Int",
+    "Real",
+    "Array",
+    "BitVec",
+    "String",
+    "Seq",
+    "RegLan",
+    "FloatingPoint",
+    "RoundingMode",
+];
+
+/// What an anonymized name was first used as, determines the prefix of the
+/// opaque name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameKind {
+    Function,
+    Quantifier,
+    Variable,
+    Sort,
+    /// A [`Meaning`](crate::items::Meaning) value.
+    Value,
+}
+
+impl NameKind {
+    fn prefix(self) -> &'static str {
+        match self {
+            Self::Function => "f",
+            Self::Quantifier => "q",
+            Self::Variable => "x",
+            Self::Sort => "S",
+            Self::Value => "v",
+        }
+    }
+}
+
+/// Rewrites z3 logs such that all user-provided names (function symbols,
+/// quantifier names, variable names and sorts) are replaced by opaque ones.
+/// The same name is always replaced by the same opaque name, term ids and
+/// all other entries are kept as is so that the anonymized log profiles
+/// identically to the original.
+#[derive(Debug, Default)]
+pub struct Anonymizer {
+    /// Also replace theory symbols (e.g. `+` or `select`) and meaning values.
+    pub anonymize_theory: bool,
+    names: FxHashMap<String, usize>,
+    /// The names of terms which z3 attached a meaning to, e.g. numerals.
+    meanings: FxHashSet<String>,
+    /// The opaque and original name, in order of first occurrence.
+    mapping: Vec<(String, String)>,
+}
+
+impl Anonymizer {
+    pub fn new(anonymize_theory: bool) -> Self {
+        Self {
+            anonymize_theory,
+            ..Default::default()
+        }
+    }
+
+    /// The opaque and original name of every replaced name, in order of
+    /// first occurrence.
+    pub fn mapping(&self) -> &[(String, String)] {
+        &self.mapping
+    }
+
+    /// Writes the mapping as tab-separated `opaque original` lines.
+    pub fn write_mapping(&self, mut writer: impl Write) -> io::Result<()> {
+        for (opaque, original) in &self.mapping {
+            writeln!(writer, "{opaque}\t{original}")?;
+        }
+        Ok(())
+    }
+
+    /// Anonymizes every line of `reader`.
+    pub fn anonymize(&mut self, reader: impl BufRead, mut writer: impl Write) -> io::Result<()> {
+        let mut lines = reader.lines().peekable();
+        while let Some(line) = lines.next() {
+            let line = line?;
+            if let Some(Ok(next)) = lines.peek() {
+                self.note_meaning(&line, next);
+            }
+            writeln!(writer, "{}", self.line(&line))?;
+        }
+        Ok(())
+    }
+
+    /// z3 attaches the meaning of a value (e.g. a numeral) right after
+    /// creating its term, the name of such a term is kept by [`Self::line`].
+    pub fn note_meaning(&mut self, line: &str, next: &str) {
+        let (mut line, mut next) = (line.split(' '), next.split(' '));
+        let (Some("[mk-app]"), Some(id), Some(name)) = (line.next(), line.next(), line.next())
+        else {
+            return;
+        };
+        if next.next() == Some("[attach-meaning]") && next.next() == Some(id) {
+            self.meanings.insert(name.to_string());
+        }
+    }
+
+    /// Whether the function symbol applied to `arity` arguments is kept.
+    fn is_theory(&self, name: &str, arity: usize) -> bool {
+        if self.anonymize_theory {
+            return false;
+        }
+        self.meanings.contains(name)
+            || THEORY_SYMBOLS
+                .iter()
+                .any(|&(symbol, allowed)| symbol == name && allowed.allows(arity))
+            || THEORY_PREFIXES.iter().any(|p| name.starts_with(p))
+    }
+
+    /// Whether the sort (or index of a sort, e.g. `32` in `(_ BitVec 32)`)
+    /// is kept.
+    fn is_theory_sort(&self, name: &str) -> bool {
+        let index = name.bytes().all(|b| b.is_ascii_digit());
+        !self.anonymize_theory && (index || THEORY_SORTS.contains(&name))
+    }
+
+    /// The opaque name of a function symbol applied to `arity` arguments.
+    fn function(&mut self, name: &str, arity: usize) -> String {
+        if self.is_theory(name, arity) {
+            return name.to_string();
+        }
+        self.name(name, NameKind::Function)
+    }
+
+    /// The opaque name of `name`, a `!123` suffix (as added by z3 to fresh
+    /// and unnamed symbols) is kept. Theory symbols are only kept for sorts,
+    /// functions are checked by [`Self::line`] which knows their arguments.
+    pub fn name(&mut self, name: &str, kind: NameKind) -> String {
+        let (base, suffix) = match name.rsplit_once('!') {
+            Some((base, id)) if !base.is_empty() && id.bytes().all(|b| b.is_ascii_digit()) => {
+                (base, &name[base.len()..])
+            }
+            _ => (name, ""),
+        };
+        // The skolem constants of z3, e.g. `k!12`.
+        let skolem = base == "k" && !suffix.is_empty() && !self.anonymize_theory;
+        let theory = kind == NameKind::Sort && self.is_theory_sort(base);
+        if base.is_empty() || skolem || theory {
+            return name.to_string();
+        }
+        let idx = *self.names.entry(base.to_string()).or_insert_with(|| {
+            let opaque = format!("{}{}", kind.prefix(), self.mapping.len());
+            self.mapping.push((opaque, base.to_string()));
+            self.mapping.len() - 1
+        });
+        format!("{}{suffix}", self.mapping[idx].0)
+    }
+
+    /// Sorts may be compound (e.g. `(Array Int Foo)`), each symbol is
+    /// replaced separately.
+    fn sort(&mut self, sort: &str) -> String {
+        let mut result = String::with_capacity(sort.len());
+        let mut symbol = String::new();
+        for c in sort.chars().chain(std::iter::once(' ')) {
+            if c.is_whitespace() || c == '(' || c == ')' {
+                if !symbol.is_empty() {
+                    result.push_str(&self.name(&symbol, NameKind::Sort));
+                    symbol.clear();
+                }
+                result.push(c);
+            } else {
+                symbol.push(c);
+            }
+        }
+        result.pop();
+        result
+    }
+
+    /// Anonymizes a single line of the log.
+    pub fn line(&mut self, line: &str) -> String {
+        let mut tokens = line.split(' ');
+        let (Some(entry), Some(id)) = (tokens.next(), tokens.next()) else {
+            return line.to_string();
+        };
+        let rest: Vec<_> = tokens.collect();
+        let rest = match entry {
+            "[mk-app]" => {
+                // A quoted name (e.g. `|foo bar|`) may contain spaces, it
+                // ends at the closing `|`.
+                let quoted = rest.first().is_some_and(|first| first.starts_with('|'));
+                let end = if quoted {
+                    rest.iter().position(|t| t.len() > 1 && t.ends_with('|'))
+                } else {
+                    (!rest.is_empty()).then_some(0)
+                };
+                let Some(end) = end else {
+                    return line.to_string();
+                };
+                let name = rest[..=end].join(" ");
+                let arity = rest.len() - end - 1;
+                let name = match name.strip_prefix('|').and_then(|n| n.strip_suffix('|')) {
+                    Some(bare) => format!("|{}|", self.function(bare, arity)),
+                    None => self.function(&name, arity),
+                };
+                [name]
+                    .into_iter()
+                    .chain(rest[end + 1..].iter().map(|s| s.to_string()))
+                    .collect()
+            }
+            "[mk-quant]" | "[mk-lambda]" => {
+                // The name may contain spaces, it ends at the number of
+                // variables.
+                let end = rest.iter().skip(1).position(|t| t.parse::<usize>().is_ok());
+                let Some(end) = end.map(|end| end + 1) else {
+                    return line.to_string();
+                };
+                let name = self.name(&rest[..end].join(" "), NameKind::Quantifier);
+                [name]
+                    .into_iter()
+                    .chain(rest[end..].iter().map(|s| s.to_string()))
+                    .collect()
+            }
+            "[attach-var-names]" => vec![self.var_names(&rest)],
+            "[attach-meaning]" if self.anonymize_theory => match rest.split_first() {
+                Some((theory, value)) => {
+                    let value = self.name(&value.join(" "), NameKind::Value);
+                    vec![theory.to_string(), value]
+                }
+                None => return line.to_string(),
+            },
+            _ => return line.to_string(),
+        };
+        let mut result = format!("{entry} {id}");
+        for token in rest {
+            result.push(' ');
+            result.push_str(&token);
+        }
+        result
+    }
+
+    /// Rewrites a list of `(|name| ; |sort|)`, `(name sort)` or `(;sort)`
+    /// tuples, keeping their layout.
+    fn var_names(&mut self, tokens: &[&str]) -> String {
+        // Whether the next part of the tuple is the sort.
+        let mut is_sort = false;
+        let mut result = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            let (open, inner) = match token.strip_prefix('(') {
+                Some(inner) => (true, inner),
+                None => (false, *token),
+            };
+            let (inner, close) = match inner.strip_suffix(')') {
+                Some(inner) => (inner, true),
+                None => (inner, false),
+            };
+            is_sort &= !open;
+            let mut parts = Vec::new();
+            for (idx, part) in inner.split(';').enumerate() {
+                is_sort |= idx > 0;
+                let bare = part.strip_prefix('|').unwrap_or(part);
+                let bare = bare.strip_suffix('|').unwrap_or(bare);
+                if bare.is_empty() {
+                    parts.push(part.to_string());
+                    continue;
+                }
+                let bare = if is_sort {
+                    self.sort(bare)
+                } else {
+                    self.name(bare, NameKind::Variable)
+                };
+                parts.push(if part.starts_with('|') {
+                    format!("|{bare}|")
+                } else {
+                    bare
+                });
+                is_sort = true;
+            }
+            let (open, close) = (if open { "(" } else { "" }, if close { ")" } else { "" });
+            result.push(format!("{open}{}{close}", parts.join(";")));
+        }
+        result.join(" ")
+    }
+}
@@ -0,0 +1,39 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+
+use smt_log_parser::{anonymize::Anonymizer, parsers::FileRead};
+
+pub fn run(
+    logfile: PathBuf,
+    output: Option<PathBuf>,
+    mapping: Option<PathBuf>,
+    anonymize_theory: bool,
+) -> Result<(), String> {
+    let (_metadata, reader) = (&logfile)
+        .read_open()
+        .map_err(|e| format!("{}: {e}", logfile.display()))?;
+    let output = output.unwrap_or_else(|| logfile.with_extension("anon.log"));
+    let mapping = mapping.unwrap_or_else(|| output.with_extension("mapping.tsv"));
+
+    let mut anonymizer = Anonymizer::new(anonymize_theory);
+    let file = File::create(&output).map_err(|e| format!("{}: {e}", output.display()))?;
+    let mut writer = BufWriter::new(file);
+    anonymizer
+        .anonymize(reader, &mut writer)
+        .and_then(|()| writer.flush())
+        .map_err(|e| format!("{}: {e}", output.display()))?;
+
+    let file = File::create(&mapping).map_err(|e| format!("{}: {e}", mapping.display()))?;
+    let mut writer = BufWriter::new(file);
+    anonymizer
+        .write_mapping(&mut writer)
+        .and_then(|()| writer.flush())
+        .map_err(|e| format!("{}: {e}", mapping.display()))?;
+    eprintln!("Replaced {} names", anonymizer.mapping().len());
+    println!("{}", output.display());
+    println!("{}", mapping.display());
+    Ok(())
+}
@@ -5,6 +5,12 @@
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+    /// Print all problems encountered while parsing the log
+    #[arg(long, global = true, default_value_t = false)]
+    pub diagnostics: bool,
+    /// Stop with an error at the first line which cannot be parsed
+    #[arg(long, global = true, default_value_t = false)]
+    pub strict: bool,
 }
 
 #[derive(clap::Subcommand)]
@@ -22,6 +28,24 @@ pub enum Commands {
         /// Whether to pretty print the output results
         #[arg(short, long, default_value_t = false)]
         pretty_print: bool,
+
+        /// Only consider instantiations of the given query (0-indexed)
+        #[arg(short, long)]
+        query: Option<usize>,
+
+        /// How the cost of an instantiation is measured: uniform, term-size,
+        /// enodes, descendants, generation or proof
+        #[arg(long, default_value = "uniform")]
+        cost_model: smt_log_parser::analysis::CostModel,
+
+        /// Instead print the graph of quantifier dependencies in DOT format
+        #[arg(long, default_value_t = false)]
+        dot: bool,
+
+        /// Instead print the graph of quantifier dependencies as JSON, with
+        /// its strongly connected components
+        #[arg(long, default_value_t = false, conflicts_with = "dot")]
+        json: bool,
     },
     #[cfg(feature = "analysis")]
     /// Print out statistics for the SMT solver
@@ -31,6 +55,274 @@ pub enum Commands {
         /// how many of the most instantiated axioms to print
         #[arg(short)]
         k: Option<usize>,
+        /// Only print statistics for the given query (0-indexed)
+        #[arg(short, long)]
+        query: Option<usize>,
+        /// How the cost of an instantiation is measured: uniform, term-size,
+        /// enodes, descendants, generation or proof. If given, quantifiers
+        /// are ranked by cost instead of by their number of instantiations.
+        /// Cannot be combined with `--query`
+        #[arg(long, conflicts_with = "query")]
+        cost_model: Option<smt_log_parser::analysis::CostModel>,
+    },
+    #[cfg(feature = "analysis")]
+    /// Print out how many instantiations were thrown away by `pop`s, per
+    /// quantifier and per stack frame
+    Pops {
+        /// The path to the smt log file
+        logfile: std::path::PathBuf,
+        /// How many of the quantifiers and frames which lost the most
+        /// instantiations to print
+        #[arg(short)]
+        k: Option<usize>,
+    },
+    #[cfg(feature = "analysis")]
+    /// Print out how many instantiations of each quantifier were used in the
+    /// proof of an unsat query (requires a log of z3 run with `proof=true`)
+    Proof {
+        /// The path to the smt log file
+        logfile: std::path::PathBuf,
+        /// How many of the quantifiers with the most wasted instantiations to
+        /// print
+        #[arg(short)]
+        k: Option<usize>,
+    },
+    #[cfg(feature = "analysis")]
+    /// Print out which files and lines of the verified program caused the
+    /// most instantiations, based on the quantifier names generated by
+    /// Boogie, Dafny, Viper and F*
+    Hotspots {
+        /// The path to the smt log file
+        logfile: std::path::PathBuf,
+        /// How many of the most costly files and lines to print
+        #[arg(short)]
+        k: Option<usize>,
+        /// Only consider instantiations of the given query (0-indexed)
+        #[arg(short, long)]
+        query: Option<usize>,
+    },
+    #[cfg(feature = "analysis")]
+    /// Print out the matching loops, including those which alternate between
+    /// several quantifiers, and explain how each one repeats
+    MatchingLoops {
+        /// The path to the smt log file
+        logfile: std::path::PathBuf,
+        /// How many of the most costly matching loops to print
+        #[arg(short)]
+        k: Option<usize>,
+    },
+    #[cfg(feature = "analysis")]
+    /// Checks the quantifiers and triggers for common causes of poor
+    /// performance, fails if any finding has the level `error`
+    Lint {
+        /// The path to the smt log file
+        logfile: std::path::PathBuf,
+        /// Set the level of a rule (allow, info, warning or error), e.g.
+        /// `mbqi-only=error`. The rules are equality-chains, unused-yields,
+        /// common-trigger, mbqi-only and repeated-bindings
+        #[arg(short, long = "level")]
+        levels: Vec<String>,
+        /// Set the threshold of a rule, e.g. `equality-chains=6`
+        #[arg(short, long = "threshold")]
+        thresholds: Vec<String>,
+        /// Print the findings as JSON
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    #[cfg(feature = "analysis")]
+    /// Runs z3 with tracing enabled on an SMT-LIB file, parsing the log while
+    /// it is written, and prints the `stats` and `dependencies` reports
+    Record {
+        /// The path to the smt2 file to run z3 on
+        input: std::path::PathBuf,
+        /// The z3 binary to run
+        #[arg(long, default_value = "z3")]
+        z3: std::path::PathBuf,
+        /// Stop z3 after this many seconds
+        #[arg(short, long)]
+        timeout: Option<u64>,
+        /// Stop z3 from making more than this many instantiations
+        /// (`smt.qi.max_instances`)
+        #[arg(short, long)]
+        max_instances: Option<u64>,
+        /// Where to write the log, defaults to the input file with a `.log`
+        /// extension
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+        /// how many of the most instantiated axioms to print
+        #[arg(short)]
+        k: Option<usize>,
+        /// Further options to pass to z3, after a `--`
+        #[arg(last = true)]
+        z3_args: Vec<String>,
+    },
+    #[cfg(feature = "analysis")]
+    /// Follows a log which is still being written, periodically printing
+    /// out statistics
+    Watch {
+        /// The path to the smt log file
+        logfile: std::path::PathBuf,
+        /// How often to print the statistics, in seconds
+        #[arg(short, long, default_value_t = 5, value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64,
+        /// how many of the most instantiated axioms to print
+        #[arg(short)]
+        k: Option<usize>,
+    },
+    /// Prints the lines of the log which created the given items
+    Lines {
+        /// The path to the smt log file
+        logfile: std::path::PathBuf,
+        /// The items to look up, e.g. `i123`, `m4`, `t56`, `e7` or `eq8`
+        #[arg(required = true)]
+        items: Vec<String>,
+    },
+    /// Splits a log into one log file per query (`check-sat`)
+    Split {
+        /// The path to the smt log file
+        logfile: std::path::PathBuf,
+        /// The directory to write the split logs to, defaults to the
+        /// directory of the log file
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+    },
+    #[cfg(feature = "serde")]
+    /// Parses a log and saves the result as a snapshot, which can be opened
+    /// in place of the log by all other commands and the GUI
+    Snapshot {
+        /// The path to the smt log file
+        logfile: std::path::PathBuf,
+        /// Where to write the snapshot, defaults to the log file with a
+        /// `.slpsnap` extension
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+        /// Also compute and store the instantiation graph, which is then
+        /// used instead of constructing it when loading the snapshot
+        #[arg(short, long, default_value_t = false)]
+        graph: bool,
+    },
+    /// Replaces all names in a log (functions, quantifiers, variables and
+    /// sorts) with opaque ones, writing the mapping to a separate file
+    Anonymize {
+        /// The path to the smt log file
+        logfile: std::path::PathBuf,
+        /// Where to write the anonymized log, defaults to the log file with
+        /// an `.anon.log` extension
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+        /// Where to write the mapping from opaque to original names,
+        /// defaults to the output with a `.mapping.tsv` extension
+        #[arg(short, long)]
+        mapping: Option<std::path::PathBuf>,
+        /// Also replace theory symbols (e.g. `+` or `select`) and the values
+        /// of literals
+        #[arg(long, default_value_t = false)]
+        anonymize_theory: bool,
+    },
+    /// Writes the log (or only some of its instantiations) back in the z3
+    /// trace format
+    Extract {
+        /// The path to the smt log file
+        logfile: std::path::PathBuf,
+        /// Only write these instantiations (by index), may be given several
+        /// times
+        #[arg(short, long = "inst")]
+        insts: Vec<usize>,
+        /// Only write the instantiations of the given query (0-indexed)
+        #[arg(short, long)]
+        query: Option<usize>,
+        /// Only write the instantiations which bind a numeral comparing as
+        /// given, e.g. `>1000`, `<(- 3)` or `=#x0f`
+        #[arg(short, long)]
+        bound_value: Option<String>,
+        #[cfg(feature = "analysis")]
+        /// Only write the instantiations of the nth longest matching loop
+        /// (0-indexed)
+        #[arg(short, long)]
+        matching_loop: Option<usize>,
+        /// Also write the instantiations which the selected ones depend on
+        #[arg(short, long, default_value_t = false)]
+        ancestors: bool,
+        /// Where to write the log, defaults to the log file with an
+        /// `.extract.log` extension
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Relates the quantifiers in a log to the SMT-LIB input problem which
+    /// was run, printing each one's source location and text
+    Source {
+        /// The path to the smt log file
+        logfile: std::path::PathBuf,
+        /// The path to the `.smt2` file which z3 was run on
+        input: std::path::PathBuf,
+        /// How many of the most instantiated quantifiers to print
+        #[arg(short)]
+        k: Option<usize>,
+    },
+    /// Shrinks a log to a small log which still reproduces a problem
+    Minimize {
+        /// The path to the smt log file
+        logfile: std::path::PathBuf,
+        /// The problem to reproduce: `panic` (while parsing or in any
+        /// analysis), `matching-loop` or the name of a parse error such as
+        /// `UnknownId`
+        #[arg(short, long)]
+        predicate: String,
+        /// Where to write the minimized log, defaults to the log file with a
+        /// `.min.log` extension
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+    },
+    #[cfg(feature = "synth")]
+    /// Generates a synthetic log from a small random model
+    Synth {
+        /// Seed for the random model, the same seed and options always
+        /// generate the same log
+        #[arg(short, long, default_value_t = 0)]
+        seed: u64,
+        /// Pick all other options at random (from the seed)
+        #[arg(short, long, default_value_t = false)]
+        random: bool,
+        /// The number of ground constants
+        #[arg(long, default_value_t = 4)]
+        constants: usize,
+        /// The number of quantifiers which do not loop
+        #[arg(long, default_value_t = 2)]
+        quantifiers: usize,
+        /// The number of instantiations of the non-looping quantifiers
+        #[arg(long, default_value_t = 8)]
+        instantiations: usize,
+        /// The length of a matching loop, may be given several times
+        #[arg(short, long = "matching-loop")]
+        matching_loops: Vec<usize>,
+        /// The length of a matching loop which alternates between two
+        /// quantifiers, may be given several times
+        #[arg(short, long = "alternating-loop")]
+        alternating_loops: Vec<usize>,
+        /// The number of equalities between constants
+        #[arg(long, default_value_t = 2)]
+        equalities: usize,
+        /// The number of `push`ed frames
+        #[arg(long, default_value_t = 2)]
+        frames: usize,
+        /// The number of frames popped without a `pop` being logged
+        #[arg(long, default_value_t = 0)]
+        implicit_pops: usize,
+        /// The number of theory-solving instantiations
+        #[arg(long, default_value_t = 1)]
+        theory_solving: usize,
+        /// The number of conflicts found by the SAT core
+        #[arg(long, default_value_t = 0)]
+        conflicts: usize,
+        /// The number of queries
+        #[arg(long, default_value_t = 0)]
+        queries: usize,
+        /// Log proof steps and refute most queries
+        #[arg(long)]
+        proofs: bool,
+        /// Where to write the log, defaults to stdout
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
     },
     /// Tests the parser and analysis, printing out timing information
     Test {
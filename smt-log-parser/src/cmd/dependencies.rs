@@ -1,17 +1,125 @@
 use std::path::PathBuf;
 
+use petgraph::visit::EdgeRef;
 use smt_log_parser::{
-    analysis::{InstGraph, QuantifierAnalysis},
-    items::QuantIdx,
+    analysis::{CostModel, InstGraph, QuantifierAnalysis, QuantifierGraph},
+    display_with::{DisplayCtxt, DisplayWithCtxt},
+    formatter::TermDisplayContext,
+    items::{QuantIdx, QueryIdx},
     Z3Parser,
 };
 
-pub fn run(logfile: PathBuf, depth: Option<u32>, pretty_print: bool) -> Result<(), String> {
-    let parser = super::run_on_logfile(logfile)?;
-    let mut inst_graph = InstGraph::new(&parser).map_err(|e| format!("{e:?}"))?;
-    inst_graph.initialise_inst_succs_and_preds(&parser);
+use super::json::Json;
 
-    let qanalysis = QuantifierAnalysis::new(&parser, &inst_graph);
+/// Formats in which the quantifier graph can be exported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Export {
+    Dot,
+    Json,
+}
+
+pub fn run(
+    logfile: PathBuf,
+    depth: Option<u32>,
+    pretty_print: bool,
+    query: Option<usize>,
+    cost_model: CostModel,
+    export: Option<Export>,
+    parse_options: super::ParseOptions,
+) -> Result<(), String> {
+    let (parser, stored) = super::run_on_logfile(logfile, parse_options)?;
+    let query = query
+        .map(|query| super::get_query(&parser, query))
+        .transpose()?;
+    let Some(export) = export else {
+        return print_dependencies(&parser, stored, depth, pretty_print, query, cost_model);
+    };
+    let qanalysis = quantifier_analysis(&parser, stored, query, cost_model)?;
+    let graph = qanalysis.graph();
+    match export {
+        Export::Dot => print_dot(&parser, &graph),
+        Export::Json => print_json(&parser, &graph),
+    }
+    Ok(())
+}
+
+fn quantifier_analysis(
+    parser: &Z3Parser,
+    stored: Option<InstGraph>,
+    query: Option<QueryIdx>,
+    cost_model: CostModel,
+) -> Result<QuantifierAnalysis, String> {
+    let mut inst_graph = super::inst_graph(parser, stored, cost_model)?;
+    inst_graph.initialise_inst_succs_and_preds(parser);
+    Ok(match query {
+        Some(query) => QuantifierAnalysis::new_for_query(parser, &inst_graph, query),
+        None => QuantifierAnalysis::new(parser, &inst_graph),
+    })
+}
+
+fn quant_name(parser: &Z3Parser, qidx: QuantIdx) -> String {
+    let ctxt = DisplayCtxt {
+        parser,
+        term_display: &TermDisplayContext::basic(),
+        config: super::plain_display_config(),
+    };
+    parser[qidx].kind.with(&ctxt).to_string()
+}
+
+fn print_dot(parser: &Z3Parser, graph: &QuantifierGraph) {
+    let ctxt = DisplayCtxt {
+        parser,
+        term_display: &TermDisplayContext::basic(),
+        config: super::plain_display_config(),
+    };
+    println!("{}", graph.to_dot(&ctxt, |_| String::new()));
+}
+
+fn print_json(parser: &Z3Parser, graph: &QuantifierGraph) {
+    let nodes = graph
+        .graph
+        .node_weights()
+        .map(|node| {
+            Json::object([
+                ("id", usize::from(node.quant).into()),
+                ("name", quant_name(parser, node.quant).into()),
+                ("instantiations", node.insts.into()),
+                ("cost", node.costs.into()),
+            ])
+        })
+        .collect();
+    let edges = graph
+        .graph
+        .edge_references()
+        .map(|edge| {
+            let (from, to) = (&graph.graph[edge.source()], &graph.graph[edge.target()]);
+            Json::object([
+                ("from", usize::from(from.quant).into()),
+                ("to", usize::from(to.quant).into()),
+                ("count", edge.weight().count.into()),
+                ("cost", edge.weight().costs.into()),
+            ])
+        })
+        .collect();
+    let sccs = graph
+        .cycles()
+        .into_iter()
+        .map(|scc| scc.into_iter().map(usize::from).collect::<Json>())
+        .collect();
+    let json = Json::object([("nodes", nodes), ("edges", edges), ("sccs", sccs)]);
+    println!("{json}");
+}
+
+/// `stored` is the instantiation graph of a snapshot, if any.
+pub fn print_dependencies(
+    parser: &Z3Parser,
+    stored: Option<InstGraph>,
+    depth: Option<u32>,
+    pretty_print: bool,
+    query: Option<QueryIdx>,
+    cost_model: CostModel,
+) -> Result<(), String> {
+    let qanalysis = quantifier_analysis(parser, stored, query, cost_model)?;
     let total_costs = qanalysis.total_costs();
     fn get_quant_name(parser: &Z3Parser, qidx: QuantIdx) -> Option<&str> {
         parser[qidx].kind.user_name().map(|name| &parser[name])
@@ -20,14 +128,14 @@ pub fn run(logfile: PathBuf, depth: Option<u32>, pretty_print: bool) -> Result<(
     if depth.is_some_and(|depth| depth == 0) {
         // TODO: deduplicate
         for (qidx, info) in qanalysis.iter_enumerated() {
-            let Some(name) = get_quant_name(&parser, qidx) else {
+            let Some(name) = get_quant_name(parser, qidx) else {
                 continue;
             };
-            let percentage = (100.0 * info.costs) / total_costs as f64;
-            let total = info.direct_deps.values().sum::<u32>() as f64;
+            let percentage = super::percentage(info.costs, total_costs);
+            let total = info.direct_deps.values().map(|dep| dep.count).sum::<u32>() as f64;
             let named = || {
-                info.direct_deps.iter().flat_map(|(ddep, count)| {
-                    get_quant_name(&parser, *ddep).map(|name| (name, *count))
+                info.direct_deps.iter().flat_map(|(ddep, dep)| {
+                    get_quant_name(parser, *ddep).map(|name| (name, dep.count))
                 })
             };
             if pretty_print {
@@ -41,13 +149,13 @@ pub fn run(logfile: PathBuf, depth: Option<u32>, pretty_print: bool) -> Result<(
                     );
                 }
                 for (dep, count) in named() {
-                    let percentage = 100.0 * count as f64 / total;
+                    let percentage = super::percentage(count as f64, total);
                     println!(" - {dep} ({percentage:.1}%)");
                 }
             } else {
                 let deps: Vec<String> = named()
                     .map(|(dep, count)| {
-                        let percentage = 100.0 * count as f64 / total;
+                        let percentage = super::percentage(count as f64, total);
                         format!("{dep} ({percentage:.1}%)")
                     })
                     .collect();
@@ -63,6 +171,7 @@ pub fn run(logfile: PathBuf, depth: Option<u32>, pretty_print: bool) -> Result<(
                 }
             }
         }
+        print_cycles(parser, &qanalysis, total_costs);
         return Ok(());
     }
 
@@ -70,11 +179,11 @@ pub fn run(logfile: PathBuf, depth: Option<u32>, pretty_print: bool) -> Result<(
 
     for (qidx, deps) in trans.iter_enumerated() {
         let info = &qanalysis[qidx];
-        let Some(name) = get_quant_name(&parser, qidx) else {
+        let Some(name) = get_quant_name(parser, qidx) else {
             continue;
         };
-        let percentage = (100.0 * info.costs as f64) / total_costs as f64;
-        let named = || deps.iter().flat_map(|ddep| get_quant_name(&parser, *ddep));
+        let percentage = super::percentage(info.costs, total_costs);
+        let named = || deps.iter().flat_map(|ddep| get_quant_name(parser, *ddep));
         if pretty_print {
             println!(
                 "axiom {name} ({percentage:.1}%) depends on {} axioms:",
@@ -88,6 +197,27 @@ pub fn run(logfile: PathBuf, depth: Option<u32>, pretty_print: bool) -> Result<(
             println!("{name} ({percentage:.1}%) = {}", deps.join(", "));
         }
     }
+    print_cycles(parser, &qanalysis, total_costs);
 
     Ok(())
 }
+
+/// Each strongly connected component of the quantifier graph is a group of
+/// quantifiers which may be instantiating each other recursively.
+fn print_cycles(parser: &Z3Parser, qanalysis: &QuantifierAnalysis, total_costs: f64) {
+    let cycles = qanalysis.graph().cycles();
+    println!("no-quantifier-cycles: {}", cycles.len());
+    for (n, scc) in cycles.into_iter().enumerate() {
+        let insts: u32 = scc.iter().map(|&qidx| qanalysis[qidx].insts).sum();
+        let costs: f64 = scc.iter().map(|&qidx| qanalysis[qidx].costs).sum();
+        let percentage = super::percentage(costs, total_costs);
+        let names: Vec<_> = scc
+            .into_iter()
+            .map(|qidx| quant_name(parser, qidx))
+            .collect();
+        println!(
+            "quantifier-cycle-{n} = {insts} instantiations, {percentage:.1}% of cost: {}",
+            names.join(", ")
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+
+#[cfg(feature = "analysis")]
+use smt_log_parser::{analysis::InstGraph, Z3Parser};
+use smt_log_parser::{
+    items::{InstIdx, Value},
+    parsers::z3::writer::TraceWriter,
+};
+
+/// Which instantiations to write, everything if none are given.
+pub struct Selection {
+    pub insts: Vec<usize>,
+    pub query: Option<usize>,
+    pub bound_value: Option<String>,
+    #[cfg(feature = "analysis")]
+    pub matching_loop: Option<usize>,
+    pub ancestors: bool,
+}
+
+impl Selection {
+    fn is_empty(&self) -> bool {
+        #[cfg(feature = "analysis")]
+        if self.matching_loop.is_some() {
+            return false;
+        }
+        self.insts.is_empty() && self.query.is_none() && self.bound_value.is_none()
+    }
+}
+
+pub fn run(
+    logfile: PathBuf,
+    selection: Selection,
+    output: Option<PathBuf>,
+    parse_options: super::ParseOptions,
+) -> Result<(), String> {
+    #[allow(unused_mut, unused_variables)]
+    let (mut parser, stored) = super::run_on_logfile(logfile.clone(), parse_options)?;
+    let mut selected = Vec::new();
+    let insts = parser.instantiations().len();
+    for &inst in &selection.insts {
+        if inst >= insts {
+            return Err(format!(
+                "Instantiation {inst} out of range, the log contains {insts} instantiations"
+            ));
+        }
+        selected.push(InstIdx::from(inst));
+    }
+    if let Some(query) = selection.query {
+        let query = super::get_query(&parser, query)?;
+        selected.extend(parser.query_items(query).insts());
+    }
+    if let Some(comparison) = &selection.bound_value {
+        let (ordering, value) = Value::parse_comparison(comparison).ok_or_else(|| {
+            format!("Invalid bound value `{comparison}`, expected e.g. `>1000` or `=#x0f`")
+        })?;
+        selected.extend(
+            parser
+                .instantiations()
+                .keys()
+                .filter(|&iidx| parser.binds_value(iidx, ordering, &value)),
+        );
+    }
+    #[cfg(feature = "analysis")]
+    if let Some(n) = selection.matching_loop {
+        selected.extend(matching_loop_insts(&mut parser, stored, n)?);
+    }
+
+    let mut writer = TraceWriter::new(&parser);
+    if !selection.is_empty() {
+        writer = writer.only_insts(selected);
+        if selection.ancestors {
+            writer = writer.with_ancestors();
+        }
+    }
+
+    let output = output.unwrap_or_else(|| logfile.with_extension("extract.log"));
+    let file = File::create(&output).map_err(|e| format!("{}: {e}", output.display()))?;
+    let mut out = BufWriter::new(file);
+    writer
+        .write(&mut out)
+        .and_then(|()| out.flush())
+        .map_err(|e| format!("{}: {e}", output.display()))?;
+    println!("{}", output.display());
+    Ok(())
+}
+
+/// The instantiations which are part of the nth longest matching loop.
+#[cfg(feature = "analysis")]
+fn matching_loop_insts(
+    parser: &mut Z3Parser,
+    stored: Option<InstGraph>,
+    n: usize,
+) -> Result<Vec<InstIdx>, String> {
+    let mut graph = super::new_inst_graph(parser, stored)?;
+    let loops = graph.search_matching_loops(parser);
+    if n >= loops {
+        return Err(format!(
+            "Matching loop {n} out of range, found {loops} matching loops"
+        ));
+    }
+    let insts = graph.raw.graph.node_weights().filter_map(|node| {
+        let inst = node.kind().inst()?;
+        node.part_of_ml.contains(&n).then_some(inst)
+    });
+    Ok(insts.collect())
+}
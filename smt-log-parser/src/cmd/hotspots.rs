@@ -0,0 +1,62 @@
+use std::path::PathBuf;
+
+use smt_log_parser::{
+    analysis::{Hotspot, HotspotAnalysis, QuantifierAnalysis},
+    qid::QidDecoders,
+    Z3Parser,
+};
+
+pub fn run(
+    logfile: PathBuf,
+    top_k: Option<usize>,
+    query: Option<usize>,
+    parse_options: super::ParseOptions,
+) -> Result<(), String> {
+    let (parser, stored) = super::run_on_logfile(logfile, parse_options)?;
+    let mut inst_graph = super::new_inst_graph(&parser, stored)?;
+    inst_graph.initialise_inst_succs_and_preds(&parser);
+    let qanalysis = match query {
+        Some(query) => {
+            let query = super::get_query(&parser, query)?;
+            QuantifierAnalysis::new_for_query(&parser, &inst_graph, query)
+        }
+        None => QuantifierAnalysis::new(&parser, &inst_graph),
+    };
+    let hotspots = HotspotAnalysis::new(&parser, &qanalysis, &QidDecoders::default());
+    print_hotspots(&parser, &hotspots, qanalysis.total_costs(), top_k);
+    Ok(())
+}
+
+fn print_hotspots(
+    parser: &Z3Parser,
+    hotspots: &HotspotAnalysis,
+    total_costs: f64,
+    top_k: Option<usize>,
+) {
+    let top_k = top_k.unwrap_or(usize::MAX);
+    println!("no-quantifiers: {}", hotspots.sources.len());
+    println!("no-decoded-quantifiers: {}", hotspots.decoded());
+    let describe = |hotspot: &Hotspot| {
+        let percentage = super::percentage(hotspot.costs, total_costs);
+        format!(
+            "{} = {} instantiations, {percentage:.1}% of cost ({})",
+            hotspot.location(),
+            hotspot.insts,
+            hotspot.tool
+        )
+    };
+    println!("top-files=");
+    for hotspot in hotspots.files.iter().take(top_k) {
+        println!("{}", describe(hotspot));
+    }
+    println!("top-lines=");
+    for hotspot in hotspots.lines.iter().take(top_k) {
+        let names: Vec<_> = hotspot
+            .quants
+            .iter()
+            .filter_map(|&qidx| parser[qidx].kind.user_name())
+            .map(|name| &parser[name])
+            .collect();
+        println!("{}: {}", describe(hotspot), names.join(", "));
+    }
+}
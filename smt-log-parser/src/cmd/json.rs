@@ -0,0 +1,156 @@
+//! The JSON printed by the commands with a `--json` option, the output is
+//! simple enough not to need a JSON library.
+
+use std::fmt;
+
+/// A JSON value, printed compactly by its `Display` implementation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Integer(u64),
+    /// JSON has no infinity or NaN, these are printed as `null`.
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    /// The fields in the order they are printed.
+    Object(Vec<(&'static str, Json)>),
+}
+
+impl Json {
+    pub fn object(fields: impl IntoIterator<Item = (&'static str, Json)>) -> Self {
+        Self::Object(fields.into_iter().collect())
+    }
+}
+
+impl From<u32> for Json {
+    fn from(value: u32) -> Self {
+        Self::Integer(value.into())
+    }
+}
+
+impl From<u64> for Json {
+    fn from(value: u64) -> Self {
+        Self::Integer(value)
+    }
+}
+
+impl From<usize> for Json {
+    fn from(value: usize) -> Self {
+        Self::Integer(value as u64)
+    }
+}
+
+impl From<f64> for Json {
+    fn from(value: f64) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Null, Into::into)
+    }
+}
+
+impl<T: Into<Json>> FromIterator<T> for Json {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::Array(iter.into_iter().map(Into::into).collect())
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Null => write!(f, "null"),
+            Self::Integer(value) => write!(f, "{value}"),
+            Self::Number(value) if value.is_finite() => write!(f, "{value}"),
+            Self::Number(_) => write!(f, "null"),
+            Self::String(s) => write_string(f, s),
+            Self::Array(values) => {
+                write!(f, "[")?;
+                for (idx, value) in values.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                write!(f, "]")
+            }
+            Self::Object(fields) => {
+                write!(f, "{{")?;
+                for (idx, (name, value)) in fields.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, name)?;
+                    write!(f, ":{value}")?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// A JSON string literal containing `s`.
+fn write_string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    write!(f, "\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Json;
+
+    #[test]
+    fn escaping() {
+        let json = Json::from("a \"quoted\" C:\\path\nnext\t\u{1}ü");
+        assert_eq!(
+            json.to_string(),
+            r#""a \"quoted\" C:\\path\nnext\u0009\u0001ü""#
+        );
+        let object = Json::object([("na\"me", Json::Null)]);
+        assert_eq!(object.to_string(), r#"{"na\"me":null}"#);
+    }
+
+    #[test]
+    fn non_finite() {
+        let values = [1.5, 2.0, f64::INFINITY, f64::NEG_INFINITY, f64::NAN];
+        let json: Json = values.into_iter().collect();
+        assert_eq!(json.to_string(), "[1.5,2,null,null,null]");
+    }
+
+    #[test]
+    fn nesting() {
+        let json = Json::object([
+            ("id", 3_usize.into()),
+            ("trigger", None::<usize>.into()),
+            ("ids", [1_usize, 2].into_iter().collect()),
+            ("empty", Json::Array(vec![])),
+        ]);
+        assert_eq!(
+            json.to_string(),
+            r#"{"id":3,"trigger":null,"ids":[1,2],"empty":[]}"#
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use std::{
+    io::{self, BufRead, Read},
+    path::PathBuf,
+};
+
+use smt_log_parser::{
+    items::{ENodeIdx, EqGivenIdx, InstIdx, MatchIdx, TermIdx},
+    parsers::{any::AnyParser, z3::provenance::LinePos, FileRead},
+    LogParser, Z3Parser,
+};
+
+pub fn run(
+    logfile: PathBuf,
+    items: Vec<String>,
+    parse_options: super::ParseOptions,
+) -> Result<(), String> {
+    let (_metadata, mut parser) =
+        AnyParser::from_file(&logfile).map_err(|e| format!("{}: {e}", logfile.display()))?;
+    parser.set_strict(parse_options.strict);
+    parser.set_provenance(true);
+    let parser: Z3Parser = parser.process_all().map_err(|e| e.to_string())?.into();
+    super::print_diagnostics(&parser, parse_options);
+
+    let mut lines = Vec::new();
+    for item in &items {
+        for (label, pos) in positions(&parser, item)? {
+            lines.push((pos, format!("{item} {label}")));
+        }
+    }
+    lines.sort_by_key(|(pos, _)| pos.byte_offset);
+
+    let (_metadata, mut reader) = (&logfile)
+        .read_open()
+        .map_err(|e| format!("{}: {e}", logfile.display()))?;
+    // The number of bytes consumed from `reader` and the last line read.
+    let mut offset = 0;
+    let mut line: Option<(usize, String)> = None;
+    for (pos, label) in lines {
+        // Several items may have been created by the same line.
+        let text = match &mut line {
+            Some((start, text)) if *start == pos.byte_offset => text,
+            _ => {
+                let skip = pos.byte_offset - offset;
+                io::copy(&mut (&mut reader).take(skip as u64), &mut io::sink())
+                    .map_err(|e| e.to_string())?;
+                let mut text = String::new();
+                let read = reader.read_line(&mut text).map_err(|e| e.to_string())?;
+                offset = pos.byte_offset + read;
+                &mut line.insert((pos.byte_offset, text)).1
+            }
+        };
+        println!("{label} (line {}): {}", pos.line_no, text.trim_end());
+    }
+    Ok(())
+}
+
+/// Parses an item id such as `i123` and returns the positions of the lines
+/// relevant to it.
+fn positions(parser: &Z3Parser, item: &str) -> Result<Vec<(&'static str, LinePos)>, String> {
+    let provenance = parser
+        .provenance()
+        .ok_or("The log was not parsed with provenance")?;
+    let (prefix, idx) = item
+        .find(|c: char| c.is_ascii_digit())
+        .map(|split| item.split_at(split))
+        .ok_or_else(|| format!("Invalid item `{item}`, expected e.g. `i123`"))?;
+    let idx: usize = idx
+        .parse()
+        .map_err(|_| format!("Invalid item `{item}`, expected e.g. `i123`"))?;
+    let missing = || format!("Item `{item}` does not exist in the log");
+    let positions = match prefix {
+        "t" => {
+            let pos = provenance.term(TermIdx::from(idx)).ok_or_else(missing)?;
+            vec![("term", pos)]
+        }
+        "m" => {
+            let pos = provenance.match_(MatchIdx::from(idx)).ok_or_else(missing)?;
+            vec![("match", pos)]
+        }
+        "i" => {
+            let iidx = InstIdx::from(idx);
+            let pos = provenance.inst(iidx).ok_or_else(missing)?;
+            let match_ = provenance.match_(parser[iidx].match_).ok_or_else(missing)?;
+            vec![("match", match_), ("instance", pos)]
+        }
+        "e" => {
+            let pos = provenance.enode(ENodeIdx::from(idx)).ok_or_else(missing)?;
+            vec![("enode", pos)]
+        }
+        "≡" | "eq" => {
+            let pos = provenance
+                .eq_given(EqGivenIdx::from(idx))
+                .ok_or_else(missing)?;
+            vec![("equality", pos)]
+        }
+        _ => {
+            return Err(format!(
+                "Unknown item kind `{prefix}`, expected one of `t`, `m`, `i`, `e` or `eq`"
+            ))
+        }
+    };
+    Ok(positions)
+}
@@ -0,0 +1,101 @@
+use std::{path::PathBuf, str::FromStr};
+
+use smt_log_parser::{
+    analysis::{LintAnalysis, LintConfig, LintLevel, LintRule},
+    display_with::DisplayCtxt,
+    formatter::TermDisplayContext,
+};
+
+use super::json::Json;
+
+pub fn run(
+    logfile: PathBuf,
+    levels: Vec<String>,
+    thresholds: Vec<String>,
+    json: bool,
+    parse_options: super::ParseOptions,
+) -> Result<(), String> {
+    let mut config = LintConfig::default();
+    for level in &levels {
+        let (rule, level) = rule_setting::<LintLevel>(level)?;
+        config[rule].level = level;
+    }
+    for threshold in &thresholds {
+        let (rule, threshold) = rule_setting::<f64>(threshold)?;
+        config[rule].threshold = threshold;
+    }
+
+    let (parser, stored) = super::run_on_logfile(logfile, parse_options)?;
+    let mut inst_graph = super::new_inst_graph(&parser, stored)?;
+    inst_graph.initialise_inst_succs_and_preds(&parser);
+    let lints = LintAnalysis::new(&parser, &inst_graph, &config);
+
+    let ctxt = DisplayCtxt {
+        parser: &parser,
+        term_display: &TermDisplayContext::basic(),
+        config: super::plain_display_config(),
+    };
+    if json {
+        let lints = lints
+            .iter()
+            .map(|lint| {
+                Json::object([
+                    ("rule", lint.rule.name().into()),
+                    ("level", lint.level.to_string().into()),
+                    ("quantifier", usize::from(lint.quant).into()),
+                    ("trigger", lint.trigger.map(usize::from).into()),
+                    ("value", lint.value.into()),
+                    ("count", lint.count.into()),
+                    (
+                        "evidence",
+                        lint.evidence.iter().map(|&i| usize::from(i)).collect(),
+                    ),
+                    ("message", lint.message(&ctxt).into()),
+                ])
+            })
+            .collect();
+        println!("{}", Json::object([("lints", lints)]));
+    } else {
+        println!("no-lints: {}", lints.len());
+        for lint in lints.iter() {
+            println!(
+                "{}[{}]: {}",
+                lint.level,
+                lint.rule.name(),
+                lint.message(&ctxt)
+            );
+            if lint.evidence.is_empty() {
+                continue;
+            }
+            let evidence: Vec<_> = lint
+                .evidence
+                .iter()
+                .map(|&iidx| usize::from(iidx).to_string())
+                .collect();
+            println!("  instantiations: {}", evidence.join(", "));
+        }
+    }
+
+    let errors = lints
+        .iter()
+        .filter(|lint| lint.level == LintLevel::Error)
+        .count();
+    if errors > 0 {
+        return Err(format!("{errors} findings with the level error"));
+    }
+    Ok(())
+}
+
+/// Parses `rule=value`.
+fn rule_setting<T: FromStr>(setting: &str) -> Result<(LintRule, T), String>
+where
+    T::Err: std::fmt::Display,
+{
+    let (rule, value) = setting
+        .split_once('=')
+        .ok_or_else(|| format!("expected `rule=value`, found `{setting}`"))?;
+    let value = value
+        .parse()
+        .map_err(|e| format!("invalid value for `{rule}`: {e}"))?;
+    Ok((rule.parse()?, value))
+}
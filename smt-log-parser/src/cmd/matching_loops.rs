@@ -0,0 +1,61 @@
+use std::path::PathBuf;
+
+use smt_log_parser::{
+    display_with::{DisplayCtxt, DisplayWithCtxt},
+    formatter::TermDisplayContext,
+};
+
+pub fn run(
+    logfile: PathBuf,
+    top_k: Option<usize>,
+    parse_options: super::ParseOptions,
+) -> Result<(), String> {
+    let (mut parser, stored) = super::run_on_logfile(logfile, parse_options)?;
+    let mut graph = super::new_inst_graph(&parser, stored)?;
+    let loops = graph.search_matching_loops(&mut parser);
+    let total_costs: f64 = graph
+        .raw
+        .graph
+        .node_weights()
+        .filter(|node| node.kind().inst().is_some())
+        .map(|node| node.cost)
+        .sum();
+
+    let ctxt = DisplayCtxt {
+        parser: &parser,
+        term_display: &TermDisplayContext::basic(),
+        config: super::plain_display_config(),
+    };
+    println!("no-matching-loops: {loops}");
+    let loops = graph.analysis.matching_loops.iter();
+    let explanations = graph.analysis.matching_loop_explanations.iter();
+    for (n, (ml, explanation)) in loops.zip(explanations).enumerate() {
+        if top_k.is_some_and(|k| n >= k) {
+            break;
+        }
+        let percentage = super::percentage(ml.cost, total_costs);
+        println!(
+            "matching-loop-{n} = {} instantiations, chain of {}, term depth {} -> {}, {percentage:.1}% of cost",
+            ml.insts.len(),
+            ml.chain.len(),
+            ml.start_depth,
+            ml.end_depth,
+        );
+        for trigger in &explanation.triggers {
+            let name = parser[trigger.quant].kind.with(&ctxt);
+            let mut quant = Some(trigger.quant);
+            let pattern = trigger.pattern.with_data(&ctxt, &mut quant);
+            println!("trigger: {name} {pattern}");
+        }
+        for &matched in &explanation.matched {
+            println!("matched: {}", matched.with(&ctxt));
+        }
+        for eq in &explanation.equalities {
+            println!("equality: {} = {}", eq.from.with(&ctxt), eq.to.with(&ctxt));
+        }
+        for sentence in explanation.sentences(&ctxt) {
+            println!("- {sentence}");
+        }
+    }
+    Ok(())
+}
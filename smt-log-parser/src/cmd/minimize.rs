@@ -0,0 +1,46 @@
+use std::{io::Read, path::PathBuf};
+
+use smt_log_parser::{
+    minimize::{minimize, Predicate},
+    parsers::FileRead,
+};
+
+pub fn run(logfile: PathBuf, predicate: String, output: Option<PathBuf>) -> Result<(), String> {
+    let predicate = match predicate.as_str() {
+        "panic" => Predicate::Panic,
+        #[cfg(feature = "analysis")]
+        "matching-loop" => Predicate::MatchingLoop,
+        kind if kind.starts_with(|c: char| c.is_ascii_uppercase()) => {
+            Predicate::Error(kind.to_string())
+        }
+        _ => {
+            return Err(format!(
+                "Invalid predicate `{predicate}`, expected `panic`, `matching-loop` or an error such as `UnknownId`"
+            ))
+        }
+    };
+    let (_metadata, mut reader) = (&logfile)
+        .read_open()
+        .map_err(|e| format!("{}: {e}", logfile.display()))?;
+    let mut log = String::new();
+    reader
+        .read_to_string(&mut log)
+        .map_err(|e| format!("{}: {e}", logfile.display()))?;
+
+    // The panics of the candidate logs are expected, don't print them.
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    let minimizer = minimize(&log, predicate);
+    std::panic::set_hook(hook);
+    let minimizer = minimizer.ok_or("The predicate does not hold for the log")?;
+
+    let output = output.unwrap_or_else(|| logfile.with_extension("min.log"));
+    std::fs::write(&output, minimizer.log()).map_err(|e| format!("{}: {e}", output.display()))?;
+    let (before, after) = minimizer.line_counts();
+    eprintln!(
+        "Reduced {before} lines to {after} lines ({} checks)",
+        minimizer.checks
+    );
+    println!("{}", output.display());
+    Ok(())
+}
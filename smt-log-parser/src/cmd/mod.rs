@@ -1,30 +1,324 @@
+mod anonymize;
 mod args;
 #[cfg(feature = "analysis")]
 mod dependencies;
+mod extract;
+#[cfg(feature = "analysis")]
+mod hotspots;
+#[cfg(feature = "analysis")]
+mod json;
+mod lines;
+#[cfg(feature = "analysis")]
+mod lint;
+#[cfg(feature = "analysis")]
+mod matching_loops;
+mod minimize;
+#[cfg(feature = "analysis")]
+mod pops;
+#[cfg(feature = "analysis")]
+mod proof;
+#[cfg(feature = "analysis")]
+mod record;
+#[cfg(feature = "serde")]
+mod snapshot;
+mod source;
+mod split;
 mod stats;
+#[cfg(feature = "synth")]
+mod synth;
 mod test;
+#[cfg(feature = "analysis")]
+mod watch;
 
 use clap::Parser;
-use smt_log_parser::{LogParser, Z3Parser};
+#[cfg(feature = "analysis")]
+use smt_log_parser::analysis::{percentage, CostModel, InstGraph, ProofAnalysis};
+use smt_log_parser::{
+    display_with::{DisplayConfiguration, SymbolReplacement},
+    items::QueryIdx,
+    parsers::any::AnyParser,
+    LogParser, Z3Parser,
+};
+
+/// The options shared by all commands which parse a log.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ParseOptions {
+    pub diagnostics: bool,
+    pub strict: bool,
+}
+
+/// The instantiation graph of a snapshot passed in place of the log.
+#[cfg(feature = "analysis")]
+type StoredGraph = InstGraph;
+#[cfg(not(feature = "analysis"))]
+type StoredGraph = std::convert::Infallible;
 
 pub fn run() -> Result<(), String> {
-    match args::Cli::parse().command {
+    let cli = args::Cli::parse();
+    let parse_options = ParseOptions {
+        diagnostics: cli.diagnostics,
+        strict: cli.strict,
+    };
+    match cli.command {
         #[cfg(feature = "analysis")]
         args::Commands::Dependencies {
             logfile,
             depth,
             pretty_print,
-        } => dependencies::run(logfile, depth, pretty_print)?,
+            query,
+            cost_model,
+            dot,
+            json,
+        } => {
+            let export = match (dot, json) {
+                (true, _) => Some(dependencies::Export::Dot),
+                (_, true) => Some(dependencies::Export::Json),
+                _ => None,
+            };
+            dependencies::run(
+                logfile,
+                depth,
+                pretty_print,
+                query,
+                cost_model,
+                export,
+                parse_options,
+            )?
+        }
+        #[cfg(feature = "analysis")]
+        args::Commands::Stats {
+            logfile,
+            k,
+            query,
+            cost_model,
+        } => stats::run(logfile, k, query, cost_model, parse_options)?,
+        #[cfg(feature = "analysis")]
+        args::Commands::Watch {
+            logfile,
+            interval,
+            k,
+        } => watch::run(logfile, interval, k, parse_options)?,
+        #[cfg(feature = "analysis")]
+        args::Commands::Pops { logfile, k } => pops::run(logfile, k, parse_options)?,
         #[cfg(feature = "analysis")]
-        args::Commands::Stats { logfile, k } => stats::run(logfile, k)?,
-        args::Commands::Test { logfiles } => test::run(logfiles)?,
+        args::Commands::Proof { logfile, k } => proof::run(logfile, k, parse_options)?,
+        #[cfg(feature = "analysis")]
+        args::Commands::Record {
+            input,
+            z3,
+            timeout,
+            max_instances,
+            output,
+            k,
+            z3_args,
+        } => {
+            let options = record::Options {
+                z3,
+                timeout,
+                max_instances,
+                output,
+                z3_args,
+            };
+            record::run(input, options, k, parse_options)?
+        }
+        #[cfg(feature = "analysis")]
+        args::Commands::Hotspots { logfile, k, query } => {
+            hotspots::run(logfile, k, query, parse_options)?
+        }
+        #[cfg(feature = "analysis")]
+        args::Commands::MatchingLoops { logfile, k } => {
+            matching_loops::run(logfile, k, parse_options)?
+        }
+        #[cfg(feature = "analysis")]
+        args::Commands::Lint {
+            logfile,
+            levels,
+            thresholds,
+            json,
+        } => lint::run(logfile, levels, thresholds, json, parse_options)?,
+        args::Commands::Lines { logfile, items } => lines::run(logfile, items, parse_options)?,
+        args::Commands::Anonymize {
+            logfile,
+            output,
+            mapping,
+            anonymize_theory,
+        } => anonymize::run(logfile, output, mapping, anonymize_theory)?,
+        args::Commands::Extract {
+            logfile,
+            insts,
+            query,
+            bound_value,
+            #[cfg(feature = "analysis")]
+            matching_loop,
+            ancestors,
+            output,
+        } => {
+            let selection = extract::Selection {
+                insts,
+                query,
+                bound_value,
+                #[cfg(feature = "analysis")]
+                matching_loop,
+                ancestors,
+            };
+            extract::run(logfile, selection, output, parse_options)?
+        }
+        args::Commands::Source { logfile, input, k } => {
+            source::run(logfile, input, k, parse_options)?
+        }
+        args::Commands::Minimize {
+            logfile,
+            predicate,
+            output,
+        } => minimize::run(logfile, predicate, output)?,
+        args::Commands::Split { logfile, output } => split::run(logfile, output)?,
+        #[cfg(feature = "serde")]
+        args::Commands::Snapshot {
+            logfile,
+            output,
+            graph,
+        } => snapshot::run(logfile, output, graph, parse_options)?,
+        #[cfg(feature = "synth")]
+        args::Commands::Synth {
+            seed,
+            random,
+            constants,
+            quantifiers,
+            instantiations,
+            matching_loops,
+            alternating_loops,
+            equalities,
+            frames,
+            implicit_pops,
+            theory_solving,
+            conflicts,
+            queries,
+            proofs,
+            output,
+        } => {
+            let config = if random {
+                smt_log_parser::synth::SynthConfig::random(seed)
+            } else {
+                smt_log_parser::synth::SynthConfig {
+                    seed,
+                    constants,
+                    quantifiers,
+                    instantiations,
+                    matching_loops,
+                    alternating_loops,
+                    equalities,
+                    frames,
+                    implicit_pops,
+                    theory_solving,
+                    conflicts,
+                    queries,
+                    proofs,
+                }
+            };
+            synth::run(config, output)?
+        }
+        args::Commands::Test { logfiles } => test::run(logfiles, parse_options)?,
     }
 
     Ok(())
 }
 
-fn run_on_logfile(logfile: std::path::PathBuf) -> Result<Z3Parser, String> {
+/// Parses `logfile`, or loads it if it is a snapshot, in which case the
+/// instantiation graph stored in the snapshot is returned as well.
+fn run_on_logfile(
+    logfile: std::path::PathBuf,
+    options: ParseOptions,
+) -> Result<(Z3Parser, Option<StoredGraph>), String> {
     let path = std::path::Path::new(&logfile);
-    let (_metadata, parser) = Z3Parser::from_file(path).map_err(|e| e.to_string())?;
-    parser.process_all().map_err(|e| e.to_string())
+    #[cfg(feature = "serde")]
+    if let Some((parser, stored)) = snapshot::try_load(path)? {
+        print_diagnostics(&parser, options);
+        return Ok((parser, stored));
+    }
+    let (_metadata, mut parser) = AnyParser::from_file(path).map_err(|e| e.to_string())?;
+    parser.set_strict(options.strict);
+    let parser = parser.process_all().map_err(|e| e.to_string())?;
+    let parser = parser.into();
+    print_diagnostics(&parser, options);
+    Ok((parser, None))
+}
+
+/// Prints the problems encountered while parsing to stderr, or only a summary
+/// if `--diagnostics` was not passed.
+fn print_diagnostics(parser: &Z3Parser, options: ParseOptions) {
+    let diagnostics = parser.diagnostics();
+    if diagnostics.is_empty() {
+        return;
+    }
+    if options.diagnostics {
+        for diagnostic in diagnostics.iter() {
+            eprintln!("{diagnostic}");
+        }
+    }
+    eprintln!(
+        "{} lines could not be parsed ({} skipped){}",
+        diagnostics.lines_affected(),
+        diagnostics.lines_skipped(),
+        if options.diagnostics {
+            ""
+        } else {
+            ", rerun with --diagnostics for details"
+        }
+    );
+}
+
+/// The instantiation graph with the costs of `cost_model`.
+#[cfg(feature = "analysis")]
+fn inst_graph(
+    parser: &Z3Parser,
+    stored: Option<InstGraph>,
+    cost_model: CostModel,
+) -> Result<InstGraph, String> {
+    if cost_model == CostModel::Proof && ProofAnalysis::new(parser).refutations.is_empty() {
+        return Err(
+            "The proof cost model needs a log of an unsat query from z3 run with `proof=true`"
+                .to_string(),
+        );
+    }
+    let mut inst_graph = new_inst_graph(parser, stored)?;
+    if cost_model != CostModel::default() {
+        inst_graph.set_cost_model(parser, cost_model);
+    }
+    Ok(inst_graph)
+}
+
+/// The instantiation graph `stored` in the snapshot, if any, otherwise a
+/// newly constructed one.
+#[cfg(feature = "analysis")]
+fn new_inst_graph(parser: &Z3Parser, stored: Option<InstGraph>) -> Result<InstGraph, String> {
+    if let Some(graph) = stored {
+        return Ok(graph);
+    }
+    InstGraph::new(parser).map_err(|e| format!("{e:?}"))
+}
+
+/// Terms are printed as plain text, without term ids.
+fn plain_display_config() -> DisplayConfiguration {
+    DisplayConfiguration {
+        display_term_ids: false,
+        display_quantifier_name: false,
+        replace_symbols: SymbolReplacement::None,
+        #[cfg(feature = "display_html")]
+        html: false,
+        enode_char_limit: None,
+        ast_depth_limit: None,
+        numeral_radix: Default::default(),
+        numeral_width: 0,
+    }
+}
+
+fn get_query(parser: &Z3Parser, query: usize) -> Result<QueryIdx, String> {
+    let queries = parser.queries().len();
+    if query < queries {
+        Ok(QueryIdx::from(query))
+    } else {
+        Err(format!(
+            "Query {query} out of range, the log contains {queries} queries"
+        ))
+    }
 }
@@ -0,0 +1,53 @@
+use std::path::PathBuf;
+
+use smt_log_parser::{analysis::PopAnalysis, Z3Parser};
+
+pub fn run(
+    logfile: PathBuf,
+    top_k: Option<usize>,
+    parse_options: super::ParseOptions,
+) -> Result<(), String> {
+    let (parser, _) = super::run_on_logfile(logfile, parse_options)?;
+    let pops = PopAnalysis::new(&parser);
+    print_pops(&parser, &pops, top_k);
+    Ok(())
+}
+
+fn print_pops(parser: &Z3Parser, pops: &PopAnalysis, top_k: Option<usize>) {
+    let top_k = top_k.unwrap_or(usize::MAX);
+    let frames = parser.stack_frames();
+    let popped = frames.iter().filter(|f| !f.active).count();
+    let implicit = frames.iter().filter(|f| f.implicit_pop).count();
+    println!("no-frames: {}", frames.len());
+    println!("no-popped-frames: {popped}");
+    println!(
+        "no-implicit-pops: {implicit} ({} instantiations, {} enodes)",
+        pops.implicit.insts, pops.implicit.enodes
+    );
+    let counts = parser.item_counts();
+    println!(
+        "no-lost-instantiations: {} of {}",
+        pops.total.insts, counts.insts
+    );
+    println!("no-lost-enodes: {} of {}", pops.total.enodes, counts.enodes);
+
+    let mut quants: Vec<_> = pops
+        .quants
+        .iter_enumerated()
+        .filter(|(_, info)| info.lost > 0)
+        .collect();
+    quants.sort_by(|(_, l), (_, r)| r.lost.cmp(&l.lost));
+    println!("top-lost-quantifiers=");
+    for (qidx, info) in quants.into_iter().take(top_k) {
+        let name = parser[qidx].kind.user_name();
+        let name = name.map_or_else(|| format!("{qidx:?}"), |name| parser[name].to_string());
+        println!("{name} = {} of {}", info.lost, info.insts);
+    }
+    println!("top-lost-frames=");
+    for (frame, lost) in pops.worst_frames().into_iter().take(top_k) {
+        println!(
+            "{frame:?} (depth {}) = {} instantiations, {} enodes",
+            frames[frame].depth, lost.insts, lost.enodes
+        );
+    }
+}
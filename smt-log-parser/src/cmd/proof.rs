@@ -0,0 +1,51 @@
+use std::path::PathBuf;
+
+use smt_log_parser::{analysis::ProofAnalysis, Z3Parser};
+
+pub fn run(
+    logfile: PathBuf,
+    top_k: Option<usize>,
+    parse_options: super::ParseOptions,
+) -> Result<(), String> {
+    let (parser, _) = super::run_on_logfile(logfile, parse_options)?;
+    let proof = ProofAnalysis::new(&parser);
+    print_proof(&parser, &proof, top_k);
+    Ok(())
+}
+
+fn print_proof(parser: &Z3Parser, proof: &ProofAnalysis, top_k: Option<usize>) {
+    let top_k = top_k.unwrap_or(usize::MAX);
+    println!("no-proof-steps: {}", proof.steps.len());
+    println!("no-refutations: {}", proof.refutations.len());
+    if !parser.queries().is_empty() {
+        println!(
+            "no-refuted-queries: {} of {}",
+            proof.refuted_queries(),
+            parser.queries().len()
+        );
+    }
+    if !proof.has_refutation() {
+        eprintln!("No refutation found, was z3 run with `proof=true` on an unsat query?");
+        return;
+    }
+    let total = proof.total();
+    println!("no-used-instantiations: {}", total.used);
+    println!("no-wasted-instantiations: {}", total.wasted);
+    println!("no-unknown-instantiations: {}", total.unknown);
+
+    let mut quants: Vec<_> = proof
+        .quants
+        .iter_enumerated()
+        .filter(|(_, info)| info.used + info.wasted > 0)
+        .collect();
+    quants.sort_by(|(_, l), (_, r)| r.wasted.cmp(&l.wasted));
+    println!("top-wasted-quantifiers=");
+    for (qidx, info) in quants.into_iter().take(top_k) {
+        let name = parser[qidx].kind.user_name();
+        let name = name.map_or_else(|| format!("{qidx:?}"), |name| parser[name].to_string());
+        println!(
+            "{name} = {} wasted, {} used in proof",
+            info.wasted, info.used
+        );
+    }
+}
@@ -0,0 +1,101 @@
+use std::{
+    fs::File,
+    path::PathBuf,
+    process::{Command, ExitStatus},
+    time::Duration,
+};
+
+use smt_log_parser::{
+    analysis::LogInfo,
+    parsers::{ParseState, ReaderState},
+    LogParser, Z3Parser,
+};
+use wasm_timer::Instant;
+
+/// How often to check for new data once we have reached the end of the file.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// How long after the timeout z3 is killed if it did not stop by itself.
+const KILL_GRACE: Duration = Duration::from_secs(1);
+
+pub struct Options {
+    pub z3: PathBuf,
+    pub timeout: Option<u64>,
+    pub max_instances: Option<u64>,
+    pub output: Option<PathBuf>,
+    pub z3_args: Vec<String>,
+}
+
+pub fn run(
+    input: PathBuf,
+    options: Options,
+    top_k: Option<usize>,
+    parse_options: super::ParseOptions,
+) -> Result<(), String> {
+    let output = options
+        .output
+        .unwrap_or_else(|| input.with_extension("log"));
+    // Create the log before z3 starts so that we can open it straight away.
+    File::create(&output).map_err(|e| format!("{}: {e}", output.display()))?;
+
+    let mut command = Command::new(&options.z3);
+    command
+        .arg("trace=true")
+        .arg("proof=true")
+        .arg(format!("trace-file-name={}", output.display()));
+    if let Some(timeout) = options.timeout {
+        command.arg(format!("-T:{timeout}"));
+    }
+    if let Some(max_instances) = options.max_instances {
+        command.arg(format!("smt.qi.max_instances={max_instances}"));
+    }
+    command.args(&options.z3_args).arg(&input);
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("{}: {e}", options.z3.display()))?;
+
+    let (_metadata, mut parser) =
+        Z3Parser::from_file(&output).map_err(|e| format!("{}: {e}", output.display()))?;
+    parser.set_follow(Some(POLL_INTERVAL));
+    parser.set_strict(parse_options.strict);
+    let deadline = options
+        .timeout
+        .map(|timeout| Instant::now() + Duration::from_secs(timeout) + KILL_GRACE);
+    // z3 only writes `[eof]` if it exits normally, so also stop following
+    // once it has exited and everything it wrote has been read.
+    let mut last_state = ReaderState::default();
+    let state = parser.process_until(|_, state| {
+        let waiting = state == last_state;
+        last_state = state;
+        if !waiting {
+            return None;
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            child.kill().ok();
+        }
+        child.try_wait().ok().flatten()
+    });
+    let status = match state {
+        ParseState::Paused(status, _) => status,
+        ParseState::Completed { .. } => child.wait().map_err(|e| e.to_string())?,
+        ParseState::Error(err) => {
+            child.kill().ok();
+            return Err(err.to_string());
+        }
+    };
+    report_status(status);
+    // Read anything left over without waiting for more.
+    parser.set_follow(None);
+    let parser = parser.process_all().map_err(|e| e.to_string())?;
+    super::print_diagnostics(&parser, parse_options);
+
+    let info = LogInfo::new(&parser);
+    super::stats::print_info(&parser, &info, top_k, None);
+    println!("dependencies=");
+    super::dependencies::print_dependencies(&parser, None, Some(0), false, None, Default::default())
+}
+
+fn report_status(status: ExitStatus) {
+    if !status.success() {
+        eprintln!("z3 exited with {status}, the log may be incomplete");
+    }
+}
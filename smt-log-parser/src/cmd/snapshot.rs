@@ -0,0 +1,65 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Seek},
+    path::{Path, PathBuf},
+};
+
+use smt_log_parser::{snapshot, Z3Parser};
+
+pub fn run(
+    logfile: PathBuf,
+    output: Option<PathBuf>,
+    graph: bool,
+    parse_options: super::ParseOptions,
+) -> Result<(), String> {
+    let (parser, _) = super::run_on_logfile(logfile.clone(), parse_options)?;
+    let output = output.unwrap_or_else(|| logfile.with_extension("slpsnap"));
+    let file = File::create(&output).map_err(|e| format!("{}: {e}", output.display()))?;
+    let writer = BufWriter::new(file);
+
+    #[cfg(feature = "analysis")]
+    {
+        let graph = graph
+            .then(|| smt_log_parser::analysis::InstGraph::new(&parser))
+            .transpose()
+            .map_err(|e| format!("{e:?}"))?;
+        parser
+            .save_snapshot_with(writer, graph.as_ref())
+            .map_err(|e| e.to_string())?;
+    }
+    #[cfg(not(feature = "analysis"))]
+    {
+        if graph {
+            return Err("Storing the graph requires the `analysis` feature".to_string());
+        }
+        parser.save_snapshot(writer).map_err(|e| e.to_string())?;
+    }
+    println!("{}", output.display());
+    Ok(())
+}
+
+/// Loads `path` as a snapshot if it starts with the snapshot magic bytes,
+/// together with the instantiation graph if one was stored.
+pub fn try_load(path: &Path) -> Result<Option<(Z3Parser, Option<super::StoredGraph>)>, String> {
+    let file = File::open(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let mut reader = BufReader::new(file);
+    let mut magic = [0; snapshot::MAGIC.len()];
+    let is_snapshot = match reader.read_exact(&mut magic) {
+        Ok(()) => snapshot::is_snapshot(&magic),
+        Err(_) => false,
+    };
+    if !is_snapshot {
+        return Ok(None);
+    }
+    reader.rewind().map_err(|e| e.to_string())?;
+    #[cfg(feature = "analysis")]
+    {
+        let loaded = Z3Parser::load_snapshot_with_graph(reader).map_err(|e| e.to_string())?;
+        Ok(Some(loaded))
+    }
+    #[cfg(not(feature = "analysis"))]
+    {
+        let parser = Z3Parser::load_snapshot(reader).map_err(|e| e.to_string())?;
+        Ok(Some((parser, None)))
+    }
+}
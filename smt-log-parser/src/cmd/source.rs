@@ -0,0 +1,90 @@
+use std::path::PathBuf;
+
+use smt_log_parser::{
+    items::{QuantIdx, QuantKind},
+    parsers::smt2::{Smt2Problem, SourceMap},
+    TiVec, Z3Parser,
+};
+
+pub fn run(
+    logfile: PathBuf,
+    input: PathBuf,
+    top_k: Option<usize>,
+    parse_options: super::ParseOptions,
+) -> Result<(), String> {
+    let (parser, _) = super::run_on_logfile(logfile, parse_options)?;
+    let text = std::fs::read_to_string(&input).map_err(|e| format!("{}: {e}", input.display()))?;
+    let problem = Smt2Problem::parse(&text).map_err(|e| format!("{}: {e:?}", input.display()))?;
+    let map = problem.map_quantifiers(&parser);
+    print_source(&parser, &problem, &map, &input, top_k);
+    Ok(())
+}
+
+fn print_source(
+    parser: &Z3Parser,
+    problem: &Smt2Problem,
+    map: &SourceMap,
+    input: &std::path::Path,
+    top_k: Option<usize>,
+) {
+    let top_k = top_k.unwrap_or(usize::MAX);
+    let mut insts: TiVec<QuantIdx, usize> = parser.quantifiers().iter().map(|_| 0).collect();
+    for inst in parser.instantiations() {
+        if let Some(qidx) = parser[inst.match_].kind.quant_idx() {
+            insts[qidx] += 1;
+        }
+    }
+    let user_quants: Vec<_> = parser
+        .quantifiers()
+        .iter_enumerated()
+        .filter(|(_, quant)| !quant.kind.is_discovered())
+        .map(|(qidx, _)| qidx)
+        .collect();
+    let unmapped: Vec<_> = user_quants
+        .iter()
+        .filter(|&&qidx| map.quants[qidx].is_none())
+        .map(|&qidx| name(parser, qidx))
+        .collect();
+    println!("no-source-quantifiers: {}", problem.quantifiers.len());
+    println!("no-log-quantifiers: {}", user_quants.len());
+    println!("no-unmapped-quantifiers: {}", unmapped.len());
+    if !unmapped.is_empty() {
+        println!("unmapped-quantifiers= {}", unmapped.join(", "));
+    }
+
+    let mut quants: Vec<_> = user_quants
+        .into_iter()
+        .filter_map(|qidx| Some((qidx, map.get(problem, qidx)?)))
+        .collect();
+    quants.sort_by_key(|(qidx, _)| std::cmp::Reverse(insts[*qidx]));
+    println!("top-quantifiers=");
+    for (qidx, source) in quants.into_iter().take(top_k) {
+        let start = source.span.start;
+        println!(
+            "{} = {} instantiations, {}:{}:{}",
+            name(parser, qidx),
+            insts[qidx],
+            input.display(),
+            start.line,
+            start.column,
+        );
+        if let Some(weight) = source.weight {
+            println!("    weight: {weight}");
+        }
+        for pattern in &source.patterns {
+            println!("    pattern: {}", pattern.join(" "));
+        }
+        for line in source.text.lines() {
+            println!("    | {line}");
+        }
+    }
+}
+
+fn name(parser: &Z3Parser, qidx: QuantIdx) -> String {
+    match &parser[qidx].kind {
+        QuantKind::UnnamedQuant { name, id } => format!("{}!{id}", &parser[*name]),
+        kind => kind
+            .user_name()
+            .map_or_else(|| format!("{qidx:?}"), |name| parser[name].to_string()),
+    }
+}
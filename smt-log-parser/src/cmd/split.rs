@@ -0,0 +1,130 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use smt_log_parser::{
+    items::{QueryIdx, QueryResult},
+    parsers::FileRead,
+};
+
+/// Entries which describe the search performed by a single query. These are
+/// dropped from the prefix of later queries, everything else (terms,
+/// quantifiers, e-nodes, equalities and scopes) is kept so that the split logs
+/// can be parsed on their own. Dropping the `[instance]` and
+/// `[end-of-instance]` lines turns the e-nodes and equalities which an
+/// instantiation yielded into ground ones, later queries may still match on
+/// them.
+const SEARCH_ENTRIES: &[&str] = &[
+    "[new-match]",
+    "[inst-discovered]",
+    "[instance]",
+    "[end-of-instance]",
+    "[decide-and-or]",
+    "[decide]",
+    "[assign]",
+    "[conflict]",
+    "[resolve-process]",
+    "[resolve-lit]",
+    "[begin-check]",
+    "[query-done]",
+    "[eof]",
+];
+
+/// Splits the log in a single pass over its lines, the part of a query ends
+/// right before the next `[begin-check]`. Items before the first
+/// `[begin-check]` belong to the first query.
+pub fn run(logfile: PathBuf, output: Option<PathBuf>) -> Result<(), String> {
+    let output = output
+        .or_else(|| logfile.parent().map(PathBuf::from))
+        .unwrap_or_default();
+    let stem = logfile
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "log".to_string());
+
+    let (_metadata, reader) = logfile.read_open().map_err(|e| e.to_string())?;
+    let mut part = Part::create(&output, &stem, QueryIdx::from(0))?;
+    let mut queries = 0;
+    // The lines of all previous queries, excluding the search entries.
+    let mut prefix = String::new();
+    let mut in_search_entry = false;
+    for (line_no, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| format!("line {}: {e}", line_no + 1))?;
+        if line.starts_with('[') {
+            let mut words = line.split(' ');
+            let kind = words.next().unwrap_or_default();
+            match kind {
+                "[begin-check]" => {
+                    if queries > 0 {
+                        part.finish()?;
+                        part = Part::create(&output, &stem, QueryIdx::from(queries))?;
+                        part.write(&prefix)?;
+                    }
+                    queries += 1;
+                }
+                "[query-done]" => {
+                    part.result = words
+                        .next()
+                        .map(QueryResult::parse)
+                        .transpose()
+                        .ok()
+                        .flatten()
+                }
+                _ => (),
+            }
+            in_search_entry = SEARCH_ENTRIES.contains(&kind);
+        }
+        part.write(&line)?;
+        part.write("\n")?;
+        if !in_search_entry {
+            prefix.push_str(&line);
+            prefix.push('\n');
+        }
+    }
+    if queries == 0 {
+        std::fs::remove_file(&part.path).ok();
+        return Err("The log does not contain any queries".to_string());
+    }
+    part.finish()
+}
+
+/// The log of a single query which is being written.
+struct Part {
+    idx: QueryIdx,
+    path: PathBuf,
+    writer: BufWriter<File>,
+    result: Option<QueryResult>,
+}
+
+impl Part {
+    fn create(output: &Path, stem: &str, idx: QueryIdx) -> Result<Self, String> {
+        let path = output.join(format!("{stem}.query{}.log", usize::from(idx)));
+        let file = File::create(&path).map_err(|e| format!("{}: {e}", path.display()))?;
+        Ok(Self {
+            idx,
+            path,
+            writer: BufWriter::new(file),
+            result: None,
+        })
+    }
+
+    fn write(&mut self, s: &str) -> Result<(), String> {
+        self.writer
+            .write_all(s.as_bytes())
+            .map_err(|e| format!("{}: {e}", self.path.display()))
+    }
+
+    fn finish(mut self) -> Result<(), String> {
+        self.writer
+            .flush()
+            .map_err(|e| format!("{}: {e}", self.path.display()))?;
+        let result = self
+            .result
+            .map(|r| r.to_string())
+            .unwrap_or_else(|| "incomplete".to_string());
+        println!("{} ({result}): {}", self.idx, self.path.display());
+        Ok(())
+    }
+}
@@ -1,11 +1,99 @@
 use std::path::PathBuf;
 
-use smt_log_parser::analysis::LogInfo;
+use smt_log_parser::{
+    analysis::{CostModel, InstGraph, LogInfo, TriggerAnalysis},
+    display_with::{DisplayCtxt, DisplayWithCtxt},
+    formatter::TermDisplayContext,
+    items::QuantIdx,
+    Z3Parser,
+};
+use typed_index_collections::TiVec;
 
-pub fn run(logfile: PathBuf, top_k: Option<usize>) -> Result<(), String> {
-    let parser = super::run_on_logfile(logfile)?;
+pub fn run(
+    logfile: PathBuf,
+    top_k: Option<usize>,
+    query: Option<usize>,
+    cost_model: Option<CostModel>,
+    parse_options: super::ParseOptions,
+) -> Result<(), String> {
+    let (parser, stored) = super::run_on_logfile(logfile, parse_options)?;
+    // Matches which were never instantiated do not belong to any query, and
+    // costs are passed on across queries.
+    if let Some(query) = query {
+        let info = LogInfo::new_for_query(&parser, super::get_query(&parser, query)?);
+        print_info(&parser, &info, top_k, None);
+        return Ok(());
+    }
     let info = LogInfo::new(&parser);
+    let inst_graph = super::inst_graph(&parser, stored, cost_model.unwrap_or_default())?;
+    let costs = cost_model.map(|_| quant_costs(&parser, &inst_graph));
+    print_info(&parser, &info, top_k, costs.as_ref());
+    let triggers = TriggerAnalysis::new(&parser, Some(&inst_graph));
+    print_triggers(&parser, &triggers, top_k);
+    Ok(())
+}
+
+/// The summed cost of the instantiations of each quantifier.
+fn quant_costs(parser: &Z3Parser, inst_graph: &InstGraph) -> TiVec<QuantIdx, f64> {
+    let mut costs: TiVec<QuantIdx, f64> = parser.quantifiers().iter().map(|_| 0.0).collect();
+    for (iidx, inst) in parser.instantiations().iter_enumerated() {
+        if let Some(qidx) = parser[inst.match_].kind.quant_idx() {
+            costs[qidx] += inst_graph.raw[iidx].cost;
+        }
+    }
+    costs
+}
+
+pub fn print_triggers(parser: &Z3Parser, triggers: &TriggerAnalysis, top_k: Option<usize>) {
+    let term_display = TermDisplayContext::basic();
+    let ctxt = DisplayCtxt {
+        parser,
+        term_display: &term_display,
+        config: super::plain_display_config(),
+    };
+    let total_costs = triggers.total_costs();
+    let mut all: Vec<_> = triggers
+        .iter_enumerated()
+        .flat_map(|(qidx, infos)| infos.iter().map(move |info| (qidx, info)))
+        .filter(|(_, info)| info.matches > 0)
+        .collect();
+    all.sort_by(|(_, l), (_, r)| {
+        r.costs
+            .total_cmp(&l.costs)
+            .then_with(|| r.insts.cmp(&l.insts))
+            .then_with(|| r.matches.cmp(&l.matches))
+    });
+
+    println!("top-triggers=");
+    for (qidx, info) in all.into_iter().take(top_k.unwrap_or(usize::MAX)) {
+        let name = parser[qidx].kind.user_name();
+        let name = name.map_or_else(|| format!("{qidx:?}"), |name| parser[name].to_string());
+        let share = super::percentage(info.costs, total_costs);
+        let symbols: Vec<_> = info
+            .top_symbols()
+            .into_iter()
+            .map(|(symbol, count)| format!("{} {count}", &parser[symbol]))
+            .collect();
+        println!(
+            "{name} {} = {} matches, {} instantiations, {share:.1}% of cost, {} via equalities, matched {}",
+            info.trigger.with(&ctxt),
+            info.matches,
+            info.insts,
+            info.equality_matches,
+            symbols.join(", "),
+        );
+    }
+}
 
+/// Prints the statistics of `info`, quantifiers are ranked by their `costs`
+/// if given and otherwise by their number of instantiations.
+pub fn print_info(
+    parser: &Z3Parser,
+    info: &LogInfo,
+    top_k: Option<usize>,
+    costs: Option<&TiVec<QuantIdx, f64>>,
+) {
+    let cost = |qidx: QuantIdx| costs.map_or(0.0, |costs| costs[qidx]);
     let mut instantiations_occurrances: Vec<_> = info
         .quants
         .0
@@ -14,11 +102,14 @@ pub fn run(logfile: PathBuf, top_k: Option<usize>) -> Result<(), String> {
             parser[qidx]
                 .kind
                 .user_name()
-                .map(|name| (&parser[name], icount))
+                .map(|name| (&parser[name], icount, cost(qidx)))
         })
         .collect();
-    instantiations_occurrances.sort_by(|l, r| Ord::cmp(&r.1, &l.1));
+    instantiations_occurrances
+        .sort_by(|l, r| r.2.total_cmp(&l.2).then_with(|| Ord::cmp(&r.1, &l.1)));
+    let total_costs: f64 = costs.map_or(0.0, |costs| costs.iter().sum());
 
+    println!("no-queries: {}", parser.queries().len());
     println!("no-enodes: {}", info.inst.enodes);
     println!("no-given-equalities: {}", info.inst.geqs);
     println!("no-trans-equalities: {}", info.inst.treqs);
@@ -31,14 +122,46 @@ pub fn run(logfile: PathBuf, top_k: Option<usize>) -> Result<(), String> {
     println!("no-axioms-instantiations: {}", info.match_.axioms);
     println!("no-quantifiers-instantiations: {}", info.match_.quantifiers);
     println!("nodes-count: {}", info.inst.total());
+    println!("no-decisions: {}", info.cdcl.decisions);
+    println!("no-propagations: {}", info.cdcl.propagations);
+    println!("no-conflicts: {}", info.cdcl.conflicts);
+    println!("no-learned-literals: {}", info.cdcl.learned_literals);
+    println!(
+        "no-instantiation-assignments: {}",
+        info.cdcl.inst_assignments
+    );
+    println!("no-instantiation-decisions: {}", info.cdcl.inst_decisions);
+    println!("no-instantiation-conflicts: {}", info.cdcl.inst_conflicts);
 
     println!("top-instantiations=");
     let iter = instantiations_occurrances
         .iter()
         .take(top_k.unwrap_or(usize::MAX));
-    for (count, inst) in iter {
-        println!("{} = {}", inst, count);
+    for (name, count, cost) in iter {
+        if costs.is_some() {
+            let share = super::percentage(*cost, total_costs);
+            println!("{count} = {name}, {share:.1}% of cost");
+        } else {
+            println!("{count} = {name}");
+        }
     }
 
-    Ok(())
+    let mut conflict_quants: Vec<_> = info
+        .cdcl
+        .conflict_quants
+        .iter_enumerated()
+        .filter(|(_, &count)| count > 0)
+        .flat_map(|(qidx, count)| {
+            parser[qidx]
+                .kind
+                .user_name()
+                .map(|name| (&parser[name], count))
+        })
+        .collect();
+    conflict_quants.sort_by(|l, r| Ord::cmp(&r.1, &l.1));
+    println!("top-conflict-quantifiers=");
+    let iter = conflict_quants.iter().take(top_k.unwrap_or(usize::MAX));
+    for (name, count) in iter {
+        println!("{name} = {count}");
+    }
 }
@@ -0,0 +1,28 @@
+use std::{io::Write, path::PathBuf};
+
+use smt_log_parser::synth::SynthConfig;
+
+pub fn run(config: SynthConfig, output: Option<PathBuf>) -> Result<(), String> {
+    let synth = config.generate();
+    match output {
+        Some(output) => {
+            std::fs::write(&output, synth.log).map_err(|e| format!("{}: {e}", output.display()))?
+        }
+        None => std::io::stdout()
+            .write_all(synth.log.as_bytes())
+            .map_err(|e| e.to_string())?,
+    }
+    let expected = &synth.expected;
+    eprintln!(
+        "{} quantifiers, {} instantiations ({} theory-solving), {} enodes, {} equalities, {} frames ({} popped, {} instantiations lost)",
+        expected.quantifiers,
+        expected.insts,
+        expected.theory_solving,
+        expected.enodes,
+        expected.given_eqs,
+        expected.frames,
+        expected.popped_frames,
+        expected.lost_insts,
+    );
+    Ok(())
+}
@@ -5,7 +5,7 @@ use smt_log_parser::parsers::LogParser;
 use std::{path::PathBuf, time::Duration};
 use wasm_timer::Instant;
 
-pub fn run(logfiles: Vec<PathBuf>) -> Result<(), String> {
+pub fn run(logfiles: Vec<PathBuf>, parse_options: super::ParseOptions) -> Result<(), String> {
     for path in logfiles {
         let path = std::path::Path::new(&path);
         let filename = path
@@ -23,7 +23,8 @@ pub fn run(logfiles: Vec<PathBuf>) -> Result<(), String> {
         // let len = file.chars().filter(|c| *c == '\n').count();
         // let parsed = StreamParser::parse_entire_string(&file, Duration::from_secs_f32(10.0));
         let to = Duration::from_secs_f32(15.0);
-        let (_metadata, parser) = Z3Parser::from_file(path).unwrap();
+        let (_metadata, mut parser) = Z3Parser::from_file(path).unwrap();
+        parser.set_strict(parse_options.strict);
         let (timeout, result) = parser.process_all_timeout(to);
         let elapsed_time = time.elapsed();
         println!(
@@ -35,6 +36,7 @@ pub fn run(logfiles: Vec<PathBuf>) -> Result<(), String> {
             },
             elapsed_time.as_secs_f32()
         );
+        super::print_diagnostics(&result, parse_options);
         #[cfg(feature = "analysis")]
         {
             let inst_graph = InstGraph::new(&result).unwrap();
@@ -0,0 +1,48 @@
+use std::{path::PathBuf, time::Duration};
+
+use smt_log_parser::{
+    analysis::LogInfo,
+    parsers::{ParseState, ReaderState},
+    LogParser, Z3Parser,
+};
+use wasm_timer::Instant;
+
+/// How often to check for new data once we have reached the end of the file.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+pub fn run(
+    logfile: PathBuf,
+    interval: u64,
+    top_k: Option<usize>,
+    parse_options: super::ParseOptions,
+) -> Result<(), String> {
+    let interval = Duration::from_secs(interval);
+    let (_metadata, mut parser) = Z3Parser::from_file(&logfile).map_err(|e| e.to_string())?;
+    parser.set_follow(Some(POLL_INTERVAL));
+    parser.set_strict(parse_options.strict);
+    loop {
+        let start = Instant::now();
+        // Checking the time is expensive, so only do so every few lines or
+        // while waiting for the solver to write more.
+        let mut next_check = 0_usize;
+        let mut last_state = ReaderState::default();
+        let state = parser.process_until(|_, state| {
+            let waiting = state == last_state;
+            last_state = state;
+            next_check = next_check.saturating_sub(1);
+            if !waiting && next_check > 0 {
+                return None;
+            }
+            next_check = 10_000;
+            (start.elapsed() >= interval).then_some(())
+        });
+        let info = LogInfo::new(parser.parser());
+        println!("=== {} lines read", parser.reader_state().lines_read);
+        super::stats::print_info(parser.parser(), &info, top_k, None);
+        match state {
+            ParseState::Paused(..) => (),
+            ParseState::Completed { .. } => return Ok(()),
+            ParseState::Error(err) => return Err(err.to_string()),
+        }
+    }
+}
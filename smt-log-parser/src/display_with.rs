@@ -119,6 +119,12 @@ pub struct DisplayConfiguration {
     // the limit will be truncated.
     pub enode_char_limit: Option<NonMaxU32>,
     pub ast_depth_limit: Option<NonMaxU32>,
+    /// The radix to print decoded numerals and bit-vectors in.
+    pub numeral_radix: Radix,
+    /// The minimum number of digits of decoded numerals, shorter ones are
+    /// padded with zeros. Numerals are printed as logged if this is 0 and
+    /// `numeral_radix` is [`Radix::AsLogged`].
+    pub numeral_width: u32,
 }
 
 impl DisplayConfiguration {
@@ -290,16 +296,15 @@ impl DisplayWithCtxt<DisplayCtxt<'_>, ()> for QuantIdx {
         self,
         f: &mut fmt::Formatter<'_>,
         ctxt: &DisplayCtxt<'_>,
-        _data: &mut (),
+        data: &mut (),
     ) -> fmt::Result {
         let quant = &ctxt.parser[self];
         if let Some(term) = quant.term {
             term.fmt_with(f, ctxt, &mut None)
         } else {
-            let QuantKind::Other(name) = quant.kind else {
-                panic!()
-            };
-            write!(f, "{}", &ctxt.parser[name])
+            // Discovered quantifiers, or quantifiers only known by name (e.g.
+            // from cvc5's `num-instantiations`).
+            quant.kind.fmt_with(f, ctxt, data)
         }
     }
 }
@@ -362,6 +367,12 @@ impl DisplayWithCtxt<DisplayCtxt<'_>, ()> for &MatchKind {
                 axiom.fmt_with(f, ctxt, data)
             }
             MatchKind::Quantifier { quant, .. } => quant.fmt_with(f, ctxt, data),
+            MatchKind::Reported { quant, source, .. } => {
+                if let Some(source) = source {
+                    write!(f, "[{}]", &ctxt.parser[*source])?;
+                }
+                quant.fmt_with(f, ctxt, data)
+            }
         }
     }
 }
@@ -476,8 +487,54 @@ impl<'a, 'b> DisplayWithCtxt<DisplayCtxt<'b>, DisplayData<'b>> for &'a TermKind
             }
             TermKind::ProofOrApp(poa) => write!(f, "{}", poa.with_data(ctxt, data)),
             TermKind::Quant(idx) => write!(f, "{}", ctxt.parser[*idx].with_data(ctxt, data)),
-            // TODO: it would be nice to display some extra information here
-            TermKind::Generalised => write!(f, "_"),
+            TermKind::Generalised(generalisation) => {
+                write!(f, "{}", generalisation.with_data(ctxt, data))
+            }
+        }
+    }
+}
+impl<'a, 'b> DisplayWithCtxt<DisplayCtxt<'b>, DisplayData<'b>> for &'a Generalisation {
+    fn fmt_with(
+        self,
+        f: &mut fmt::Formatter<'_>,
+        ctxt: &DisplayCtxt<'b>,
+        data: &mut DisplayData<'b>,
+    ) -> fmt::Result {
+        match self {
+            Generalisation::Opaque => write!(f, "_"),
+            Generalisation::Iterated => {
+                let &[chain, base] = data.children() else {
+                    return write!(f, "_");
+                };
+                let mut names = Vec::new();
+                let mut term = &ctxt.parser[chain];
+                while let TermKind::ProofOrApp(function) = term.kind {
+                    let name = &ctxt.parser[function.name];
+                    #[cfg(feature = "display_html")]
+                    let name = if ctxt.config.html() {
+                        Cow::Owned(ammonia::clean_text(name))
+                    } else {
+                        Cow::Borrowed(name)
+                    };
+                    names.push(name);
+                    term = &ctxt.parser[term.child_ids[0]];
+                }
+                match names.as_slice() {
+                    [name] => write!(f, "{name}^k(")?,
+                    names => write!(f, "({})^k(", names.join("∘"))?,
+                }
+                display_child(f, base, ctxt, data)?;
+                write!(f, ")")
+            }
+            Generalisation::Progression => {
+                let Some((first, step, _)) = ctxt.parser.terms.progression(data.children()) else {
+                    return write!(f, "_");
+                };
+                match step.to_string().as_str() {
+                    "1" => write!(f, "{first} + k"),
+                    step => write!(f, "{first} + {step}k"),
+                }
+            }
         }
     }
 }
@@ -653,10 +710,17 @@ impl<'a> DisplayWithCtxt<DisplayCtxt<'a>, DisplayData<'a>> for &'a Meaning {
         self,
         f: &mut fmt::Formatter<'_>,
         ctxt: &DisplayCtxt<'a>,
-        _data: &mut DisplayData<'a>,
+        data: &mut DisplayData<'a>,
     ) -> fmt::Result {
         let theory = &ctxt.parser[self.theory];
         let value = &ctxt.parser[self.value];
+        let (radix, width) = (ctxt.config.numeral_radix, ctxt.config.numeral_width);
+        if radix != Radix::AsLogged || width != 0 {
+            let decoded = ctxt.parser.value(data.term);
+            if let Some(decoded) = decoded.filter(|v| !matches!(v, Value::String(_))) {
+                return decoded.fmt_radix(f, radix, width);
+            }
+        }
         match theory {
             "arith" | "bv" => write!(f, "{value}"),
             theory => write!(f, "/{theory} {value}\\"),
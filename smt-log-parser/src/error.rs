@@ -5,6 +5,7 @@ use std::{collections::TryReserveError, num::ParseIntError};
 use mem_dbg::{MemDbg, MemSize};
 
 use crate::items::{BlameKind, ENodeIdx, Fingerprint, StackIdx, TermId, TermIdx};
+use crate::parsers::diagnostics::Diagnostic;
 
 pub type Result<T> = std::result::Result<T, Error>;
 pub type FResult<T> = std::result::Result<T, FatalError>;
@@ -78,6 +79,20 @@ pub enum Error {
     InvalidGeneration(ParseIntError),
     EnodeRootMismatch(ENodeIdx, ENodeIdx),
 
+    // CDCL
+    InvalidLiteral(String),
+    InvalidJustificationTheory(ParseIntError),
+    InvalidResolveLevel(ParseIntError),
+
+    // Query
+    InvalidQueryResult(String),
+    UnmatchedQueryDone,
+
+    // S-expressions (cvc5 output and SMT-LIB input)
+    UnmatchedCloseParen,
+    UnexpectedSExp(String),
+    InvalidInstCount(ParseIntError),
+
     // Stack
     StackFrameNotPushed,
     InvalidFrameInteger(ParseIntError),
@@ -105,6 +120,49 @@ impl Error {
         matches!(self, Self::Allocation(_))
     }
 
+    /// The name of the variant, used to group diagnostics of the same kind.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::UnknownLine(..) => "UnknownLine",
+            Self::UnexpectedNewline => "UnexpectedNewline",
+            Self::ExpectedNewline(..) => "ExpectedNewline",
+            Self::UnexpectedEnd => "UnexpectedEnd",
+            Self::InvalidVersion(..) => "InvalidVersion",
+            Self::InvalidIdNumber(..) => "InvalidIdNumber",
+            Self::InvalidIdHash(..) => "InvalidIdHash",
+            Self::UnknownId(..) => "UnknownId",
+            Self::InvalidVar(..) => "InvalidVar",
+            Self::VarNamesListInconsistent => "VarNamesListInconsistent",
+            Self::VarNamesNoBar => "VarNamesNoBar",
+            Self::UnknownQuantifierIdx(..) => "UnknownQuantifierIdx",
+            Self::NonRewriteAxiomInvalidEnode(..) => "NonRewriteAxiomInvalidEnode",
+            Self::RewriteAxiomMultipleTerms1(..) => "RewriteAxiomMultipleTerms1",
+            Self::RewriteAxiomMultipleTerms2(..) => "RewriteAxiomMultipleTerms2",
+            Self::UnknownInstMethod(..) => "UnknownInstMethod",
+            Self::UnmatchedEndOfInstance => "UnmatchedEndOfInstance",
+            Self::TupleMissingParens => "TupleMissingParens",
+            Self::UnequalTupleForms(..) => "UnequalTupleForms",
+            Self::InvalidFingerprint(..) => "InvalidFingerprint",
+            Self::UnknownFingerprint(..) => "UnknownFingerprint",
+            Self::UnknownEnode(..) => "UnknownEnode",
+            Self::EnodePoppedFrame(..) => "EnodePoppedFrame",
+            Self::InvalidGeneration(..) => "InvalidGeneration",
+            Self::EnodeRootMismatch(..) => "EnodeRootMismatch",
+            Self::InvalidLiteral(..) => "InvalidLiteral",
+            Self::InvalidJustificationTheory(..) => "InvalidJustificationTheory",
+            Self::InvalidResolveLevel(..) => "InvalidResolveLevel",
+            Self::InvalidQueryResult(..) => "InvalidQueryResult",
+            Self::UnmatchedQueryDone => "UnmatchedQueryDone",
+            Self::UnmatchedCloseParen => "UnmatchedCloseParen",
+            Self::UnexpectedSExp(..) => "UnexpectedSExp",
+            Self::InvalidInstCount(..) => "InvalidInstCount",
+            Self::StackFrameNotPushed => "StackFrameNotPushed",
+            Self::InvalidFrameInteger(..) => "InvalidFrameInteger",
+            Self::FileRead(..) => "FileRead",
+            Self::Allocation(..) => "Allocation",
+        }
+    }
+
     pub fn as_fatal(self) -> Option<FatalError> {
         match self {
             Self::Allocation(alloc) => Some(FatalError::Allocation(alloc)),
@@ -117,6 +175,28 @@ impl Error {
 pub enum FatalError {
     Allocation(TryReserveError),
     Io(std::rc::Rc<std::io::Error>),
+    /// A line could not be parsed and the parser is in strict mode.
+    Strict(Box<Diagnostic>),
+}
+
+impl FatalError {
+    /// The kind of the problem, named as in [`Diagnostic::kind`].
+    pub fn kind(&self) -> &str {
+        match self {
+            Self::Allocation(_) => "Allocation",
+            Self::Io(_) => "FileRead",
+            Self::Strict(diagnostic) => &diagnostic.kind,
+        }
+    }
+
+    /// The kind of log entry on which parsing stopped, empty if the problem
+    /// is not with a specific line.
+    pub fn entry(&self) -> &str {
+        match self {
+            Self::Allocation(_) | Self::Io(_) => "",
+            Self::Strict(diagnostic) => &diagnostic.entry,
+        }
+    }
 }
 
 impl From<std::io::Error> for FatalError {
@@ -130,6 +210,7 @@ impl fmt::Display for FatalError {
         match self {
             Self::Allocation(alloc) => write!(f, "Allocation error: {alloc}"),
             Self::Io(err) => write!(f, "IO error: {err}"),
+            Self::Strict(diagnostic) => write!(f, "Parse error: {diagnostic}"),
         }
     }
 }
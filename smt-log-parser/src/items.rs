@@ -2,7 +2,7 @@
 use mem_dbg::{MemDbg, MemSize};
 
 use crate::error::Either;
-use crate::{BoxSlice, FxHashMap, IString, NonMaxU32, StringTable, Z3Parser};
+use crate::{BigInt, BoxSlice, FxHashMap, IString, NonMaxU32, StringTable, Z3Parser};
 use crate::{Error, Result};
 use std::fmt;
 use std::ops::Index;
@@ -45,6 +45,10 @@ idx!(MatchIdx, "m{}");
 idx!(EqGivenIdx, "≡{}");
 idx!(EqTransIdx, "={}");
 idx!(GraphIdx, "g{}");
+idx!(DecisionIdx, "d{}");
+idx!(AssignIdx, "a{}");
+idx!(ConflictIdx, "c{}");
+idx!(QueryIdx, "Q{}");
 
 /// A Z3 term and associated data.
 #[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
@@ -64,7 +68,28 @@ pub enum TermKind {
     Var(usize),
     ProofOrApp(ProofOrApp),
     Quant(QuantIdx),
-    Generalised,
+    /// A term created by
+    /// [`Terms::generalise`](crate::parsers::z3::terms::Terms::generalise)
+    /// to stand for several terms which differ at this point.
+    Generalised(Generalisation),
+}
+
+/// What the terms replaced by a [`TermKind::Generalised`] have in common.
+#[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Generalisation {
+    /// Nothing, displayed as `_`. The children are the generalised terms.
+    Opaque,
+    /// A chain of unary functions applied a varying number of times to the
+    /// same (generalised) term, displayed as `f^k(x)` or `(f∘g)^k(x)`. The
+    /// children are the chain, e.g. `f(g(_))` with an [`Opaque`](Self::Opaque)
+    /// leaf, and `x`.
+    Iterated,
+    /// Integers in an arithmetic progression, displayed as `c + k` (or
+    /// `c + dk` for a step of `d`). The children are its first, second and
+    /// last integer.
+    Progression,
 }
 
 #[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
@@ -112,6 +137,235 @@ pub struct Meaning {
     pub value: IString,
 }
 
+/// The decoded value of a [`Meaning`], see [`Value::parse`].
+#[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum Value {
+    Bool(bool),
+    Int(BigInt),
+    /// A rational which is not an integer, in lowest terms and with a
+    /// positive denominator.
+    Rational {
+        numer: BigInt,
+        denom: BigInt,
+    },
+    /// A bit-vector of the given width, the value is in `0..2^width`.
+    BitVec {
+        value: BigInt,
+        width: u32,
+    },
+    String(String),
+}
+
+/// How numerals are printed.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Radix {
+    /// Exactly as in the log.
+    #[default]
+    AsLogged,
+    Decimal,
+    Hexadecimal,
+    Binary,
+}
+
+impl Value {
+    /// Decodes the value of an `[attach-meaning]`, e.g. `arith (- 3)`,
+    /// `arith (/ 1 2)`, `bv #x0001` or `seq "abc"`. Returns `None` for values
+    /// which are not understood.
+    pub fn parse(theory: &str, value: &str) -> Option<Self> {
+        let value = value.trim();
+        match value {
+            "true" => return Some(Self::Bool(true)),
+            "false" => return Some(Self::Bool(false)),
+            _ => (),
+        }
+        if let Some(string) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+            return Some(Self::String(string.replace("\"\"", "\"")));
+        }
+        if let Some(hex) = value.strip_prefix("#x") {
+            let value = num_bigint::BigInt::parse_bytes(hex.as_bytes(), 16)?;
+            let width = u32::try_from(hex.len() * 4).ok()?;
+            return Some(Self::bit_vec(value, width));
+        }
+        if let Some(bin) = value.strip_prefix("#b") {
+            let value = num_bigint::BigInt::parse_bytes(bin.as_bytes(), 2)?;
+            let width = u32::try_from(bin.len()).ok()?;
+            return Some(Self::bit_vec(value, width));
+        }
+        match theory {
+            "bv" => Self::parse_bv(value),
+            "arith" | "int" | "real" => {
+                let (numer, denom) = Self::parse_rational(value)?;
+                Some(Self::rational(numer, denom))
+            }
+            _ => None,
+        }
+    }
+
+    fn bit_vec(value: num_bigint::BigInt, width: u32) -> Self {
+        Self::BitVec {
+            value: value.into(),
+            width,
+        }
+    }
+
+    /// `(_ bv5 8)` or `5bv8`.
+    fn parse_bv(value: &str) -> Option<Self> {
+        let (value, width) = match value.strip_prefix("(_ bv") {
+            Some(rest) => rest.strip_suffix(')')?.split_once(' ')?,
+            None => value.split_once("bv")?,
+        };
+        let value = value.parse::<num_bigint::BigInt>().ok()?;
+        let width = width.trim().parse().ok()?;
+        (value.sign() != num_bigint::Sign::Minus && value.bits() <= u64::from(width))
+            .then(|| Self::bit_vec(value, width))
+    }
+
+    /// Integers, decimals and fractions, optionally negated with `-` or
+    /// `(- ...)` and written as `n/d` or `(/ n d)`.
+    fn parse_rational(value: &str) -> Option<(num_bigint::BigInt, num_bigint::BigInt)> {
+        let value = value.trim();
+        if let Some(inner) = value.strip_prefix("(-").and_then(|v| v.strip_suffix(')')) {
+            let (numer, denom) = Self::parse_rational(inner)?;
+            return Some((-numer, denom));
+        }
+        if let Some(inner) = value.strip_prefix("(/").and_then(|v| v.strip_suffix(')')) {
+            let inner = inner.trim();
+            // Either argument may be a negated numeral in parentheses.
+            let split = match inner.strip_prefix('(') {
+                Some(rest) => rest.find(')').map(|idx| idx + 2),
+                None => inner.find(' '),
+            }?;
+            let (numer, denom) = inner.split_at(split);
+            let (n1, d1) = Self::parse_rational(numer)?;
+            let (n2, d2) = Self::parse_rational(denom)?;
+            return Some((n1 * d2, d1 * n2));
+        }
+        if let Some((numer, denom)) = value.split_once('/') {
+            return Some((numer.trim().parse().ok()?, denom.trim().parse().ok()?));
+        }
+        if let Some((int, frac)) = value.split_once('.') {
+            let scale = num_bigint::BigInt::from(10).pow(u32::try_from(frac.len()).ok()?);
+            let numer: num_bigint::BigInt = format!("{int}{frac}").parse().ok()?;
+            return Some((numer, scale));
+        }
+        Some((value.parse().ok()?, 1.into()))
+    }
+
+    /// Normalises `numer / denom` to an [`Value::Int`] or a
+    /// [`Value::Rational`] in lowest terms.
+    fn rational(numer: num_bigint::BigInt, denom: num_bigint::BigInt) -> Self {
+        use num_integer::Integer;
+        use num_traits::{One, Signed, Zero};
+        if denom.is_zero() {
+            // Division by zero is left uninterpreted by z3, keep it as is.
+            return Self::Rational {
+                numer: numer.into(),
+                denom: denom.into(),
+            };
+        }
+        let gcd = numer.gcd(&denom);
+        let (mut numer, mut denom) = (numer / &gcd, denom / gcd);
+        if denom.is_negative() {
+            (numer, denom) = (-numer, -denom);
+        }
+        if denom.is_one() {
+            Self::Int(numer.into())
+        } else {
+            Self::Rational {
+                numer: numer.into(),
+                denom: denom.into(),
+            }
+        }
+    }
+
+    /// The value as a fraction, for integers, rationals and (unsigned)
+    /// bit-vectors.
+    pub fn as_rational(&self) -> Option<(num_bigint::BigInt, num_bigint::BigInt)> {
+        match self {
+            Self::Int(value) | Self::BitVec { value, .. } => Some(((**value).clone(), 1.into())),
+            Self::Rational { numer, denom } => Some(((**numer).clone(), (**denom).clone())),
+            Self::Bool(_) | Self::String(_) => None,
+        }
+    }
+
+    /// Compares two numeric values (see [`Value::as_rational`]), e.g. to find
+    /// all terms which are integers larger than 1000.
+    pub fn cmp_numeric(&self, other: &Value) -> Option<std::cmp::Ordering> {
+        let (n1, d1) = self.as_rational()?;
+        let (n2, d2) = other.as_rational()?;
+        Some((n1 * d2).cmp(&(n2 * d1)))
+    }
+
+    /// Parses a comparison with a numeric value such as `> 1000`, `<(- 3)`,
+    /// `= 1/2`, `=#x0f` or `< (_ bv5 8)`, e.g. to filter instantiations by the values of
+    /// their bound terms.
+    pub fn parse_comparison(comparison: &str) -> Option<(std::cmp::Ordering, Self)> {
+        use std::cmp::Ordering;
+        let comparison = comparison.trim();
+        let mut chars = comparison.chars();
+        let ordering = match chars.next()? {
+            '<' => Ordering::Less,
+            '=' => Ordering::Equal,
+            '>' => Ordering::Greater,
+            _ => return None,
+        };
+        let value = chars.as_str();
+        let value = Self::parse("arith", value).or_else(|| Self::parse("bv", value))?;
+        value.as_rational().is_some().then_some((ordering, value))
+    }
+
+    /// Writes the value with numerals in the given radix, padded with zeros
+    /// to at least `width` digits. Bit-vectors are also padded to their own
+    /// width. [`Radix::AsLogged`] is treated as [`Radix::Decimal`].
+    pub fn fmt_radix(&self, f: &mut fmt::Formatter<'_>, radix: Radix, width: u32) -> fmt::Result {
+        use num_traits::Signed;
+        let width = width as usize;
+        let int = |f: &mut fmt::Formatter<'_>, value: &BigInt| {
+            let sign = if value.is_negative() { "-" } else { "" };
+            let abs = value.abs();
+            match radix {
+                Radix::AsLogged | Radix::Decimal => write!(f, "{sign}{abs:0>width$}"),
+                Radix::Hexadecimal => write!(f, "{sign}0x{:0>width$}", abs.to_str_radix(16)),
+                Radix::Binary => write!(f, "{sign}0b{:0>width$}", abs.to_str_radix(2)),
+            }
+        };
+        match self {
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Int(value) => int(f, value),
+            Self::Rational { numer, denom } => {
+                int(f, numer)?;
+                write!(f, "/")?;
+                int(f, denom)
+            }
+            Self::BitVec { value, width: bits } => {
+                let bits = *bits as usize;
+                match radix {
+                    Radix::Hexadecimal if bits % 4 == 0 => {
+                        let digits = (bits / 4).max(width);
+                        write!(f, "#x{:0>digits$}", value.to_str_radix(16))
+                    }
+                    Radix::Hexadecimal | Radix::Binary => {
+                        write!(f, "#b{:0>1$}", value.to_str_radix(2), bits.max(width))
+                    }
+                    Radix::AsLogged | Radix::Decimal => {
+                        write!(f, "(_ bv{value:0>width$} {bits})")
+                    }
+                }
+            }
+            Self::String(value) => write!(f, "\"{}\"", value.replace('"', "\"\"")),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_radix(f, Radix::Decimal, 0)
+    }
+}
+
 /// Returned when indexing with `TermIdx`
 #[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
@@ -129,6 +383,9 @@ pub struct Quantifier {
     pub num_vars: usize,
     pub term: Option<TermIdx>,
     pub vars: Option<VarNames>,
+    /// The `pattern` terms of the quantifier, one per trigger. A trigger
+    /// with several children is a multi-pattern, all of which must match.
+    pub triggers: Box<[TermIdx]>,
 }
 
 /// Represents an ID string of the form `name!id`.
@@ -217,6 +474,8 @@ pub struct Instantiation {
     pub proof_id: Option<Either<TermIdx, TermId>>,
     pub z3_generation: Option<u32>,
     pub yields_terms: Box<[ENodeIdx]>,
+    /// The stack frame which was active when the instantiation was made.
+    pub frame: Option<StackIdx>,
 }
 
 impl Instantiation {
@@ -278,19 +537,30 @@ pub enum MatchKind {
         pattern: TermIdx,
         bound_terms: Box<[ENodeIdx]>,
     },
+    /// An instantiation reported by a solver which does not log how it was
+    /// matched (e.g. cvc5). Only the quantifier and, if logged, the bound
+    /// terms are known.
+    Reported {
+        quant: QuantIdx,
+        /// The solver's name for the technique which found the
+        /// instantiation (e.g. `QUANTIFIERS_INST_E_MATCHING`).
+        source: Option<IString>,
+        bound_terms: Box<[TermIdx]>,
+    },
 }
 impl MatchKind {
     pub fn quant_idx(&self) -> Option<QuantIdx> {
         match self {
             Self::MBQI { quant, .. }
             | Self::Axiom { axiom: quant, .. }
-            | Self::Quantifier { quant, .. } => Some(*quant),
+            | Self::Quantifier { quant, .. }
+            | Self::Reported { quant, .. } => Some(*quant),
             _ => None,
         }
     }
     pub fn pattern(&self) -> Option<TermIdx> {
         match self {
-            Self::MBQI { .. } | Self::TheorySolving { .. } => None,
+            Self::MBQI { .. } | Self::TheorySolving { .. } | Self::Reported { .. } => None,
             Self::Axiom { pattern, .. } | Self::Quantifier { pattern, .. } => Some(*pattern),
         }
     }
@@ -303,9 +573,9 @@ impl MatchKind {
             Self::MBQI { bound_terms, .. } | Self::Quantifier { bound_terms, .. } => {
                 bound_terms.iter().map(|&x| enode(x)).collect()
             }
-            Self::TheorySolving { bound_terms, .. } | Self::Axiom { bound_terms, .. } => {
-                bound_terms.iter().map(|&x| term(x)).collect()
-            }
+            Self::TheorySolving { bound_terms, .. }
+            | Self::Axiom { bound_terms, .. }
+            | Self::Reported { bound_terms, .. } => bound_terms.iter().map(|&x| term(x)).collect(),
         }
     }
     pub fn is_discovered(&self) -> bool {
@@ -435,6 +705,7 @@ impl TermId {
 /// may repeat and so we want to map to the latest current `TermIdx`. Has a
 /// special fast path for the common empty namespace case.
 #[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug)]
 pub struct TermIdToIdxMap {
     empty_string: IString,
@@ -689,3 +960,240 @@ impl TransitiveExplSegmentKind {
         }
     }
 }
+
+/// A boolean literal as printed by Z3 in the CDCL lines, i.e. `#n`, `(not #n)`,
+/// `true` or `false`.
+#[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Literal {
+    /// The boolean term, or `None` for the constant `true`/`false` literals.
+    pub term: Option<TermIdx>,
+    /// Is this the positive literal (`#n`) or the negated one (`(not #n)`)?
+    pub value: bool,
+}
+
+impl Literal {
+    pub fn negate(self) -> Self {
+        Self {
+            term: self.term,
+            value: !self.value,
+        }
+    }
+}
+
+/// A case split made by the SAT core, either through `[decide]` or
+/// `[decide-and-or]`.
+#[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone)]
+pub struct Decision {
+    pub kind: DecisionKind,
+    /// The stack frame which was active when the decision was made.
+    pub frame: Option<StackIdx>,
+    /// The instantiation which produced the decided literal (or child), if
+    /// any.
+    pub produced_by: Option<InstIdx>,
+}
+
+#[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone)]
+pub enum DecisionKind {
+    /// A `[decide]` of a literal.
+    Literal(Literal),
+    /// A `[decide-and-or]` choosing which child of a relevant `and`/`or` term
+    /// to case split on.
+    AndOr { term: TermIdx, child: TermIdx },
+}
+
+/// A `[assign]` of a literal, either as a decision or by propagation.
+#[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone)]
+pub struct Assignment {
+    pub literal: Literal,
+    pub decision: bool,
+    pub justification: Justification,
+    /// The stack frame which was active when the literal was assigned.
+    pub frame: Option<StackIdx>,
+    /// The instantiation which was being processed when the literal was
+    /// assigned, if any.
+    pub created_by: Option<InstIdx>,
+}
+
+/// Why a literal was assigned a value.
+#[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone)]
+pub enum Justification {
+    Axiom,
+    /// Unit propagation of a binary clause, the other literal is given.
+    BinClause(Literal),
+    /// Unit propagation of a clause.
+    Clause(Box<[Literal]>),
+    /// Propagated by a theory (the `theory` is Z3's internal theory id) from
+    /// the given literals.
+    Theory {
+        theory: i32,
+        literals: Box<[Literal]>,
+    },
+    Unknown {
+        kind: IString,
+        args: Box<[IString]>,
+    },
+}
+
+impl Justification {
+    pub fn literals(&self) -> &[Literal] {
+        match self {
+            Self::BinClause(lit) => std::slice::from_ref(lit),
+            Self::Clause(literals) | Self::Theory { literals, .. } => literals,
+            Self::Axiom | Self::Unknown { .. } => &[],
+        }
+    }
+}
+
+/// A conflict found by the SAT core. The `[resolve-process]` and
+/// `[resolve-lit]` lines are emitted by Z3 while analysing the conflict,
+/// followed by a `[conflict]` line with the learned clause.
+#[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    /// The learned clause.
+    pub clause: Box<[Literal]>,
+    /// The resolution steps which lead to the learned clause.
+    pub resolution: Box<[ResolveStep]>,
+    /// The stack frame which was active when the conflict was found.
+    pub frame: Option<StackIdx>,
+    /// The most recent decision before the conflict.
+    pub last_decision: Option<DecisionIdx>,
+    /// The instantiations which produced the literals of the learned clause
+    /// or of the resolution steps, sorted and without duplicates.
+    pub produced_by: Box<[InstIdx]>,
+}
+
+#[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy)]
+pub enum ResolveStep {
+    /// `[resolve-process]`: the consequent which is being resolved.
+    Process(Literal),
+    /// `[resolve-lit]`: an antecedent which was marked, at the given distance
+    /// from the conflict level.
+    Lit { level: u32, literal: Literal },
+}
+
+impl ResolveStep {
+    pub fn literal(&self) -> Literal {
+        match self {
+            Self::Process(literal) | Self::Lit { literal, .. } => *literal,
+        }
+    }
+}
+
+/// A single `check-sat` call, started by a `[begin-check]` line and ended by a
+/// `[query-done]` line. Every item created after the start of this query and
+/// before the start of the next one belongs to this query (items created
+/// before the first query belong to the first one).
+#[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone)]
+pub struct Query {
+    /// The scope level at which the check was started.
+    pub scope: usize,
+    /// The line number of the `[begin-check]` line.
+    pub start_line: usize,
+    /// The line number of the `[query-done]` line, `None` if the log ended
+    /// before the query was done.
+    pub end_line: Option<usize>,
+    pub result: Option<QueryResult>,
+    /// How many of each item had been created when the query started.
+    pub start: ItemCounts,
+    /// How many of each item had been created when the query was done, or
+    /// when the next query started if it was never done. `None` for the last
+    /// query if the log ended before it was done.
+    pub end: Option<ItemCounts>,
+}
+
+#[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryResult {
+    Sat,
+    Unsat,
+    Unknown,
+}
+
+impl QueryResult {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "sat" => Ok(Self::Sat),
+            "unsat" => Ok(Self::Unsat),
+            "unknown" | "undef" => Ok(Self::Unknown),
+            _ => Err(Error::InvalidQueryResult(value.to_string())),
+        }
+    }
+}
+impl fmt::Display for QueryResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Sat => write!(f, "sat"),
+            Self::Unsat => write!(f, "unsat"),
+            Self::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// The number of each kind of item at some point during parsing.
+#[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ItemCounts {
+    pub terms: usize,
+    pub insts: usize,
+    pub enodes: usize,
+    pub given_eqs: usize,
+    pub trans_eqs: usize,
+    pub decisions: usize,
+    pub assignments: usize,
+    pub conflicts: usize,
+}
+
+/// The range of each kind of item which belongs to a query (or the entire log).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ItemRanges {
+    pub terms: std::ops::Range<usize>,
+    pub insts: std::ops::Range<usize>,
+    pub enodes: std::ops::Range<usize>,
+    pub given_eqs: std::ops::Range<usize>,
+    pub trans_eqs: std::ops::Range<usize>,
+    pub decisions: std::ops::Range<usize>,
+    pub assignments: std::ops::Range<usize>,
+    pub conflicts: std::ops::Range<usize>,
+}
+
+impl ItemRanges {
+    pub fn new(start: ItemCounts, end: ItemCounts) -> Self {
+        Self {
+            terms: start.terms..end.terms,
+            insts: start.insts..end.insts,
+            enodes: start.enodes..end.enodes,
+            given_eqs: start.given_eqs..end.given_eqs,
+            trans_eqs: start.trans_eqs..end.trans_eqs,
+            decisions: start.decisions..end.decisions,
+            assignments: start.assignments..end.assignments,
+            conflicts: start.conflicts..end.conflicts,
+        }
+    }
+    pub fn terms(&self) -> impl Iterator<Item = TermIdx> {
+        self.terms.clone().map(TermIdx::from)
+    }
+    pub fn insts(&self) -> impl Iterator<Item = InstIdx> {
+        self.insts.clone().map(InstIdx::from)
+    }
+    pub fn enodes(&self) -> impl Iterator<Item = ENodeIdx> {
+        self.enodes.clone().map(ENodeIdx::from)
+    }
+}
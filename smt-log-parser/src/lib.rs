@@ -13,13 +13,31 @@ pub mod formatter;
 #[cfg(feature = "analysis")]
 pub mod analysis;
 
+/// Saving and restoring fully parsed logs.
+#[cfg(feature = "serde")]
+pub mod snapshot;
+
+/// Decoding the source locations which front-ends encode in quantifier
+/// names.
+pub mod qid;
+
+/// Replacing confidential names in logs.
+pub mod anonymize;
+
+/// Shrinking logs which reproduce a problem.
+pub mod minimize;
+
+/// Generating synthetic logs for testing.
+#[cfg(feature = "synth")]
+pub mod synth;
+
 mod error;
 mod mem_dbg;
 
 pub use error::{Error, FResult, FatalError, Result};
 pub use mem_dbg::{
-    BoxSlice, DiGraph, FxHashMap, Graph, IString, NonMaxU32, NonMaxUsize, StringTable, TiVec,
-    UnGraph,
+    BigInt, BoxSlice, DiGraph, FxHashMap, Graph, IString, NonMaxU32, NonMaxUsize, StringTable,
+    TiVec, UnGraph,
 };
 pub use parsers::z3::z3parser::Z3Parser;
 pub use parsers::LogParser;
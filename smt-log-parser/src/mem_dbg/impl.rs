@@ -8,7 +8,9 @@ use petgraph::{
 use crate::analysis::subgraph::TransitiveClosure;
 use crate::parsers::z3::VersionInfo;
 
-use super::{BoxSlice, FxHashMap, Graph, IString, NonMaxU32, NonMaxUsize, StringTable, TiVec};
+use super::{
+    BigInt, BoxSlice, FxHashMap, Graph, IString, NonMaxU32, NonMaxUsize, StringTable, TiVec,
+};
 
 macro_rules! copy_impl {
     ($t:ty) => {
@@ -28,6 +30,18 @@ copy_impl!(NonMaxU32);
 copy_impl!(NonMaxUsize);
 copy_impl!(IString);
 
+// BigInt
+
+impl MemDbgImpl for BigInt {}
+impl MemSize for BigInt {
+    fn mem_size(&self, _flags: mem_dbg::SizeFlags) -> usize {
+        core::mem::size_of::<Self>() + self.0.bits().div_ceil(8) as usize
+    }
+}
+impl CopyType for BigInt {
+    type Copy = False;
+}
+
 // TiVec
 
 impl<K, V> MemDbgImpl for TiVec<K, V>
@@ -107,6 +107,20 @@ macro_rules! derive_non_max {
 derive_non_max!(NonMaxU32, u32);
 derive_non_max!(NonMaxUsize, usize);
 
+// BigInt
+
+derive_wrapper!(num_bigint::BigInt: PartialEq + Eq + PartialOrd + Ord + Hash + Default);
+impl From<num_bigint::BigInt> for BigInt {
+    fn from(value: num_bigint::BigInt) -> Self {
+        Self(value)
+    }
+}
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
 // TiVec
 
 derive_wrapper!(typed_index_collections::TiVec<K, V>);
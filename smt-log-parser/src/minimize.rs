@@ -0,0 +1,399 @@
+use std::panic::{self, AssertUnwindSafe};
+
+#[cfg(feature = "analysis")]
+use crate::analysis::{
+    CostModel, HotspotAnalysis, InstGraph, LintAnalysis, LintConfig, LogInfo, PopAnalysis,
+    ProofAnalysis, QuantifierAnalysis, QuantifierGraph, TriggerAnalysis,
+};
+#[cfg(feature = "analysis")]
+use crate::qid::QidDecoders;
+use fxhash::FxHashSet;
+
+use crate::{FxHashMap, LogParser, Z3Parser};
+
+/// The problem which a minimized log must still reproduce.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Predicate {
+    /// Parsing reports an [`Error`](crate::Error) of this kind, i.e. the name
+    /// of the variant such as `UnknownId`.
+    Error(String),
+    /// Parsing or analysing the log panics, with the same message as the
+    /// original log. All analyses are run, including the instantiation graph
+    /// under every [`CostModel`](crate::analysis::CostModel), the quantifier,
+    /// hotspot, trigger, proof and pop analyses, the lints and the matching
+    /// loop search.
+    Panic,
+    /// A matching loop is still found by
+    /// [`InstGraph::search_matching_loops`].
+    #[cfg(feature = "analysis")]
+    MatchingLoop,
+}
+
+/// What happened when parsing and analysing a candidate log.
+#[derive(Debug, Default)]
+struct Outcome {
+    panic: Option<String>,
+    /// The `(entry, kind)` of each problem reported while parsing.
+    errors: FxHashSet<(String, String)>,
+    #[cfg(feature = "analysis")]
+    matching_loops: usize,
+}
+
+impl Outcome {
+    fn of(log: &str, predicate: &Predicate) -> Self {
+        let mut outcome = Outcome::default();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let parser = Z3Parser::from_str(log).process_all();
+            let parser = match parser {
+                Ok(parser) => parser,
+                Err(err) => {
+                    outcome
+                        .errors
+                        .insert((err.entry().to_string(), err.kind().to_string()));
+                    return;
+                }
+            };
+            outcome.errors.extend(
+                parser
+                    .diagnostics()
+                    .iter()
+                    .map(|d| (d.entry.clone(), d.kind.clone())),
+            );
+            #[cfg(feature = "analysis")]
+            match predicate {
+                Predicate::Error(_) => (),
+                Predicate::Panic => Self::analyse_all(parser),
+                Predicate::MatchingLoop => {
+                    let mut parser = parser;
+                    if let Ok(mut graph) = InstGraph::new(&parser) {
+                        outcome.matching_loops = graph.search_matching_loops(&mut parser);
+                    }
+                }
+            }
+            #[cfg(not(feature = "analysis"))]
+            let _ = predicate;
+        }));
+        if let Err(payload) = result {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            outcome.panic = Some(message);
+        }
+        outcome
+    }
+
+    /// Runs every analysis the command line exposes, for their panics.
+    #[cfg(feature = "analysis")]
+    fn analyse_all(mut parser: Z3Parser) {
+        LogInfo::new(&parser);
+        PopAnalysis::new(&parser);
+        ProofAnalysis::new(&parser);
+        let Ok(mut graph) = InstGraph::new(&parser) else {
+            return;
+        };
+        for model in CostModel::ALL {
+            graph.set_cost_model(&parser, model);
+        }
+        graph.set_cost_model(&parser, CostModel::default());
+        graph.initialise_inst_succs_and_preds(&parser);
+        let quants = QuantifierAnalysis::new(&parser, &graph);
+        QuantifierGraph::new(&quants);
+        HotspotAnalysis::new(&parser, &quants, &QidDecoders::default());
+        TriggerAnalysis::new(&parser, Some(&graph));
+        LintAnalysis::new(&parser, &graph, &LintConfig::default());
+        graph.search_matching_loops(&mut parser);
+    }
+}
+
+/// Log entries which define a term (or quantifier, proof step, ...), their
+/// first argument is the id of the new term.
+const DEFINITIONS: &[&str] = &[
+    "[mk-app]",
+    "[mk-var]",
+    "[mk-quant]",
+    "[mk-lambda]",
+    "[mk-proof]",
+];
+/// Log entries which only add information to an existing term and are
+/// dropped together with it.
+const ATTACHMENTS: &[&str] = &["[attach-enode]", "[attach-meaning]", "[attach-var-names]"];
+
+fn entry(line: &str) -> &str {
+    line.split_ascii_whitespace().next().unwrap_or_default()
+}
+
+/// The term ids mentioned in a line, e.g. `#12` or `datatype#3`.
+fn ids(line: &str) -> impl Iterator<Item = &str> {
+    line.split(|c: char| c.is_ascii_whitespace() || c == '(' || c == ')' || c == ';')
+        .skip(1)
+        .filter(|token| token.contains('#') && token.ends_with(|c: char| c.is_ascii_digit()))
+}
+
+/// Shrinks a log while the predicate keeps holding, by delta debugging over
+/// groups of lines which can be removed without leaving the log
+/// inconsistent.
+pub struct Minimizer<'a> {
+    lines: Vec<&'a str>,
+    kept: Vec<bool>,
+    predicate: Predicate,
+    original: Outcome,
+    /// How many candidate logs were parsed.
+    pub checks: usize,
+}
+
+impl<'a> Minimizer<'a> {
+    /// Returns `None` if the predicate does not hold for `log` to begin
+    /// with.
+    pub fn new(log: &'a str, predicate: Predicate) -> Option<Self> {
+        let lines: Vec<_> = log.lines().collect();
+        let kept = vec![true; lines.len()];
+        let mut this = Self {
+            lines,
+            kept,
+            predicate,
+            original: Outcome::default(),
+            checks: 0,
+        };
+        let kept = this.kept.clone();
+        this.original = this.outcome(&kept);
+        let holds = this.holds(&this.original);
+        holds.then_some(this)
+    }
+
+    fn outcome(&mut self, kept: &[bool]) -> Outcome {
+        self.checks += 1;
+        let log = self.render(kept);
+        Outcome::of(&log, &self.predicate)
+    }
+
+    fn holds(&self, outcome: &Outcome) -> bool {
+        match &self.predicate {
+            Predicate::Error(kind) => {
+                outcome.panic.is_none() && outcome.errors.iter().any(|(_, k)| k == kind)
+            }
+            Predicate::Panic => outcome.panic.is_some() && outcome.panic == self.original.panic,
+            #[cfg(feature = "analysis")]
+            Predicate::MatchingLoop => outcome.panic.is_none() && outcome.matching_loops > 0,
+        }
+    }
+
+    /// Whether removing the lines of `kept` did not introduce any new
+    /// problems, and the predicate still holds.
+    fn accept(&mut self, kept: &[bool]) -> bool {
+        let outcome = self.outcome(kept);
+        self.holds(&outcome) && outcome.errors.is_subset(&self.original.errors)
+    }
+
+    fn render(&self, kept: &[bool]) -> String {
+        let mut log = String::new();
+        for (line, _) in self.lines.iter().zip(kept).filter(|(_, &k)| k) {
+            log.push_str(line);
+            log.push('\n');
+        }
+        log
+    }
+
+    /// The minimized log.
+    pub fn log(&self) -> String {
+        self.render(&self.kept)
+    }
+    /// The number of lines in the original and in the minimized log.
+    pub fn line_counts(&self) -> (usize, usize) {
+        let kept = self.kept.iter().filter(|&&k| k).count();
+        (self.lines.len(), kept)
+    }
+
+    /// Removes as much as possible, repeating all steps until none of them
+    /// makes progress.
+    pub fn run(&mut self) {
+        loop {
+            let before = self.line_counts().1;
+            let units = self.instance_units();
+            self.ddmin(units);
+            let units = self.frame_units();
+            self.ddmin(units);
+            while self.remove_unreferenced() {}
+            if self.line_counts().1 == before {
+                break;
+            }
+        }
+    }
+
+    /// Each `[new-match]` together with its `[instance]` block (if any) and
+    /// each `[inst-discovered]` together with its `[instance]` block.
+    fn instance_units(&self) -> Vec<Vec<usize>> {
+        let mut units: Vec<Vec<usize>> = Vec::new();
+        // Matches which may still be instantiated, by fingerprint.
+        let mut matches = FxHashMap::default();
+        let mut current: Option<usize> = None;
+        for (idx, line) in self.lines.iter().enumerate() {
+            if !self.kept[idx] {
+                continue;
+            }
+            let entry = entry(line);
+            match entry {
+                "[new-match]" | "[inst-discovered]" => {
+                    // `[inst-discovered]` has the method before the fingerprint.
+                    let nth = if entry == "[new-match]" { 1 } else { 2 };
+                    let fingerprint = line.split_ascii_whitespace().nth(nth).unwrap_or_default();
+                    matches.insert(fingerprint, units.len());
+                    units.push(vec![idx]);
+                }
+                "[instance]" => {
+                    let fingerprint = line.split_ascii_whitespace().nth(1).unwrap_or_default();
+                    let unit = matches.remove(fingerprint).unwrap_or_else(|| {
+                        units.push(Vec::new());
+                        units.len() - 1
+                    });
+                    units[unit].push(idx);
+                    current = Some(unit);
+                }
+                "[end-of-instance]" => {
+                    if let Some(unit) = current.take() {
+                        units[unit].push(idx);
+                    }
+                }
+                _ => {
+                    if let Some(unit) = current {
+                        units[unit].push(idx);
+                    }
+                }
+            }
+        }
+        units
+    }
+
+    /// Each `[push]` up to the `[pop]` which returns to the same height, or
+    /// to the end of the log if it is never popped. Regions closed by a pop
+    /// of several frames at once cannot be removed on their own and are
+    /// skipped.
+    fn frame_units(&self) -> Vec<Vec<usize>> {
+        let mut units = Vec::new();
+        // The indices of the pushes of the currently open frames.
+        let mut open: Vec<usize> = Vec::new();
+        for (idx, line) in self.lines.iter().enumerate() {
+            if !self.kept[idx] {
+                continue;
+            }
+            match entry(line) {
+                "[push]" => open.push(idx),
+                "[pop]" => {
+                    let num = line.split_ascii_whitespace().nth(1);
+                    let num = num.and_then(|n| n.parse::<usize>().ok()).unwrap_or(1);
+                    let start = open.len().saturating_sub(num);
+                    let popped = open.split_off(start);
+                    if let [push] = popped[..] {
+                        units.push((push..=idx).filter(|&i| self.kept[i]).collect());
+                    }
+                }
+                _ => (),
+            }
+        }
+        for push in open {
+            let region = (push..self.lines.len()).filter(|&i| self.kept[i]);
+            units.push(
+                region
+                    .filter(|&i| entry(self.lines[i]) != "[eof]")
+                    .collect(),
+            );
+        }
+        units
+    }
+
+    /// Removes definitions of terms which are not mentioned by any other
+    /// line, together with their attachments. Returns whether anything was
+    /// removed.
+    fn remove_unreferenced(&mut self) -> bool {
+        let mut referenced = FxHashSet::default();
+        let mut definitions = Vec::new();
+        for (idx, line) in self.lines.iter().enumerate() {
+            if !self.kept[idx] {
+                continue;
+            }
+            let entry = entry(line);
+            let mut ids = ids(line);
+            if DEFINITIONS.contains(&entry) {
+                if let Some(id) = ids.next() {
+                    definitions.push((id, idx));
+                }
+            } else if ATTACHMENTS.contains(&entry) {
+                ids.next();
+            }
+            referenced.extend(ids);
+        }
+        let unreferenced: FxHashSet<_> = definitions
+            .iter()
+            .filter(|(id, _)| !referenced.contains(id))
+            .map(|(id, _)| *id)
+            .collect();
+        if unreferenced.is_empty() {
+            return false;
+        }
+        let mut units: Vec<Vec<usize>> = Vec::new();
+        let mut by_id = FxHashMap::default();
+        for (idx, line) in self.lines.iter().enumerate() {
+            if !self.kept[idx] {
+                continue;
+            }
+            let entry = entry(line);
+            if !DEFINITIONS.contains(&entry) && !ATTACHMENTS.contains(&entry) {
+                continue;
+            }
+            let Some(id) = ids(line).next().filter(|id| unreferenced.contains(id)) else {
+                continue;
+            };
+            let unit = *by_id.entry(id).or_insert_with(|| {
+                units.push(Vec::new());
+                units.len() - 1
+            });
+            units[unit].push(idx);
+        }
+        let before = self.line_counts().1;
+        self.ddmin(units);
+        self.line_counts().1 < before
+    }
+
+    /// Removes as many of the units as possible, trying to remove ever
+    /// smaller chunks of them at once.
+    fn ddmin(&mut self, mut units: Vec<Vec<usize>>) {
+        let mut chunks = 2;
+        while !units.is_empty() {
+            let size = units.len().div_ceil(chunks.min(units.len()));
+            let mut removed_any = false;
+            let mut start = 0;
+            while start < units.len() {
+                let end = (start + size).min(units.len());
+                let mut kept = self.kept.clone();
+                for &idx in units[start..end].iter().flatten() {
+                    kept[idx] = false;
+                }
+                if self.accept(&kept) {
+                    self.kept = kept;
+                    units.drain(start..end);
+                    removed_any = true;
+                } else {
+                    start = end;
+                }
+            }
+            if removed_any {
+                chunks = (chunks - 1).max(2);
+            } else if size == 1 {
+                break;
+            } else {
+                chunks *= 2;
+            }
+        }
+    }
+}
+
+/// Shrinks `log` to a smaller log for which `predicate` still holds, see
+/// [`Minimizer::log`]. Returns `None` if the predicate does not hold for
+/// `log` itself. Panics are caught, but are still reported by the panic hook
+/// which the caller may want to silence while minimizing.
+pub fn minimize(log: &str, predicate: Predicate) -> Option<Minimizer<'_>> {
+    let mut minimizer = Minimizer::new(log, predicate)?;
+    minimizer.run();
+    Some(minimizer)
+}
@@ -0,0 +1,86 @@
+use super::{cvc5::cvc5parser::Cvc5Parser, LogParser};
+use crate::{FResult, Z3Parser};
+
+/// Parses either a Z3 log or a cvc5 instantiation trace, the format is
+/// detected from the first non-empty line. Z3 log lines always start with a
+/// `[`, whereas cvc5 prints s-expressions or command responses.
+#[derive(Debug)]
+pub enum AnyParser {
+    Unknown { strict: bool, provenance: bool },
+    Z3(Z3Parser),
+    Cvc5(Cvc5Parser),
+}
+
+impl Default for AnyParser {
+    fn default() -> Self {
+        Self::Unknown {
+            strict: false,
+            provenance: false,
+        }
+    }
+}
+
+impl LogParser for AnyParser {
+    fn is_line_start(&mut self, first_byte: u8) -> bool {
+        match self {
+            Self::Unknown { .. } => true,
+            Self::Z3(parser) => parser.is_line_start(first_byte),
+            Self::Cvc5(parser) => parser.is_line_start(first_byte),
+        }
+    }
+
+    fn set_strict(&mut self, strict: bool) {
+        match self {
+            Self::Unknown { strict: s, .. } => *s = strict,
+            Self::Z3(parser) => parser.set_strict(strict),
+            Self::Cvc5(parser) => parser.set_strict(strict),
+        }
+    }
+
+    fn set_provenance(&mut self, enabled: bool) {
+        match self {
+            Self::Unknown { provenance, .. } => *provenance = enabled,
+            Self::Z3(parser) => parser.set_provenance(enabled),
+            Self::Cvc5(parser) => parser.set_provenance(enabled),
+        }
+    }
+
+    fn process_line(&mut self, line: &str, line_no: usize, byte_offset: usize) -> FResult<bool> {
+        if let Self::Unknown { strict, provenance } = *self {
+            let line = line.trim_start();
+            if line.is_empty() {
+                return Ok(true);
+            }
+            *self = if line.starts_with('[') {
+                Self::Z3(Z3Parser::default())
+            } else {
+                Self::Cvc5(Cvc5Parser::default())
+            };
+            self.set_strict(strict);
+            self.set_provenance(provenance);
+        }
+        match self {
+            Self::Unknown { .. } => unreachable!(),
+            Self::Z3(parser) => parser.process_line(line, line_no, byte_offset),
+            Self::Cvc5(parser) => parser.process_line(line, line_no, byte_offset),
+        }
+    }
+
+    fn end_of_file(&mut self) {
+        match self {
+            Self::Unknown { .. } => (),
+            Self::Z3(parser) => parser.end_of_file(),
+            Self::Cvc5(parser) => parser.end_of_file(),
+        }
+    }
+}
+
+impl From<AnyParser> for Z3Parser {
+    fn from(parser: AnyParser) -> Self {
+        match parser {
+            AnyParser::Unknown { .. } => Z3Parser::default(),
+            AnyParser::Z3(parser) => parser,
+            AnyParser::Cvc5(parser) => parser.into_inner(),
+        }
+    }
+}
@@ -0,0 +1,374 @@
+use std::{
+    cell::Cell,
+    io::{self, BufRead, Read},
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+#[cfg(feature = "compression")]
+use std::{cell::RefCell, collections::VecDeque};
+
+use futures::{AsyncBufRead, AsyncBufReadExt, AsyncRead};
+
+use super::{AsyncParser, LogParser, StreamParser};
+
+/// The compression format of a log, detected from the first few bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+    Xz,
+}
+
+impl Compression {
+    /// The longest magic number of all supported formats.
+    pub const MAGIC_LEN: usize = 6;
+
+    /// Detects the compression format from the start of the input, `magic`
+    /// should contain at least [`Self::MAGIC_LEN`] bytes unless the input is
+    /// shorter than that.
+    pub fn detect(magic: &[u8]) -> Self {
+        if magic.starts_with(&[0x1f, 0x8b]) {
+            Self::Gzip
+        } else if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Self::Zstd
+        } else if magic.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Self::Xz
+        } else {
+            Self::None
+        }
+    }
+
+    #[cfg(not(feature = "compression"))]
+    fn unsupported(self) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{self:?} compressed logs require the `compression` feature"),
+        )
+    }
+}
+
+/// Shared count of the bytes consumed from the compressed input, such that
+/// the parser can report its progress relative to the size of the file.
+#[derive(Debug, Clone, Default)]
+pub struct ByteCounter(Rc<Cell<usize>>);
+
+impl ByteCounter {
+    pub fn get(&self) -> usize {
+        self.0.get()
+    }
+    #[cfg(feature = "compression")]
+    fn add(&self, bytes: usize) {
+        self.0.set(self.0.get() + bytes);
+    }
+}
+
+/// Wraps the raw (compressed) input and counts how much of it has been read.
+#[cfg(feature = "compression")]
+struct Counting<R> {
+    inner: R,
+    counter: ByteCounter,
+}
+
+#[cfg(feature = "compression")]
+impl<R: Read> Read for Counting<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.counter.add(read);
+        Ok(read)
+    }
+}
+#[cfg(feature = "compression")]
+impl<R: BufRead> BufRead for Counting<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+    fn consume(&mut self, amt: usize) {
+        self.counter.add(amt);
+        self.inner.consume(amt)
+    }
+}
+
+/// A reader which transparently decompresses its input if it starts with the
+/// magic number of a supported compression format.
+pub struct Decompressed<'r> {
+    compression: Compression,
+    reader: Box<dyn BufRead + 'r>,
+    /// Only set if the input is compressed, otherwise the parser counts the
+    /// bytes itself.
+    counter: Option<ByteCounter>,
+}
+
+impl<'r> Decompressed<'r> {
+    pub fn new(mut reader: impl BufRead + 'r) -> io::Result<Self> {
+        let compression = Compression::detect(reader.fill_buf()?);
+        if compression == Compression::None {
+            return Ok(Self {
+                compression,
+                reader: Box::new(reader),
+                counter: None,
+            });
+        }
+        #[cfg(not(feature = "compression"))]
+        return Err(compression.unsupported());
+        #[cfg(feature = "compression")]
+        {
+            let counter = ByteCounter::default();
+            let inner = Counting {
+                inner: reader,
+                counter: counter.clone(),
+            };
+            let reader: Box<dyn BufRead + 'r> = match compression {
+                Compression::None => unreachable!(),
+                Compression::Gzip => Box::new(io::BufReader::new(
+                    flate2::bufread::MultiGzDecoder::new(inner),
+                )),
+                Compression::Zstd => Box::new(io::BufReader::new(ZstdReader::new(inner)?)),
+                Compression::Xz => {
+                    Box::new(io::BufReader::new(lzma_rust2::XzReader::new(inner, true)))
+                }
+            };
+            Ok(Self {
+                compression,
+                reader,
+                counter: Some(counter),
+            })
+        }
+    }
+
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+
+    /// Creates a new parser from the decompressed input. Shadows
+    /// [`IntoStreamParser::into_parser`](super::IntoStreamParser::into_parser)
+    /// such that the [`ReaderState`](super::ReaderState) reports progress in
+    /// terms of the compressed input.
+    pub fn into_parser<Parser: LogParser>(self) -> StreamParser<'r, Parser> {
+        StreamParser::new_counted(self.reader, self.counter)
+    }
+}
+
+impl Read for Decompressed<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+impl BufRead for Decompressed<'_> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.reader.fill_buf()
+    }
+    fn consume(&mut self, amt: usize) {
+        self.reader.consume(amt)
+    }
+}
+
+/// The async counterpart of [`Decompressed`].
+pub struct AsyncDecompressed<'r> {
+    compression: Compression,
+    reader: Pin<Box<dyn AsyncBufRead + 'r>>,
+    counter: Option<ByteCounter>,
+}
+
+impl<'r> AsyncDecompressed<'r> {
+    pub async fn new(mut reader: impl AsyncBufRead + Unpin + 'r) -> io::Result<Self> {
+        let compression = Compression::detect(reader.fill_buf().await?);
+        if compression == Compression::None {
+            return Ok(Self {
+                compression,
+                reader: Box::pin(reader),
+                counter: None,
+            });
+        }
+        #[cfg(not(feature = "compression"))]
+        return Err(compression.unsupported());
+        #[cfg(feature = "compression")]
+        {
+            // None of the decoders support async input, instead the
+            // compressed input is read into a bounded buffer from which the
+            // decoder is fed.
+            let mut input = AsyncInput {
+                source: Box::pin(reader),
+                buffered: SharedBuffer::default(),
+            };
+            futures::future::poll_fn(|cx| input.poll_top_up(cx)).await?;
+            let buffered = io::BufReader::new(input.buffered.clone());
+            let decompressed = Decompressed::new(buffered)?;
+            Ok(Self {
+                compression,
+                reader: Box::pin(AsyncDecoder {
+                    input,
+                    decoder: decompressed.reader,
+                }),
+                counter: decompressed.counter,
+            })
+        }
+    }
+
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+
+    /// Creates a new async parser from the decompressed input, see
+    /// [`Decompressed::into_parser`].
+    pub fn into_async_parser<Parser: LogParser>(self) -> AsyncParser<'r, Parser> {
+        AsyncParser::new_counted(self.reader, self.counter)
+    }
+}
+
+impl AsyncRead for AsyncDecompressed<'_> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().reader.as_mut().poll_read(cx, buf)
+    }
+}
+impl AsyncBufRead for AsyncDecompressed<'_> {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        self.get_mut().reader.as_mut().poll_fill_buf(cx)
+    }
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        self.get_mut().reader.as_mut().consume(amt)
+    }
+}
+
+/// The compressed input which was read but not yet decoded.
+#[cfg(feature = "compression")]
+#[derive(Debug, Clone, Default)]
+struct SharedBuffer(Rc<RefCell<(VecDeque<u8>, bool)>>);
+
+#[cfg(feature = "compression")]
+impl Read for SharedBuffer {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let (buffered, eof) = &mut *self.0.borrow_mut();
+        if buffered.is_empty() && !*eof && !buf.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "the decoder read past the buffered compressed input",
+            ));
+        }
+        buffered.read(buf)
+    }
+}
+
+/// The async source of the compressed input of an [`AsyncDecompressed`].
+#[cfg(feature = "compression")]
+struct AsyncInput<'r> {
+    source: Pin<Box<dyn AsyncBufRead + 'r>>,
+    buffered: SharedBuffer,
+}
+
+#[cfg(feature = "compression")]
+impl AsyncInput<'_> {
+    /// The decoders cannot wait for more input, so at least this much of the
+    /// compressed input is buffered before each read from them. This is more
+    /// than any of them needs to produce some output, e.g. zstd blocks are at
+    /// most 128KiB. If the limit is ever exceeded decoding fails with an
+    /// [`io::ErrorKind::UnexpectedEof`] error.
+    const MIN_BUFFERED: usize = 1 << 18;
+
+    /// Reads from the source until [`Self::MIN_BUFFERED`] bytes are buffered
+    /// or it is exhausted.
+    fn poll_top_up(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        loop {
+            let (buffered, eof) = &mut *self.buffered.0.borrow_mut();
+            if *eof || buffered.len() >= Self::MIN_BUFFERED {
+                return Poll::Ready(Ok(()));
+            }
+            let read = match self.source.as_mut().poll_fill_buf(cx) {
+                Poll::Ready(Ok(read)) => read,
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending => return Poll::Pending,
+            };
+            *eof = read.is_empty();
+            buffered.extend(read);
+            let read = read.len();
+            self.source.as_mut().consume(read);
+        }
+    }
+}
+
+/// Decodes the buffered compressed input, topping it up before each read.
+#[cfg(feature = "compression")]
+struct AsyncDecoder<'r> {
+    input: AsyncInput<'r>,
+    decoder: Box<dyn BufRead + 'r>,
+}
+
+#[cfg(feature = "compression")]
+impl AsyncRead for AsyncDecoder<'_> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        match this.input.poll_top_up(cx) {
+            Poll::Ready(Ok(())) => Poll::Ready(this.decoder.read(buf)),
+            Poll::Ready(Err(err)) => Poll::Ready(Err(err)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+#[cfg(feature = "compression")]
+impl AsyncBufRead for AsyncDecoder<'_> {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        let this = self.get_mut();
+        match this.input.poll_top_up(cx) {
+            Poll::Ready(Ok(())) => Poll::Ready(this.decoder.fill_buf()),
+            Poll::Ready(Err(err)) => Poll::Ready(Err(err)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        self.get_mut().decoder.consume(amt)
+    }
+}
+
+/// Decodes all zstd frames in the input, the [`ruzstd`] decoder stops after
+/// the first one.
+#[cfg(feature = "compression")]
+struct ZstdReader<R: BufRead> {
+    decoder: Option<ruzstd::StreamingDecoder<R, ruzstd::FrameDecoder>>,
+}
+
+#[cfg(feature = "compression")]
+impl<R: BufRead> ZstdReader<R> {
+    fn new(inner: R) -> io::Result<Self> {
+        let decoder = ruzstd::StreamingDecoder::new(inner).map_err(invalid_data)?;
+        Ok(Self {
+            decoder: Some(decoder),
+        })
+    }
+}
+
+#[cfg(feature = "compression")]
+impl<R: BufRead> Read for ZstdReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let Some(decoder) = &mut self.decoder else {
+                return Ok(0);
+            };
+            let read = decoder.read(buf)?;
+            if read != 0 || buf.is_empty() {
+                return Ok(read);
+            }
+            let (mut inner, frame_decoder) = self.decoder.take().unwrap().into_parts();
+            if inner.fill_buf()?.is_empty() {
+                return Ok(0);
+            }
+            let decoder = ruzstd::StreamingDecoder::new_with_decoder(inner, frame_decoder)
+                .map_err(invalid_data)?;
+            self.decoder = Some(decoder);
+        }
+    }
+}
+
+#[cfg(feature = "compression")]
+fn invalid_data(err: impl std::fmt::Display) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err.to_string())
+}
@@ -0,0 +1,427 @@
+#[cfg(feature = "mem_dbg")]
+use mem_dbg::{MemDbg, MemSize};
+
+use crate::parsers::sexp::{symbol, Balance, SExp};
+use crate::{
+    items::{
+        Fingerprint, Instantiation, Match, MatchKind, QuantIdx, QuantKind, Quantifier, Term,
+        TermIdx, TermKind, VarNames,
+    },
+    parsers::{
+        diagnostics::Diagnostics,
+        z3::{provenance::LinePos, Z3LogParser},
+    },
+    Error, FxHashMap, IString, Result, Z3Parser,
+};
+
+/// A parser for cvc5 instantiation traces. The parsed items are stored in a
+/// [`Z3Parser`] (accessible through `Deref` or `into_inner`) so that all
+/// analyses can be run on them.
+///
+/// cvc5 does not log how an instantiation was matched, instantiations are
+/// thus represented with [`MatchKind::Reported`] and have no dependencies
+/// between each other.
+#[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[derive(Debug, Default)]
+pub struct Cvc5Parser {
+    parser: Z3Parser,
+    /// The lines of an s-expression which has not been closed yet.
+    pending: String,
+    balance: Balance,
+    /// cvc5 prints terms rather than ids, identical terms are shared.
+    term_ids: FxHashMap<Term, TermIdx>,
+    /// Maps the `:qid` name or the printed formula of a quantifier to its
+    /// index.
+    quant_names: FxHashMap<String, QuantIdx>,
+    /// The number of instantiations of each quantifier which have been
+    /// logged in full, used to avoid double counting those in
+    /// `num-instantiations`.
+    logged_insts: FxHashMap<QuantIdx, usize>,
+}
+
+/// The variables in scope while converting an s-expression to a term.
+#[derive(Default)]
+struct Env<'a> {
+    /// Variables bound by quantifiers, innermost last.
+    bound: Vec<Vec<&'a str>>,
+    /// Names bound by `let`, innermost last.
+    lets: Vec<(&'a str, TermIdx)>,
+}
+
+impl Cvc5Parser {
+    pub fn into_inner(self) -> Z3Parser {
+        self.parser
+    }
+
+    pub(super) fn parse_line(&mut self, line: &str) -> Result<()> {
+        if self.pending.is_empty() && line.trim().is_empty() {
+            return Ok(());
+        }
+        self.pending.try_reserve(line.len() + 1)?;
+        self.pending.push_str(line);
+        self.pending.push('\n');
+        let complete = self.balance.feed(line);
+        if !matches!(complete, Ok(false)) {
+            self.balance = Balance::default();
+            let pending = std::mem::take(&mut self.pending);
+            complete?;
+            for sexp in SExp::parse_all(&pending)? {
+                self.process_sexp(&sexp)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(super) fn diagnostics_mut(&mut self) -> &mut Diagnostics {
+        Z3LogParser::diagnostics_mut(&mut self.parser)
+    }
+
+    pub(super) fn set_provenance(&mut self, enabled: bool) {
+        Z3LogParser::set_provenance(&mut self.parser, enabled);
+    }
+
+    /// Items of an s-expression spanning multiple lines are attributed to
+    /// its last line.
+    pub(super) fn line_done(&mut self, pos: LinePos) -> Result<()> {
+        Z3LogParser::line_done(&mut self.parser, pos)
+    }
+
+    pub(super) fn eof(&mut self) {
+        Z3LogParser::eof(&mut self.parser);
+    }
+
+    fn process_sexp(&mut self, sexp: &SExp) -> Result<()> {
+        if let Some(atom) = sexp.as_atom() {
+            // Responses to commands, e.g. to `(check-sat)`.
+            return match atom {
+                "sat" | "unsat" | "unknown" | "success" | "unsupported" => Ok(()),
+                _ => Err(Error::UnknownLine(atom.to_string())),
+            };
+        }
+        let (head, rest) = sexp.as_app().ok_or_else(|| sexp.unexpected())?;
+        match head {
+            "instantiations" => self.instantiations(rest),
+            "num-instantiations" => self.num_instantiations(rest),
+            // We do not model skolemization.
+            "skolem" => Ok(()),
+            _ => Err(Error::UnknownLine(sexp.to_string())),
+        }
+    }
+
+    /// `(instantiations <quant> (<terms>) ... (! (<terms>) :source <id>) ...)`
+    fn instantiations(&mut self, rest: &[SExp]) -> Result<()> {
+        let (quant, entries) = rest.split_first().ok_or(Error::UnexpectedEnd)?;
+        let quant = self.quantifier(quant)?;
+        for entry in entries {
+            let (terms, source) = match entry.as_app() {
+                Some(("!", [terms, attrs @ ..])) => {
+                    let source = SExp::attribute(attrs, ":source")
+                        .next()
+                        .and_then(SExp::as_atom)
+                        .map(|source| IString(self.parser.strings.get_or_intern(source)));
+                    (terms, source)
+                }
+                _ => (entry, None),
+            };
+            let terms = terms.as_list().ok_or_else(|| terms.unexpected())?;
+            let bound_terms = terms
+                .iter()
+                .map(|term| self.term(term, &mut Env::default()))
+                .collect::<Result<_>>()?;
+            self.new_instantiation(quant, source, bound_terms)?;
+        }
+        *self.logged_insts.entry(quant).or_default() += entries.len();
+        Ok(())
+    }
+
+    /// `(num-instantiations <quant> <count>)`
+    fn num_instantiations(&mut self, rest: &[SExp]) -> Result<()> {
+        let [quant, count] = rest else {
+            return Err(Error::UnexpectedSExp(SExp::List(rest.to_vec()).to_string()));
+        };
+        let quant = self.quantifier(quant)?;
+        let count = count.as_atom().ok_or_else(|| count.unexpected())?;
+        let count = count.parse::<usize>().map_err(Error::InvalidInstCount)?;
+        let logged = self.logged_insts.entry(quant).or_default();
+        let missing = count.saturating_sub(*logged);
+        *logged += missing;
+        // The bound terms of these are unknown.
+        for _ in 0..missing {
+            self.new_instantiation(quant, None, Default::default())?;
+        }
+        Ok(())
+    }
+
+    fn new_instantiation(
+        &mut self,
+        quant: QuantIdx,
+        source: Option<IString>,
+        bound_terms: Box<[TermIdx]>,
+    ) -> Result<()> {
+        let q = &mut self.parser.quantifiers[quant];
+        if q.term.is_none() {
+            // Only the name of the quantifier is known.
+            q.num_vars = q.num_vars.max(bound_terms.len());
+        }
+        // cvc5 does not log fingerprints, use a unique one per match.
+        let fingerprint = Fingerprint(self.parser.insts.matches.len() as u64 + 1);
+        let match_ = Match {
+            kind: MatchKind::Reported {
+                quant,
+                source,
+                bound_terms,
+            },
+            blamed: Default::default(),
+        };
+        let match_ = self.parser.insts.new_match(fingerprint, match_)?;
+        let inst = Instantiation {
+            match_,
+            fingerprint,
+            proof_id: None,
+            z3_generation: None,
+            yields_terms: Default::default(),
+            frame: None,
+        };
+        self.parser.insts.new_inst(fingerprint, inst, false)?;
+        Ok(())
+    }
+
+    /// A quantifier is printed either as its `:qid` name or as the full
+    /// formula.
+    fn quantifier(&mut self, quant: &SExp) -> Result<QuantIdx> {
+        let key = match quant.as_atom() {
+            Some(name) => symbol(name).to_string(),
+            None => quant.to_string(),
+        };
+        if let Some(&qidx) = self.quant_names.get(&key) {
+            return Ok(qidx);
+        }
+        let qidx = match quant.as_atom() {
+            Some(_) => {
+                let name = IString(self.parser.strings.get_or_intern(&key));
+                self.new_quantifier(QuantKind::NamedQuant(name), 0, None)?
+            }
+            None => {
+                let tidx = self.term(quant, &mut Env::default())?;
+                self.parser.terms[tidx]
+                    .kind
+                    .quant_idx()
+                    .ok_or_else(|| quant.unexpected())?
+            }
+        };
+        self.quant_names.try_reserve(1)?;
+        self.quant_names.insert(key, qidx);
+        Ok(qidx)
+    }
+
+    fn new_quantifier(
+        &mut self,
+        kind: QuantKind,
+        num_vars: usize,
+        vars: Option<VarNames>,
+    ) -> Result<QuantIdx> {
+        let q = Quantifier {
+            kind,
+            num_vars,
+            term: None,
+            vars,
+            triggers: Default::default(),
+        };
+        self.parser.quantifiers.raw.try_reserve(1)?;
+        Ok(self.parser.quantifiers.push_and_get_key(q))
+    }
+
+    fn term<'a>(&mut self, sexp: &SExp<'a>, env: &mut Env<'a>) -> Result<TermIdx> {
+        let (head, args) = match sexp {
+            SExp::Atom(atom) => return self.atom(atom, env),
+            SExp::List(list) => list.split_first().ok_or_else(|| sexp.unexpected())?,
+        };
+        let name = match head.as_atom() {
+            Some("forall" | "exists" | "lambda") => return self.quant_term(sexp, env),
+            Some("let") => return self.let_term(sexp, env),
+            // Annotations outside of quantifiers (e.g. `:named`) are dropped.
+            Some("!") => {
+                let term = args.first().ok_or_else(|| sexp.unexpected())?;
+                return self.term(term, env);
+            }
+            // An indexed identifier such as `(_ bv5 32)`.
+            Some("_") => return self.app(&sexp.to_string(), Default::default()),
+            Some(name) => symbol(name).to_string(),
+            // An application of an indexed identifier such as `((_ extract 7 0) x)`.
+            None => head.to_string(),
+        };
+        let children = args
+            .iter()
+            .map(|arg| self.term(arg, env))
+            .collect::<Result<_>>()?;
+        self.app(&name, children)
+    }
+
+    fn atom<'a>(&mut self, atom: &'a str, env: &Env<'a>) -> Result<TermIdx> {
+        let name = symbol(atom);
+        // The innermost binder of the name, variables are numbered from the
+        // outermost quantifier as in `DisplayData::find_quant`.
+        let binder = env
+            .bound
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(depth, vars)| {
+                let idx = vars.iter().position(|v| *v == name)?;
+                Some((depth, idx))
+            });
+        if let Some((depth, idx)) = binder {
+            let offset: usize = env.bound[..depth].iter().map(Vec::len).sum();
+            return self.new_term(TermKind::Var(offset + idx), Default::default());
+        }
+        if let Some((_, tidx)) = env.lets.iter().rev().find(|(n, _)| *n == name) {
+            return Ok(*tidx);
+        }
+        self.app(name, Default::default())
+    }
+
+    fn app(&mut self, name: &str, children: Box<[TermIdx]>) -> Result<TermIdx> {
+        let name = IString(self.parser.strings.get_or_intern(name));
+        self.new_term(TermKind::parse_proof_app(false, name), children)
+    }
+
+    fn new_term(&mut self, kind: TermKind, child_ids: Box<[TermIdx]>) -> Result<TermIdx> {
+        let term = Term {
+            id: None,
+            kind,
+            child_ids,
+        };
+        if let Some(&tidx) = self.term_ids.get(&term) {
+            return Ok(tidx);
+        }
+        let tidx = self.parser.terms.new_term(term.clone())?;
+        self.term_ids.try_reserve(1)?;
+        self.term_ids.insert(term, tidx);
+        Ok(tidx)
+    }
+
+    /// `(let ((<name> <term>) ...) <body>)`
+    fn let_term<'a>(&mut self, sexp: &SExp<'a>, env: &mut Env<'a>) -> Result<TermIdx> {
+        let Some(("let", [bindings, body])) = sexp.as_app() else {
+            return Err(sexp.unexpected());
+        };
+        let bindings = bindings.as_list().ok_or_else(|| bindings.unexpected())?;
+        // All bindings of a `let` are evaluated in the outer scope.
+        let mut bound = Vec::new();
+        for binding in bindings {
+            let Some([SExp::Atom(name), value]) = binding.as_list() else {
+                return Err(binding.unexpected());
+            };
+            bound.try_reserve(1)?;
+            bound.push((symbol(name), self.term(value, env)?));
+        }
+        let len = env.lets.len();
+        env.lets.try_reserve(bound.len())?;
+        env.lets.extend(bound);
+        let body = self.term(body, env);
+        env.lets.truncate(len);
+        body
+    }
+
+    /// `(forall ((<var> <sort>) ...) (! <body> :pattern (<terms>) :qid <name>))`
+    fn quant_term<'a>(&mut self, sexp: &SExp<'a>, env: &mut Env<'a>) -> Result<TermIdx> {
+        let Some((kind, [vars, body])) = sexp.as_app() else {
+            return Err(sexp.unexpected());
+        };
+        let vars = vars.as_list().ok_or_else(|| vars.unexpected())?;
+        let vars = vars
+            .iter()
+            .map(|var| match var.as_list() {
+                Some([SExp::Atom(name), sort]) => Ok((symbol(name), sort)),
+                _ => Err(var.unexpected()),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let (body, attrs) = match body.as_app() {
+            Some(("!", [body, attrs @ ..])) => (body, attrs),
+            _ => (body, &[][..]),
+        };
+        let qid = SExp::attribute(attrs, ":qid")
+            .next()
+            .and_then(SExp::as_atom)
+            .map(symbol);
+
+        let var_names = vars
+            .iter()
+            .map(|(name, sort)| {
+                let name = IString(self.parser.strings.get_or_intern(name));
+                let sort = IString(self.parser.strings.get_or_intern(sort.to_string()));
+                (name, sort)
+            })
+            .collect();
+        let quant_kind = match (kind, qid) {
+            ("lambda", _) => QuantKind::Lambda,
+            (_, Some(qid)) => {
+                QuantKind::NamedQuant(IString(self.parser.strings.get_or_intern(qid)))
+            }
+            (kind, None) => QuantKind::UnnamedQuant {
+                name: IString(self.parser.strings.get_or_intern(kind)),
+                id: self.parser.quantifiers.len(),
+            },
+        };
+        // Create the quantifier before its body, which may contain other
+        // quantifiers.
+        let qidx = self.new_quantifier(
+            quant_kind,
+            vars.len(),
+            Some(VarNames::NameAndType(var_names)),
+        )?;
+
+        env.bound.try_reserve(1)?;
+        env.bound.push(vars.iter().map(|(name, _)| *name).collect());
+        let children = self.quant_children(body, attrs, env);
+        env.bound.pop();
+        let children = children?;
+
+        let tidx = self
+            .parser
+            .terms
+            .new_synthetic_term(TermKind::Quant(qidx), children, None);
+        self.parser.quantifiers[qidx].triggers = self.parser.triggers(&self.parser[tidx].child_ids);
+        self.parser.quantifiers[qidx].term = Some(tidx);
+        if let Some(qid) = qid {
+            self.quant_names.try_reserve(1)?;
+            self.quant_names.insert(qid.to_string(), qidx);
+        }
+        Ok(tidx)
+    }
+
+    /// The patterns followed by the body, as in Z3.
+    fn quant_children<'a>(
+        &mut self,
+        body: &SExp<'a>,
+        attrs: &[SExp<'a>],
+        env: &mut Env<'a>,
+    ) -> Result<Box<[TermIdx]>> {
+        let mut children = Vec::new();
+        for pattern in SExp::attribute(attrs, ":pattern") {
+            let terms = pattern.as_list().ok_or_else(|| pattern.unexpected())?;
+            let terms = terms
+                .iter()
+                .map(|term| self.term(term, env))
+                .collect::<Result<_>>()?;
+            children.try_reserve(1)?;
+            children.push(self.app("pattern", terms)?);
+        }
+        children.try_reserve(1)?;
+        children.push(self.term(body, env)?);
+        Ok(children.into_boxed_slice())
+    }
+}
+
+impl std::ops::Deref for Cvc5Parser {
+    type Target = Z3Parser;
+    fn deref(&self) -> &Self::Target {
+        &self.parser
+    }
+}
+
+impl From<Cvc5Parser> for Z3Parser {
+    fn from(parser: Cvc5Parser) -> Self {
+        parser.into_inner()
+    }
+}
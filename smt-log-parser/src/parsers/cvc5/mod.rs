@@ -0,0 +1,37 @@
+use super::{z3::provenance::LinePos, LogParser};
+use crate::FResult;
+
+/// Parser for the instantiation output of cvc5 (`-o inst`, optionally with
+/// `--print-inst=full` or `--dump-instantiations`). Builds the same item
+/// model as the Z3 parser so that the analyses work on cvc5 runs.
+pub mod cvc5parser;
+
+use cvc5parser::Cvc5Parser;
+
+impl LogParser for Cvc5Parser {
+    fn set_strict(&mut self, strict: bool) {
+        self.diagnostics_mut().strict = strict;
+    }
+
+    fn set_provenance(&mut self, enabled: bool) {
+        self.set_provenance(enabled);
+    }
+
+    fn process_line(&mut self, line: &str, line_no: usize, byte_offset: usize) -> FResult<bool> {
+        let pos = LinePos {
+            line_no,
+            byte_offset,
+        };
+        match self.parse_line(line).and(self.line_done(pos)) {
+            Ok(()) => Ok(true),
+            Err(err) => self
+                .diagnostics_mut()
+                .report(err, line, line_no, byte_offset)
+                .map(|()| true),
+        }
+    }
+
+    fn end_of_file(&mut self) {
+        self.eof();
+    }
+}
@@ -0,0 +1,162 @@
+use core::fmt;
+
+#[cfg(feature = "mem_dbg")]
+use mem_dbg::{MemDbg, MemSize};
+
+use crate::{Error, FResult, FatalError, FxHashMap};
+
+/// How much a problem with a line affects the parsed result.
+#[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The line was not understood, but it also does not describe anything
+    /// which the parser tracks (e.g. entries from newer solver versions).
+    Warning,
+    /// The line was skipped, the items it describes are missing.
+    Error,
+    /// Parsing could not continue past this line.
+    Fatal,
+}
+
+impl Severity {
+    pub fn of(error: &Error) -> Self {
+        match error {
+            Error::UnknownLine(_) => Self::Warning,
+            Error::Allocation(_) => Self::Fatal,
+            _ => Self::Error,
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+            Self::Fatal => write!(f, "fatal"),
+        }
+    }
+}
+
+/// A problem encountered while parsing. Repeated problems of the same kind
+/// with the same kind of line are only stored once, for the first line on
+/// which they occurred.
+#[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The (1-indexed) line on which the problem first occurred.
+    pub line_no: usize,
+    /// The offset of the start of that line in the (decompressed) log.
+    pub byte_offset: usize,
+    /// The kind of log entry, e.g. `[new-match]`.
+    pub entry: String,
+    /// The name of the [`Error`] variant.
+    pub kind: String,
+    /// The full error of the first occurrence.
+    pub message: String,
+    pub severity: Severity,
+    /// How many lines had this problem.
+    pub count: usize,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: line {} (byte {}) {} {}",
+            self.severity, self.line_no, self.byte_offset, self.entry, self.message
+        )?;
+        if self.count > 1 {
+            write!(f, " (x{})", self.count)?;
+        }
+        Ok(())
+    }
+}
+
+/// All problems encountered while parsing a log.
+#[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    /// In strict mode any problem stops parsing, otherwise only
+    /// [`Severity::Fatal`] ones do.
+    pub strict: bool,
+    diagnostics: Vec<Diagnostic>,
+    dedup: FxHashMap<(String, String), usize>,
+}
+
+impl Diagnostics {
+    /// Records a problem with the line `line`. Returns an error if parsing
+    /// should stop, i.e. if the problem is fatal or we are in strict mode.
+    pub fn report(
+        &mut self,
+        error: Error,
+        line: &str,
+        line_no: usize,
+        byte_offset: usize,
+    ) -> FResult<()> {
+        let severity = Severity::of(&error);
+        let kind = error.kind().to_string();
+        let message = format!("{error:?}");
+        let entry = line
+            .split_ascii_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        let idx = *self
+            .dedup
+            .entry((kind.clone(), entry.clone()))
+            .or_insert_with(|| {
+                self.diagnostics.push(Diagnostic {
+                    line_no,
+                    byte_offset,
+                    entry,
+                    kind,
+                    message,
+                    severity,
+                    count: 0,
+                });
+                self.diagnostics.len() - 1
+            });
+        self.diagnostics[idx].count += 1;
+        if let Some(fatal) = error.as_fatal() {
+            return Err(fatal);
+        }
+        if self.strict {
+            let mut diagnostic = self.diagnostics[idx].clone();
+            diagnostic.line_no = line_no;
+            diagnostic.byte_offset = byte_offset;
+            return Err(FatalError::Strict(Box::new(diagnostic)));
+        }
+        Ok(())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics.iter()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+    /// The number of distinct problems.
+    pub fn len(&self) -> usize {
+        self.diagnostics.len()
+    }
+    /// The number of lines which could not be parsed.
+    pub fn lines_affected(&self) -> usize {
+        self.diagnostics.iter().map(|d| d.count).sum()
+    }
+    /// The number of lines which were skipped, i.e. whose items are missing
+    /// from the parsed result.
+    pub fn lines_skipped(&self) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity >= Severity::Error)
+            .map(|d| d.count)
+            .sum()
+    }
+    pub fn max_severity(&self) -> Option<Severity> {
+        self.diagnostics.iter().map(|d| d.severity).max()
+    }
+}
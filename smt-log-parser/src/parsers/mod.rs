@@ -1,9 +1,11 @@
 use crate::FResult;
 use crate::FatalError;
 
+pub use self::compression::{AsyncDecompressed, Compression, Decompressed};
 pub use self::wrapper_async_parser::*;
 pub use self::wrapper_stream_parser::*;
-use futures::{AsyncBufRead, AsyncBufReadExt, AsyncRead};
+use compression::ByteCounter;
+use futures::{AsyncBufRead, AsyncBufReadExt, AsyncRead, Future};
 use std::fmt::Debug;
 use std::fs::{File, Metadata};
 use std::io::{BufRead, BufReader};
@@ -11,6 +13,14 @@ use std::path::Path;
 use std::time::Duration;
 use wasm_timer::Instant;
 
+pub mod any;
+pub mod compression;
+pub mod cvc5;
+pub mod diagnostics;
+pub mod sexp;
+/// Parser for SMT-LIB 2 input problems, to map quantifiers back to the
+/// source they were written in.
+pub mod smt2;
 pub mod z3;
 
 /// Trait for a generic SMT solver trace parser. Intended to support different
@@ -21,9 +31,20 @@ pub trait LogParser: Default {
         true
     }
 
+    /// Choose whether any line which cannot be parsed should stop parsing
+    /// (strict), or only be recorded as a diagnostic (lenient, the default).
+    fn set_strict(&mut self, _strict: bool) {}
+
+    /// Choose whether to record the line on which each item was created,
+    /// disabled by default. Must be enabled before parsing starts.
+    fn set_provenance(&mut self, _enabled: bool) {}
+
     /// Process a single line of the log file. Return `true` if parsing should
-    /// continue, or `false` if parsing should stop.
-    fn process_line(&mut self, line: &str, line_no: usize) -> FResult<bool>;
+    /// continue, or `false` if parsing should stop. For an entry spanning
+    /// multiple lines, `line_no` and `byte_offset` are those of its first
+    /// line, with `byte_offset` being the position in the (decompressed)
+    /// input.
+    fn process_line(&mut self, line: &str, line_no: usize, byte_offset: usize) -> FResult<bool>;
 
     fn end_of_file(&mut self);
 
@@ -57,6 +78,8 @@ pub trait LogParser: Default {
 
     /// Creates a new streaming parser from a file. Additionally returns the
     /// file metadata so that the progress can be calculated from the file size.
+    /// Compressed files are decompressed on the fly (see [`Decompressed`]),
+    /// the progress is reported in terms of the compressed size.
     ///
     /// This method is an alternative to
     /// `from_string(fs::read_to_string(self)?)`. This approach to parsing is
@@ -96,11 +119,12 @@ impl<T: AsRef<[u8]>> CursorRead for T {}
 pub trait FileRead: AsRef<Path> + Sized {
     /// Opens a file and returns a buffered reader and the file's metadata. A
     /// more memory efficient alternative to
-    /// `fs::read_to_string(self)?.into_cursor()`.
-    fn read_open(self) -> std::io::Result<(Metadata, BufReader<File>)> {
+    /// `fs::read_to_string(self)?.into_cursor()`. Compressed files are
+    /// detected and decompressed.
+    fn read_open(self) -> std::io::Result<(Metadata, Decompressed<'static>)> {
         let file = File::open(self)?;
         let metadata = file.metadata()?;
-        let reader = BufReader::new(file);
+        let reader = Decompressed::new(BufReader::new(file))?;
         Ok((metadata, reader))
     }
 }
@@ -124,6 +148,18 @@ pub trait AsyncBufferRead: AsyncRead + Sized {
     fn buffer(self) -> futures::io::BufReader<Self> {
         futures::io::BufReader::new(self)
     }
+    /// Buffers the stream and detects whether it is compressed, if so it is
+    /// decompressed on the fly. Use
+    /// [`into_async_parser`](AsyncDecompressed::into_async_parser) on the
+    /// result to get progress reported in terms of the compressed stream.
+    fn buffer_decompressed<'r>(
+        self,
+    ) -> impl Future<Output = std::io::Result<AsyncDecompressed<'r>>> + 'r
+    where
+        Self: Unpin + 'r,
+    {
+        AsyncDecompressed::new(self.buffer())
+    }
 }
 impl<T: AsyncRead> AsyncBufferRead for T {}
 
@@ -158,16 +194,17 @@ impl<T> ParseState<T> {
 /// Progress information for a parser.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct ReaderState {
-    /// The number of bytes parsed so far.
+    /// The number of bytes parsed so far. For compressed input this counts
+    /// the compressed bytes.
     pub bytes_read: usize,
     /// The number of lines parsed so far.
     pub lines_read: usize,
 }
 
 #[duplicate::duplicate_item(
-    EitherParser   ReadBound                   async   add_await(code);
-    [StreamParser] [BufRead + 'r]              []      [code];
-    [AsyncParser]  [AsyncBufRead + Unpin + 'r] [async] [code.await];
+    EitherParser   ReadBound                   async   add_await(code)  sleep(duration);
+    [StreamParser] [BufRead + 'r]              []      [code]           [std::thread::sleep(duration)];
+    [AsyncParser]  [AsyncBufRead + Unpin + 'r] [async] [code.await]     [{ let _ = wasm_timer::Delay::new(duration).await; }];
 )]
 mod wrapper {
     use super::*;
@@ -181,6 +218,13 @@ mod wrapper {
         reader: Option<Box<dyn ReadBound>>,
         reader_state: ReaderState,
         parser: Parser,
+        /// The entry currently being read, may be partial in follow mode.
+        buf: String,
+        follow: Option<Duration>,
+        /// Counts the bytes read from the compressed input, if any.
+        compressed: Option<ByteCounter>,
+        /// The number of (decompressed) bytes read so far.
+        offset: usize,
     }
     impl<'r, Parser: LogParser, R: ReadBound> From<R> for EitherParser<'r, Parser> {
         fn from(reader: R) -> Self {
@@ -189,13 +233,44 @@ mod wrapper {
     }
     impl<'r, Parser: LogParser> EitherParser<'r, Parser> {
         pub(super) fn new(reader: impl ReadBound) -> Self {
+            Self::new_counted(reader, None)
+        }
+        pub(super) fn new_counted(reader: impl ReadBound, compressed: Option<ByteCounter>) -> Self {
             Self {
                 reader: Some(Box::new(reader)),
                 reader_state: ReaderState::default(),
                 parser: Parser::default(),
+                buf: String::new(),
+                follow: None,
+                compressed,
+                offset: 0,
             }
         }
 
+        /// Enable follow mode (similar to `tail -f`) for reading a log which
+        /// is still being written. Instead of stopping at the end of the
+        /// input, the parser waits for `poll_interval` and then tries to read
+        /// more. Parsing only completes once an `[eof]` line is seen. The
+        /// predicate of e.g. [`process_until`] is still called while waiting,
+        /// so it can be used to stop parsing.
+        ///
+        /// Entries are parsed as soon as their last line has been completely
+        /// written; an entry spanning multiple lines may thus be split if the
+        /// solver has only written part of it.
+        pub fn set_follow(&mut self, poll_interval: Option<Duration>) {
+            self.follow = poll_interval;
+        }
+
+        /// See [`LogParser::set_strict`].
+        pub fn set_strict(&mut self, strict: bool) {
+            self.parser.set_strict(strict);
+        }
+
+        /// See [`LogParser::set_provenance`].
+        pub fn set_provenance(&mut self, enabled: bool) {
+            self.parser.set_provenance(enabled);
+        }
+
         /// Get the current parser state.
         pub fn parser(&self) -> &Parser {
             &self.parser
@@ -213,18 +288,40 @@ mod wrapper {
             self.reader.is_none()
         }
 
+        /// Returns `Ok(None)` if the line was parsed and parsing should
+        /// continue, `Ok(Some(true))` at the end of the input and
+        /// `Ok(Some(false))` if the parser requested to stop. In follow mode
+        /// `Ok(Some(true))` means that we are waiting for more input.
         async fn process_line(
             reader: &mut Box<dyn ReadBound>,
             reader_state: &mut ReaderState,
             parser: &mut Parser,
             buf: &mut String,
+            follow: bool,
+            compressed: Option<&ByteCounter>,
+            offset: &mut usize,
         ) -> Result<Option<bool>, FatalError> {
-            buf.clear();
+            // The first line of the entry, `buf` may already contain complete
+            // lines in follow mode.
+            let line_no = reader_state.lines_read + 1 - buf.matches('\n').count();
             // Read line
-            let mut bytes_read = 0;
-
             loop {
-                bytes_read += add_await([reader.read_line(buf)])?;
+                // In follow mode `buf` may contain a partial line from before.
+                let bytes_read = add_await([reader.read_line(buf)])?;
+                *offset += bytes_read;
+                match compressed {
+                    Some(compressed) => reader_state.bytes_read = compressed.get(),
+                    None => reader_state.bytes_read += bytes_read,
+                }
+                if !buf.ends_with('\n') {
+                    // End of the input, in follow mode the line may not
+                    // have been completely written yet.
+                    if follow || buf.is_empty() {
+                        return Ok(Some(true));
+                    }
+                    reader_state.lines_read += 1;
+                    break;
+                }
                 reader_state.lines_read += 1;
                 let peek = add_await([reader.fill_buf()])?;
                 // Stop reading if this is the end or we don't have a multiline.
@@ -233,19 +330,16 @@ mod wrapper {
                 }
             }
             // Remove newline from end
-            if buf.ends_with('\n') {
-                buf.pop();
-                if buf.ends_with('\r') {
-                    buf.pop();
-                }
-            }
-            if bytes_read == 0 {
-                return Ok(Some(true));
+            let mut line = buf.as_str();
+            if let Some(l) = line.strip_suffix('\n') {
+                line = l.strip_suffix('\r').unwrap_or(l);
             }
 
             // Parse line
-            reader_state.bytes_read += bytes_read;
-            let stop_parsing = !parser.process_line(buf, reader_state.lines_read)?;
+            let byte_offset = *offset - buf.len();
+            let result = parser.process_line(line, line_no, byte_offset);
+            buf.clear();
+            let stop_parsing = !result?;
             Ok(stop_parsing.then_some(false))
         }
 
@@ -268,7 +362,6 @@ mod wrapper {
                     end_of_stream: true,
                 };
             };
-            let mut buf = String::new();
             loop {
                 if let Some(t) = predicate(&self.parser, self.reader_state) {
                     return ParseState::Paused(t, self.reader_state);
@@ -277,9 +370,17 @@ mod wrapper {
                     reader,
                     &mut self.reader_state,
                     &mut self.parser,
-                    &mut buf,
+                    &mut self.buf,
+                    self.follow.is_some(),
+                    self.compressed.as_ref(),
+                    &mut self.offset,
                 )]) {
                     Ok(None) => continue,
+                    Ok(Some(true)) if self.follow.is_some() => {
+                        let duration = self.follow.unwrap();
+                        sleep([duration]);
+                        continue;
+                    }
                     Ok(Some(end_of_stream)) => ParseState::Completed { end_of_stream },
                     Err(err) => ParseState::Error(err),
                 };
@@ -0,0 +1,230 @@
+use std::{fmt, ops::Range};
+
+#[cfg(feature = "mem_dbg")]
+use mem_dbg::{MemDbg, MemSize};
+
+use crate::{Error, Result};
+
+/// A minimal SMT-LIB s-expression, borrowing its atoms from the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SExp<'a> {
+    Atom(&'a str),
+    List(Vec<SExp<'a>>),
+}
+
+impl<'a> SExp<'a> {
+    /// Parses all top-level s-expressions in `input`.
+    pub fn parse_all(input: &'a str) -> Result<Vec<Self>> {
+        let mut stack: Vec<Vec<SExp<'a>>> = vec![Vec::new()];
+        for token in (Tokens { input, pos: 0 }) {
+            match token {
+                "(" => stack.push(Vec::new()),
+                ")" => {
+                    let list = stack.pop().ok_or(Error::UnmatchedCloseParen)?;
+                    let parent = stack.last_mut().ok_or(Error::UnmatchedCloseParen)?;
+                    parent.try_reserve(1)?;
+                    parent.push(SExp::List(list));
+                }
+                atom => {
+                    let parent = stack.last_mut().unwrap();
+                    parent.try_reserve(1)?;
+                    parent.push(SExp::Atom(atom));
+                }
+            }
+        }
+        if stack.len() != 1 {
+            return Err(Error::UnexpectedEnd);
+        }
+        Ok(stack.pop().unwrap())
+    }
+
+    /// Like [`Self::parse_all`], but also returns the byte range in `input`
+    /// of every list, in the order in which the lists are opened (i.e. a
+    /// pre-order traversal of the returned s-expressions).
+    pub fn parse_all_spanned(input: &'a str) -> Result<(Vec<Self>, Vec<Range<usize>>)> {
+        let mut spans = Vec::new();
+        let mut open = Vec::new();
+        let mut stack: Vec<Vec<SExp<'a>>> = vec![Vec::new()];
+        let mut tokens = Tokens { input, pos: 0 };
+        while let Some(token) = tokens.next() {
+            let start = tokens.pos - token.len();
+            match token {
+                "(" => {
+                    open.push(spans.len());
+                    spans.try_reserve(1)?;
+                    spans.push(start..start);
+                    stack.push(Vec::new());
+                }
+                ")" => {
+                    let span = open.pop().ok_or(Error::UnmatchedCloseParen)?;
+                    spans[span].end = tokens.pos;
+                    let list = stack.pop().unwrap();
+                    let parent = stack.last_mut().unwrap();
+                    parent.try_reserve(1)?;
+                    parent.push(SExp::List(list));
+                }
+                atom => {
+                    let parent = stack.last_mut().unwrap();
+                    parent.try_reserve(1)?;
+                    parent.push(SExp::Atom(atom));
+                }
+            }
+        }
+        if stack.len() != 1 {
+            return Err(Error::UnexpectedEnd);
+        }
+        Ok((stack.pop().unwrap(), spans))
+    }
+
+    pub fn as_atom(&self) -> Option<&'a str> {
+        match self {
+            Self::Atom(atom) => Some(atom),
+            Self::List(_) => None,
+        }
+    }
+    pub fn as_list(&self) -> Option<&[SExp<'a>]> {
+        match self {
+            Self::Atom(_) => None,
+            Self::List(list) => Some(list),
+        }
+    }
+
+    /// Splits a list into its head atom and the remaining elements.
+    pub fn as_app(&self) -> Option<(&'a str, &[SExp<'a>])> {
+        let (head, rest) = self.as_list()?.split_first()?;
+        Some((head.as_atom()?, rest))
+    }
+
+    /// Returns the value following the keyword `key` in an attribute list
+    /// such as `:qid name :pattern (...)`.
+    pub fn attribute<'s>(
+        attrs: &'s [SExp<'a>],
+        key: &'s str,
+    ) -> impl Iterator<Item = &'s SExp<'a>> {
+        attrs
+            .windows(2)
+            .filter(move |w| w[0].as_atom() == Some(key))
+            .map(|w| &w[1])
+    }
+
+    pub fn unexpected(&self) -> Error {
+        Error::UnexpectedSExp(self.to_string())
+    }
+}
+
+/// Removes the `|` quotes from an SMT-LIB symbol, `|x|` and `x` are the
+/// same symbol.
+pub fn symbol(atom: &str) -> &str {
+    atom.strip_prefix('|')
+        .and_then(|a| a.strip_suffix('|'))
+        .unwrap_or(atom)
+}
+
+impl fmt::Display for SExp<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Atom(atom) => write!(f, "{atom}"),
+            Self::List(list) => {
+                write!(f, "(")?;
+                for (idx, item) in list.iter().enumerate() {
+                    if idx != 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+struct Tokens<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = &'a str;
+    fn next(&mut self) -> Option<Self::Item> {
+        let bytes = self.input.as_bytes();
+        // Skip whitespace and comments
+        loop {
+            match bytes.get(self.pos)? {
+                b';' => {
+                    while bytes.get(self.pos).is_some_and(|&b| b != b'\n') {
+                        self.pos += 1;
+                    }
+                }
+                b if b.is_ascii_whitespace() => self.pos += 1,
+                _ => break,
+            }
+        }
+        let start = self.pos;
+        match bytes[start] {
+            b'(' | b')' => self.pos += 1,
+            b'|' => {
+                self.pos += 1;
+                while bytes.get(self.pos).is_some_and(|&b| b != b'|') {
+                    self.pos += 1;
+                }
+                self.pos = (self.pos + 1).min(bytes.len());
+            }
+            b'"' => {
+                self.pos += 1;
+                // A `""` within a string literal is an escaped quote.
+                while self.pos < bytes.len() {
+                    self.pos += 1;
+                    if bytes[self.pos - 1] == b'"' {
+                        if bytes.get(self.pos) != Some(&b'"') {
+                            break;
+                        }
+                        self.pos += 1;
+                    }
+                }
+            }
+            _ => {
+                while bytes
+                    .get(self.pos)
+                    .is_some_and(|&b| !b.is_ascii_whitespace() && !matches!(b, b'(' | b')'))
+                {
+                    self.pos += 1;
+                }
+            }
+        }
+        Some(&self.input[start..self.pos])
+    }
+}
+
+/// Tracks whether the lines seen so far form complete s-expressions, used to
+/// join an s-expression spanning multiple lines.
+#[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[derive(Debug, Default)]
+pub struct Balance {
+    depth: usize,
+    in_symbol: bool,
+    in_string: bool,
+}
+
+impl Balance {
+    /// Feed in the next line, returns `true` if all opened s-expressions have
+    /// been closed by the end of the line.
+    pub fn feed(&mut self, line: &str) -> Result<bool> {
+        for b in line.bytes() {
+            match b {
+                b'|' if !self.in_string => self.in_symbol = !self.in_symbol,
+                b'"' if !self.in_symbol => self.in_string = !self.in_string,
+                _ if self.in_symbol || self.in_string => (),
+                b';' => break,
+                b'(' => self.depth += 1,
+                b')' => {
+                    self.depth = self
+                        .depth
+                        .checked_sub(1)
+                        .ok_or(Error::UnmatchedCloseParen)?
+                }
+                _ => (),
+            }
+        }
+        Ok(self.depth == 0 && !self.in_symbol && !self.in_string)
+    }
+}
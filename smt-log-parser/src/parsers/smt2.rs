@@ -0,0 +1,328 @@
+use std::ops::Range;
+
+use fxhash::FxHashSet;
+
+use super::sexp::{symbol, SExp};
+use crate::{
+    items::{QuantIdx, QuantKind, VarNames},
+    FxHashMap, Result, TiVec, Z3Parser,
+};
+
+/// A position in an SMT-LIB input file, both 1-based. The column counts
+/// bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SourcePos {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceSpan {
+    pub bytes: Range<usize>,
+    pub start: SourcePos,
+    pub end: SourcePos,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Binder {
+    Forall,
+    Exists,
+}
+
+/// A quantifier as written in the input problem.
+#[derive(Debug, Clone)]
+pub struct SourceQuantifier {
+    pub binder: Binder,
+    /// The `:qid` attribute, without `|` quotes.
+    pub qid: Option<String>,
+    pub skolemid: Option<String>,
+    pub weight: Option<u32>,
+    /// The name and sort of each bound variable.
+    pub vars: Vec<(String, String)>,
+    /// The explicit triggers, each being one `:pattern` (a multi-pattern if
+    /// it contains several terms).
+    pub patterns: Vec<Vec<String>>,
+    pub no_patterns: Vec<String>,
+    pub span: SourceSpan,
+    /// The full quantifier, exactly as written.
+    pub text: String,
+}
+
+/// A `declare-fun` or `declare-const`.
+#[derive(Debug, Clone)]
+pub struct FunctionDecl {
+    pub params: Vec<String>,
+    pub sort: String,
+}
+
+/// The parts of an SMT-LIB 2 input problem which are relevant for relating
+/// it to a log: the declarations and all (possibly nested) quantifiers.
+#[derive(Debug, Default)]
+pub struct Smt2Problem {
+    /// The sorts introduced by `declare-sort` or `define-sort`.
+    pub sorts: Vec<String>,
+    pub functions: FxHashMap<String, FunctionDecl>,
+    pub asserts: usize,
+    /// All quantifiers in the order they appear in the input.
+    pub quantifiers: Vec<SourceQuantifier>,
+}
+
+impl Smt2Problem {
+    pub fn parse(input: &str) -> Result<Self> {
+        let (commands, spans) = SExp::parse_all_spanned(input)?;
+        let mut walker = Walker {
+            input,
+            spans: &spans,
+            next_span: 0,
+            line_starts: line_starts(input),
+            problem: Self::default(),
+        };
+        for command in &commands {
+            walker.command(command)?;
+        }
+        Ok(walker.problem)
+    }
+
+    /// Finds the source of each quantifier in the log, see
+    /// [`SourceMap::new`].
+    pub fn map_quantifiers(&self, parser: &Z3Parser) -> SourceMap {
+        SourceMap::new(self, parser)
+    }
+}
+
+/// The source quantifier (an index into [`Smt2Problem::quantifiers`]) of
+/// each quantifier in a log.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    pub quants: TiVec<QuantIdx, Option<usize>>,
+}
+
+impl SourceMap {
+    /// Quantifiers with a `:qid` are matched by name. z3 names the remaining
+    /// ones `k!<line>` (or `<name>!<n>`), these are matched in order to the
+    /// source quantifiers without a `:qid` which bind the same variables,
+    /// preferring one on the given line.
+    pub fn new(problem: &Smt2Problem, parser: &Z3Parser) -> Self {
+        let mut by_qid = FxHashMap::default();
+        for (idx, quant) in problem.quantifiers.iter().enumerate() {
+            if let Some(qid) = &quant.qid {
+                by_qid.entry(qid.as_str()).or_insert(idx);
+            }
+        }
+        let mut claimed = FxHashSet::default();
+        let quants = parser
+            .quantifiers()
+            .iter()
+            .map(|quant| {
+                let found = match &quant.kind {
+                    QuantKind::NamedQuant(name) => {
+                        // z3 keeps the quotes of names such as `|ax 1|`.
+                        let name = &parser[*name];
+                        let name = name
+                            .strip_prefix('|')
+                            .and_then(|name| name.strip_suffix('|'))
+                            .unwrap_or(name);
+                        by_qid.get(name).copied()
+                    }
+                    QuantKind::UnnamedQuant { id, .. } => {
+                        let mut candidates = problem
+                            .quantifiers
+                            .iter()
+                            .enumerate()
+                            .filter(|(idx, source)| {
+                                source.qid.is_none()
+                                    && !claimed.contains(idx)
+                                    && same_vars(parser, quant.vars.as_ref(), source)
+                            })
+                            .peekable();
+                        let first = candidates.peek().map(|(idx, _)| *idx);
+                        candidates
+                            .find(|(_, source)| source.span.start.line == *id)
+                            .map(|(idx, _)| idx)
+                            .or(first)
+                    }
+                    QuantKind::Other(_) | QuantKind::Lambda => None,
+                };
+                if let Some(found) = found {
+                    claimed.insert(found);
+                }
+                found
+            })
+            .collect();
+        Self { quants }
+    }
+
+    pub fn get<'a>(
+        &self,
+        problem: &'a Smt2Problem,
+        quant: QuantIdx,
+    ) -> Option<&'a SourceQuantifier> {
+        let idx = (*self.quants.get(quant)?)?;
+        problem.quantifiers.get(idx)
+    }
+}
+
+fn same_vars(parser: &Z3Parser, vars: Option<&VarNames>, source: &SourceQuantifier) -> bool {
+    match vars {
+        None => true,
+        Some(VarNames::TypeOnly(sorts)) => sorts.len() == source.vars.len(),
+        Some(VarNames::NameAndType(vars)) => {
+            vars.len() == source.vars.len()
+                && vars
+                    .iter()
+                    .zip(&source.vars)
+                    .all(|((name, _), (source, _))| parser[*name] == *source)
+        }
+    }
+}
+
+fn line_starts(input: &str) -> Vec<usize> {
+    let newlines = input.bytes().enumerate().filter(|(_, b)| *b == b'\n');
+    [0].into_iter()
+        .chain(newlines.map(|(idx, _)| idx + 1))
+        .collect()
+}
+
+struct Walker<'a, 's> {
+    input: &'a str,
+    /// The span of every list, in pre-order.
+    spans: &'s [Range<usize>],
+    next_span: usize,
+    line_starts: Vec<usize>,
+    problem: Smt2Problem,
+}
+
+impl<'a> Walker<'a, '_> {
+    fn command(&mut self, sexp: &SExp<'a>) -> Result<()> {
+        match sexp.as_app() {
+            Some(("declare-sort" | "define-sort", [name, ..])) => {
+                let name = name.as_atom().ok_or_else(|| name.unexpected())?;
+                self.problem.sorts.try_reserve(1)?;
+                self.problem.sorts.push(symbol(name).to_string());
+            }
+            Some(("declare-fun", [name, params, sort])) => {
+                let params = params.as_list().ok_or_else(|| params.unexpected())?;
+                let params = params.iter().map(sort_name).collect();
+                self.declare(name, params, sort)?;
+            }
+            Some(("declare-const", [name, sort])) => self.declare(name, Vec::new(), sort)?,
+            Some(("assert", _)) => self.problem.asserts += 1,
+            _ => (),
+        }
+        self.visit(sexp)
+    }
+
+    fn declare(&mut self, name: &SExp<'a>, params: Vec<String>, sort: &SExp<'a>) -> Result<()> {
+        let name = name.as_atom().ok_or_else(|| name.unexpected())?;
+        let decl = FunctionDecl {
+            params,
+            sort: sort_name(sort),
+        };
+        self.problem.functions.try_reserve(1)?;
+        self.problem
+            .functions
+            .insert(symbol(name).to_string(), decl);
+        Ok(())
+    }
+
+    /// Walks all s-expressions in pre-order (the order of `spans`),
+    /// recording the quantifiers.
+    fn visit(&mut self, sexp: &SExp<'a>) -> Result<()> {
+        let Some(list) = sexp.as_list() else {
+            return Ok(());
+        };
+        let span = self.spans[self.next_span].clone();
+        self.next_span += 1;
+        match sexp.as_app() {
+            Some(("forall", [vars, body])) => self.quantifier(Binder::Forall, vars, body, span)?,
+            Some(("exists", [vars, body])) => self.quantifier(Binder::Exists, vars, body, span)?,
+            _ => (),
+        }
+        for child in list {
+            self.visit(child)?;
+        }
+        Ok(())
+    }
+
+    /// `(forall ((<var> <sort>) ...) (! <body> :qid <name> :pattern (<terms>) ...))`
+    fn quantifier(
+        &mut self,
+        binder: Binder,
+        vars: &SExp<'a>,
+        mut body: &SExp<'a>,
+        span: Range<usize>,
+    ) -> Result<()> {
+        let vars = vars.as_list().ok_or_else(|| vars.unexpected())?;
+        let vars = vars
+            .iter()
+            .map(|var| match var.as_list() {
+                Some([SExp::Atom(name), sort]) => Ok((symbol(name).to_string(), sort_name(sort))),
+                _ => Err(var.unexpected()),
+            })
+            .collect::<Result<_>>()?;
+        // Annotations may be nested, e.g. `(! (! <body> :qid q) :weight 2)`.
+        let mut attrs = Vec::new();
+        while let Some(("!", [inner, rest @ ..])) = body.as_app() {
+            attrs.try_reserve(rest.len())?;
+            attrs.extend(rest.iter().cloned());
+            body = inner;
+        }
+        let atom = |key| {
+            SExp::attribute(&attrs, key)
+                .next()
+                .and_then(SExp::as_atom)
+                .map(|value| symbol(value).to_string())
+        };
+        let weight = SExp::attribute(&attrs, ":weight")
+            .next()
+            .map(|weight| {
+                weight
+                    .as_atom()
+                    .and_then(|w| w.parse().ok())
+                    .ok_or_else(|| weight.unexpected())
+            })
+            .transpose()?;
+        let patterns = SExp::attribute(&attrs, ":pattern")
+            .map(|pattern| match pattern.as_list() {
+                Some(terms) => Ok(terms.iter().map(SExp::to_string).collect()),
+                None => Err(pattern.unexpected()),
+            })
+            .collect::<Result<_>>()?;
+        let no_patterns = SExp::attribute(&attrs, ":no-pattern")
+            .map(SExp::to_string)
+            .collect();
+        let quant = SourceQuantifier {
+            binder,
+            qid: atom(":qid"),
+            skolemid: atom(":skolemid"),
+            weight,
+            vars,
+            patterns,
+            no_patterns,
+            span: SourceSpan {
+                start: self.pos(span.start),
+                end: self.pos(span.end),
+                bytes: span.clone(),
+            },
+            text: self.input[span].to_string(),
+        };
+        self.problem.quantifiers.try_reserve(1)?;
+        self.problem.quantifiers.push(quant);
+        Ok(())
+    }
+
+    fn pos(&self, offset: usize) -> SourcePos {
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        SourcePos {
+            line,
+            column: offset - self.line_starts[line - 1] + 1,
+        }
+    }
+}
+
+fn sort_name(sort: &SExp) -> String {
+    match sort {
+        SExp::Atom(atom) => symbol(atom).to_string(),
+        SExp::List(_) => sort.to_string(),
+    }
+}
@@ -0,0 +1,83 @@
+#[cfg(feature = "mem_dbg")]
+use mem_dbg::{MemDbg, MemSize};
+
+use crate::{
+    items::{
+        AssignIdx, Assignment, Conflict, ConflictIdx, Decision, DecisionIdx, InstIdx, ResolveStep,
+        TermIdx,
+    },
+    FxHashMap, Result, TiVec,
+};
+
+#[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Default)]
+pub struct Cdcl {
+    pub(crate) decisions: TiVec<DecisionIdx, Decision>,
+    pub(crate) assignments: TiVec<AssignIdx, Assignment>,
+    pub(crate) conflicts: TiVec<ConflictIdx, Conflict>,
+    /// The `[resolve-*]` lines seen since the last `[conflict]`.
+    resolution: Vec<ResolveStep>,
+    /// The instantiation during which each term was most recently assigned.
+    assigned_by: FxHashMap<TermIdx, InstIdx>,
+}
+
+impl Cdcl {
+    pub fn new_decision(&mut self, decision: Decision) -> Result<DecisionIdx> {
+        self.decisions.raw.try_reserve(1)?;
+        Ok(self.decisions.push_and_get_key(decision))
+    }
+
+    pub fn new_assignment(&mut self, assignment: Assignment) -> Result<AssignIdx> {
+        if let (Some(term), Some(iidx)) = (assignment.literal.term, assignment.created_by) {
+            self.assigned_by.try_reserve(1)?;
+            self.assigned_by.insert(term, iidx);
+        }
+        self.assignments.raw.try_reserve(1)?;
+        Ok(self.assignments.push_and_get_key(assignment))
+    }
+
+    pub fn new_resolve_step(&mut self, step: ResolveStep) -> Result<()> {
+        self.resolution.try_reserve(1)?;
+        self.resolution.push(step);
+        Ok(())
+    }
+
+    /// The instantiation during which the term was most recently assigned.
+    pub fn assigned_by(&self, term: TermIdx) -> Option<InstIdx> {
+        self.assigned_by.get(&term).copied()
+    }
+
+    /// The resolution steps seen since the previous conflict.
+    pub fn resolution(&self) -> &[ResolveStep] {
+        &self.resolution
+    }
+
+    /// Creates a new conflict, taking ownership of all resolution steps seen
+    /// since the previous conflict.
+    pub fn new_conflict(&mut self, mut conflict: Conflict) -> Result<ConflictIdx> {
+        conflict.resolution = std::mem::take(&mut self.resolution).into_boxed_slice();
+        conflict.last_decision = self.decisions.last_key();
+        self.conflicts.raw.try_reserve(1)?;
+        Ok(self.conflicts.push_and_get_key(conflict))
+    }
+}
+
+impl std::ops::Index<DecisionIdx> for Cdcl {
+    type Output = Decision;
+    fn index(&self, idx: DecisionIdx) -> &Self::Output {
+        &self.decisions[idx]
+    }
+}
+impl std::ops::Index<AssignIdx> for Cdcl {
+    type Output = Assignment;
+    fn index(&self, idx: AssignIdx) -> &Self::Output {
+        &self.assignments[idx]
+    }
+}
+impl std::ops::Index<ConflictIdx> for Cdcl {
+    type Output = Conflict;
+    fn index(&self, idx: ConflictIdx) -> &Self::Output {
+        &self.conflicts[idx]
+    }
+}
@@ -19,6 +19,7 @@ use crate::{
 use super::stack::Stack;
 
 #[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Default)]
 pub struct EGraph {
     term_to_enode: FxHashMap<TermIdx, ENodeIdx>,
@@ -69,13 +70,20 @@ impl EGraph {
             .ok_or_else(|| Error::UnknownEnode(term))?;
         let frame = self.enodes[enode].frame;
         // This cannot be an enode if it points to a popped stack frame
-        if frame.is_some_and(|f| !stack.stack_frames[f].active) {
-            Err(Error::EnodePoppedFrame(frame.unwrap()))
-        } else {
+        if stack.is_live(frame) {
             Ok(enode)
+        } else {
+            Err(Error::EnodePoppedFrame(frame.unwrap()))
         }
     }
 
+    /// The instantiation which created the most recent enode of the term,
+    /// even if its frame has since been popped.
+    pub fn created_by(&self, term: TermIdx) -> Option<InstIdx> {
+        let enode = *self.term_to_enode.get(&term)?;
+        self.enodes[enode].created_by
+    }
+
     pub fn get_owner(&self, enode: ENodeIdx) -> TermIdx {
         self.enodes[enode].owner
     }
@@ -91,7 +99,7 @@ impl EGraph {
         let expl = self.equalities.given.push_and_get_key(expl);
         let enode = &mut self.enodes[from];
         let eq = Equality {
-            _frame: stack.active_frame(),
+            frame: stack.active_frame(),
             to,
             expl,
         };
@@ -373,6 +381,7 @@ impl std::ops::Index<ENodeIdx> for EGraph {
 }
 
 #[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug)]
 pub struct ENode {
     frame: Option<StackIdx>,
@@ -389,22 +398,37 @@ pub struct ENode {
 }
 
 impl ENode {
-    pub fn get_equality(&self, _stack: &Stack) -> Option<&Equality> {
-        // TODO: why are we allowed to use equalities from popped stack frames?
-        // self.equalities.iter().rev().find(|eq| eq.frame.map(|f| stack.stack_frames[f].active).unwrap_or(true))
-        self.equalities.last()
+    /// The stack frame which was active when the enode was created.
+    pub fn frame(&self) -> Option<StackIdx> {
+        self.frame
+    }
+
+    /// All equalities from this enode, in the order they were added.
+    pub(crate) fn equalities(&self) -> &[Equality] {
+        &self.equalities
+    }
+
+    /// The most recent equality which has not been undone by a pop.
+    pub fn get_equality(&self, stack: &Stack) -> Option<&Equality> {
+        self.equalities
+            .iter()
+            .rev()
+            .find(|eq| stack.is_live(eq.frame))
     }
 }
 
 #[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug)]
 pub struct Equality {
-    _frame: Option<StackIdx>,
+    /// The stack frame which was active when the equality was added.
+    pub frame: Option<StackIdx>,
     pub to: ENodeIdx,
     pub expl: EqGivenIdx,
 }
 
 #[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Default)]
 pub struct Equalities {
     pub(crate) given: TiVec<EqGivenIdx, EqualityExpl>,
@@ -7,6 +7,7 @@ use crate::{
 };
 
 #[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Default)]
 pub struct Insts {
     // `theory-solving` fingerprints are always 0, others rarely repeat.
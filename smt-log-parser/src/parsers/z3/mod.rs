@@ -1,12 +1,16 @@
 use std::fmt::Debug;
 
-use super::LogParser;
+use super::{diagnostics::Diagnostics, LogParser};
 use crate::{Error, FResult, Result};
+use provenance::LinePos;
 
+pub mod cdcl;
 pub mod egraph;
 pub mod inst;
+pub mod provenance;
 pub mod stack;
 pub mod terms;
+pub mod writer;
 /// Original Z3 log parser. Works with Z3 v.4.12.1, should work with other versions
 /// as long as the log format is the same for the important line cases.
 /// Compare with the log files in the `logs/` folder to see if this is the case.
@@ -17,7 +21,15 @@ impl<T: Z3LogParser + Default> LogParser for T {
         first_byte == b'['
     }
 
-    fn process_line(&mut self, line: &str, line_no: usize) -> FResult<bool> {
+    fn set_strict(&mut self, strict: bool) {
+        self.diagnostics_mut().strict = strict;
+    }
+
+    fn set_provenance(&mut self, enabled: bool) {
+        Z3LogParser::set_provenance(self, enabled);
+    }
+
+    fn process_line(&mut self, line: &str, line_no: usize, byte_offset: usize) -> FResult<bool> {
         // Much faster than `split_whitespace` or `split(' ')` since it works on
         // [u8] instead of [char] and so doesn't need to convert to UTF-8.
         let mut split = line.split_ascii_whitespace();
@@ -44,27 +56,25 @@ impl<T: Z3LogParser + Default> LogParser for T {
             "[assign]" => self.assign(split),
             "[push]" => self.push(split),
             "[pop]" => self.pop(split),
-            "[begin-check]" => self.begin_check(split),
-            "[query-done]" => self.query_done(split),
+            "[begin-check]" => self.begin_check(split, line_no),
+            "[query-done]" => self.query_done(split, line_no),
             "[eof]" => return Ok(false),
             "[resolve-process]" => self.resolve_process(split),
             "[resolve-lit]" => self.resolve_lit(split),
             "[conflict]" => self.conflict(split),
             _ => Err(Error::UnknownLine(first.to_owned())),
         };
+        let pos = LinePos {
+            line_no,
+            byte_offset,
+        };
+        let parse = parse.and(self.line_done(pos));
         match parse {
             Ok(()) => Ok(true),
-            Err(err) => {
-                eprintln!("Error parsing line {line_no} ({err:?}): {line:?}");
-                let fatal = err.as_fatal();
-                if std::env::var("SLP_TEST_MODE").is_ok() {
-                    panic!();
-                }
-                match fatal {
-                    Some(err) => Err(err),
-                    None => Ok(true),
-                }
-            }
+            Err(err) => self
+                .diagnostics_mut()
+                .report(err, line, line_no, byte_offset)
+                .map(|()| true),
         }
     }
 
@@ -94,6 +104,14 @@ pub trait Z3LogParser {
     fn push<'a>(&mut self, _l: impl Iterator<Item = &'a str>) -> Result<()>;
     fn pop<'a>(&mut self, _l: impl Iterator<Item = &'a str>) -> Result<()>;
     fn eof(&mut self);
+    fn diagnostics_mut(&mut self) -> &mut Diagnostics;
+
+    /// Record the line on which each item was created from now on.
+    fn set_provenance(&mut self, _enabled: bool) {}
+    /// Called after each line, with the position of the line.
+    fn line_done(&mut self, _pos: LinePos) -> Result<()> {
+        DEFAULT
+    }
 
     // unused in original parser
     fn decide_and_or<'a>(&mut self, _l: impl Iterator<Item = &'a str>) -> Result<()> {
@@ -105,10 +123,14 @@ pub trait Z3LogParser {
     fn assign<'a>(&mut self, _l: impl Iterator<Item = &'a str>) -> Result<()> {
         DEFAULT
     }
-    fn begin_check<'a>(&mut self, _l: impl Iterator<Item = &'a str>) -> Result<()> {
+    fn begin_check<'a>(
+        &mut self,
+        _l: impl Iterator<Item = &'a str>,
+        _line_no: usize,
+    ) -> Result<()> {
         DEFAULT
     }
-    fn query_done<'a>(&mut self, _l: impl Iterator<Item = &'a str>) -> Result<()> {
+    fn query_done<'a>(&mut self, _l: impl Iterator<Item = &'a str>, _line_no: usize) -> Result<()> {
         DEFAULT
     }
     fn resolve_process<'a>(&mut self, _l: impl Iterator<Item = &'a str>) -> Result<()> {
@@ -123,6 +145,7 @@ pub trait Z3LogParser {
 }
 
 /// Type of solver and version number
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, PartialEq, Default)]
 pub enum VersionInfo {
     #[default]
@@ -0,0 +1,76 @@
+#[cfg(feature = "mem_dbg")]
+use mem_dbg::{MemDbg, MemSize};
+
+use crate::{
+    items::{ENodeIdx, EqGivenIdx, InstIdx, MatchIdx, TermIdx},
+    Result, TiVec,
+};
+
+/// The position of a line in the (decompressed) log.
+#[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LinePos {
+    /// The (1-indexed) line number.
+    pub line_no: usize,
+    /// The offset of the first byte of the line.
+    pub byte_offset: usize,
+}
+
+/// The line on which each item was created. Only recorded if enabled with
+/// [`LogParser::set_provenance`](crate::LogParser::set_provenance), since it
+/// adds 16 bytes per item.
+#[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Default)]
+pub struct Provenance {
+    pub(crate) terms: TiVec<TermIdx, LinePos>,
+    pub(crate) matches: TiVec<MatchIdx, LinePos>,
+    pub(crate) insts: TiVec<InstIdx, LinePos>,
+    pub(crate) enodes: TiVec<ENodeIdx, LinePos>,
+    pub(crate) eq_given: TiVec<EqGivenIdx, LinePos>,
+}
+
+/// The number of items of each kind, all items beyond these were created by
+/// the current line.
+pub(crate) struct ItemLens {
+    pub terms: usize,
+    pub matches: usize,
+    pub insts: usize,
+    pub enodes: usize,
+    pub eq_given: usize,
+}
+
+impl Provenance {
+    /// Attributes all items created since the previous line to `pos`.
+    pub(crate) fn line_done(&mut self, lens: ItemLens, pos: LinePos) -> Result<()> {
+        fn extend<K>(positions: &mut TiVec<K, LinePos>, len: usize, pos: LinePos) -> Result<()> {
+            if let Some(additional) = len.checked_sub(positions.len()) {
+                positions.raw.try_reserve(additional)?;
+                positions.raw.resize(len, pos);
+            }
+            Ok(())
+        }
+        extend(&mut self.terms, lens.terms, pos)?;
+        extend(&mut self.matches, lens.matches, pos)?;
+        extend(&mut self.insts, lens.insts, pos)?;
+        extend(&mut self.enodes, lens.enodes, pos)?;
+        extend(&mut self.eq_given, lens.eq_given, pos)
+    }
+
+    pub fn term(&self, idx: TermIdx) -> Option<LinePos> {
+        self.terms.get(idx).copied()
+    }
+    pub fn match_(&self, idx: MatchIdx) -> Option<LinePos> {
+        self.matches.get(idx).copied()
+    }
+    pub fn inst(&self, idx: InstIdx) -> Option<LinePos> {
+        self.insts.get(idx).copied()
+    }
+    pub fn enode(&self, idx: ENodeIdx) -> Option<LinePos> {
+        self.enodes.get(idx).copied()
+    }
+    pub fn eq_given(&self, idx: EqGivenIdx) -> Option<LinePos> {
+        self.eq_given.get(idx).copied()
+    }
+}
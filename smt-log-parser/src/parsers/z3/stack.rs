@@ -4,6 +4,7 @@ use mem_dbg::{MemDbg, MemSize};
 use crate::{items::StackIdx, Error, Result, TiVec};
 
 #[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Default)]
 pub struct Stack {
     pub(super) stack: Vec<StackIdx>,
@@ -13,14 +14,18 @@ pub struct Stack {
 impl Stack {
     fn add_frame(&mut self) -> Result<()> {
         self.stack_frames.raw.try_reserve(1)?;
-        let idx = self.stack_frames.push_and_get_key(StackFrame::new());
+        let idx = self
+            .stack_frames
+            .push_and_get_key(StackFrame::new(self.stack.len()));
         self.stack.try_reserve(1)?;
         self.stack.push(idx);
         Ok(())
     }
-    fn remove_frame(&mut self, active: bool) -> Option<StackIdx> {
+    fn remove_frame(&mut self, implicit: bool) -> Option<StackIdx> {
         let idx = self.stack.pop()?;
-        self.stack_frames[idx].active = active;
+        let frame = &mut self.stack_frames[idx];
+        frame.active = false;
+        frame.implicit_pop = implicit;
         Some(idx)
     }
     fn ensure_height(&mut self, height: usize) -> Result<()> {
@@ -34,9 +39,8 @@ impl Stack {
         while height < self.stack.len() {
             // This can happen when pushing a new frame in e.g. z3 v4.8.17 and
             // v4.11.2.
-            // It seems that there is a bug where the pop doesn't get emitted
-            // and so we need to conservatively leak the frame and treat it as
-            // always active.
+            // It seems that there is a bug where the pop doesn't get emitted,
+            // the frame is popped all the same.
             self.remove_frame(true);
         }
         res
@@ -60,22 +64,34 @@ impl Stack {
     pub(super) fn active_frame(&self) -> Option<StackIdx> {
         self.stack.last().copied()
     }
+
+    /// Is the item created in `frame` still live, i.e. has the frame not been
+    /// popped? Items created outside of any frame are always live.
+    pub(crate) fn is_live(&self, frame: Option<StackIdx>) -> bool {
+        frame.map_or(true, |f| self.stack_frames[f].active)
+    }
 }
 
 #[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, Copy)]
 pub struct StackFrame {
+    /// Set to `false` once the frame is popped, all items created within it
+    /// are then discarded by z3. Also if the pop was not logged.
     pub active: bool,
-}
-
-impl Default for StackFrame {
-    fn default() -> Self {
-        Self::new()
-    }
+    /// The number of frames below this one on the stack.
+    pub depth: usize,
+    /// The frame was popped without a `[pop]` being logged, we only noticed
+    /// once a frame was pushed at a lower depth.
+    pub implicit_pop: bool,
 }
 
 impl StackFrame {
-    pub fn new() -> Self {
-        Self { active: true }
+    pub fn new(depth: usize) -> Self {
+        Self {
+            active: true,
+            depth,
+            implicit_pop: false,
+        }
     }
 }
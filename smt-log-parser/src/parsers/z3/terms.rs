@@ -1,21 +1,29 @@
 #[cfg(feature = "mem_dbg")]
 use mem_dbg::{MemDbg, MemSize};
+use num_bigint::BigInt;
 
 use crate::{
     error::Either,
-    items::{Meaning, QuantIdx, Term, TermAndMeaning, TermId, TermIdToIdxMap, TermIdx, TermKind},
+    items::{
+        Meaning, QuantIdx, Term, TermAndMeaning, TermId, TermIdToIdxMap, TermIdx, TermKind, Value,
+    },
     Error, FxHashMap, Result, StringTable, TiVec,
 };
 
 #[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug)]
 pub struct Terms {
     term_id_map: TermIdToIdxMap,
     terms: TiVec<TermIdx, Term>,
     meanings: FxHashMap<TermIdx, Meaning>,
+    /// The decoded value of each meaning which could be decoded.
+    values: FxHashMap<Meaning, Value>,
     parsed_terms: Option<TermIdx>,
 
-    synthetic_terms: FxHashMap<TermAndMeaning<'static>, TermIdx>,
+    /// The synthetic terms by the hash of the term and its meaning, such that
+    /// each is only created once (also after loading a snapshot).
+    synthetic_terms: FxHashMap<u64, Vec<TermIdx>>,
 }
 
 impl Terms {
@@ -24,13 +32,18 @@ impl Terms {
             term_id_map: TermIdToIdxMap::new(strings),
             terms: TiVec::default(),
             meanings: FxHashMap::default(),
+            values: FxHashMap::default(),
             parsed_terms: None,
 
             synthetic_terms: FxHashMap::default(),
         }
     }
 
-    pub(super) fn new_term(&mut self, term: Term) -> Result<TermIdx> {
+    pub(crate) fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    pub(crate) fn new_term(&mut self, term: Term) -> Result<TermIdx> {
         self.terms.raw.try_reserve(1)?;
         let id = term.id;
         let idx = self.terms.push_and_get_key(term);
@@ -61,6 +74,28 @@ impl Terms {
     pub fn meaning(&self, tidx: TermIdx) -> Option<&Meaning> {
         self.meanings.get(&tidx)
     }
+    /// The decoded value of the meaning of the term, if it has one.
+    pub fn value(&self, tidx: TermIdx) -> Option<&Value> {
+        self.values.get(self.meanings.get(&tidx)?)
+    }
+    /// The first value, the step and the number of integers of a
+    /// [`Generalisation::Progression`](crate::items::Generalisation::Progression)
+    /// with the given children.
+    pub fn progression(&self, children: &[TermIdx]) -> Option<(BigInt, BigInt, BigInt)> {
+        let values = children
+            .iter()
+            .map(|&t| match self.value(t)? {
+                Value::Int(value) => Some(&**value),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let [first, second, last] = values[..] else {
+            return None;
+        };
+        let step = second - first;
+        let count = (last - first) / &step + 1;
+        Some((first.clone(), step, count))
+    }
     pub(super) fn quant(&self, quant: TermIdx) -> Result<QuantIdx> {
         self[quant]
             .kind
@@ -68,7 +103,16 @@ impl Terms {
             .ok_or_else(|| Error::UnknownQuantifierIdx(quant))
     }
 
-    pub(super) fn new_meaning(&mut self, term: TermIdx, meaning: Meaning) -> Result<()> {
+    pub(super) fn new_meaning(
+        &mut self,
+        term: TermIdx,
+        meaning: Meaning,
+        value: Option<Value>,
+    ) -> Result<()> {
+        if let Some(value) = value {
+            self.values.try_reserve(1)?;
+            self.values.entry(meaning).or_insert(value);
+        }
         self.meanings.try_reserve(1)?;
         use std::collections::hash_map::Entry;
         match self.meanings.entry(term) {
@@ -102,28 +146,19 @@ impl Terms {
             kind,
             child_ids,
         };
-        let term_and_meaning = TermAndMeaning {
-            term: &term,
-            meaning: meaning.as_ref(),
-        };
-        if let Some(&tidx) = self.synthetic_terms.get(&term_and_meaning) {
-            tidx
-        } else {
-            let tidx = self.terms.push_and_get_key(term);
-            if let Some(meaning) = meaning {
-                self.meanings.insert(tidx, meaning);
-            }
-            let term = self.get_term(tidx);
-            // Safety: this will only ever be stored in the keys of the
-            // `synthetic_terms` map and the API ensures that these keys never
-            // leak out. The keys of the map are dropped at the same time that
-            // the lifetime expires. The existing `Term` and `Meaning` values
-            // are never mutated.
-            let term =
-                unsafe { std::mem::transmute::<TermAndMeaning, TermAndMeaning<'static>>(term) };
-            self.synthetic_terms.insert(term, tidx);
-            tidx
+        let hash = fxhash::hash64(&(&term, &meaning));
+        let mut existing = self.synthetic_terms.get(&hash).into_iter().flatten();
+        if let Some(&tidx) = existing
+            .find(|&&tidx| self.terms[tidx] == term && self.meanings.get(&tidx) == meaning.as_ref())
+        {
+            return tidx;
+        }
+        let tidx = self.terms.push_and_get_key(term);
+        if let Some(meaning) = meaning {
+            self.meanings.insert(tidx, meaning);
         }
+        self.synthetic_terms.entry(hash).or_default().push(tidx);
+        tidx
     }
 }
 
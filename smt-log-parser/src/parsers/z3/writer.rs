@@ -0,0 +1,981 @@
+use std::io::{self, Write};
+
+use crate::{
+    error::Either,
+    items::*,
+    parsers::z3::{egraph::Equalities, VersionInfo},
+    FxHashMap, TiVec, Z3Parser,
+};
+
+/// Serializes a parsed log back to the z3 trace format, optionally restricted
+/// to a subset of the instantiations (and everything they need). Parsing the
+/// written log gives the same terms, e-nodes, equalities, matches,
+/// instantiations and stack frames (up to the indices of items which were
+/// left out). The CDCL search, queries and instantiations reported by
+/// solvers other than z3 are not written.
+///
+/// The parser does not record the relative order of items of different
+/// kinds, so they are written in an order consistent with their
+/// dependencies and stack frames.
+pub struct TraceWriter<'a> {
+    parser: &'a Z3Parser,
+    /// `None` to write everything.
+    insts: Option<TiVec<InstIdx, bool>>,
+}
+
+impl<'a> TraceWriter<'a> {
+    pub fn new(parser: &'a Z3Parser) -> Self {
+        Self {
+            parser,
+            insts: None,
+        }
+    }
+
+    /// Only write the given instantiations and the terms, e-nodes,
+    /// equalities and stack frames they need. Can be called multiple times
+    /// to add more instantiations.
+    pub fn only_insts(mut self, insts: impl IntoIterator<Item = InstIdx>) -> Self {
+        let len = self.parser.insts.insts.len();
+        let selected = self.insts.get_or_insert_with(|| filled(false, len));
+        for inst in insts {
+            selected[inst] = true;
+        }
+        self
+    }
+
+    /// Only write the instantiations of the given query.
+    pub fn only_query(self, query: QueryIdx) -> Self {
+        let insts = self.parser.query_items(query).insts();
+        self.only_insts(insts)
+    }
+
+    /// Also write all instantiations which the selected ones (transitively)
+    /// depend on, i.e. those which created the blamed e-nodes and
+    /// equalities.
+    pub fn with_ancestors(mut self) -> Self {
+        let parser = self.parser;
+        let Some(selected) = &mut self.insts else {
+            return self;
+        };
+        let mut todo: Vec<_> = selected
+            .iter_enumerated()
+            .filter_map(|(idx, &s)| s.then_some(idx))
+            .collect();
+        while let Some(inst) = todo.pop() {
+            let mut parents = Vec::new();
+            for blame in parser[parser[inst].match_].blamed.iter() {
+                match blame {
+                    BlameKind::Term { term } => parents.push(parser[*term].created_by),
+                    BlameKind::Equality { eq } => {
+                        parents.extend(parser[*eq].get_creator_insts(parser))
+                    }
+                }
+            }
+            for parent in parents.into_iter().flatten() {
+                if !selected[parent] {
+                    selected[parent] = true;
+                    todo.push(parent);
+                }
+            }
+        }
+        self
+    }
+
+    pub fn write(&self, writer: impl Write) -> io::Result<()> {
+        let selection = match &self.insts {
+            None => Selection::all(self.parser),
+            Some(insts) => Selection::closure(self.parser, insts),
+        };
+        Emitter::new(self.parser, selection, writer).run()
+    }
+
+    /// Writes the log to a string.
+    pub fn write_string(&self) -> io::Result<String> {
+        let mut out = Vec::new();
+        self.write(&mut out)?;
+        String::from_utf8(out).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+fn filled<K, V: Clone>(value: V, len: usize) -> TiVec<K, V> {
+    std::iter::repeat(value).take(len).collect()
+}
+
+/// The items which will be written.
+struct Selection {
+    terms: TiVec<TermIdx, bool>,
+    enodes: TiVec<ENodeIdx, bool>,
+    eqs: TiVec<EqGivenIdx, bool>,
+    matches: TiVec<MatchIdx, bool>,
+    insts: TiVec<InstIdx, bool>,
+    frames: TiVec<StackIdx, bool>,
+}
+
+/// Whether a match can be written in the z3 format at all.
+fn writable(kind: &MatchKind) -> bool {
+    !matches!(kind, MatchKind::Reported { .. })
+}
+
+impl Selection {
+    fn all(parser: &Z3Parser) -> Self {
+        let matches: TiVec<MatchIdx, bool> = parser
+            .insts
+            .matches
+            .iter()
+            .map(|m| writable(&m.kind))
+            .collect();
+        let insts = parser
+            .insts
+            .insts
+            .iter()
+            .map(|i| matches[i.match_])
+            .collect();
+        Self {
+            terms: (0..parser.terms.len())
+                .map(|t| parser[TermIdx::from(t)].id.is_some())
+                .collect(),
+            enodes: filled(true, parser.egraph.enodes.len()),
+            eqs: filled(true, parser.egraph.equalities.given.len()),
+            matches,
+            insts,
+            frames: filled(true, parser.stack.stack_frames.len()),
+        }
+    }
+
+    fn closure(parser: &Z3Parser, insts: &TiVec<InstIdx, bool>) -> Self {
+        let mut this = Self {
+            terms: filled(false, parser.terms.len()),
+            enodes: filled(false, parser.egraph.enodes.len()),
+            eqs: filled(false, parser.egraph.equalities.given.len()),
+            matches: filled(false, parser.insts.matches.len()),
+            insts: filled(false, parser.insts.insts.len()),
+            frames: filled(false, parser.stack.stack_frames.len()),
+        };
+        let mut closure = Closure::default();
+        for (idx, inst) in parser.insts.insts.iter_enumerated() {
+            if !insts[idx] || !writable(&parser[inst.match_].kind) {
+                continue;
+            }
+            this.insts[idx] = true;
+            closure.frame(inst.frame);
+            closure.match_(parser, inst.match_);
+            if let Some(Either::Left(proof)) = inst.proof_id {
+                closure.terms.push(proof);
+            }
+            closure.enodes.extend(inst.yields_terms.iter().copied());
+        }
+        closure.run(parser, &mut this);
+        this
+    }
+}
+
+/// Worklists of items whose dependencies still need to be selected.
+#[derive(Default)]
+struct Closure {
+    terms: Vec<TermIdx>,
+    enodes: Vec<ENodeIdx>,
+    eqs: Vec<EqGivenIdx>,
+    trans: Vec<EqTransIdx>,
+    matches: Vec<MatchIdx>,
+    frames: Vec<StackIdx>,
+}
+
+impl Closure {
+    fn frame(&mut self, frame: Option<StackIdx>) {
+        self.frames.extend(frame);
+    }
+    fn match_(&mut self, parser: &Z3Parser, m: MatchIdx) {
+        self.matches.push(m);
+        let match_ = &parser[m];
+        let kind = &match_.kind;
+        if let Some(quant) = kind.quant_idx() {
+            self.terms.extend(parser[quant].term);
+        }
+        self.terms.extend(kind.pattern());
+        self.terms.extend(kind.rewrite_of());
+        match kind {
+            MatchKind::MBQI { bound_terms, .. } | MatchKind::Quantifier { bound_terms, .. } => {
+                self.enodes.extend(bound_terms.iter().copied())
+            }
+            MatchKind::TheorySolving { bound_terms, .. }
+            | MatchKind::Axiom { bound_terms, .. }
+            | MatchKind::Reported { bound_terms, .. } => {
+                self.terms.extend(bound_terms.iter().copied())
+            }
+        }
+        for blame in match_.blamed.iter() {
+            match blame {
+                BlameKind::Term { term } => self.enodes.push(*term),
+                BlameKind::Equality { eq } => self.trans.push(*eq),
+            }
+        }
+    }
+
+    fn run(mut self, parser: &Z3Parser, sel: &mut Selection) {
+        let mut trans_done = vec![false; parser.egraph.equalities.transitive.len()];
+        let frames = Frames::new(parser);
+        loop {
+            if let Some(m) = self.matches.pop() {
+                sel.matches[m] = true;
+            } else if let Some(trans) = self.trans.pop() {
+                if std::mem::replace(&mut trans_done[usize::from(trans)], true) {
+                    continue;
+                }
+                let eqs = &parser.egraph.equalities;
+                self.enodes.push(eqs.from(trans));
+                self.enodes.push(eqs.transitive[trans].to);
+                for seg in eqs.transitive[trans].path.iter() {
+                    match seg.kind {
+                        TransitiveExplSegmentKind::Given(eq, use_) => {
+                            self.eqs.push(eq);
+                            if let (EqualityExpl::Congruence { uses, .. }, Some(use_)) =
+                                (&eqs.given[eq], use_)
+                            {
+                                self.trans.extend(uses[use_.get() as usize].iter().copied());
+                            }
+                        }
+                        TransitiveExplSegmentKind::Transitive(trans) => self.trans.push(trans),
+                    }
+                }
+            } else if let Some(eq) = self.eqs.pop() {
+                if std::mem::replace(&mut sel.eqs[eq], true) {
+                    continue;
+                }
+                let expl = &parser[eq];
+                self.enodes.push(expl.from());
+                self.enodes.push(expl.to());
+                match expl {
+                    EqualityExpl::Literal { eq, .. } => self.enodes.push(*eq),
+                    EqualityExpl::Congruence { arg_eqs, .. } => {
+                        for (from, to) in arg_eqs.iter() {
+                            self.enodes.extend([*from, *to]);
+                        }
+                    }
+                    _ => (),
+                }
+                self.frames.extend(equality_frame(parser, eq));
+            } else if let Some(enode) = self.enodes.pop() {
+                if std::mem::replace(&mut sel.enodes[enode], true) {
+                    continue;
+                }
+                self.terms.push(parser[enode].owner);
+                self.frame(parser[enode].frame());
+            } else if let Some(term) = self.terms.pop() {
+                if std::mem::replace(&mut sel.terms[term], true) {
+                    continue;
+                }
+                let term = &parser[term];
+                self.terms.extend(term.child_ids.iter().copied());
+                if let TermKind::Quant(quant) = term.kind {
+                    self.terms.extend(parser[quant].term);
+                }
+            } else if let Some(frame) = self.frames.pop() {
+                if std::mem::replace(&mut sel.frames[frame], true) {
+                    continue;
+                }
+                self.frame(frames.parent[frame]);
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+/// The stack frame in which a given equality was added.
+fn equality_frame(parser: &Z3Parser, eq: EqGivenIdx) -> Option<StackIdx> {
+    let from = parser[eq].from();
+    parser[from]
+        .equalities()
+        .iter()
+        .find(|e| e.expl == eq)
+        .and_then(|e| e.frame)
+}
+
+/// The tree of stack frames.
+struct Frames {
+    parent: TiVec<StackIdx, Option<StackIdx>>,
+}
+
+impl Frames {
+    fn new(parser: &Z3Parser) -> Self {
+        let mut stack: Vec<StackIdx> = Vec::new();
+        let mut parent = TiVec::default();
+        for (idx, frame) in parser.stack.stack_frames.iter_enumerated() {
+            stack.truncate(frame.depth);
+            parent.push(stack.last().copied());
+            stack.push(idx);
+        }
+        Self { parent }
+    }
+}
+
+/// An item which is written on its own line (or block of lines).
+#[derive(Debug, Clone, Copy)]
+enum Item {
+    ENode(ENodeIdx),
+    Eq(EqGivenIdx),
+    Match(MatchIdx),
+    Inst(InstIdx),
+}
+
+/// What an item refers to, computed once when the emitter is created.
+struct Refs {
+    /// The frame in which the item must be created, if it has one.
+    own: Option<Option<StackIdx>>,
+    enodes: Box<[ENodeIdx]>,
+    eqs: Box<[EqGivenIdx]>,
+    /// The frames of the item, i.e. its own frame and those of the items it
+    /// references. A frame cannot be popped until all of these are written.
+    frames: Box<[StackIdx]>,
+}
+
+/// Writes the selected items, interleaving them such that each item's
+/// dependencies are written before it and it is created in the same stack
+/// frame as in the original log.
+struct Emitter<'a, W> {
+    parser: &'a Z3Parser,
+    sel: Selection,
+    out: W,
+    frames: Frames,
+
+    next_term: usize,
+    /// The selected items of each kind, in order, and the position of the
+    /// first one which was not yet written.
+    queues: [(Vec<Item>, usize); 4],
+    enode_done: TiVec<ENodeIdx, bool>,
+    eq_done: TiVec<EqGivenIdx, bool>,
+    match_done: TiVec<MatchIdx, bool>,
+
+    /// The references of each item not yet written.
+    refs: FxHashMap<(u8, usize), Refs>,
+    /// The number of items not yet written in each frame.
+    pending: TiVec<StackIdx, usize>,
+    /// The number of items referencing each e-node which were not yet
+    /// written, a newer e-node of the same term cannot be created before.
+    enode_refs: TiVec<ENodeIdx, usize>,
+    prev_enode: TiVec<ENodeIdx, Option<ENodeIdx>>,
+    /// The number of selected instantiations of each match not yet written.
+    match_insts: TiVec<MatchIdx, usize>,
+    /// The last written match of each fingerprint.
+    fingerprints: FxHashMap<Fingerprint, MatchIdx>,
+    /// All instantiations of a match share its fingerprint, matches without
+    /// instantiations are written with a fingerprint of their own.
+    match_fingerprints: TiVec<MatchIdx, Fingerprint>,
+
+    stack: Vec<StackIdx>,
+    next_frame: usize,
+}
+
+impl<'a, W: Write> Emitter<'a, W> {
+    fn new(parser: &'a Z3Parser, sel: Selection, out: W) -> Self {
+        let frames = Frames::new(parser);
+        let enodes = sel.enodes.iter_enumerated();
+        let enodes = enodes
+            .filter_map(|(i, &s)| s.then_some(Item::ENode(i)))
+            .collect();
+        let eqs = sel.eqs.iter_enumerated();
+        let eqs = eqs.filter_map(|(i, &s)| s.then_some(Item::Eq(i))).collect();
+        let matches = sel.matches.iter_enumerated();
+        let matches = matches
+            .filter_map(|(i, &s)| s.then_some(Item::Match(i)))
+            .collect();
+        let insts = sel.insts.iter_enumerated();
+        let insts = insts
+            .filter_map(|(i, &s)| s.then_some(Item::Inst(i)))
+            .collect();
+
+        let mut match_fingerprints: TiVec<MatchIdx, _> = (0..sel.matches.len())
+            .map(|m| Fingerprint(u64::MAX - m as u64))
+            .collect();
+        for inst in parser.insts.insts.iter() {
+            match_fingerprints[inst.match_] = inst.fingerprint;
+        }
+        let mut this = Self {
+            parser,
+            out,
+            frames,
+            next_term: 0,
+            queues: [(enodes, 0), (eqs, 0), (matches, 0), (insts, 0)],
+            enode_done: filled(false, sel.enodes.len()),
+            eq_done: filled(false, sel.eqs.len()),
+            match_done: filled(false, sel.matches.len()),
+            refs: FxHashMap::default(),
+            pending: filled(0, sel.frames.len()),
+            enode_refs: filled(0, sel.enodes.len()),
+            prev_enode: filled(None, sel.enodes.len()),
+            match_insts: filled(0, sel.matches.len()),
+            fingerprints: FxHashMap::default(),
+            match_fingerprints,
+            stack: Vec::new(),
+            next_frame: 0,
+            sel,
+        };
+        let mut last_enode: FxHashMap<TermIdx, ENodeIdx> = FxHashMap::default();
+        for queue in 0..this.queues.len() {
+            for idx in 0..this.queues[queue].0.len() {
+                let item = this.queues[queue].0[idx];
+                let (own, enodes, eqs) = this.references(item);
+                let mut frames: Vec<_> = own.into_iter().flatten().collect();
+                // Frames popped without a `[pop]` being logged are left on the
+                // stack, their pop is implied by a later `[push]` or `[pop]`.
+                let live = |f: &StackIdx| !parser.stack.stack_frames[*f].implicit_pop;
+                for &enode in &enodes {
+                    this.enode_refs[enode] += 1;
+                    frames.extend(parser[enode].frame().filter(live));
+                }
+                let eq_frames = eqs.iter().filter_map(|&eq| equality_frame(parser, eq));
+                frames.extend(eq_frames.filter(live));
+                frames.sort_unstable();
+                frames.dedup();
+                for &frame in &frames {
+                    this.pending[frame] += 1;
+                }
+                let refs = Refs {
+                    own,
+                    enodes: enodes.into(),
+                    eqs: eqs.into(),
+                    frames: frames.into(),
+                };
+                this.refs.insert(Self::key(item), refs);
+                match item {
+                    Item::ENode(enode) => {
+                        let owner = parser[enode].owner;
+                        this.prev_enode[enode] = last_enode.insert(owner, enode);
+                    }
+                    Item::Inst(inst) => this.match_insts[parser[inst].match_] += 1,
+                    _ => (),
+                }
+            }
+        }
+        this
+    }
+
+    fn key(item: Item) -> (u8, usize) {
+        match item {
+            Item::ENode(i) => (0, i.into()),
+            Item::Eq(i) => (1, i.into()),
+            Item::Match(i) => (2, i.into()),
+            Item::Inst(i) => (3, i.into()),
+        }
+    }
+
+    /// The frame in which the item must be created (if it has one), and the
+    /// e-nodes and equalities it refers to.
+    fn references(&self, item: Item) -> (Option<Option<StackIdx>>, Vec<ENodeIdx>, Vec<EqGivenIdx>) {
+        let parser = self.parser;
+        match item {
+            Item::ENode(enode) => (Some(parser[enode].frame()), Vec::new(), Vec::new()),
+            Item::Eq(eq) => {
+                let expl = &parser[eq];
+                let mut enodes = vec![expl.from(), expl.to()];
+                match expl {
+                    EqualityExpl::Literal { eq, .. } => enodes.push(*eq),
+                    EqualityExpl::Congruence { arg_eqs, .. } => {
+                        enodes.extend(arg_eqs.iter().flat_map(|(from, to)| [*from, *to]))
+                    }
+                    _ => (),
+                }
+                (Some(equality_frame(parser, eq)), enodes, Vec::new())
+            }
+            Item::Match(m) => {
+                let match_ = &parser[m];
+                let mut enodes = Vec::new();
+                let mut eqs = Vec::new();
+                if let MatchKind::MBQI { bound_terms, .. }
+                | MatchKind::Quantifier { bound_terms, .. } = &match_.kind
+                {
+                    enodes.extend(bound_terms.iter().copied());
+                }
+                let equalities = &parser.egraph.equalities;
+                let mut trans = Vec::new();
+                for blame in match_.blamed.iter() {
+                    match blame {
+                        BlameKind::Term { term } => enodes.push(*term),
+                        BlameKind::Equality { eq } => {
+                            enodes.push(equalities.from(*eq));
+                            enodes.push(equalities.transitive[*eq].to);
+                            trans.push(*eq);
+                        }
+                    }
+                }
+                given_eqs(equalities, trans, &mut eqs);
+                enodes.retain(|&e| self.sel.enodes[e]);
+                eqs.retain(|&e| self.sel.eqs[e]);
+                (None, enodes, eqs)
+            }
+            Item::Inst(inst) => (Some(parser[inst].frame), Vec::new(), Vec::new()),
+        }
+    }
+
+    fn head(&self, queue: usize) -> Option<Item> {
+        let (items, pos) = &self.queues[queue];
+        items.get(*pos).copied()
+    }
+
+    /// Moves the position of each queue past the items which were written
+    /// out of order (e-nodes within instantiations).
+    fn advance(&mut self) {
+        for queue in 0..self.queues.len() {
+            while let Some(item) = self.head(queue) {
+                if !self.is_done(item) {
+                    break;
+                }
+                self.queues[queue].1 += 1;
+            }
+        }
+    }
+
+    fn is_done(&self, item: Item) -> bool {
+        match item {
+            Item::ENode(e) => self.enode_done[e],
+            Item::Eq(e) => self.eq_done[e],
+            Item::Match(m) => self.match_done[m],
+            // Instantiations are only ever written from their queue.
+            Item::Inst(_) => false,
+        }
+    }
+
+    fn ready(&self, item: Item) -> bool {
+        let parser = self.parser;
+        let refs = &self.refs[&Self::key(item)];
+        if refs
+            .own
+            .is_some_and(|frame| frame != self.stack.last().copied())
+        {
+            return false;
+        }
+        let enodes_done = refs.enodes.iter().all(|&e| self.enode_done[e]);
+        if !enodes_done || !refs.eqs.iter().all(|&e| self.eq_done[e]) {
+            return false;
+        }
+        match item {
+            Item::ENode(enode) => {
+                let created_by = parser[enode].created_by;
+                let in_inst = created_by.is_some_and(|i| self.sel.insts[i]);
+                let prev_used = self.prev_enode[enode].is_some_and(|p| self.enode_refs[p] > 0);
+                !in_inst && !prev_used
+            }
+            Item::Eq(_) => true,
+            Item::Match(m) => {
+                let fingerprint = self.fingerprint(m);
+                let previous = self.fingerprints.get(&fingerprint);
+                previous.map_or(true, |&p| self.match_insts[p] == 0)
+            }
+            Item::Inst(inst) => {
+                let match_ = parser[inst].match_;
+                if !self.match_done[match_]
+                    || self.fingerprints.get(&self.fingerprint(match_)) != Some(&match_)
+                {
+                    return false;
+                }
+                // Keep the order relative to other e-nodes.
+                let first_yield = parser[inst]
+                    .yields_terms
+                    .iter()
+                    .copied()
+                    .find(|&e| self.sel.enodes[e]);
+                let head = self.head(0);
+                first_yield.map_or(true, |y| matches!(head, Some(Item::ENode(h)) if h >= y))
+            }
+        }
+    }
+
+    fn fingerprint(&self, m: MatchIdx) -> Fingerprint {
+        self.match_fingerprints[m]
+    }
+
+    fn done(&mut self, item: Item) {
+        match item {
+            Item::ENode(e) => self.enode_done[e] = true,
+            Item::Eq(e) => self.eq_done[e] = true,
+            Item::Match(m) => self.match_done[m] = true,
+            Item::Inst(i) => self.match_insts[self.parser[i].match_] -= 1,
+        }
+        let refs = self.refs.remove(&Self::key(item)).unwrap();
+        for &frame in refs.frames.iter() {
+            self.pending[frame] -= 1;
+        }
+        for &enode in refs.enodes.iter() {
+            self.enode_refs[enode] -= 1;
+        }
+    }
+
+    fn run(mut self) -> io::Result<()> {
+        if let VersionInfo::Present { solver, version } = &self.parser.version_info {
+            writeln!(self.out, "[tool-version] {solver} {version}")?;
+        }
+        loop {
+            let mut progress = false;
+            for queue in 0..self.queues.len() {
+                while let Some(item) = self.head(queue) {
+                    if !self.ready(item) {
+                        break;
+                    }
+                    self.queues[queue].1 += 1;
+                    self.emit(item)?;
+                    progress = true;
+                }
+            }
+            if progress {
+                continue;
+            }
+            if self.stack_step()? {
+                continue;
+            }
+            // Nothing can be written without breaking some constraint, this
+            // should not happen for logs which z3 wrote. Write the earliest
+            // item anyway to make progress.
+            let Some((queue, item)) = (0..self.queues.len()).find_map(|q| Some((q, self.head(q)?)))
+            else {
+                break;
+            };
+            self.queues[queue].1 += 1;
+            self.emit(item)?;
+        }
+        // Pop all frames which were popped in the original log.
+        loop {
+            let height = self.stack.len() - self.leaked_frames();
+            let Some(&top) = self.stack[..height].last() else {
+                break;
+            };
+            let frame = &self.parser.stack.stack_frames[top];
+            if frame.active || frame.implicit_pop {
+                break;
+            }
+            self.stack.truncate(height);
+            self.pop()?;
+        }
+        // Terms not needed by any of the items.
+        self.terms_upto(self.parser.terms.len())?;
+        writeln!(self.out, "[eof]")
+    }
+
+    /// The number of frames at the top of the stack which were popped without
+    /// a `[pop]` being logged and have nothing left to write. As in the
+    /// original log, their pop is only implied by the next `[push]` or `[pop]`
+    /// of a frame below them.
+    fn leaked_frames(&self) -> usize {
+        let frames = &self.parser.stack.stack_frames;
+        self.stack
+            .iter()
+            .rev()
+            .take_while(|&&f| frames[f].implicit_pop && self.pending[f] == 0)
+            .count()
+    }
+
+    /// Pops or pushes a frame if that could allow more items to be written.
+    fn stack_step(&mut self) -> io::Result<bool> {
+        let frames = &self.parser.stack.stack_frames;
+        let next = (self.next_frame..frames.len()).find(|&f| self.sel.frames[StackIdx::from(f)]);
+        let next = next.map(StackIdx::from);
+        for leaked in 0..=self.leaked_frames() {
+            let height = self.stack.len() - leaked;
+            let top = self.stack[..height].last().copied();
+            if let Some(top) = top {
+                let child_pending = next.is_some_and(|n| frames[n].depth > frames[top].depth);
+                let popped = !frames[top].active && !frames[top].implicit_pop;
+                if self.pending[top] == 0 && popped && !child_pending {
+                    self.stack.truncate(height);
+                    self.pop()?;
+                    return Ok(true);
+                }
+            }
+            let Some(next) = next else {
+                continue;
+            };
+            if self.frames.parent[next] != top {
+                continue;
+            }
+            self.stack.truncate(height);
+            writeln!(self.out, "[push] {}", self.stack.len())?;
+            self.stack.push(next);
+            self.next_frame = usize::from(next) + 1;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    fn pop(&mut self) -> io::Result<()> {
+        writeln!(self.out, "[pop] 1 {}", self.stack.len())?;
+        self.stack.pop();
+        Ok(())
+    }
+
+    fn id(&self, term: TermIdx) -> String {
+        let id = self.parser[term].id.unwrap_or_default();
+        self.term_id(id)
+    }
+
+    fn term_id(&self, id: TermId) -> String {
+        let namespace = &self.parser[id.namespace];
+        match id.id {
+            Some(id) => format!("{namespace}#{id}"),
+            None => format!("{namespace}#"),
+        }
+    }
+
+    fn enode(&self, enode: ENodeIdx) -> String {
+        self.id(self.parser[enode].owner)
+    }
+
+    /// Writes all selected terms up to (excluding) `end`.
+    fn terms_upto(&mut self, end: usize) -> io::Result<()> {
+        let parser = self.parser;
+        while self.next_term < end {
+            let tidx = TermIdx::from(self.next_term);
+            self.next_term += 1;
+            if !self.sel.terms[tidx] {
+                continue;
+            }
+            let term = &parser[tidx];
+            let id = self.id(tidx);
+            let children: Spaced = term.child_ids.iter().map(|&c| self.id(c)).collect();
+            match term.kind {
+                TermKind::Var(idx) => writeln!(self.out, "[mk-var] {id} {idx}")?,
+                TermKind::ProofOrApp(ProofOrApp { is_proof, name }) => {
+                    let entry = if is_proof { "[mk-proof]" } else { "[mk-app]" };
+                    writeln!(self.out, "{entry} {id} {}{children}", &parser[name])?
+                }
+                TermKind::Quant(qidx) => {
+                    let quant = &parser[qidx];
+                    let name = match &quant.kind {
+                        QuantKind::Lambda => "<null>".to_string(),
+                        QuantKind::NamedQuant(name) | QuantKind::Other(name) => {
+                            parser[*name].to_string()
+                        }
+                        QuantKind::UnnamedQuant { name, id } => format!("{}!{id}", &parser[*name]),
+                    };
+                    let num_vars = quant.num_vars;
+                    writeln!(self.out, "[mk-quant] {id} {name} {num_vars}{children}")?;
+                    if let Some(vars) = &quant.vars {
+                        write!(self.out, "[attach-var-names] {id}")?;
+                        match vars {
+                            VarNames::TypeOnly(types) => {
+                                for ty in types.iter() {
+                                    write!(self.out, " (;{})", &parser[*ty])?;
+                                }
+                            }
+                            VarNames::NameAndType(names) => {
+                                for (name, ty) in names.iter() {
+                                    write!(
+                                        self.out,
+                                        " (|{}| ; |{}|)",
+                                        &parser[*name], &parser[*ty]
+                                    )?;
+                                }
+                            }
+                        }
+                        writeln!(self.out)?;
+                    }
+                }
+                TermKind::Generalised(_) => continue,
+            }
+            if let Some(meaning) = parser.meaning(tidx) {
+                let (theory, value) = (&parser[meaning.theory], &parser[meaning.value]);
+                writeln!(self.out, "[attach-meaning] {id} {theory} {value}")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn needs_terms(&mut self, terms: impl IntoIterator<Item = TermIdx>) -> io::Result<()> {
+        let max = terms.into_iter().map(usize::from).max();
+        match max {
+            Some(max) => self.terms_upto(max + 1),
+            None => Ok(()),
+        }
+    }
+
+    fn emit(&mut self, item: Item) -> io::Result<()> {
+        let parser = self.parser;
+        match item {
+            Item::ENode(enode) => self.emit_enode(enode)?,
+            Item::Eq(eq) => {
+                let expl = &parser[eq];
+                let mut enodes = vec![expl.from(), expl.to()];
+                match expl {
+                    EqualityExpl::Literal { eq, .. } => enodes.push(*eq),
+                    EqualityExpl::Congruence { arg_eqs, .. } => {
+                        enodes.extend(arg_eqs.iter().flat_map(|(f, t)| [*f, *t]))
+                    }
+                    _ => (),
+                }
+                self.needs_terms(enodes.iter().map(|&e| parser[e].owner))?;
+                let from = self.enode(expl.from());
+                let to = self.enode(expl.to());
+                match expl {
+                    EqualityExpl::Root { .. } => writeln!(self.out, "[eq-expl] {from} root")?,
+                    EqualityExpl::Literal { eq, .. } => {
+                        let eq = self.enode(*eq);
+                        writeln!(self.out, "[eq-expl] {from} lit {eq} ; {to}")?
+                    }
+                    EqualityExpl::Congruence { arg_eqs, .. } => {
+                        let args: Spaced = arg_eqs
+                            .iter()
+                            .map(|(f, t)| format!("({} {})", self.enode(*f), self.enode(*t)))
+                            .collect();
+                        writeln!(self.out, "[eq-expl] {from} cg{args} ; {to}")?
+                    }
+                    EqualityExpl::Theory { theory, .. } => {
+                        let theory = &parser[*theory];
+                        writeln!(self.out, "[eq-expl] {from} th {theory} ; {to}")?
+                    }
+                    EqualityExpl::Axiom { .. } => writeln!(self.out, "[eq-expl] {from} ax ; {to}")?,
+                    EqualityExpl::Unknown { kind, args, .. } => {
+                        let args: Spaced = args.iter().map(|a| parser[*a].to_string()).collect();
+                        writeln!(self.out, "[eq-expl] {from} {}{args} ; {to}", &parser[*kind])?
+                    }
+                }
+            }
+            Item::Match(m) => self.emit_match(m)?,
+            Item::Inst(inst) => {
+                let inst_ = &parser[inst];
+                let proof = match inst_.proof_id {
+                    Some(Either::Left(proof)) => {
+                        self.needs_terms([proof])?;
+                        format!(" {}", self.id(proof))
+                    }
+                    Some(Either::Right(id)) => format!(" {}", self.term_id(id)),
+                    None => String::new(),
+                };
+                let generation = inst_
+                    .z3_generation
+                    .map(|g| format!(" ; {g}"))
+                    .unwrap_or_default();
+                let fingerprint = self.fingerprint(inst_.match_);
+                writeln!(self.out, "[instance] 0x{fingerprint}{proof}{generation}")?;
+                for &enode in inst_.yields_terms.iter() {
+                    if self.sel.enodes[enode] && !self.enode_done[enode] {
+                        self.emit_enode(enode)?;
+                        self.done(Item::ENode(enode));
+                    }
+                }
+                writeln!(self.out, "[end-of-instance]")?;
+            }
+        }
+        self.done(item);
+        self.advance();
+        Ok(())
+    }
+
+    fn emit_enode(&mut self, enode: ENodeIdx) -> io::Result<()> {
+        let owner = self.parser[enode].owner;
+        self.needs_terms([owner])?;
+        let id = self.id(owner);
+        match self.parser[enode].z3_generation {
+            Some(generation) => writeln!(self.out, "[attach-enode] {id} {generation}"),
+            None => writeln!(self.out, "[attach-enode] {id}"),
+        }
+    }
+
+    fn emit_match(&mut self, m: MatchIdx) -> io::Result<()> {
+        let parser = self.parser;
+        let match_ = &parser[m];
+        let fingerprint = self.fingerprint(m);
+        self.fingerprints.insert(fingerprint, m);
+
+        let mut terms = Vec::new();
+        terms.extend(match_.kind.quant_idx().and_then(|q| parser[q].term));
+        terms.extend(match_.kind.pattern());
+        terms.extend(match_.kind.rewrite_of());
+        let bound = match_.kind.bound_terms(|e| parser[e].owner, |t| t);
+        terms.extend(bound.iter().copied());
+        self.needs_terms(terms)?;
+
+        let bound: Spaced = bound.iter().map(|&t| self.id(t)).collect();
+        let equalities = &parser.egraph.equalities;
+        let blamed: Spaced = match_
+            .blamed
+            .iter()
+            .map(|blame| match blame {
+                BlameKind::Term { term } => self.enode(*term),
+                BlameKind::Equality { eq } => {
+                    let from = self.enode(equalities.from(*eq));
+                    let to = self.enode(equalities.transitive[*eq].to);
+                    format!("({from} {to})")
+                }
+            })
+            .collect();
+        match &match_.kind {
+            MatchKind::Quantifier { quant, pattern, .. }
+            | MatchKind::Axiom {
+                axiom: quant,
+                pattern,
+                ..
+            } => {
+                let quant = self.id(parser[*quant].term.unwrap());
+                let pattern = self.id(*pattern);
+                writeln!(
+                    self.out,
+                    "[new-match] 0x{fingerprint} {quant} {pattern}{bound} ;{blamed}"
+                )
+            }
+            MatchKind::TheorySolving {
+                axiom_id,
+                rewrite_of,
+                ..
+            } => {
+                let axiom = self.term_id(*axiom_id);
+                let rewrite_of = rewrite_of
+                    .map(|r| format!(" {}", self.id(r)))
+                    .unwrap_or_default();
+                writeln!(
+                    self.out,
+                    "[inst-discovered] theory-solving 0x{fingerprint} {axiom}{bound} ;{blamed}{rewrite_of}"
+                )
+            }
+            MatchKind::MBQI { quant, .. } => {
+                let quant = self.id(parser[*quant].term.unwrap());
+                writeln!(
+                    self.out,
+                    "[inst-discovered] MBQI 0x{fingerprint} {quant}{bound}"
+                )
+            }
+            MatchKind::Reported { .. } => unreachable!(),
+        }
+    }
+}
+
+/// All given equalities which the transitive equalities are made up of.
+fn given_eqs(equalities: &Equalities, mut trans: Vec<EqTransIdx>, out: &mut Vec<EqGivenIdx>) {
+    let mut seen = fxhash::FxHashSet::default();
+    while let Some(eq) = trans.pop() {
+        if !seen.insert(eq) {
+            continue;
+        }
+        for seg in equalities.transitive[eq].path.iter() {
+            match seg.kind {
+                TransitiveExplSegmentKind::Given(given, use_) => {
+                    out.push(given);
+                    if let (EqualityExpl::Congruence { uses, .. }, Some(use_)) =
+                        (&equalities.given[given], use_)
+                    {
+                        trans.extend(uses[use_.get() as usize].iter().copied());
+                    }
+                }
+                TransitiveExplSegmentKind::Transitive(t) => trans.push(t),
+            }
+        }
+    }
+}
+
+/// Arguments of a log line, each preceded by a space.
+#[derive(Default)]
+struct Spaced(String);
+
+impl FromIterator<String> for Spaced {
+    fn from_iter<I: IntoIterator<Item = String>>(iter: I) -> Self {
+        let mut spaced = Self::default();
+        for arg in iter {
+            spaced.0.push(' ');
+            spaced.0.push_str(&arg);
+        }
+        spaced
+    }
+}
+
+impl std::fmt::Display for Spaced {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
@@ -4,20 +4,28 @@ use typed_index_collections::TiSlice;
 
 use crate::{
     items::*,
-    parsers::z3::{VersionInfo, Z3LogParser},
+    parsers::{
+        diagnostics::Diagnostics,
+        z3::{
+            provenance::{ItemLens, LinePos, Provenance},
+            VersionInfo, Z3LogParser,
+        },
+    },
     Error, IString, Result, StringTable, TiVec,
 };
 
 use super::{
+    cdcl::Cdcl,
     egraph::{EGraph, ENode},
     inst::Insts,
-    stack::Stack,
+    stack::{Stack, StackFrame},
     terms::Terms,
 };
 
 /// A parser for Z3 log files. Use one of the various `Z3Parser::from_*` methods
 /// to construct this parser.
 #[cfg_attr(feature = "mem_dbg", derive(MemSize, MemDbg))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug)]
 pub struct Z3Parser {
     pub(crate) version_info: VersionInfo,
@@ -30,6 +38,10 @@ pub struct Z3Parser {
 
     pub(crate) egraph: EGraph,
     pub(crate) stack: Stack,
+    pub(crate) cdcl: Cdcl,
+    pub(crate) queries: TiVec<QueryIdx, Query>,
+    pub(crate) diagnostics: Diagnostics,
+    pub(crate) provenance: Option<Provenance>,
 
     pub strings: StringTable,
 }
@@ -45,6 +57,10 @@ impl Default for Z3Parser {
             inst_stack: Default::default(),
             egraph: Default::default(),
             stack: Default::default(),
+            cdcl: Default::default(),
+            queries: Default::default(),
+            diagnostics: Default::default(),
+            provenance: None,
             strings,
         }
     }
@@ -212,6 +228,56 @@ impl Z3Parser {
         Ok(())
     }
 
+    /// Parse a literal of the form `#n`, `(not #n)`, `true` or `false`. The
+    /// `(not #n)` form spans two elements of `l`.
+    fn parse_literal<'a>(
+        &mut self,
+        first: &str,
+        l: &mut impl Iterator<Item = &'a str>,
+    ) -> Result<Literal> {
+        let (id, value) = match first {
+            "true" | "false" => {
+                return Ok(Literal {
+                    term: None,
+                    value: first == "true",
+                })
+            }
+            "(not" => {
+                let id = l.next().ok_or(Error::UnexpectedNewline)?;
+                let id = id
+                    .strip_suffix(')')
+                    .ok_or_else(|| Error::InvalidLiteral(format!("(not {id}")))?;
+                (id, false)
+            }
+            id => (id, true),
+        };
+        let term = self.terms.parse_existing_id(&mut self.strings, id)?;
+        Ok(Literal {
+            term: Some(term),
+            value,
+        })
+    }
+    fn gobble_literals<'a>(
+        &mut self,
+        mut l: impl Iterator<Item = &'a str>,
+    ) -> Result<Box<[Literal]>> {
+        let mut literals = Vec::new();
+        while let Some(first) = l.next() {
+            let literal = self.parse_literal(first, &mut l)?;
+            literals.try_reserve(1)?;
+            literals.push(literal);
+        }
+        Ok(literals.into_boxed_slice())
+    }
+    /// The instantiation which produced the term of a literal: the one which
+    /// created its enode or, for terms which are not enodes, the one during
+    /// which it was last assigned.
+    fn literal_origin(&self, term: TermIdx) -> Option<InstIdx> {
+        self.egraph
+            .created_by(term)
+            .or_else(|| self.cdcl.assigned_by(term))
+    }
+
     /// Create a new iterator which will only consume elements from `l` until
     /// it finds `end`. The element `end` will also be consumed but no other elements after that will.
     fn iter_until_eq<'a, 's>(
@@ -233,7 +299,6 @@ impl Z3LogParser for Z3Parser {
         // Return if there is unexpectedly more data
         Self::expect_completed(l)?;
         let version = semver::Version::parse(version)?;
-        println!("{solver} {version}");
         self.version_info = VersionInfo::Present { solver, version };
         Ok(())
     }
@@ -254,6 +319,7 @@ impl Z3LogParser for Z3Parser {
         let num_vars = num_vars.unwrap();
         let child_ids = self.gobble_children(l)?;
         assert!(!child_ids.is_empty());
+        let triggers = self.triggers(&child_ids);
         let qidx = self.quantifiers.next_key();
         let term = Term {
             id: Some(full_id),
@@ -266,6 +332,7 @@ impl Z3LogParser for Z3Parser {
             kind: quant_name,
             term: Some(tidx),
             vars: None,
+            triggers,
         };
         self.quantifiers.raw.try_reserve(1)?;
         let qidx2 = self.quantifiers.push_and_get_key(q);
@@ -314,14 +381,14 @@ impl Z3LogParser for Z3Parser {
 
     fn attach_meaning<'a>(&mut self, mut l: impl Iterator<Item = &'a str>) -> Result<()> {
         let id = l.next().ok_or(Error::UnexpectedNewline)?;
-        let theory = IString(
-            self.strings
-                .get_or_intern(l.next().ok_or(Error::UnexpectedNewline)?),
-        );
-        let value = IString(self.strings.get_or_intern(l.collect::<Vec<_>>().join(" ")));
+        let theory = l.next().ok_or(Error::UnexpectedNewline)?;
+        let value = l.collect::<Vec<_>>().join(" ");
+        let decoded = Value::parse(theory, &value);
+        let theory = IString(self.strings.get_or_intern(theory));
+        let value = IString(self.strings.get_or_intern(value));
         let meaning = Meaning { theory, value };
         let idx = self.terms.parse_existing_id(&mut self.strings, id)?;
-        self.terms.new_meaning(idx, meaning)?;
+        self.terms.new_meaning(idx, meaning, decoded)?;
         Ok(())
     }
 
@@ -587,6 +654,7 @@ impl Z3LogParser for Z3Parser {
             proof_id,
             z3_generation,
             yields_terms: Default::default(),
+            frame: self.stack.active_frame(),
         };
         // In version 4.12.2 & 4.12.4, I have on very rare occasions seen an
         // `[instance]` repeated twice with the same fingerprint (without an
@@ -609,6 +677,197 @@ impl Z3LogParser for Z3Parser {
         self.terms.end_of_file();
     }
 
+    fn diagnostics_mut(&mut self) -> &mut Diagnostics {
+        &mut self.diagnostics
+    }
+
+    fn set_provenance(&mut self, enabled: bool) {
+        if !enabled {
+            self.provenance = None;
+        } else if self.provenance.is_none() {
+            self.provenance = Some(Provenance::default());
+        }
+    }
+
+    fn line_done(&mut self, pos: LinePos) -> Result<()> {
+        let Some(provenance) = &mut self.provenance else {
+            return Ok(());
+        };
+        let lens = ItemLens {
+            terms: self.terms.len(),
+            matches: self.insts.matches.len(),
+            insts: self.insts.insts.len(),
+            enodes: self.egraph.enodes.len(),
+            eq_given: self.egraph.equalities.given.len(),
+        };
+        provenance.line_done(lens, pos)
+    }
+
+    fn decide_and_or<'a>(&mut self, mut l: impl Iterator<Item = &'a str>) -> Result<()> {
+        let term = l.next().ok_or(Error::UnexpectedNewline)?;
+        let term = self.terms.parse_existing_id(&mut self.strings, term)?;
+        let child = l.next().ok_or(Error::UnexpectedNewline)?;
+        let child = self.terms.parse_existing_id(&mut self.strings, child)?;
+        // Return if there is unexpectedly more data
+        Self::expect_completed(l)?;
+        let decision = Decision {
+            kind: DecisionKind::AndOr { term, child },
+            frame: self.stack.active_frame(),
+            produced_by: self.literal_origin(child),
+        };
+        self.cdcl.new_decision(decision)?;
+        Ok(())
+    }
+
+    fn decide<'a>(&mut self, mut l: impl Iterator<Item = &'a str>) -> Result<()> {
+        let first = l.next().ok_or(Error::UnexpectedNewline)?;
+        let literal = self.parse_literal(first, &mut l)?;
+        // Some versions additionally log the activity or phase of the decided
+        // literal, we do not use it.
+        let decision = Decision {
+            kind: DecisionKind::Literal(literal),
+            frame: self.stack.active_frame(),
+            produced_by: literal.term.and_then(|term| self.literal_origin(term)),
+        };
+        self.cdcl.new_decision(decision)?;
+        Ok(())
+    }
+
+    fn assign<'a>(&mut self, mut l: impl Iterator<Item = &'a str>) -> Result<()> {
+        let first = l.next().ok_or(Error::UnexpectedNewline)?;
+        let literal = self.parse_literal(first, &mut l)?;
+        let mut kind = l.next().ok_or(Error::UnexpectedNewline)?;
+        let decision = kind == "decision";
+        if decision {
+            kind = l.next().ok_or(Error::UnexpectedNewline)?;
+        }
+        let justification = match kind {
+            "axiom" => {
+                Self::expect_completed(l)?;
+                Justification::Axiom
+            }
+            "bin" | "bin-clause" => {
+                let first = l.next().ok_or(Error::UnexpectedNewline)?;
+                let other = self.parse_literal(first, &mut l)?;
+                Self::expect_completed(l)?;
+                Justification::BinClause(other)
+            }
+            "clause" => Justification::Clause(self.gobble_literals(l)?),
+            "justification" => {
+                let theory = l.next().ok_or(Error::UnexpectedNewline)?;
+                let theory = theory.strip_suffix(':').unwrap_or(theory);
+                let theory = theory
+                    .parse::<i32>()
+                    .map_err(Error::InvalidJustificationTheory)?;
+                let literals = self.gobble_literals(l)?;
+                Justification::Theory { theory, literals }
+            }
+            kind => {
+                let args = l.map(|s| IString(self.strings.get_or_intern(s))).collect();
+                Justification::Unknown {
+                    kind: IString(self.strings.get_or_intern(kind)),
+                    args,
+                }
+            }
+        };
+        let assignment = Assignment {
+            literal,
+            decision,
+            justification,
+            frame: self.stack.active_frame(),
+            created_by: self.inst_stack.last().map(|(iidx, _)| *iidx),
+        };
+        self.cdcl.new_assignment(assignment)?;
+        Ok(())
+    }
+
+    fn resolve_process<'a>(&mut self, mut l: impl Iterator<Item = &'a str>) -> Result<()> {
+        let first = l.next().ok_or(Error::UnexpectedNewline)?;
+        let literal = self.parse_literal(first, &mut l)?;
+        // Return if there is unexpectedly more data
+        Self::expect_completed(l)?;
+        self.cdcl.new_resolve_step(ResolveStep::Process(literal))
+    }
+
+    fn resolve_lit<'a>(&mut self, mut l: impl Iterator<Item = &'a str>) -> Result<()> {
+        let level = l.next().ok_or(Error::UnexpectedNewline)?;
+        let level = level.parse::<u32>().map_err(Error::InvalidResolveLevel)?;
+        let first = l.next().ok_or(Error::UnexpectedNewline)?;
+        let literal = self.parse_literal(first, &mut l)?;
+        // Return if there is unexpectedly more data
+        Self::expect_completed(l)?;
+        self.cdcl
+            .new_resolve_step(ResolveStep::Lit { level, literal })
+    }
+
+    fn conflict<'a>(&mut self, l: impl Iterator<Item = &'a str>) -> Result<()> {
+        let clause = self.gobble_literals(l)?;
+        let resolution = self.cdcl.resolution().iter().map(|step| step.literal());
+        let mut produced_by: Vec<_> = clause
+            .iter()
+            .copied()
+            .chain(resolution)
+            .filter_map(|literal| self.literal_origin(literal.term?))
+            .collect();
+        produced_by.sort_unstable();
+        produced_by.dedup();
+        let conflict = Conflict {
+            clause,
+            resolution: Default::default(),
+            frame: self.stack.active_frame(),
+            last_decision: None,
+            produced_by: produced_by.into_boxed_slice(),
+        };
+        self.cdcl.new_conflict(conflict)?;
+        Ok(())
+    }
+
+    fn begin_check<'a>(
+        &mut self,
+        mut l: impl Iterator<Item = &'a str>,
+        line_no: usize,
+    ) -> Result<()> {
+        let scope = l.next().ok_or(Error::UnexpectedNewline)?;
+        let scope = scope.parse::<usize>().map_err(Error::InvalidFrameInteger)?;
+        // Return if there is unexpectedly more data
+        Self::expect_completed(l)?;
+        let start = self.item_counts();
+        if let Some(last) = self.queries.last_mut() {
+            last.end.get_or_insert(start);
+        }
+        let query = Query {
+            scope,
+            start_line: line_no,
+            end_line: None,
+            result: None,
+            start,
+            end: None,
+        };
+        self.queries.raw.try_reserve(1)?;
+        self.queries.push(query);
+        Ok(())
+    }
+
+    fn query_done<'a>(
+        &mut self,
+        mut l: impl Iterator<Item = &'a str>,
+        line_no: usize,
+    ) -> Result<()> {
+        let result = l.next().map(QueryResult::parse).transpose()?;
+        // Return if there is unexpectedly more data
+        Self::expect_completed(l)?;
+        let end = self.item_counts();
+        let query = self
+            .queries
+            .last_mut()
+            .filter(|q| q.end_line.is_none())
+            .ok_or(Error::UnmatchedQueryDone)?;
+        query.end_line = Some(line_no);
+        query.result = result;
+        query.end = Some(end);
+        Ok(())
+    }
+
     fn push<'a>(&mut self, mut l: impl Iterator<Item = &'a str>) -> Result<()> {
         let scope = l.next().ok_or(Error::UnexpectedNewline)?;
         let scope = scope.parse::<usize>().map_err(Error::InvalidFrameInteger)?;
@@ -629,9 +888,36 @@ impl Z3LogParser for Z3Parser {
 }
 
 impl Z3Parser {
+    /// The `pattern` applications among the children of a quantifier term.
+    pub(crate) fn triggers(&self, children: &[TermIdx]) -> Box<[TermIdx]> {
+        children
+            .iter()
+            .copied()
+            .filter(|&child| {
+                self[child]
+                    .kind
+                    .app_name()
+                    .is_some_and(|name| &self[name] == "pattern")
+            })
+            .collect()
+    }
     pub fn meaning(&self, tidx: TermIdx) -> Option<&Meaning> {
         self.terms.meaning(tidx)
     }
+    /// The decoded value of the meaning of the term, if it has one.
+    pub fn value(&self, tidx: TermIdx) -> Option<&Value> {
+        self.terms.value(tidx)
+    }
+    /// Whether any term bound by the instantiation has a numeric value which
+    /// compares to `value` as `ordering`, see [`Value::cmp_numeric`].
+    pub fn binds_value(&self, iidx: InstIdx, ordering: std::cmp::Ordering, value: &Value) -> bool {
+        let bound = self[self[iidx].match_]
+            .kind
+            .bound_terms(|enode| self[enode].owner, |term| term);
+        bound
+            .into_iter()
+            .any(|term| self.value(term).and_then(|v| v.cmp_numeric(value)) == Some(ordering))
+    }
 
     pub fn quant_count_incl_theory_solving(&self) -> (usize, bool) {
         (self.quantifiers.len(), self.insts.has_theory_solving_inst())
@@ -643,6 +929,96 @@ impl Z3Parser {
     pub fn instantiations(&self) -> &TiSlice<InstIdx, Instantiation> {
         &self.insts.insts
     }
+    pub fn stack_frames(&self) -> &TiSlice<StackIdx, StackFrame> {
+        &self.stack.stack_frames
+    }
+    pub fn decisions(&self) -> &TiSlice<DecisionIdx, Decision> {
+        &self.cdcl.decisions
+    }
+    pub fn assignments(&self) -> &TiSlice<AssignIdx, Assignment> {
+        &self.cdcl.assignments
+    }
+    pub fn conflicts(&self) -> &TiSlice<ConflictIdx, Conflict> {
+        &self.cdcl.conflicts
+    }
+    /// The problems encountered while parsing the log.
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+    /// The line on which each item was created, only available if enabled
+    /// with [`LogParser::set_provenance`](crate::LogParser::set_provenance)
+    /// before parsing.
+    pub fn provenance(&self) -> Option<&Provenance> {
+        self.provenance.as_ref()
+    }
+    pub fn queries(&self) -> &TiSlice<QueryIdx, Query> {
+        &self.queries
+    }
+
+    /// The number of each kind of item created so far.
+    pub fn item_counts(&self) -> ItemCounts {
+        ItemCounts {
+            terms: self.terms.len(),
+            insts: self.insts.insts.len(),
+            enodes: self.egraph.enodes.len(),
+            given_eqs: self.egraph.equalities.given.len(),
+            trans_eqs: self.egraph.equalities.transitive.len(),
+            decisions: self.cdcl.decisions.len(),
+            assignments: self.cdcl.assignments.len(),
+            conflicts: self.cdcl.conflicts.len(),
+        }
+    }
+    /// The ranges of all items in the log.
+    pub fn all_items(&self) -> ItemRanges {
+        ItemRanges::new(ItemCounts::default(), self.item_counts())
+    }
+    /// The ranges of all items which belong to the given query, those
+    /// created after the previous query was done (i.e. the assertions for
+    /// this query) up until this query was done.
+    pub fn query_items(&self, query: QueryIdx) -> ItemRanges {
+        let start = usize::from(query)
+            .checked_sub(1)
+            .and_then(|prev| self.queries[QueryIdx::from(prev)].end)
+            .unwrap_or_default();
+        let end = self.queries[query]
+            .end
+            .unwrap_or_else(|| self.item_counts());
+        ItemRanges::new(start, end)
+    }
+    fn query_containing(
+        &self,
+        idx: usize,
+        count: impl Fn(&ItemCounts) -> usize,
+    ) -> Option<QueryIdx> {
+        let query = self
+            .queries
+            .raw
+            .partition_point(|q| q.end.is_some_and(|end| count(&end) <= idx));
+        let query = QueryIdx::from(query);
+        self.queries.get(query).map(|_| query)
+    }
+    /// Which query was the term created in? Returns `None` if it was created
+    /// after the last query was done or there were no queries in the log.
+    pub fn term_query(&self, tidx: TermIdx) -> Option<QueryIdx> {
+        self.query_containing(tidx.into(), |c| c.terms)
+    }
+    /// Which query does the instantiation belong to? Returns `None` if it was
+    /// created after the last query was done or there were no queries in the
+    /// log.
+    pub fn inst_query(&self, iidx: InstIdx) -> Option<QueryIdx> {
+        self.query_containing(iidx.into(), |c| c.insts)
+    }
+    /// Which query does the enode belong to? Returns `None` if it was created
+    /// after the last query was done or there were no queries in the log.
+    pub fn enode_query(&self, eidx: ENodeIdx) -> Option<QueryIdx> {
+        self.query_containing(eidx.into(), |c| c.enodes)
+    }
+    /// Which query does the given equality belong to? Returns `None` if it
+    /// was created after the last query was done or there were no queries in
+    /// the log.
+    pub fn eq_given_query(&self, eq: EqGivenIdx) -> Option<QueryIdx> {
+        self.query_containing(eq.into(), |c| c.given_eqs)
+    }
 }
 
 impl std::ops::Index<TermIdx> for Z3Parser {
@@ -687,6 +1063,30 @@ impl std::ops::Index<EqTransIdx> for Z3Parser {
         &self.egraph.equalities.transitive[idx]
     }
 }
+impl std::ops::Index<DecisionIdx> for Z3Parser {
+    type Output = Decision;
+    fn index(&self, idx: DecisionIdx) -> &Self::Output {
+        &self.cdcl[idx]
+    }
+}
+impl std::ops::Index<AssignIdx> for Z3Parser {
+    type Output = Assignment;
+    fn index(&self, idx: AssignIdx) -> &Self::Output {
+        &self.cdcl[idx]
+    }
+}
+impl std::ops::Index<ConflictIdx> for Z3Parser {
+    type Output = Conflict;
+    fn index(&self, idx: ConflictIdx) -> &Self::Output {
+        &self.cdcl[idx]
+    }
+}
+impl std::ops::Index<QueryIdx> for Z3Parser {
+    type Output = Query;
+    fn index(&self, idx: QueryIdx) -> &Self::Output {
+        &self.queries[idx]
+    }
+}
 impl std::ops::Index<IString> for Z3Parser {
     type Output = str;
     fn index(&self, idx: IString) -> &Self::Output {
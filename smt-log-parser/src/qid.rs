@@ -0,0 +1,241 @@
+use std::fmt;
+
+/// Where a quantifier came from according to its name.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QidSource {
+    /// The front-end which generated the quantifier, e.g. `Boogie`.
+    pub tool: String,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    /// What the quantifier encodes, e.g. `precondition` or `prelude`, if
+    /// the name says so.
+    pub kind: Option<String>,
+}
+
+impl QidSource {
+    fn new(tool: &str) -> Self {
+        Self {
+            tool: tool.to_string(),
+            file: None,
+            line: None,
+            column: None,
+            kind: None,
+        }
+    }
+
+    /// The location without the kind, e.g. `test.dfy:12:7`, or the tool if
+    /// neither file nor line are known.
+    pub fn location(&self) -> String {
+        let mut location = self.file.clone().unwrap_or_else(|| self.tool.clone());
+        if let Some(line) = self.line {
+            location += &format!(":{line}");
+            if let Some(column) = self.column {
+                location += &format!(":{column}");
+            }
+        }
+        location
+    }
+}
+
+impl fmt::Display for QidSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.location())?;
+        if let Some(kind) = &self.kind {
+            write!(f, " ({kind})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Extracts a [`QidSource`] from a quantifier name. Closures of the form
+/// `Fn(&str) -> Option<QidSource>` can be used directly.
+pub trait QidDecoder {
+    fn decode(&self, qid: &str) -> Option<QidSource>;
+}
+
+impl<F: Fn(&str) -> Option<QidSource>> QidDecoder for F {
+    fn decode(&self, qid: &str) -> Option<QidSource> {
+        self(qid)
+    }
+}
+
+/// An ordered list of decoders, the first one which recognises a name wins.
+/// The [`Default`] contains decoders for Dafny, Boogie (and thus Carbon),
+/// Viper (Silicon, and the front-ends built on it such as Prusti and Gobra)
+/// and F*.
+pub struct QidDecoders(Vec<Box<dyn QidDecoder>>);
+
+impl Default for QidDecoders {
+    fn default() -> Self {
+        let mut decoders = Self::empty();
+        decoders.push(dafny);
+        decoders.push(boogie);
+        decoders.push(viper);
+        decoders.push(fstar);
+        decoders
+    }
+}
+
+impl QidDecoders {
+    pub fn empty() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Adds a decoder which is tried after all existing ones.
+    pub fn push(&mut self, decoder: impl QidDecoder + 'static) {
+        self.0.push(Box::new(decoder));
+    }
+
+    /// Adds a decoder which is tried before all existing ones.
+    pub fn push_front(&mut self, decoder: impl QidDecoder + 'static) {
+        self.0.insert(0, Box::new(decoder));
+    }
+
+    pub fn decode(&self, qid: &str) -> Option<QidSource> {
+        self.0.iter().find_map(|decoder| decoder.decode(qid))
+    }
+}
+
+/// Splits off a trailing `<sep><number>`.
+fn split_number(s: &str, sep: char) -> Option<(&str, u32)> {
+    let (rest, number) = s.rsplit_once(sep)?;
+    let number = number
+        .bytes()
+        .all(|b| b.is_ascii_digit())
+        .then(|| number.parse().ok())??;
+    Some((rest, number))
+}
+
+/// Boogie names quantifiers `<file>.<line>:<column>` where the `.` before
+/// the file extension is removed, e.g. `Preludebpl.77:15`.
+fn boogie_location(qid: &str) -> Option<(&str, u32, u32)> {
+    let (rest, column) = split_number(qid, ':')?;
+    let (file, line) = split_number(rest, '.')?;
+    (!file.is_empty()).then_some((file, line, column))
+}
+
+/// Restores the `.` before a known extension, `testdfy` is `test.dfy`.
+fn with_extension(file: &str, extension: &str) -> String {
+    match file.strip_suffix(extension) {
+        Some(stem) if !stem.is_empty() && !stem.ends_with('.') => format!("{stem}.{extension}"),
+        _ => file.to_string(),
+    }
+}
+
+/// `<file>dfy.<line>:<column>` for quantifiers written by the user,
+/// `DafnyPreludebpl.<line>:<column>` for those of the prelude and
+/// `<name>:dafny.<line>.<column>` for generated ones.
+fn dafny(qid: &str) -> Option<QidSource> {
+    let mut source = QidSource::new("Dafny");
+    if let Some((file, line, column)) = boogie_location(qid) {
+        if file == "DafnyPreludebpl" {
+            source.kind = Some("prelude".to_string());
+            source.file = Some(with_extension(file, "bpl"));
+        } else if file.ends_with("dfy") {
+            source.file = Some(with_extension(file, "dfy"));
+        } else {
+            return None;
+        }
+        source.line = Some(line);
+        source.column = Some(column);
+        return Some(source);
+    }
+    let (rest, column) = split_number(qid, '.')?;
+    let (rest, line) = split_number(rest, '.')?;
+    let kind = rest.strip_suffix("dafny")?;
+    let kind = kind.strip_suffix(':').unwrap_or(kind);
+    source.kind = (!kind.is_empty()).then(|| kind.to_string());
+    source.line = Some(line);
+    source.column = Some(column);
+    Some(source)
+}
+
+/// `<file>.<line>:<column>`, Carbon generates Boogie and so its quantifiers
+/// also end up here.
+fn boogie(qid: &str) -> Option<QidSource> {
+    let (file, line, column) = boogie_location(qid)?;
+    let mut source = QidSource::new("Boogie");
+    source.file = Some(with_extension(file, "bpl"));
+    source.line = Some(line);
+    source.column = Some(column);
+    Some(source)
+}
+
+/// Silicon names quantifiers from the program `prog.l<line>`, optionally
+/// followed by `-<kind>` or `_<kind>` (e.g. `prog.l12-aux` or
+/// `prog.l12_precondition`). Its own axioms are `qp.<name><n>` (quantified
+/// permissions), `quant-u-<n>` (unnamed) or `prog.<name>` (domain axioms),
+/// the latter prefixed by the instantiation of a generic domain as in
+/// `$Seq[Int]_prog.<name>`. The program file is not part of the name.
+fn viper(qid: &str) -> Option<QidSource> {
+    let mut source = QidSource::new("Viper");
+    let program = qid.strip_prefix("prog.").or_else(|| {
+        let (domain, rest) = qid.split_once("_prog.")?;
+        domain.starts_with('$').then_some(rest)
+    });
+    if let Some(rest) = program {
+        let located = rest.strip_prefix('l').and_then(|rest| {
+            let end = rest.bytes().position(|b| !b.is_ascii_digit());
+            let (line, kind) = rest.split_at(end.unwrap_or(rest.len()));
+            let line = line.parse().ok()?;
+            let kind = match kind.as_bytes().first() {
+                None => None,
+                Some(b'-' | b'_') => Some(kind[1..].to_string()),
+                Some(_) => return None,
+            };
+            Some((line, kind))
+        });
+        match located {
+            Some((line, kind)) => {
+                source.line = Some(line);
+                source.kind = kind;
+            }
+            None => source.kind = Some(rest.to_string()),
+        }
+        return Some(source);
+    }
+    let kind = if let Some(rest) = qid.strip_prefix("qp.") {
+        rest.trim_end_matches(|c: char| c.is_ascii_digit())
+    } else if let Some(id) = qid.strip_prefix("quant-u-") {
+        id.parse::<u32>().ok()?;
+        "quant-u"
+    } else {
+        return None;
+    };
+    source.kind = Some(kind.to_string());
+    Some(source)
+}
+
+/// F* names quantifiers `<kind>_<module>.<name>`, e.g.
+/// `equation_FStar.List.Tot.Base.length`. There are no line numbers, the
+/// module is used as the file.
+fn fstar(qid: &str) -> Option<QidSource> {
+    const KINDS: &[&str] = &[
+        "constructor_distinct",
+        "disc_equation",
+        "equation",
+        "equation_with_fuel",
+        "fuel_guarded_inversion",
+        "function_token_typing",
+        "interpretation",
+        "kinding",
+        "primitive",
+        "projection_inverse",
+        "subterm_ordering",
+        "token_correspondence",
+        "typing",
+    ];
+    let (kind, name) = KINDS
+        .iter()
+        .filter_map(|kind| Some((*kind, qid.strip_prefix(kind)?.strip_prefix('_')?)))
+        .max_by_key(|(kind, _)| kind.len())?;
+    let (module, _) = name.rsplit_once('.')?;
+    if !module.starts_with(|c: char| c.is_ascii_uppercase()) {
+        return None;
+    }
+    let mut source = QidSource::new("F*");
+    source.file = Some(module.to_string());
+    source.kind = Some(kind.to_string());
+    Some(source)
+}
@@ -0,0 +1,131 @@
+use core::fmt;
+use std::io::{Read, Write};
+
+#[cfg(feature = "analysis")]
+use crate::analysis::InstGraph;
+use crate::Z3Parser;
+
+/// The first bytes of every snapshot, used to tell snapshots apart from logs.
+pub const MAGIC: &[u8; 8] = b"SLPSNAP\0";
+/// Bumped whenever the layout of any of the serialized structs changes, a
+/// snapshot is only ever loaded by the version which wrote it.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+#[cfg(feature = "analysis")]
+const HAS_GRAPH: u8 = 1 << 0;
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(std::io::Error),
+    Encoding(bincode::Error),
+    InvalidMagic,
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Encoding(err) => write!(f, "invalid snapshot: {err}"),
+            Self::InvalidMagic => write!(f, "not a snapshot file"),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "snapshot version {version} is not supported (expected {SNAPSHOT_VERSION})"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl From<std::io::Error> for SnapshotError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+impl From<bincode::Error> for SnapshotError {
+    fn from(err: bincode::Error) -> Self {
+        Self::Encoding(err)
+    }
+}
+
+pub type SnapshotResult<T> = std::result::Result<T, SnapshotError>;
+
+/// Returns `true` if `bytes` (the start of a file) look like a snapshot.
+pub fn is_snapshot(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+fn write_header(mut writer: impl Write, flags: u8) -> SnapshotResult<()> {
+    writer.write_all(MAGIC)?;
+    writer.write_all(&SNAPSHOT_VERSION.to_le_bytes())?;
+    writer.write_all(&[flags])?;
+    Ok(())
+}
+
+fn read_header(mut reader: impl Read) -> SnapshotResult<u8> {
+    let mut magic = [0; MAGIC.len()];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(SnapshotError::InvalidMagic);
+    }
+    let mut version = [0; 4];
+    reader.read_exact(&mut version)?;
+    let version = u32::from_le_bytes(version);
+    if version != SNAPSHOT_VERSION {
+        return Err(SnapshotError::UnsupportedVersion(version));
+    }
+    let mut flags = [0];
+    reader.read_exact(&mut flags)?;
+    Ok(flags[0])
+}
+
+impl Z3Parser {
+    /// Writes the fully parsed state to `writer` such that it can be
+    /// restored with [`Z3Parser::load_snapshot`] without re-parsing the log.
+    pub fn save_snapshot(&self, mut writer: impl Write) -> SnapshotResult<()> {
+        write_header(&mut writer, 0)?;
+        bincode::serialize_into(&mut writer, self)?;
+        Ok(())
+    }
+
+    /// Restores a parser from a snapshot written by
+    /// [`Z3Parser::save_snapshot`]. Any instantiation graph stored in the
+    /// snapshot is skipped.
+    pub fn load_snapshot(mut reader: impl Read) -> SnapshotResult<Self> {
+        let _flags = read_header(&mut reader)?;
+        Ok(bincode::deserialize_from(&mut reader)?)
+    }
+
+    /// Same as [`Z3Parser::save_snapshot`], but additionally stores the
+    /// (already computed) instantiation graph.
+    #[cfg(feature = "analysis")]
+    pub fn save_snapshot_with(
+        &self,
+        mut writer: impl Write,
+        graph: Option<&InstGraph>,
+    ) -> SnapshotResult<()> {
+        let flags = if graph.is_some() { HAS_GRAPH } else { 0 };
+        write_header(&mut writer, flags)?;
+        bincode::serialize_into(&mut writer, self)?;
+        if let Some(graph) = graph {
+            bincode::serialize_into(&mut writer, graph)?;
+        }
+        Ok(())
+    }
+
+    /// Restores a parser and, if one was stored, its instantiation graph.
+    #[cfg(feature = "analysis")]
+    pub fn load_snapshot_with_graph(
+        mut reader: impl Read,
+    ) -> SnapshotResult<(Self, Option<InstGraph>)> {
+        let flags = read_header(&mut reader)?;
+        let parser = bincode::deserialize_from(&mut reader)?;
+        let graph = if flags & HAS_GRAPH != 0 {
+            Some(bincode::deserialize_from(&mut reader)?)
+        } else {
+            None
+        };
+        Ok((parser, graph))
+    }
+}
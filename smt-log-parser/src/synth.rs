@@ -0,0 +1,639 @@
+use std::fmt::{self, Write};
+
+use fastrand::Rng;
+
+#[cfg(feature = "analysis")]
+use crate::analysis::analysis::matching_loop::MIN_MATCHING_LOOP_LENGTH;
+
+/// The shape of the random model from which a log is generated. Use
+/// [`SynthConfig::random`] for a random configuration or set the fields
+/// directly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SynthConfig {
+    /// Seed for all random choices, the same configuration always generates
+    /// the same log.
+    pub seed: u64,
+    /// The number of ground constants which the quantifiers are instantiated
+    /// with, at least one.
+    pub constants: usize,
+    /// The number of quantifiers which are only ever instantiated once per
+    /// trigger term, at least one.
+    pub quantifiers: usize,
+    /// The number of instantiations of the above quantifiers.
+    pub instantiations: usize,
+    /// The lengths of the matching loops, each one is a chain of that many
+    /// instantiations of its own quantifier.
+    pub matching_loops: Vec<usize>,
+    /// The lengths of the matching loops which alternate between two
+    /// quantifiers, each instantiation of one matches a term yielded by the
+    /// other.
+    pub alternating_loops: Vec<usize>,
+    /// The number of equalities between constants, instantiations may match
+    /// modulo these equalities.
+    pub equalities: usize,
+    /// The number of `[push]`es, each frame is popped at a random later point
+    /// (or never).
+    pub frames: usize,
+    /// The number of frames which are popped without a `[pop]` being logged,
+    /// as z3 v4.8.17 and v4.11.2 sometimes do. A `[push]` at the lower depth
+    /// follows, and the e-nodes of the frame are still referred to after it.
+    pub implicit_pops: usize,
+    /// The number of `theory-solving` instantiations.
+    pub theory_solving: usize,
+    /// The number of conflicts, each is found after deciding on a term
+    /// yielded by an earlier quantifier instantiation.
+    pub conflicts: usize,
+    /// The number of queries (`[begin-check]`s), zero for a log without
+    /// any.
+    pub queries: usize,
+    /// Log `[mk-proof]` steps as with `proof=true`. Most queries (or the log
+    /// if it has none) end in a refutation which uses some of their
+    /// instantiations.
+    pub proofs: bool,
+}
+
+impl Default for SynthConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            constants: 4,
+            quantifiers: 2,
+            instantiations: 8,
+            matching_loops: vec![5],
+            equalities: 2,
+            frames: 2,
+            implicit_pops: 0,
+            theory_solving: 1,
+            alternating_loops: Vec::new(),
+            conflicts: 0,
+            queries: 0,
+            proofs: false,
+        }
+    }
+}
+
+/// What the parser and analyses should find in a generated log.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Expected {
+    pub quantifiers: usize,
+    /// All instantiations, including theory-solving ones.
+    pub insts: usize,
+    pub theory_solving: usize,
+    pub enodes: usize,
+    pub given_eqs: usize,
+    pub frames: usize,
+    pub popped_frames: usize,
+    /// Instantiations made within a frame which was later popped.
+    pub lost_insts: usize,
+    /// The number of matching loops long enough to be reported.
+    #[cfg(feature = "analysis")]
+    pub matching_loops: usize,
+    /// The number of instantiations of each quantifier, by name.
+    pub quant_insts: Vec<(String, usize)>,
+    /// The queries (or the log if there are none) ending in a refutation.
+    pub refutations: usize,
+    /// Quantifier instantiations which are part of the refutation of their
+    /// query.
+    pub used_insts: usize,
+    /// Quantifier instantiations of refuted queries which are not part of
+    /// the refutation.
+    pub wasted_insts: usize,
+}
+
+/// A generated log together with what it contains.
+#[derive(Debug, Clone)]
+pub struct SynthLog {
+    pub config: SynthConfig,
+    pub log: String,
+    pub expected: Expected,
+}
+
+impl SynthConfig {
+    /// A small random configuration, intended for property-based testing.
+    pub fn random(seed: u64) -> Self {
+        let mut rng = Rng::with_seed(seed);
+        let loops = rng.usize(0..=3);
+        Self {
+            seed,
+            constants: rng.usize(1..=8),
+            quantifiers: rng.usize(1..=4),
+            instantiations: rng.usize(0..=24),
+            matching_loops: (0..loops).map(|_| rng.usize(1..=10)).collect(),
+            equalities: rng.usize(0..=6),
+            frames: rng.usize(0..=6),
+            theory_solving: rng.usize(0..=3),
+            alternating_loops: (0..rng.usize(0..=2)).map(|_| rng.usize(1..=10)).collect(),
+            conflicts: rng.usize(0..=3),
+            queries: rng.usize(0..=3),
+            implicit_pops: rng.usize(0..=2),
+            proofs: rng.bool(),
+        }
+    }
+
+    pub fn generate(&self) -> SynthLog {
+        let mut synth = Synth::new(self);
+        synth.run(self);
+        SynthLog {
+            config: self.clone(),
+            log: synth.log,
+            expected: synth.expected,
+        }
+    }
+}
+
+/// The quantifiers are `forall x. h(x) = k(x)` with trigger `h(x)` for
+/// single instantiations and `forall x. f(x) = f(g(x))` with trigger `f(x)`
+/// for matching loops. Alternating loops use `forall x. a(x) = b(s(x))`
+/// and `forall x. b(x) = a(t(x))`.
+struct Quant {
+    id: usize,
+    pattern: usize,
+    /// `h` or `f`.
+    trigger_fn: String,
+    /// `k` or `g`.
+    result_fn: String,
+}
+
+struct Constant {
+    id: usize,
+    /// An equality `self = to`, and the frame in which it was added.
+    eq: Option<(usize, Option<usize>)>,
+}
+
+/// A `[push]`ed frame which has not been popped yet.
+struct Frame {
+    id: usize,
+    insts: usize,
+    /// The last term yielded by an instantiation within this frame.
+    yielded: Option<usize>,
+}
+
+enum Task {
+    Inst(usize),
+    /// The indices into `Synth::loops` of the quantifiers which take turns,
+    /// and the length.
+    Loop(Vec<usize>, usize),
+    TheorySolving,
+    Conflict,
+    /// Ends the current query and begins the next one.
+    Check,
+    Equality,
+    Push,
+    Pop,
+    ImplicitPop,
+}
+
+struct Synth {
+    rng: Rng,
+    log: String,
+    expected: Expected,
+    next_id: usize,
+    next_fingerprint: u64,
+    constants: Vec<Constant>,
+    quants: Vec<Quant>,
+    loops: Vec<Quant>,
+    frames: Vec<Frame>,
+    next_frame: usize,
+    /// The terms yielded by quantifier instantiations so far.
+    yielded: Vec<usize>,
+    proofs: bool,
+    false_term: usize,
+    /// The `quant-inst` proof steps (and their lemma) of the instantiations
+    /// in the current query.
+    query_insts: Vec<(usize, usize)>,
+}
+
+impl Synth {
+    fn new(config: &SynthConfig) -> Self {
+        Self {
+            rng: Rng::with_seed(config.seed),
+            log: String::new(),
+            expected: Expected::default(),
+            next_id: 1,
+            next_fingerprint: 1,
+            constants: Vec::new(),
+            quants: Vec::new(),
+            loops: Vec::new(),
+            frames: Vec::new(),
+            next_frame: 0,
+            yielded: Vec::new(),
+            proofs: config.proofs,
+            false_term: 0,
+            query_insts: Vec::new(),
+        }
+    }
+
+    fn line(&mut self, args: fmt::Arguments) {
+        self.log.write_fmt(args).unwrap();
+        self.log.push('\n');
+    }
+
+    fn term(&mut self, name: &str, args: &[usize]) -> usize {
+        self.app("[mk-app]", name, args)
+    }
+
+    /// A proof step, the last argument is its conclusion.
+    fn proof(&mut self, rule: &str, args: &[usize]) -> usize {
+        self.app("[mk-proof]", rule, args)
+    }
+
+    fn app(&mut self, entry: &str, name: &str, args: &[usize]) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        let mut line = format!("{entry} #{id} {name}");
+        for arg in args {
+            write!(line, " #{arg}").unwrap();
+        }
+        self.line(format_args!("{line}"));
+        id
+    }
+
+    fn enode(&mut self, term: usize, generation: usize) {
+        self.line(format_args!("[attach-enode] #{term} {generation}"));
+        self.expected.enodes += 1;
+    }
+
+    fn fingerprint(&mut self) -> u64 {
+        let fingerprint = self.next_fingerprint;
+        self.next_fingerprint += 1;
+        fingerprint
+    }
+
+    /// A looping quantifier wraps its result in `next_fn`, the trigger
+    /// function of the next quantifier of the loop.
+    fn quantifier(
+        &mut self,
+        name: &str,
+        trigger_fn: String,
+        result_fn: String,
+        next_fn: Option<&str>,
+    ) {
+        let looping = next_fn.is_some();
+        let var = self.next_id;
+        self.next_id += 1;
+        self.line(format_args!("[mk-var] #{var} 0"));
+        let trigger = self.term(&trigger_fn, &[var]);
+        let result = self.term(&result_fn, &[var]);
+        let rhs = match next_fn {
+            Some(next_fn) => self.term(next_fn, &[result]),
+            None => result,
+        };
+        let body = self.term("=", &[trigger, rhs]);
+        let pattern = self.term("pattern", &[trigger]);
+        let id = self.next_id;
+        self.next_id += 1;
+        self.line(format_args!("[mk-quant] #{id} {name} 1 #{pattern} #{body}"));
+        self.line(format_args!("[attach-var-names] #{id} (|x| ; |Int|)"));
+        let quant = Quant {
+            id,
+            pattern,
+            trigger_fn,
+            result_fn,
+        };
+        if looping {
+            self.loops.push(quant);
+        } else {
+            self.quants.push(quant);
+        }
+        self.expected.quantifiers += 1;
+        self.expected.quant_insts.push((name.to_string(), 0));
+    }
+
+    fn yield_term(&mut self, term: usize) {
+        self.yielded.push(term);
+        if let Some(frame) = self.frames.last_mut() {
+            frame.yielded = Some(term);
+        }
+    }
+
+    fn inst_done(&mut self, quant_name_idx: Option<usize>) {
+        self.expected.insts += 1;
+        if let Some(idx) = quant_name_idx {
+            self.expected.quant_insts[idx].1 += 1;
+        }
+        if let Some(frame) = self.frames.last_mut() {
+            frame.insts += 1;
+        }
+    }
+
+    fn run(&mut self, config: &SynthConfig) {
+        self.line(format_args!("[tool-version] Z3 4.12.2"));
+        self.term("true", &[]);
+        self.false_term = self.term("false", &[]);
+        for idx in 0..config.quantifiers.max(1) {
+            self.quantifier(
+                &format!("q{idx}"),
+                format!("h{idx}"),
+                format!("k{idx}"),
+                None,
+            );
+        }
+        for idx in 0..config.matching_loops.len() {
+            let trigger_fn = format!("f{idx}");
+            self.quantifier(
+                &format!("ml{idx}"),
+                trigger_fn.clone(),
+                format!("g{idx}"),
+                Some(&trigger_fn),
+            );
+        }
+        for idx in 0..config.alternating_loops.len() {
+            let (a, b) = (format!("a{idx}"), format!("b{idx}"));
+            self.quantifier(
+                &format!("alt{idx}a"),
+                a.clone(),
+                format!("s{idx}"),
+                Some(&b),
+            );
+            self.quantifier(&format!("alt{idx}b"), b, format!("t{idx}"), Some(&a));
+        }
+        #[cfg(feature = "analysis")]
+        {
+            self.expected.matching_loops = config
+                .matching_loops
+                .iter()
+                .chain(&config.alternating_loops)
+                .filter(|&&len| len >= MIN_MATCHING_LOOP_LENGTH)
+                .count();
+        }
+        for idx in 0..config.constants.max(1) {
+            let id = self.term(&format!("c{idx}"), &[]);
+            self.enode(id, 0);
+            self.constants.push(Constant { id, eq: None });
+        }
+
+        let mut tasks = Vec::new();
+        tasks.extend(
+            (0..config.instantiations).map(|_| Task::Inst(self.rng.usize(..self.quants.len()))),
+        );
+        tasks.extend(
+            config
+                .matching_loops
+                .iter()
+                .enumerate()
+                .map(|(idx, &len)| Task::Loop(vec![idx], len)),
+        );
+        let alternating = config.matching_loops.len();
+        tasks.extend(
+            config
+                .alternating_loops
+                .iter()
+                .enumerate()
+                .map(|(idx, &len)| {
+                    let a = alternating + 2 * idx;
+                    Task::Loop(vec![a, a + 1], len)
+                }),
+        );
+        tasks.extend((0..config.theory_solving).map(|_| Task::TheorySolving));
+        tasks.extend((0..config.conflicts).map(|_| Task::Conflict));
+        tasks.extend((1..config.queries).map(|_| Task::Check));
+        tasks.extend((0..config.equalities).map(|_| Task::Equality));
+        tasks.extend((0..config.frames).map(|_| Task::Push));
+        tasks.extend(
+            (0..config.frames)
+                .filter(|_| self.rng.bool())
+                .map(|_| Task::Pop),
+        );
+        tasks.extend((0..config.implicit_pops).map(|_| Task::ImplicitPop));
+        self.rng.shuffle(&mut tasks);
+        if config.queries > 0 {
+            self.begin_check();
+        }
+        for task in tasks {
+            match task {
+                Task::Inst(quant) => self.instantiate(quant),
+                Task::Loop(quants, len) => self.matching_loop(&quants, len),
+                Task::TheorySolving => self.theory_solving(),
+                // Conflicts before any instantiation are skipped.
+                Task::Conflict => self.conflict(),
+                Task::Check => {
+                    self.refute();
+                    self.line(format_args!("[query-done] unsat"));
+                    self.begin_check();
+                }
+                Task::Equality => self.equality(),
+                Task::Push => self.push(),
+                // Pops before any push are skipped.
+                Task::Pop => self.pop(),
+                Task::ImplicitPop => self.implicit_pop(),
+            }
+        }
+        self.refute();
+        if config.queries > 0 {
+            self.line(format_args!("[query-done] unsat"));
+        }
+        self.line(format_args!("[eof]"));
+    }
+
+    fn instantiate(&mut self, quant: usize) {
+        let bound = self.rng.usize(..self.constants.len());
+        // Match modulo an equality `trigger = bound` if there is one.
+        let equal: Vec<_> = (0..self.constants.len())
+            .filter(|&c| self.constants[c].eq.is_some_and(|(to, _)| to == bound))
+            .collect();
+        let matched =
+            (!equal.is_empty() && self.rng.bool()).then(|| equal[self.rng.usize(..equal.len())]);
+        let bound_id = self.constants[bound].id;
+        let Quant {
+            id,
+            pattern,
+            ref trigger_fn,
+            ref result_fn,
+        } = self.quants[quant];
+        let (trigger_fn, result_fn) = (trigger_fn.clone(), result_fn.clone());
+        let matched_id = matched.map_or(bound_id, |c| self.constants[c].id);
+        let trigger = self.term(&trigger_fn, &[matched_id]);
+        self.enode(trigger, 0);
+        let fingerprint = self.fingerprint();
+        let blame_eq = matched
+            .map(|_| format!(" (#{matched_id} #{bound_id})"))
+            .unwrap_or_default();
+        self.line(format_args!(
+            "[new-match] 0x{fingerprint:x} #{id} #{pattern} #{bound_id} ; #{trigger}{blame_eq}"
+        ));
+        let result = self.term(&result_fn, &[bound_id]);
+        let lemma = self.term("=", &[trigger, result]);
+        let proof = self.quant_inst(lemma);
+        self.line(format_args!("[instance] 0x{fingerprint:x} #{proof} ; 1"));
+        self.enode(result, 1);
+        self.yield_term(result);
+        self.line(format_args!("[end-of-instance]"));
+        self.inst_done(Some(quant));
+    }
+
+    /// The `n`th instantiation is of `quants[n % quants.len()]`.
+    fn matching_loop(&mut self, quants: &[usize], len: usize) {
+        let mut bound = self.constants[self.rng.usize(..self.constants.len())].id;
+        let first_fn = self.loops[quants[0]].trigger_fn.clone();
+        let mut trigger = self.term(&first_fn, &[bound]);
+        self.enode(trigger, 0);
+        for generation in 1..=len {
+            let quant = quants[(generation - 1) % quants.len()];
+            let next_quant = quants[generation % quants.len()];
+            let Quant {
+                id,
+                pattern,
+                ref result_fn,
+                ..
+            } = self.loops[quant];
+            let result_fn = result_fn.clone();
+            let next_fn = self.loops[next_quant].trigger_fn.clone();
+            let fingerprint = self.fingerprint();
+            self.line(format_args!(
+                "[new-match] 0x{fingerprint:x} #{id} #{pattern} #{bound} ; #{trigger}"
+            ));
+            let result = self.term(&result_fn, &[bound]);
+            let next = self.term(&next_fn, &[result]);
+            let lemma = self.term("=", &[trigger, next]);
+            let proof = self.quant_inst(lemma);
+            self.line(format_args!(
+                "[instance] 0x{fingerprint:x} #{proof} ; {generation}"
+            ));
+            self.enode(result, generation);
+            self.enode(next, generation);
+            self.yield_term(next);
+            self.line(format_args!("[end-of-instance]"));
+            self.inst_done(Some(self.quants.len() + quant));
+            (bound, trigger) = (result, next);
+        }
+    }
+
+    /// The term which the `[instance]` of a quantifier instantiation proving
+    /// `lemma` refers to.
+    fn quant_inst(&mut self, lemma: usize) -> usize {
+        if !self.proofs {
+            return lemma;
+        }
+        let step = self.proof("quant-inst", &[lemma]);
+        self.query_insts.push((step, lemma));
+        step
+    }
+
+    /// Ends the current query (or the log), most are refuted using a random
+    /// subset of their quantifier instantiations.
+    fn refute(&mut self) {
+        let insts = std::mem::take(&mut self.query_insts);
+        if !self.proofs || self.rng.usize(..4) == 0 {
+            return;
+        }
+        let mut premises = Vec::new();
+        for (step, lemma) in insts {
+            if self.rng.bool() {
+                premises.push(self.proof("mp", &[step, lemma]));
+                self.expected.used_insts += 1;
+            } else {
+                self.expected.wasted_insts += 1;
+            }
+        }
+        premises.push(self.false_term);
+        self.proof("unit-resolution", &premises);
+        self.expected.refutations += 1;
+    }
+
+    fn theory_solving(&mut self) {
+        let constant = self.constants[self.rng.usize(..self.constants.len())].id;
+        self.line(format_args!(
+            "[inst-discovered] theory-solving 0x0 arith# ; #{constant}"
+        ));
+        let result = self.term("+", &[constant, constant]);
+        self.line(format_args!("[instance] 0x0 #{result} ; 1"));
+        self.enode(result, 1);
+        self.line(format_args!("[end-of-instance]"));
+        self.inst_done(None);
+        self.expected.theory_solving += 1;
+    }
+
+    fn begin_check(&mut self) {
+        let depth = self.frames.len();
+        self.line(format_args!("[begin-check] {depth}"));
+    }
+
+    /// Decides on a yielded term, propagates its negation and immediately
+    /// learns the negation of the decision.
+    fn conflict(&mut self) {
+        if self.yielded.is_empty() {
+            return;
+        }
+        let term = self.yielded[self.rng.usize(..self.yielded.len())];
+        self.line(format_args!("[decide] #{term}"));
+        self.line(format_args!("[assign] #{term} decision axiom"));
+        self.line(format_args!("[resolve-process] #{term}"));
+        self.line(format_args!("[resolve-lit] 0 #{term}"));
+        self.line(format_args!("[conflict] (not #{term})"));
+    }
+
+    /// Adds `from = to` for two constants, `to` is always the lower index so
+    /// that the equalities form a forest.
+    fn equality(&mut self) {
+        let candidates: Vec<_> = (1..self.constants.len())
+            .filter(|&c| self.constants[c].eq.is_none())
+            .collect();
+        if candidates.is_empty() {
+            return;
+        }
+        let from = candidates[self.rng.usize(..candidates.len())];
+        let to = self.rng.usize(..from);
+        let frame = self.frames.last().map(|f| f.id);
+        self.constants[from].eq = Some((to, frame));
+        let (from, to) = (self.constants[from].id, self.constants[to].id);
+        self.line(format_args!("[eq-expl] #{from} ax ; #{to}"));
+        self.expected.given_eqs += 1;
+    }
+
+    fn push(&mut self) {
+        let depth = self.frames.len();
+        self.line(format_args!("[push] {depth}"));
+        self.frames.push(Frame {
+            id: self.next_frame,
+            insts: 0,
+            yielded: None,
+        });
+        self.next_frame += 1;
+        self.expected.frames += 1;
+    }
+
+    fn pop(&mut self) {
+        let depth = self.frames.len();
+        if depth == 0 {
+            return;
+        }
+        let count = self.rng.usize(1..=depth);
+        self.line(format_args!("[pop] {count} {depth}"));
+        for _ in 0..count {
+            let frame = self.frames.pop().unwrap();
+            self.expected.popped_frames += 1;
+            self.expected.lost_insts += frame.insts;
+            self.drop_equalities(&frame);
+        }
+    }
+
+    /// Pops the top frame without logging it and pushes a new one, the parser
+    /// only notices the pop then. The last term yielded within the popped
+    /// frame is then used in an equality, which requires z3 to first attach
+    /// a new e-node to it.
+    fn implicit_pop(&mut self) {
+        let Some(frame) = self.frames.pop() else {
+            return;
+        };
+        self.expected.popped_frames += 1;
+        self.expected.lost_insts += frame.insts;
+        self.drop_equalities(&frame);
+        self.push();
+        if let Some(term) = frame.yielded {
+            self.line(format_args!("[attach-enode] #{term} 0"));
+            self.expected.enodes += 1;
+            let constant = self.constants[0].id;
+            self.line(format_args!("[eq-expl] #{term} ax ; #{constant}"));
+            self.expected.given_eqs += 1;
+        }
+    }
+
+    /// The equalities added within `frame` are gone once it is popped.
+    fn drop_equalities(&mut self, frame: &Frame) {
+        for constant in &mut self.constants {
+            if constant.eq.is_some_and(|(_, f)| f == Some(frame.id)) {
+                constant.eq = None;
+            }
+        }
+    }
+}
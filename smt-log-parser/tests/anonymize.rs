@@ -0,0 +1,131 @@
+//! Anonymizes synthetic logs, which must profile identically afterwards.
+use std::collections::HashMap;
+
+use smt_log_parser::{
+    analysis::{InstGraph, LogInfo},
+    anonymize::{Anonymizer, NameKind},
+    items::ItemCounts,
+    synth::SynthConfig,
+};
+
+mod common;
+
+/// The statistics which must not change with the names.
+#[derive(Debug, PartialEq)]
+struct Stats {
+    counts: ItemCounts,
+    quant_insts: Vec<u64>,
+    matching_loops: usize,
+}
+
+impl Stats {
+    fn of(log: &str) -> Self {
+        let mut parser = common::parse(log);
+        let info = LogInfo::new(&parser);
+        let mut graph = InstGraph::new(&parser).unwrap();
+        Self {
+            counts: parser.item_counts(),
+            quant_insts: info.quants.0.iter().copied().collect(),
+            matching_loops: graph.search_matching_loops(&mut parser),
+        }
+    }
+}
+
+#[test]
+fn anonymize_random() {
+    for seed in common::seeds(64) {
+        let synth = SynthConfig::random(seed).generate();
+        let mut anonymizer = Anonymizer::new(false);
+        let mut anonymized = Vec::new();
+        anonymizer
+            .anonymize(synth.log.as_bytes(), &mut anonymized)
+            .unwrap();
+        let anonymized = String::from_utf8(anonymized).unwrap();
+        assert_eq!(Stats::of(&synth.log), Stats::of(&anonymized), "seed {seed}");
+
+        // Synthetic names look like opaque ones (e.g. `f0`), so compare the
+        // logs token by token.
+        let renamed: HashMap<_, _> = anonymizer
+            .mapping()
+            .iter()
+            .map(|(opaque, original)| (original.as_str(), opaque.as_str()))
+            .collect();
+        for (line, anon) in synth.log.lines().zip(anonymized.lines()) {
+            let (line, anon): (Vec<_>, Vec<_>) = (tokens(line).collect(), tokens(anon).collect());
+            assert_eq!(line.len(), anon.len(), "{anon:?}");
+            for (token, anon) in line.into_iter().zip(anon) {
+                assert_eq!(renamed.get(token).copied().unwrap_or(token), anon);
+            }
+        }
+        for (name, _) in &synth.expected.quant_insts {
+            assert!(renamed.contains_key(name.as_str()), "{name} not replaced");
+        }
+        assert!(renamed.contains_key("c0") && renamed.contains_key("x"));
+    }
+}
+
+fn tokens(line: &str) -> impl Iterator<Item = &str> {
+    line.split(|c: char| c.is_ascii_whitespace() || "|();".contains(c))
+        .filter(|t| !t.is_empty())
+}
+
+#[test]
+fn anonymize_names() {
+    let mut anonymizer = Anonymizer::new(false);
+    // Quoted names may contain spaces.
+    assert_eq!(
+        anonymizer.line("[mk-app] #5 |foo bar| #3 #4"),
+        "[mk-app] #5 |f0| #3 #4"
+    );
+    assert_eq!(anonymizer.line("[mk-app] #6 |foo bar|"), "[mk-app] #6 |f0|");
+    // Skolem constants are kept, a user symbol `k` is not.
+    assert_eq!(anonymizer.line("[mk-app] #7 k!12"), "[mk-app] #7 k!12");
+    assert_eq!(anonymizer.line("[mk-app] #8 k #7"), "[mk-app] #8 f1 #7");
+    assert_eq!(
+        anonymizer.line("[mk-app] #9 + #7 #8"),
+        "[mk-app] #9 + #7 #8"
+    );
+    assert_eq!(anonymizer.name("foo!3", NameKind::Function), "f2!3");
+
+    let mut anonymizer = Anonymizer::new(true);
+    assert_eq!(anonymizer.line("[mk-app] #7 k!12"), "[mk-app] #7 f0!12");
+}
+
+/// Theory symbols are only kept when applied to the right number of
+/// arguments and values only when z3 attached a meaning to them, a user
+/// symbol named `map` is renamed.
+#[test]
+fn anonymize_theory_symbols() {
+    const LOG: &str = "\
+[mk-app] #1 c
+[mk-app] #2 1
+[attach-meaning] #2 arith 1
+[mk-app] #3 map #1 #2
+[mk-app] #4 + #1 #2
+[mk-app] #5 select #3 #2
+[mk-app] #6 select #3
+[mk-app] #7 const #1
+[mk-app] #8 3
+[attach-var-names] #9 (|map| ; |Int|)
+";
+    let mut anonymizer = Anonymizer::new(false);
+    let mut anonymized = Vec::new();
+    anonymizer
+        .anonymize(LOG.as_bytes(), &mut anonymized)
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(anonymized).unwrap(),
+        "\
+[mk-app] #1 f0
+[mk-app] #2 1
+[attach-meaning] #2 arith 1
+[mk-app] #3 f1 #1 #2
+[mk-app] #4 + #1 #2
+[mk-app] #5 select #3 #2
+[mk-app] #6 f2 #3
+[mk-app] #7 f3 #1
+[mk-app] #8 f4
+[attach-var-names] #9 (|f1| ; |Int|)
+"
+    );
+}
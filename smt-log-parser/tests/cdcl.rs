@@ -0,0 +1,104 @@
+//! Relates the decisions and conflicts of the SAT core to the instantiations
+//! which produced their literals.
+#![cfg(feature = "analysis")]
+
+use smt_log_parser::{
+    analysis::LogInfo,
+    items::{DecisionKind, Justification, ResolveStep},
+    LogParser, Z3Parser,
+};
+
+/// The instantiation of `q` yields `(= (f c) (g c))`, which is decided and
+/// then ends up in the learned clause. `p` is not produced by any
+/// instantiation.
+const LOG: &str = "\
+[tool-version] Z3 4.12.2
+[mk-app] #1 true
+[mk-app] #2 false
+[mk-var] #3 0
+[mk-app] #4 f #3
+[mk-app] #5 g #3
+[mk-app] #6 = #4 #5
+[mk-app] #7 pattern #4
+[mk-quant] #8 q 1 #7 #6
+[attach-var-names] #8 (|x| ; |Int|)
+[mk-app] #9 c
+[attach-enode] #9 0
+[mk-app] #10 f #9
+[attach-enode] #10 0
+[new-match] 0x1 #8 #7 #9 ; #10
+[mk-app] #11 g #9
+[mk-app] #12 = #10 #11
+[instance] 0x1 #12 ; 1
+[attach-enode] #11 1
+[assign] #12 axiom
+[end-of-instance]
+[mk-app] #13 p
+[attach-enode] #13 0
+[mk-app] #14 or #12 #13
+[decide] #12
+[assign] #12 decision axiom
+[assign] (not #13) bin #12
+[assign] #14 clause #12 #13
+[assign] #13 justification -1: #12
+[decide-and-or] #14 #13
+[resolve-process] (not #13)
+[resolve-lit] 0 #12
+[conflict] (not #12) #13
+[eof]
+";
+
+#[test]
+fn cdcl_events() {
+    let parser = Z3Parser::from_str(LOG).process_all().unwrap();
+    assert!(parser.diagnostics().is_empty());
+    let inst = parser.instantiations().first_key().unwrap();
+
+    let decisions: Vec<_> = parser.decisions().iter().collect();
+    let [decide, and_or] = &decisions[..] else {
+        panic!("{decisions:?}");
+    };
+    let DecisionKind::Literal(literal) = decide.kind else {
+        panic!("{decide:?}");
+    };
+    assert!(literal.value);
+    assert_eq!(decide.produced_by, Some(inst));
+    assert!(matches!(and_or.kind, DecisionKind::AndOr { .. }));
+    assert_eq!(and_or.produced_by, None);
+
+    let assignments: Vec<_> = parser.assignments().iter().collect();
+    assert_eq!(assignments.len(), 5);
+    assert_eq!(assignments[0].created_by, Some(inst));
+    assert!(assignments[1].decision);
+    assert!(assignments[1..].iter().all(|a| a.created_by.is_none()));
+    let justifications: Vec<_> = assignments.iter().map(|a| &a.justification).collect();
+    assert!(matches!(justifications[2], Justification::BinClause(lit) if lit.value));
+    assert!(matches!(justifications[3], Justification::Clause(lits) if lits.len() == 2));
+    assert!(
+        matches!(justifications[4], Justification::Theory { theory: -1, literals } if literals.len() == 1)
+    );
+
+    let conflicts: Vec<_> = parser.conflicts().iter().collect();
+    let [conflict] = &conflicts[..] else {
+        panic!("{conflicts:?}");
+    };
+    assert_eq!(conflict.clause.len(), 2);
+    assert!(!conflict.clause[0].value && conflict.clause[1].value);
+    assert!(matches!(conflict.resolution[0], ResolveStep::Process(lit) if !lit.value));
+    assert!(matches!(
+        conflict.resolution[1],
+        ResolveStep::Lit { level: 0, .. }
+    ));
+    assert_eq!(conflict.last_decision, parser.decisions().last_key());
+    assert_eq!(&*conflict.produced_by, &[inst]);
+
+    let cdcl = LogInfo::new(&parser).cdcl;
+    assert_eq!(cdcl.decisions, 2);
+    assert_eq!(cdcl.propagations, 4);
+    assert_eq!(cdcl.conflicts, 1);
+    assert_eq!(cdcl.learned_literals, 2);
+    assert_eq!(cdcl.inst_assignments, 1);
+    assert_eq!(cdcl.inst_decisions, 1);
+    assert_eq!(cdcl.inst_conflicts, 1);
+    assert_eq!(cdcl.conflict_quants.raw, [1]);
+}
@@ -0,0 +1,37 @@
+//! Helpers shared by the integration tests, not every test uses all of them.
+#![allow(dead_code)]
+
+use smt_log_parser::{LogParser, Z3Parser};
+
+/// The seeds of the random models to check, `cases` of them unless
+/// overridden with `SLP_SYNTH_CASES`. A failing case can be reproduced with
+/// `SLP_SYNTH_SEED`.
+pub fn seeds(cases: u64) -> Vec<u64> {
+    if let Some(seed) = std::env::var("SLP_SYNTH_SEED")
+        .ok()
+        .and_then(|s| s.parse().ok())
+    {
+        return vec![seed];
+    }
+    let cases = std::env::var("SLP_SYNTH_CASES")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(cases);
+    (0..cases).collect()
+}
+
+/// Parses the z3 log in strict mode, which must not report any diagnostics.
+pub fn parse(log: &str) -> Z3Parser {
+    parse_with::<Z3Parser>(log, false)
+}
+
+/// As [`parse`], but with any parser which can be turned into a
+/// [`Z3Parser`] and optionally tracking the provenance of items.
+pub fn parse_with<P: LogParser + Into<Z3Parser>>(log: &str, provenance: bool) -> Z3Parser {
+    let mut parser = P::from_str(log);
+    parser.set_strict(true);
+    parser.set_provenance(provenance);
+    let parser: Z3Parser = parser.process_all().unwrap().into();
+    assert!(parser.diagnostics().is_empty(), "{log}");
+    parser
+}
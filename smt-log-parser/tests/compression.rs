@@ -0,0 +1,174 @@
+//! Compresses synthetic logs and parses them through both the sync and the
+//! async decompressing readers.
+#![cfg(feature = "compression")]
+
+use std::{
+    cell::Cell,
+    io::Write,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use futures::AsyncRead;
+use smt_log_parser::{
+    analysis::LogInfo,
+    items::ItemCounts,
+    parsers::{AsyncBufferRead, AsyncDecompressed, Compression, Decompressed, ParseState},
+    synth::SynthConfig,
+    LogParser, Z3Parser,
+};
+
+/// The statistics which must not change with the compression.
+#[derive(Debug, PartialEq)]
+struct Stats {
+    counts: ItemCounts,
+    quant_insts: Vec<u64>,
+    diagnostics: usize,
+}
+
+impl Stats {
+    fn of(parser: &Z3Parser) -> Self {
+        let info = LogInfo::new(parser);
+        Self {
+            counts: parser.item_counts(),
+            quant_insts: info.quants.0.iter().copied().collect(),
+            diagnostics: parser.diagnostics().len(),
+        }
+    }
+}
+
+fn gzip(log: &str) -> Vec<u8> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(log.as_bytes()).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn xz(log: &str) -> Vec<u8> {
+    let options = lzma_rust2::XzOptions::with_preset(6);
+    let mut encoder = lzma_rust2::XzWriter::new(Vec::new(), options).unwrap();
+    encoder.write_all(log.as_bytes()).unwrap();
+    encoder.finish().unwrap()
+}
+
+/// There is no pure Rust zstd encoder, instead the log is stored in raw
+/// (uncompressed) blocks. The first half and the second half are written as
+/// separate frames since the decoder must continue after the first one.
+fn zstd(log: &str) -> Vec<u8> {
+    const MAX_BLOCK: usize = 1 << 17;
+    let (first, second) = log.as_bytes().split_at(log.len() / 2);
+    let mut out = Vec::new();
+    for frame in [first, second] {
+        out.extend_from_slice(&[0x28, 0xb5, 0x2f, 0xfd]);
+        // No content size, checksum or dictionary with a window of
+        // `MAX_BLOCK` bytes.
+        out.extend_from_slice(&[0x00, 0x38]);
+        let blocks: Vec<_> = frame.chunks(MAX_BLOCK).collect();
+        let blocks = if blocks.is_empty() {
+            vec![&[][..]]
+        } else {
+            blocks
+        };
+        for (idx, block) in blocks.iter().enumerate() {
+            let last = (idx + 1 == blocks.len()) as u32;
+            // Block type 0 (raw) in bits 1-2.
+            let header = last | (block.len() as u32) << 3;
+            out.extend_from_slice(&header.to_le_bytes()[..3]);
+            out.extend_from_slice(block);
+        }
+    }
+    out
+}
+
+#[test]
+fn compressed_roundtrip() {
+    type Encoder = fn(&str) -> Vec<u8>;
+    let encoders: [(Compression, Encoder); 3] = [
+        (Compression::Gzip, gzip),
+        (Compression::Xz, xz),
+        (Compression::Zstd, zstd),
+    ];
+    for seed in 0..8 {
+        let synth = SynthConfig::random(seed).generate();
+        let parser = Z3Parser::from_str(&synth.log).process_all().unwrap();
+        let expected = Stats::of(&parser);
+        for (compression, encode) in encoders {
+            let bytes = encode(&synth.log);
+            assert_eq!(Compression::detect(&bytes), compression);
+
+            let reader = Decompressed::new(&bytes[..]).unwrap();
+            assert_eq!(reader.compression(), compression);
+            let parser = reader.into_parser::<Z3Parser>().process_all().unwrap();
+            assert_eq!(Stats::of(&parser), expected, "{compression:?} seed {seed}");
+
+            let parser = futures::executor::block_on(async {
+                let reader = AsyncDecompressed::new(&bytes[..]).await.unwrap();
+                assert_eq!(reader.compression(), compression);
+                reader.into_async_parser::<Z3Parser>().process_all().await
+            })
+            .unwrap();
+            assert_eq!(
+                Stats::of(&parser),
+                expected,
+                "async {compression:?} seed {seed}"
+            );
+        }
+    }
+}
+
+/// An async reader which counts how much of the input was read from it.
+struct CountingReader<'a> {
+    input: &'a [u8],
+    read: Rc<Cell<usize>>,
+}
+
+impl AsyncRead for CountingReader<'_> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = self.get_mut();
+        let read = buf.len().min(this.input.len());
+        buf[..read].copy_from_slice(&this.input[..read]);
+        this.input = &this.input[read..];
+        this.read.set(this.read.get() + read);
+        Poll::Ready(Ok(read))
+    }
+}
+
+/// The async reader decompresses the input as it is parsed and reports the
+/// progress in terms of the compressed input, rather than reading all of it
+/// upfront.
+#[test]
+fn compressed_async_progress() {
+    let mut log = String::from("[tool-version] Z3 4.12.2\n");
+    for idx in 0..100_000 {
+        log.push_str(&format!("[mk-app] #{idx} c{idx}\n"));
+    }
+    log.push_str("[eof]\n");
+    // Stored in raw blocks, so the compressed input is as large as the log.
+    let bytes = zstd(&log);
+    let read = Rc::new(Cell::new(0));
+    let reader = CountingReader {
+        input: &bytes,
+        read: read.clone(),
+    };
+    futures::executor::block_on(async {
+        let reader = reader.buffer_decompressed().await.unwrap();
+        let mut parser = reader.into_async_parser::<Z3Parser>();
+        let state = parser
+            .process_until(|_, state| (state.lines_read == 1000).then_some(()))
+            .await;
+        let ParseState::Paused((), state) = state else {
+            panic!("{state:?}");
+        };
+        assert!(state.bytes_read > 0);
+        assert!(state.bytes_read < bytes.len() / 4, "{state:?}");
+        assert!(read.get() < bytes.len() / 2, "{}", read.get());
+
+        let parser = parser.process_all().await.unwrap();
+        assert_eq!(parser.item_counts().terms, 100_000);
+    });
+    assert_eq!(read.get(), bytes.len());
+}
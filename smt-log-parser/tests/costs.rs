@@ -0,0 +1,134 @@
+//! Computes the cost of instantiations under each cost model.
+#![cfg(feature = "analysis")]
+
+use smt_log_parser::{
+    analysis::{CostModel, InstGraph},
+    items::InstIdx,
+};
+
+mod common;
+
+/// The instantiation of `root` yields `p(c)` and `r(c)`, matched by `left`
+/// and `right` respectively. Their yields `u(c)` and `v(c)` are together
+/// matched by the multi-trigger of `join`, making a diamond.
+const LOG: &str = "\
+[tool-version] Z3 4.12.2
+[mk-app] #1 true
+[mk-app] #2 false
+[mk-var] #3 0
+[mk-app] #4 s #3
+[mk-app] #5 p #3
+[mk-app] #6 r #3
+[mk-app] #7 and #5 #6
+[mk-app] #8 pattern #4
+[mk-quant] #9 root 1 #8 #7
+[attach-var-names] #9 (|x| ; |Int|)
+[mk-app] #10 u #3
+[mk-app] #11 pattern #5
+[mk-quant] #12 left 1 #11 #10
+[attach-var-names] #12 (|x| ; |Int|)
+[mk-app] #13 v #3
+[mk-app] #14 pattern #6
+[mk-quant] #15 right 1 #14 #13
+[attach-var-names] #15 (|x| ; |Int|)
+[mk-var] #16 1
+[mk-app] #17 v #16
+[mk-app] #18 w #3 #16
+[mk-app] #19 pattern #10 #17
+[mk-quant] #20 join 2 #19 #18
+[attach-var-names] #20 (|x| ; |Int|) (|y| ; |Int|)
+[mk-app] #21 c
+[attach-enode] #21 0
+[mk-app] #22 s #21
+[attach-enode] #22 0
+[new-match] 0x1 #9 #8 #21 ; #22
+[mk-app] #23 p #21
+[mk-app] #24 r #21
+[mk-app] #25 and #23 #24
+[instance] 0x1 #25 ; 1
+[attach-enode] #23 1
+[attach-enode] #24 1
+[end-of-instance]
+[new-match] 0x2 #12 #11 #21 ; #23
+[mk-app] #26 u #21
+[instance] 0x2 #26 ; 2
+[attach-enode] #26 2
+[end-of-instance]
+[new-match] 0x3 #15 #14 #21 ; #24
+[mk-app] #27 v #21
+[instance] 0x3 #27 ; 2
+[attach-enode] #27 2
+[end-of-instance]
+[new-match] 0x4 #20 #19 #21 #21 ; #26 #27
+[mk-app] #28 w #21 #21
+[instance] 0x4 #28 ; 3
+[attach-enode] #28 3
+[end-of-instance]
+[eof]
+";
+
+#[test]
+fn cost_models() {
+    let parser = common::parse(LOG);
+    let mut graph = InstGraph::new(&parser).unwrap();
+    // The instantiations are `root`, `left`, `right` and `join`. Except for
+    // `Descendants`, the cost of `join` is split evenly between its parents.
+    let expected = [
+        (CostModel::Uniform, [4.0, 1.5, 1.5, 1.0]),
+        // `p(c)` and `r(c)` are 4 terms, `u(c)` 2 and `w(c, c)` 3.
+        (CostModel::TermSize, [11.0, 3.5, 3.5, 3.0]),
+        (CostModel::ENodes, [5.0, 1.5, 1.5, 1.0]),
+        // `join` is counted once in the cost of `root`.
+        (CostModel::Descendants, [4.0, 2.0, 2.0, 1.0]),
+        (CostModel::Generation, [12.0, 5.0, 5.0, 4.0]),
+        // There is no proof.
+        (CostModel::Proof, [0.0; 4]),
+    ];
+    for (model, expected) in expected {
+        graph.set_cost_model(&parser, model);
+        let costs: Vec<_> = (0..4)
+            .map(|iidx| graph.raw[InstIdx::from(iidx)].cost)
+            .collect();
+        assert_eq!(costs, expected, "{model:?}");
+    }
+}
+
+/// `stats` ranks quantifiers by the selected cost model, which is rejected
+/// for a single query.
+#[test]
+fn stats_command() {
+    let dir = std::env::temp_dir().join(format!("slp-costs-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let log = dir.join("costs.log");
+    std::fs::write(&log, LOG).unwrap();
+    let run = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_smt-log-parser"))
+            .arg("stats")
+            .arg(&log)
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    let output = run(&["--cost-model", "uniform"]);
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let top: Vec<_> = stdout
+        .lines()
+        .skip_while(|line| *line != "top-instantiations=")
+        .skip(1)
+        .take(4)
+        .collect();
+    assert_eq!(
+        top,
+        [
+            "1 = root, 50.0% of cost",
+            "1 = left, 18.8% of cost",
+            "1 = right, 18.8% of cost",
+            "1 = join, 12.5% of cost",
+        ]
+    );
+    assert!(!run(&["--cost-model", "uniform", "--query", "0"])
+        .status
+        .success());
+    std::fs::remove_dir_all(&dir).ok();
+}
@@ -0,0 +1,86 @@
+//! Parses a small canned cvc5 instantiation trace.
+
+use smt_log_parser::{
+    display_with::{DisplayConfiguration, DisplayCtxt, DisplayWithCtxt, SymbolReplacement},
+    formatter::TermDisplayContext,
+    items::{MatchKind, QuantIdx},
+    parsers::any::AnyParser,
+    Z3Parser,
+};
+
+const LOG: &str = include_str!("fixtures/cvc5-inst.log");
+
+mod common;
+
+fn parse() -> Z3Parser {
+    common::parse_with::<AnyParser>(LOG, false)
+}
+
+/// The quantifiers, instantiations and variable names of the fixture.
+#[test]
+fn cvc5_fixture() {
+    let parser = parse();
+    let ctxt = DisplayCtxt {
+        parser: &parser,
+        term_display: &TermDisplayContext::basic(),
+        config: DisplayConfiguration {
+            display_term_ids: false,
+            display_quantifier_name: false,
+            replace_symbols: SymbolReplacement::None,
+            #[cfg(feature = "display_html")]
+            html: false,
+            enode_char_limit: None,
+            ast_depth_limit: None,
+            numeral_radix: Default::default(),
+            numeral_width: 0,
+        },
+    };
+    let names: Vec<_> = parser
+        .quantifiers()
+        .iter()
+        .map(|q| q.kind.user_name().map(|name| &parser.strings[*name]))
+        .collect();
+    assert_eq!(names, [Some("ax_named"), None, None, Some("ax_other")]);
+
+    // Variables of nested quantifiers are named by their own binder.
+    let quant = &parser.quantifiers()[QuantIdx::from(1)];
+    assert_eq!(quant.num_vars, 2);
+    let formula = quant.term.unwrap().with(&ctxt).to_string();
+    assert_eq!(
+        formula,
+        "FORALL x: Int, y: Int :: { f(x, y) } f(x, y) = FORALL z: Int :: (g(x, z) > y)"
+    );
+
+    let mut insts = vec![0; names.len()];
+    let mut logged = Vec::new();
+    for inst in parser.instantiations() {
+        let MatchKind::Reported {
+            quant,
+            source,
+            bound_terms,
+        } = &parser[inst.match_].kind
+        else {
+            panic!("{:?}", parser[inst.match_].kind);
+        };
+        insts[usize::from(*quant)] += 1;
+        if !bound_terms.is_empty() {
+            let source = source.map(|source| &parser.strings[*source]);
+            let terms: Vec<_> = bound_terms
+                .iter()
+                .map(|t| t.with(&ctxt).to_string())
+                .collect();
+            logged.push((usize::from(*quant), source, terms.join(", ")));
+        }
+    }
+    // `num-instantiations` adds those which were not logged in full.
+    assert_eq!(insts, [3, 2, 0, 4]);
+    assert_eq!(
+        logged,
+        [
+            (0, None, "a, b".to_string()),
+            (0, None, "b, c".to_string()),
+            (1, Some("E_MATCHING"), "1, 2".to_string()),
+            (1, None, "3, 1 + 2".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,205 @@
+//! Builds the quantifier dependency graph and runs `dependencies` with a
+//! cost model under which all costs are zero.
+#![cfg(feature = "analysis")]
+
+use std::process::Command;
+
+use smt_log_parser::{
+    analysis::{InstGraph, QuantifierAnalysis},
+    display_with::{DisplayConfiguration, DisplayCtxt, SymbolReplacement},
+    formatter::TermDisplayContext,
+    items::QuantIdx,
+    synth::SynthConfig,
+};
+
+mod common;
+
+/// `a` and `b` instantiate each other: `a` yields `g(s(x))` matching `b`,
+/// which yields `f(t(x))` matching `a`. `leaf` also matches the yield of `a`
+/// but nothing depends on it.
+const LOG: &str = "\
+[tool-version] Z3 4.12.2
+[mk-app] #1 true
+[mk-app] #2 false
+[mk-var] #3 0
+[mk-app] #4 f #3
+[mk-app] #5 s #3
+[mk-app] #6 g #5
+[mk-app] #7 = #4 #6
+[mk-app] #8 pattern #4
+[mk-quant] #9 a 1 #8 #7
+[attach-var-names] #9 (|x| ; |Int|)
+[mk-app] #10 g #3
+[mk-app] #11 t #3
+[mk-app] #12 f #11
+[mk-app] #13 = #10 #12
+[mk-app] #14 pattern #10
+[mk-quant] #15 b 1 #14 #13
+[attach-var-names] #15 (|x| ; |Int|)
+[mk-app] #16 h #3
+[mk-app] #17 = #10 #16
+[mk-quant] #18 leaf 1 #14 #17
+[attach-var-names] #18 (|x| ; |Int|)
+[mk-app] #19 c
+[attach-enode] #19 0
+[mk-app] #20 f #19
+[attach-enode] #20 0
+[new-match] 0x1 #9 #8 #19 ; #20
+[mk-app] #21 s #19
+[mk-app] #22 g #21
+[mk-app] #23 = #20 #22
+[instance] 0x1 #23 ; 1
+[attach-enode] #21 1
+[attach-enode] #22 1
+[end-of-instance]
+[new-match] 0x2 #15 #14 #21 ; #22
+[mk-app] #24 t #21
+[mk-app] #25 f #24
+[mk-app] #26 = #22 #25
+[instance] 0x2 #26 ; 2
+[attach-enode] #24 2
+[attach-enode] #25 2
+[end-of-instance]
+[new-match] 0x3 #18 #14 #21 ; #22
+[mk-app] #27 h #21
+[mk-app] #28 = #22 #27
+[instance] 0x3 #28 ; 2
+[attach-enode] #27 2
+[end-of-instance]
+[new-match] 0x4 #9 #8 #24 ; #25
+[mk-app] #29 s #24
+[mk-app] #30 g #29
+[mk-app] #31 = #25 #30
+[instance] 0x4 #31 ; 3
+[attach-enode] #29 3
+[attach-enode] #30 3
+[end-of-instance]
+[eof]
+";
+
+#[test]
+fn dependency_cycles() {
+    let parser = common::parse(LOG);
+    let mut inst_graph = InstGraph::new(&parser).unwrap();
+    inst_graph.initialise_inst_succs_and_preds(&parser);
+    let analysis = QuantifierAnalysis::new(&parser, &inst_graph);
+    let name = |qidx: QuantIdx| &parser.strings[*parser[qidx].kind.user_name().unwrap()];
+    let sorted = |deps: &mut dyn Iterator<Item = QuantIdx>| {
+        let mut deps: Vec<_> = deps.map(name).collect();
+        deps.sort();
+        deps
+    };
+
+    let direct: Vec<_> = analysis
+        .iter()
+        .map(|info| sorted(&mut info.direct_deps.keys().copied()))
+        .collect();
+    assert_eq!(direct, [["b"], ["a"], ["a"]]);
+    assert!(analysis
+        .iter()
+        .all(|info| info.direct_deps.values().all(|dep| dep.count == 1)));
+    // Under the uniform cost model the first instantiation of `a` carries
+    // the cost of all four.
+    let costs: Vec<_> = analysis
+        .iter()
+        .map(|info| (info.insts, info.costs))
+        .collect();
+    assert_eq!(costs, [(2, 5.0), (1, 2.0), (1, 1.0)]);
+
+    let cycles: Vec<_> = analysis
+        .graph()
+        .cycles()
+        .into_iter()
+        .map(|scc| sorted(&mut scc.into_iter()))
+        .collect();
+    assert_eq!(cycles, [["a", "b"]]);
+
+    let transitive = |steps| -> Vec<_> {
+        let deps = analysis.calculate_transitive(steps);
+        deps.iter()
+            .map(|deps| sorted(&mut deps.iter().copied()))
+            .collect()
+    };
+    assert_eq!(transitive(None), [["a", "b"], ["a", "b"], ["a", "b"]]);
+    assert_eq!(transitive(Some(0)), [["b"], ["a"], ["a"]]);
+    assert_eq!(transitive(Some(1)), transitive(None));
+}
+
+/// `a` and `b`, in a cycle, are drawn in red and `leaf` is not.
+#[test]
+fn dependency_dot() {
+    let parser = common::parse(LOG);
+    let mut inst_graph = InstGraph::new(&parser).unwrap();
+    inst_graph.initialise_inst_succs_and_preds(&parser);
+    let graph = QuantifierAnalysis::new(&parser, &inst_graph).graph();
+    let ctxt = DisplayCtxt {
+        parser: &parser,
+        term_display: &TermDisplayContext::basic(),
+        config: DisplayConfiguration {
+            display_term_ids: false,
+            display_quantifier_name: false,
+            replace_symbols: SymbolReplacement::None,
+            #[cfg(feature = "display_html")]
+            html: false,
+            enode_char_limit: None,
+            ast_depth_limit: None,
+            numeral_radix: Default::default(),
+            numeral_width: 0,
+        },
+    };
+    let dot = graph.to_dot(&ctxt, |quant| format!("id=q{}", usize::from(quant)));
+    let lines: Vec<_> = dot.lines().map(str::trim).collect();
+    for expected in [
+        r#"0 [ label="a\n2 instantiations, 62.5%" shape=box id=q0 color=red penwidth=2]"#,
+        r#"1 [ label="b\n1 instantiations, 25.0%" shape=box id=q1 color=red penwidth=2]"#,
+        r#"2 [ label="leaf\n1 instantiations, 12.5%" shape=box id=q2]"#,
+    ] {
+        assert!(lines.contains(&expected), "{dot}");
+    }
+    let edges: Vec<_> = lines.iter().filter(|line| line.contains("->")).collect();
+    assert_eq!(edges.len(), 3, "{dot}");
+    let red = edges.iter().filter(|line| line.contains("color=red"));
+    assert_eq!(red.count(), 2, "{dot}");
+}
+
+#[test]
+fn dependencies_zero_costs() {
+    let dir = std::env::temp_dir().join(format!("slp-dependencies-{}", std::process::id()));
+    std::fs::remove_dir_all(&dir).ok();
+    std::fs::create_dir_all(&dir).unwrap();
+    let log = dir.join("synth.log");
+    // A proof which uses none of the instantiations, the proof costs are all
+    // zero.
+    let synth = (0..)
+        .map(|seed| {
+            let config = SynthConfig {
+                seed,
+                matching_loops: vec![3],
+                alternating_loops: vec![2],
+                proofs: true,
+                ..Default::default()
+            };
+            config.generate()
+        })
+        .find(|synth| synth.expected.refutations > 0 && synth.expected.used_insts == 0)
+        .unwrap();
+    std::fs::write(&log, synth.log).unwrap();
+
+    for args in [&[][..], &["--depth", "2"], &["--dot"], &["--json"]] {
+        let output = Command::new(env!("CARGO_BIN_EXE_smt-log-parser"))
+            .arg("dependencies")
+            .arg(&log)
+            .args(["--cost-model", "proof"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains("0.0%") || args == ["--json"], "{stdout}");
+        assert!(
+            !stdout.contains("NaN") && !stdout.contains("inf"),
+            "{stdout}"
+        );
+    }
+    std::fs::remove_dir_all(&dir).ok();
+}
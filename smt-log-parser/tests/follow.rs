@@ -0,0 +1,85 @@
+//! Follows a log which is appended to slowly, both with the library and with
+//! the `watch` command.
+#![cfg(feature = "analysis")]
+
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use smt_log_parser::{
+    analysis::LogInfo, parsers::ParseState, synth::SynthConfig, LogParser, Z3Parser,
+};
+
+fn setup(name: &str) -> (PathBuf, String) {
+    let dir = std::env::temp_dir().join(format!("slp-follow-{name}-{}", std::process::id()));
+    std::fs::remove_dir_all(&dir).ok();
+    std::fs::create_dir_all(&dir).unwrap();
+    let log = SynthConfig::default().generate().log;
+    let path = dir.join("slow.log");
+    std::fs::write(&path, "").unwrap();
+    (path, log)
+}
+
+/// Appends the log in a few chunks, each ending in the middle of a line.
+fn append_slowly(path: &Path, log: String) -> JoinHandle<()> {
+    let path = path.to_path_buf();
+    std::thread::spawn(move || {
+        let mut file = std::fs::OpenOptions::new().append(true).open(path).unwrap();
+        let chunks = 5;
+        let len = log.len().div_ceil(chunks);
+        for chunk in log.as_bytes().chunks(len) {
+            std::thread::sleep(Duration::from_millis(500));
+            file.write_all(chunk).unwrap();
+            file.flush().unwrap();
+        }
+    })
+}
+
+fn insts(log: &str) -> u64 {
+    let parser = Z3Parser::from_str(log).process_all().unwrap();
+    LogInfo::new(&parser).inst.insts
+}
+
+#[test]
+fn follow_parser() {
+    let (path, log) = setup("parser");
+    let expected = insts(&log);
+    let writer = append_slowly(&path, log);
+    let (_metadata, mut parser) = Z3Parser::from_file(&path).unwrap();
+    parser.set_follow(Some(Duration::from_millis(10)));
+    parser.set_strict(true);
+    let (state, parser) = parser.process_all_timeout(Duration::from_secs(30));
+    writer.join().unwrap();
+    assert!(
+        matches!(state, ParseState::Completed { .. }),
+        "stopped before `[eof]`"
+    );
+    assert!(parser.diagnostics().is_empty());
+    assert_eq!(LogInfo::new(&parser).inst.insts, expected);
+    std::fs::remove_dir_all(path.parent().unwrap()).ok();
+}
+
+#[test]
+fn follow_watch() {
+    let (path, log) = setup("watch");
+    let expected = format!("no-instantiations: {}\n", insts(&log));
+    let writer = append_slowly(&path, log);
+    let output = Command::new(env!("CARGO_BIN_EXE_smt-log-parser"))
+        .arg("watch")
+        .arg(&path)
+        .args(["--interval", "1"])
+        .output()
+        .unwrap();
+    writer.join().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{output:?}");
+    // Statistics are printed while waiting and once more at the end.
+    assert!(stdout.matches("=== ").count() > 1, "{stdout}");
+    let last = stdout.rsplit("=== ").next().unwrap();
+    assert!(last.contains(&expected), "{stdout}");
+    std::fs::remove_dir_all(path.parent().unwrap()).ok();
+}
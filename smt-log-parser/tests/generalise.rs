@@ -0,0 +1,126 @@
+//! Generalises the terms matched along a matching loop.
+#![cfg(feature = "analysis")]
+
+use smt_log_parser::{
+    analysis::InstGraph,
+    display_with::{DisplayConfiguration, DisplayCtxt, DisplayWithCtxt, SymbolReplacement},
+    formatter::TermDisplayContext,
+    items::{Generalisation, TermIdx, TermKind},
+};
+
+mod common;
+
+/// The loop `f(x, n) = f(g(x), n + 1)`, each instantiation matches the term
+/// yielded by the previous one: `f(c, 0)`, `f(g(c), 1)`, `f(g(g(c)), 2)`...
+const LOG: &str = "\
+[tool-version] Z3 4.12.2
+[mk-app] #1 true
+[mk-app] #2 false
+[mk-var] #3 0
+[mk-var] #4 1
+[mk-app] #5 f #3 #4
+[mk-app] #6 g #3
+[mk-app] #7 1
+[attach-meaning] #7 arith 1
+[mk-app] #8 + #4 #7
+[mk-app] #9 f #6 #8
+[mk-app] #10 = #5 #9
+[mk-app] #11 pattern #5
+[mk-quant] #12 q 2 #11 #10
+[attach-var-names] #12 (|x| ; |Int|) (|n| ; |Int|)
+[mk-app] #13 c
+[attach-enode] #13 0
+[mk-app] #14 0
+[attach-meaning] #14 arith 0
+[attach-enode] #14 0
+[mk-app] #15 f #13 #14
+[attach-enode] #15 0
+[new-match] 0x1 #12 #11 #13 #14 ; #15
+[mk-app] #16 g #13
+[mk-app] #17 f #16 #7
+[mk-app] #18 = #15 #17
+[instance] 0x1 #18 ; 1
+[attach-enode] #16 1
+[attach-enode] #7 1
+[attach-enode] #17 1
+[end-of-instance]
+[new-match] 0x2 #12 #11 #16 #7 ; #17
+[mk-app] #19 g #16
+[mk-app] #20 2
+[attach-meaning] #20 arith 2
+[mk-app] #21 f #19 #20
+[mk-app] #22 = #17 #21
+[instance] 0x2 #22 ; 2
+[attach-enode] #19 2
+[attach-enode] #20 2
+[attach-enode] #21 2
+[end-of-instance]
+[new-match] 0x3 #12 #11 #19 #20 ; #21
+[mk-app] #23 g #19
+[mk-app] #24 3
+[attach-meaning] #24 arith 3
+[mk-app] #25 f #23 #24
+[mk-app] #26 = #21 #25
+[instance] 0x3 #26 ; 3
+[attach-enode] #23 3
+[attach-enode] #24 3
+[attach-enode] #25 3
+[end-of-instance]
+[new-match] 0x4 #12 #11 #23 #24 ; #25
+[mk-app] #27 g #23
+[mk-app] #28 4
+[attach-meaning] #28 arith 4
+[mk-app] #29 f #27 #28
+[mk-app] #30 = #25 #29
+[instance] 0x4 #30 ; 4
+[attach-enode] #27 4
+[attach-enode] #28 4
+[attach-enode] #29 4
+[end-of-instance]
+[eof]
+";
+
+/// The generalised terms matched along the loop, and the integers which each
+/// progression within them is stored as.
+fn matched(log: &str) -> (Vec<String>, Vec<Vec<String>>) {
+    let mut parser = common::parse(log);
+    let mut graph = InstGraph::new(&parser).unwrap();
+    assert_eq!(graph.search_matching_loops(&mut parser), 1);
+    let ctxt = DisplayCtxt {
+        parser: &parser,
+        term_display: &TermDisplayContext::basic(),
+        config: DisplayConfiguration {
+            display_term_ids: false,
+            display_quantifier_name: false,
+            replace_symbols: SymbolReplacement::None,
+            #[cfg(feature = "display_html")]
+            html: false,
+            enode_char_limit: None,
+            ast_depth_limit: None,
+            numeral_radix: Default::default(),
+            numeral_width: 0,
+        },
+    };
+    let matched = &graph.analysis.matching_loop_explanations[0].matched;
+    let display = |term: &TermIdx| term.with(&ctxt).to_string();
+    let mut progressions = Vec::new();
+    let mut todo = matched.clone();
+    while let Some(term) = todo.pop() {
+        let children = &parser[term].child_ids;
+        if parser[term].kind == TermKind::Generalised(Generalisation::Progression) {
+            progressions.push(children.iter().map(display).collect());
+        }
+        todo.extend(children.iter().copied());
+    }
+    (matched.iter().map(display).collect(), progressions)
+}
+
+#[test]
+fn iterated_and_progression() {
+    // `f(c, 0)` was not yielded by the loop, so the terms start from `f(g(c), 1)`.
+    let (matched, progressions) = matched(LOG);
+    assert_eq!(matched, ["f(g(g^k(c)), 1 + k)"]);
+    // `1` and `3` are generalised first, the progression is then filled in by
+    // `2` and kept as its first, second and last integer.
+    assert_eq!(progressions, [["1", "2", "3"]]);
+}
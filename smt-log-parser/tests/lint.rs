@@ -0,0 +1,101 @@
+//! Reports common causes of poor solver performance.
+#![cfg(feature = "analysis")]
+
+use smt_log_parser::analysis::{InstGraph, LintAnalysis, LintConfig, LintLevel, LintRule};
+
+mod common;
+
+/// `m` is only instantiated by MBQI, `e` both by MBQI and by matching its
+/// trigger `r(x)`, three times with the same binding `c`.
+const LOG: &str = "\
+[tool-version] Z3 4.12.2
+[mk-app] #1 true
+[mk-app] #2 false
+[mk-var] #3 0
+[mk-app] #4 p #3
+[mk-app] #5 q #3
+[mk-app] #6 pattern #4
+[mk-quant] #7 m 1 #6 #5
+[attach-var-names] #7 (|x| ; |Int|)
+[mk-app] #8 r #3
+[mk-app] #9 pattern #8
+[mk-quant] #10 e 1 #9 #5
+[attach-var-names] #10 (|x| ; |Int|)
+[mk-app] #11 c
+[attach-enode] #11 0
+[mk-app] #12 r #11
+[attach-enode] #12 0
+[inst-discovered] MBQI 0x1 #7 #11
+[mk-app] #13 q #11
+[instance] 0x1 #13 ; 1
+[attach-enode] #13 1
+[end-of-instance]
+[inst-discovered] MBQI 0x2 #10 #11
+[instance] 0x2 #13 ; 1
+[end-of-instance]
+[new-match] 0x3 #10 #9 #11 ; #12
+[instance] 0x3 #13 ; 1
+[end-of-instance]
+[new-match] 0x4 #10 #9 #11 ; #12
+[instance] 0x4 #13 ; 1
+[end-of-instance]
+[eof]
+";
+
+#[test]
+fn lints() {
+    let parser = common::parse(LOG);
+    let mut inst_graph = InstGraph::new(&parser).unwrap();
+    inst_graph.initialise_inst_succs_and_preds(&parser);
+    let found = |config: &LintConfig| -> Vec<_> {
+        let lints = LintAnalysis::new(&parser, &inst_graph, config);
+        lints
+            .iter()
+            .map(|lint| {
+                let quant = parser[lint.quant].kind.user_name().unwrap();
+                let evidence: Vec<_> = lint.evidence.iter().map(|&i| usize::from(i)).collect();
+                (lint.rule, lint.level, &parser.strings[*quant], evidence)
+            })
+            .collect()
+    };
+
+    // `r` is the symbol of one of the three e-nodes.
+    let mut config = LintConfig::default();
+    assert_eq!(
+        found(&config),
+        [
+            (LintRule::CommonTrigger, LintLevel::Warning, "e", vec![2, 3]),
+            (
+                LintRule::RepeatedBindings,
+                LintLevel::Warning,
+                "e",
+                vec![1, 2, 3]
+            ),
+            (LintRule::MbqiOnly, LintLevel::Info, "m", vec![0]),
+        ]
+    );
+    let lints = LintAnalysis::new(&parser, &inst_graph, &config);
+    assert_eq!(lints[0].trigger, Some(parser[lints[0].quant].triggers[0]));
+    assert_eq!(lints[1].count, 1);
+    assert_eq!(lints[2].value, 1.0);
+
+    // Nothing depends on the yielded `q(c)`, the findings of a rule are
+    // ordered by decreasing value.
+    config[LintRule::UnusedYields].threshold = 1.0;
+    config[LintRule::CommonTrigger].level = LintLevel::Allow;
+    config[LintRule::RepeatedBindings].level = LintLevel::Allow;
+    config[LintRule::MbqiOnly].level = LintLevel::Error;
+    assert_eq!(
+        found(&config),
+        [
+            (LintRule::MbqiOnly, LintLevel::Error, "m", vec![0]),
+            (
+                LintRule::UnusedYields,
+                LintLevel::Warning,
+                "e",
+                vec![1, 2, 3]
+            ),
+            (LintRule::UnusedYields, LintLevel::Warning, "m", vec![0]),
+        ]
+    );
+}
@@ -0,0 +1,164 @@
+//! Finds matching loops which alternate between several quantifiers.
+#![cfg(feature = "analysis")]
+
+use smt_log_parser::{analysis::InstGraph, items::InstIdx, Z3Parser};
+
+mod common;
+
+/// Neither `a` nor `b` loops on its own: `a` yields `g(s(x))` which only
+/// matches the trigger of `b`, which in turn yields `f(t(x))` matching `a`.
+const LOG: &str = "\
+[tool-version] Z3 4.12.2
+[mk-app] #1 true
+[mk-app] #2 false
+[mk-var] #3 0
+[mk-app] #4 f #3
+[mk-app] #5 s #3
+[mk-app] #6 g #5
+[mk-app] #7 = #4 #6
+[mk-app] #8 pattern #4
+[mk-quant] #9 a 1 #8 #7
+[attach-var-names] #9 (|x| ; |Int|)
+[mk-var] #10 0
+[mk-app] #11 g #10
+[mk-app] #12 t #10
+[mk-app] #13 f #12
+[mk-app] #14 = #11 #13
+[mk-app] #15 pattern #11
+[mk-quant] #16 b 1 #15 #14
+[attach-var-names] #16 (|x| ; |Int|)
+[mk-app] #17 c
+[attach-enode] #17 0
+[mk-app] #18 f #17
+[attach-enode] #18 0
+[new-match] 0x1 #9 #8 #17 ; #18
+[mk-app] #19 s #17
+[mk-app] #20 g #19
+[mk-app] #21 = #18 #20
+[instance] 0x1 #21 ; 1
+[attach-enode] #19 1
+[attach-enode] #20 1
+[end-of-instance]
+[new-match] 0x2 #16 #15 #19 ; #20
+[mk-app] #22 t #19
+[mk-app] #23 f #22
+[mk-app] #24 = #20 #23
+[instance] 0x2 #24 ; 2
+[attach-enode] #22 2
+[attach-enode] #23 2
+[end-of-instance]
+[new-match] 0x3 #9 #8 #22 ; #23
+[mk-app] #25 s #22
+[mk-app] #26 g #25
+[mk-app] #27 = #23 #26
+[instance] 0x3 #27 ; 3
+[attach-enode] #25 3
+[attach-enode] #26 3
+[end-of-instance]
+[new-match] 0x4 #16 #15 #25 ; #26
+[mk-app] #28 t #25
+[mk-app] #29 f #28
+[mk-app] #30 = #26 #29
+[instance] 0x4 #30 ; 4
+[attach-enode] #28 4
+[attach-enode] #29 4
+[end-of-instance]
+[eof]
+";
+
+fn search(log: &str) -> (Z3Parser, InstGraph, usize) {
+    let mut parser = common::parse(log);
+    let mut graph = InstGraph::new(&parser).unwrap();
+    let loops = graph.search_matching_loops(&mut parser);
+    (parser, graph, loops)
+}
+
+#[test]
+fn alternating_loop() {
+    let (parser, graph, loops) = search(LOG);
+    assert_eq!(loops, 1);
+    let ml = &graph.analysis.matching_loops[0];
+    let quants: Vec<_> = ml
+        .triggers
+        .iter()
+        .map(|trigger| {
+            let name = parser[trigger.quant].kind.user_name().unwrap();
+            &parser.strings[*name]
+        })
+        .collect();
+    assert_eq!(quants, ["a", "b"]);
+    let insts: Vec<_> = (0..4).map(InstIdx::from).collect();
+    assert_eq!(ml.insts, insts);
+    assert_eq!(ml.chain, insts);
+    assert!(ml.start_depth < ml.end_depth);
+    for inst in insts {
+        assert!(graph.raw[inst].part_of_ml.contains(&0));
+    }
+}
+
+/// Two links are not enough to tell a loop from a one-off.
+#[test]
+fn alternating_too_short() {
+    let (_, graph, loops) = search(LOG.split("[new-match] 0x3").next().unwrap());
+    assert_eq!(loops, 0);
+    assert!(graph.analysis.matching_loops.is_empty());
+}
+
+/// `m` is instantiated by MBQI and yields `f(c)`, which seeds a loop of `a`
+/// from `f(x)` to `f(g(x))`. The creator of the first matched term has no
+/// pattern.
+const MBQI_SEEDED_LOG: &str = "\
+[tool-version] Z3 4.12.2
+[mk-app] #1 true
+[mk-app] #2 false
+[mk-var] #3 0
+[mk-app] #4 p #3
+[mk-app] #5 f #3
+[mk-app] #6 pattern #4
+[mk-quant] #7 m 1 #6 #5
+[attach-var-names] #7 (|x| ; |Int|)
+[mk-app] #8 g #3
+[mk-app] #9 f #8
+[mk-app] #10 pattern #5
+[mk-quant] #11 a 1 #10 #9
+[attach-var-names] #11 (|x| ; |Int|)
+[mk-app] #12 c
+[attach-enode] #12 0
+[inst-discovered] MBQI 0x1 #7 #12
+[mk-app] #13 f #12
+[instance] 0x1 #13 ; 1
+[attach-enode] #13 1
+[end-of-instance]
+[new-match] 0x2 #11 #10 #12 ; #13
+[mk-app] #14 g #12
+[mk-app] #15 f #14
+[instance] 0x2 #15 ; 2
+[attach-enode] #14 2
+[attach-enode] #15 2
+[end-of-instance]
+[new-match] 0x3 #11 #10 #14 ; #15
+[mk-app] #16 g #14
+[mk-app] #17 f #16
+[instance] 0x3 #17 ; 3
+[attach-enode] #16 3
+[attach-enode] #17 3
+[end-of-instance]
+[new-match] 0x4 #11 #10 #16 ; #17
+[mk-app] #18 g #16
+[mk-app] #19 f #18
+[instance] 0x4 #19 ; 4
+[attach-enode] #18 4
+[attach-enode] #19 4
+[end-of-instance]
+[eof]
+";
+
+#[test]
+fn mbqi_seeded_loop() {
+    let (_, graph, loops) = search(MBQI_SEEDED_LOG);
+    assert_eq!(loops, 1);
+    let ml = &graph.analysis.matching_loops[0];
+    let insts: Vec<_> = (1..4).map(InstIdx::from).collect();
+    assert_eq!(ml.chain, insts);
+    assert_eq!(graph.analysis.matching_loop_graphs.len(), 1);
+}
@@ -0,0 +1,124 @@
+//! Minimizes logs while keeping a parse error or their matching loop.
+#![cfg(feature = "analysis")]
+
+use smt_log_parser::{
+    analysis::InstGraph,
+    minimize::{minimize, Predicate},
+    synth::SynthConfig,
+    LogParser, Z3Parser,
+};
+
+fn matching_loops(log: &str) -> usize {
+    let mut parser = Z3Parser::from_str(log).process_all().unwrap();
+    assert!(parser.diagnostics().is_empty(), "{log}");
+    let mut graph = InstGraph::new(&parser).unwrap();
+    graph.search_matching_loops(&mut parser)
+}
+
+#[test]
+fn minimize_matching_loop() {
+    for seed in 0..4 {
+        let config = SynthConfig {
+            seed,
+            frames: 3,
+            conflicts: 2,
+            ..Default::default()
+        };
+        let synth = config.generate();
+        assert_eq!(matching_loops(&synth.log), 1);
+
+        let minimizer = minimize(&synth.log, Predicate::MatchingLoop).unwrap();
+        let (before, after) = minimizer.line_counts();
+        assert!(after < before, "{after} of {before} lines kept");
+        let log = minimizer.log();
+        assert_eq!(log.lines().count(), after);
+        assert!(matching_loops(&log) > 0, "{log}");
+        // The instantiations outside of the loop are removed.
+        assert!(!log.contains(" q0 "), "{log}");
+    }
+}
+
+/// A log without a matching loop is rejected.
+#[test]
+fn minimize_no_matching_loop() {
+    let config = SynthConfig {
+        matching_loops: Vec::new(),
+        ..Default::default()
+    };
+    let synth = config.generate();
+    assert!(minimize(&synth.log, Predicate::MatchingLoop).is_none());
+}
+
+/// No analysis panics on a synthetic log, so there is nothing to minimize.
+#[test]
+fn minimize_no_panic() {
+    for seed in 0..4 {
+        let config = SynthConfig {
+            seed,
+            frames: 3,
+            conflicts: 2,
+            ..Default::default()
+        };
+        let synth = config.generate();
+        assert!(minimize(&synth.log, Predicate::Panic).is_none());
+    }
+}
+
+/// The e-node `h(c)` is attached with a malformed generation.
+const MALFORMED: &str = "\
+[tool-version] Z3 4.12.2
+[mk-app] #1 true
+[mk-app] #2 false
+[mk-var] #3 0
+[mk-app] #4 f #3
+[mk-app] #5 g #3
+[mk-app] #6 = #4 #5
+[mk-app] #7 pattern #4
+[mk-quant] #8 q 1 #7 #6
+[attach-var-names] #8 (|x| ; |Int|)
+[mk-app] #9 c
+[attach-enode] #9 0
+[mk-app] #10 f #9
+[attach-enode] #10 0
+[new-match] 0x1 #8 #7 #9 ; #10
+[mk-app] #11 g #9
+[mk-app] #12 = #10 #11
+[instance] 0x1 #12 ; 1
+[attach-enode] #11 1
+[end-of-instance]
+[mk-app] #13 h #9
+[attach-enode] #13 one
+[eof]
+";
+
+/// Only the malformed line and the term it refers to are kept, along with
+/// the first and last line.
+#[test]
+fn minimize_error() {
+    let predicate = Predicate::Error("InvalidGeneration".to_string());
+    let minimizer = minimize(MALFORMED, predicate).unwrap();
+    let log = minimizer.log();
+    let lines: Vec<_> = log.lines().collect();
+    assert_eq!(
+        lines,
+        [
+            "[tool-version] Z3 4.12.2",
+            "[mk-app] #9 c",
+            "[attach-enode] #9 0",
+            "[mk-app] #13 h #9",
+            "[attach-enode] #13 one",
+            "[eof]",
+        ],
+        "{log}"
+    );
+    let parser = Z3Parser::from_str(&log).process_all().unwrap();
+    let kinds: Vec<_> = parser.diagnostics().iter().map(|d| &d.kind).collect();
+    assert_eq!(kinds, ["InvalidGeneration"]);
+}
+
+/// A log without the error is rejected.
+#[test]
+fn minimize_no_error() {
+    let predicate = Predicate::Error("UnknownId".to_string());
+    assert!(minimize(MALFORMED, predicate).is_none());
+}
@@ -3,15 +3,13 @@ use std::time::{Duration, Instant};
 
 use mem_dbg::*;
 use smt_log_parser::analysis::InstGraph;
-use smt_log_parser::{LogParser, Z3Parser};
+use smt_log_parser::{parsers::ParseState, LogParser, Z3Parser};
 
 #[global_allocator]
 static ALLOCATOR: Cap<std::alloc::System> = Cap::new(std::alloc::System, usize::MAX);
 
 #[test]
 fn parse_all_logs() {
-    std::env::set_var("SLP_TEST_MODE", "true");
-
     let mem = std::env::var("SLP_MEMORY_LIMIT_GB")
         .ok()
         .and_then(|mem| mem.parse().ok());
@@ -38,6 +36,7 @@ fn parse_all_logs() {
 
             let filename = log.path();
             let (metadata, mut parser) = Z3Parser::from_file(&filename).unwrap();
+            parser.set_strict(true);
             let file_size = metadata.len();
             let parse_bytes = file_size.min(parse_limit);
             let mb = 1024_u64 * 1024_u64;
@@ -63,10 +62,13 @@ fn parse_all_logs() {
             );
             let now = Instant::now();
 
-            parser.process_check_every(Duration::from_millis(100), |_, s| {
+            let state = parser.process_check_every(Duration::from_millis(100), |_, s| {
                 assert!(now.elapsed() < timeout, "Parsing took longer than timeout");
                 (parse_limit <= s.bytes_read as u64).then_some(())
             });
+            if let ParseState::Error(err) = state {
+                panic!("{err}");
+            }
             let elapsed = now.elapsed();
             max_parse_ovhd = f64::max(
                 max_parse_ovhd,
@@ -0,0 +1,172 @@
+//! Tracks the stack frames of items across `[push]`/`[pop]` and reports the
+//! work lost to pops.
+#![cfg(feature = "analysis")]
+
+use smt_log_parser::{
+    analysis::PopAnalysis,
+    items::{BlameKind, ENodeIdx, MatchIdx, StackIdx},
+    LogParser, Z3Parser,
+};
+
+mod common;
+
+/// Frames 0 and 1 are popped together, losing one instantiation each. Frame
+/// 3 is popped without a `[pop]` being logged, which is only noticed at the
+/// last `[push]`, and its term needs a new e-node to be used in an equality.
+const LOG: &str = "\
+[tool-version] Z3 4.12.2
+[mk-app] #1 true
+[mk-app] #2 false
+[mk-var] #3 0
+[mk-app] #4 f #3
+[mk-app] #5 g #3
+[mk-app] #6 = #4 #5
+[mk-app] #7 pattern #4
+[mk-quant] #8 q 1 #7 #6
+[attach-var-names] #8 (|x| ; |Int|)
+[mk-app] #9 c
+[attach-enode] #9 0
+[push] 0
+[mk-app] #10 f #9
+[attach-enode] #10 0
+[new-match] 0x1 #8 #7 #9 ; #10
+[mk-app] #11 g #9
+[mk-app] #12 = #10 #11
+[instance] 0x1 #12 ; 1
+[attach-enode] #11 1
+[end-of-instance]
+[push] 1
+[new-match] 0x2 #8 #7 #9 ; #10
+[instance] 0x2 #12 ; 1
+[end-of-instance]
+[pop] 2 2
+[push] 0
+[mk-app] #13 f #9
+[attach-enode] #13 0
+[push] 1
+[new-match] 0x3 #8 #7 #9 ; #13
+[mk-app] #14 g #9
+[mk-app] #15 = #13 #14
+[instance] 0x3 #15 ; 1
+[attach-enode] #14 1
+[end-of-instance]
+[push] 1
+[attach-enode] #14 0
+[eq-expl] #14 ax ; #9
+[eof]
+";
+
+#[test]
+fn lost_to_pops() {
+    let parser = Z3Parser::from_str(LOG).process_all().unwrap();
+    assert!(parser.diagnostics().is_empty());
+
+    let frames = parser.stack_frames();
+    let active: Vec<_> = frames.iter().map(|f| f.active).collect();
+    assert_eq!(active, [false, false, true, false, true]);
+    let implicit: Vec<_> = frames.iter().map(|f| f.implicit_pop).collect();
+    assert_eq!(implicit, [false, false, false, true, false]);
+    let depths: Vec<_> = frames.iter().map(|f| f.depth).collect();
+    assert_eq!(depths, [0, 1, 0, 1, 1]);
+    let inst_frames: Vec<_> = parser.instantiations().iter().map(|i| i.frame).collect();
+    let frame = |idx: usize| Some(StackIdx::from(idx));
+    assert_eq!(inst_frames, [frame(0), frame(1), frame(3)]);
+
+    let pops = PopAnalysis::new(&parser);
+    assert_eq!((pops.total.insts, pops.total.enodes), (3, 3));
+    assert_eq!((pops.implicit.insts, pops.implicit.enodes), (1, 1));
+    let lost: Vec<_> = pops.frames.iter().map(|w| (w.insts, w.enodes)).collect();
+    assert_eq!(lost, [(1, 2), (1, 0), (0, 0), (1, 1), (0, 0)]);
+    assert_eq!((pops.quants.raw[0].insts, pops.quants.raw[0].lost), (3, 3));
+    let worst: Vec<_> = pops.worst_frames().into_iter().map(|(f, _)| f).collect();
+    assert_eq!(worst.len(), 3);
+    assert_eq!(worst[0], StackIdx::from(0));
+    assert!(worst.contains(&StackIdx::from(1)) && worst.contains(&StackIdx::from(3)));
+}
+
+/// The equality `c = d` is undone by the pop, the match modulo `c = e` must
+/// then be explained by the older equality rather than through `d`.
+const POPPED_EQ: &str = "\
+[tool-version] Z3 4.12.2
+[mk-app] #1 true
+[mk-app] #2 false
+[mk-var] #3 0
+[mk-app] #4 f #3
+[mk-app] #5 = #4 #4
+[mk-app] #6 pattern #4
+[mk-quant] #7 q 1 #6 #5
+[attach-var-names] #7 (|x| ; |Int|)
+[mk-app] #8 c
+[attach-enode] #8 0
+[mk-app] #9 d
+[attach-enode] #9 0
+[mk-app] #10 e
+[attach-enode] #10 0
+[eq-expl] #8 ax ; #10
+[push] 0
+[eq-expl] #8 ax ; #9
+[pop] 1 1
+[mk-app] #11 f #8
+[attach-enode] #11 0
+[new-match] 0x1 #7 #6 #10 ; #11 (#8 #10)
+[eof]
+";
+
+#[test]
+fn popped_equality() {
+    let parser = common::parse(POPPED_EQ);
+
+    let (c, e) = (ENodeIdx::from(0), ENodeIdx::from(2));
+    let match_ = &parser[MatchIdx::from(0)];
+    let [BlameKind::Term { .. }, BlameKind::Equality { eq }] = &*match_.blamed else {
+        panic!("{:?}", match_.blamed);
+    };
+    let trans = &parser[*eq];
+    assert_eq!((trans.given_len, trans.to), (1, e));
+    let given = trans.path[0].kind.given().unwrap();
+    assert_eq!((parser[given].from(), parser[given].to()), (c, e));
+}
+
+/// As in `POPPED_EQ`, but the frame with `c = d` is popped without a `[pop]`
+/// being logged, which is only noticed at the second `[push]`.
+const IMPLICIT_POPPED_EQ: &str = "\
+[tool-version] Z3 4.12.2
+[mk-app] #1 true
+[mk-app] #2 false
+[mk-var] #3 0
+[mk-app] #4 f #3
+[mk-app] #5 = #4 #4
+[mk-app] #6 pattern #4
+[mk-quant] #7 q 1 #6 #5
+[attach-var-names] #7 (|x| ; |Int|)
+[mk-app] #8 c
+[attach-enode] #8 0
+[mk-app] #9 d
+[attach-enode] #9 0
+[mk-app] #10 e
+[attach-enode] #10 0
+[eq-expl] #8 ax ; #10
+[push] 0
+[eq-expl] #8 ax ; #9
+[push] 0
+[mk-app] #11 f #8
+[attach-enode] #11 0
+[new-match] 0x1 #7 #6 #10 ; #11 (#8 #10)
+[eof]
+";
+
+#[test]
+fn implicitly_popped_equality() {
+    let parser = common::parse(IMPLICIT_POPPED_EQ);
+    assert!(parser.stack_frames().raw[0].implicit_pop);
+
+    let (c, e) = (ENodeIdx::from(0), ENodeIdx::from(2));
+    let match_ = &parser[MatchIdx::from(0)];
+    let [BlameKind::Term { .. }, BlameKind::Equality { eq }] = &*match_.blamed else {
+        panic!("{:?}", match_.blamed);
+    };
+    let trans = &parser[*eq];
+    assert_eq!((trans.given_len, trans.to), (1, e));
+    let given = trans.path[0].kind.given().unwrap();
+    assert_eq!((parser[given].from(), parser[given].to()), (c, e));
+}
@@ -0,0 +1,81 @@
+//! Classifies instantiations as used in or wasted for the refutation.
+#![cfg(feature = "analysis")]
+
+use smt_log_parser::{
+    analysis::{InstUsage, ProofAnalysis},
+    items::InstIdx,
+    LogParser, Z3Parser,
+};
+
+/// Both `f(c) = g(c)` and `f(d) = g(d)` are instantiated, but only the first
+/// is a premise of the refutation.
+const LOG: &str = "\
+[tool-version] Z3 4.12.2
+[mk-app] #1 true
+[mk-app] #2 false
+[mk-var] #3 0
+[mk-app] #4 f #3
+[mk-app] #5 g #3
+[mk-app] #6 = #4 #5
+[mk-app] #7 pattern #4
+[mk-quant] #8 q 1 #7 #6
+[attach-var-names] #8 (|x| ; |Int|)
+[mk-app] #9 c
+[attach-enode] #9 0
+[mk-app] #10 d
+[attach-enode] #10 0
+[mk-app] #11 f #9
+[attach-enode] #11 0
+[new-match] 0x1 #8 #7 #9 ; #11
+[mk-app] #12 g #9
+[mk-app] #13 = #11 #12
+[mk-proof] #14 quant-inst #13
+[instance] 0x1 #14 ; 1
+[attach-enode] #12 1
+[end-of-instance]
+[mk-app] #15 f #10
+[attach-enode] #15 0
+[new-match] 0x2 #8 #7 #10 ; #15
+[mk-app] #16 g #10
+[mk-app] #17 = #15 #16
+[mk-proof] #18 quant-inst #17
+[instance] 0x2 #18 ; 1
+[attach-enode] #16 1
+[end-of-instance]
+[mk-proof] #19 mp #14 #13
+[mk-proof] #20 unit-resolution #19 #2
+[eof]
+";
+
+#[test]
+fn used_and_wasted() {
+    let parser = Z3Parser::from_str(LOG).process_all().unwrap();
+    assert!(parser.diagnostics().is_empty());
+
+    let proofs = ProofAnalysis::new(&parser);
+    assert_eq!(proofs.steps.len(), 4);
+    assert_eq!(proofs.refutations.len(), 1);
+    let (query, refutation) = proofs.refutations[0];
+    assert_eq!(query, None);
+    let refutation = &proofs.steps[&refutation];
+    assert_eq!(&parser[refutation.rule], "unit-resolution");
+    assert_eq!(refutation.premises.len(), 1);
+
+    let usage: Vec<_> = proofs.usage.iter().copied().collect();
+    assert_eq!(usage, [InstUsage::UsedInProof, InstUsage::Wasted]);
+    let step = proofs.inst_steps[InstIdx::from(0)].unwrap();
+    assert_eq!(&parser[proofs.steps[&step].rule], "quant-inst");
+    let quant = &proofs.quants.raw[0];
+    assert_eq!((quant.used, quant.wasted, quant.unknown), (1, 1, 0));
+}
+
+/// Without a refutation the usage of all instantiations is unknown.
+#[test]
+fn no_refutation() {
+    let log = LOG.replace("[mk-proof] #20 unit-resolution #19 #2\n", "");
+    let parser = Z3Parser::from_str(&log).process_all().unwrap();
+    let proofs = ProofAnalysis::new(&parser);
+    assert!(!proofs.has_refutation());
+    let total = proofs.total();
+    assert_eq!((total.used, total.wasted, total.unknown), (0, 0, 2));
+}
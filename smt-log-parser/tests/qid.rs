@@ -0,0 +1,184 @@
+//! Decodes the source locations of the quantifier names generated by
+//! various front-ends.
+
+use std::path::Path;
+
+use smt_log_parser::qid::{QidDecoders, QidSource};
+
+fn source(
+    tool: &str,
+    file: Option<&str>,
+    line: Option<u32>,
+    column: Option<u32>,
+    kind: Option<&str>,
+) -> Option<QidSource> {
+    Some(QidSource {
+        tool: tool.to_string(),
+        file: file.map(str::to_string),
+        line,
+        column,
+        kind: kind.map(str::to_string),
+    })
+}
+
+fn check(cases: &[(&str, Option<QidSource>)]) {
+    let decoders = QidDecoders::default();
+    for (qid, expected) in cases {
+        assert_eq!(&decoders.decode(qid), expected, "{qid}");
+    }
+}
+
+#[test]
+fn decode_dafny() {
+    check(&[
+        (
+            "testdfy.12:7",
+            source("Dafny", Some("test.dfy"), Some(12), Some(7), None),
+        ),
+        (
+            "DafnyPreludebpl.77:15",
+            source(
+                "Dafny",
+                Some("DafnyPrelude.bpl"),
+                Some(77),
+                Some(15),
+                Some("prelude"),
+            ),
+        ),
+        (
+            "funType:dafny.3.9",
+            source("Dafny", None, Some(3), Some(9), Some("funType")),
+        ),
+        ("dafny.3.9", source("Dafny", None, Some(3), Some(9), None)),
+        ("funType:dafny.3", None),
+    ]);
+}
+
+#[test]
+fn decode_boogie() {
+    check(&[
+        (
+            "Preludebpl.77:15",
+            source("Boogie", Some("Prelude.bpl"), Some(77), Some(15), None),
+        ),
+        (
+            "carbon.3:4",
+            source("Boogie", Some("carbon"), Some(3), Some(4), None),
+        ),
+        (".3:4", None),
+        ("Preludebpl.x:15", None),
+    ]);
+}
+
+#[test]
+fn decode_viper() {
+    check(&[
+        ("prog.l12", source("Viper", None, Some(12), None, None)),
+        (
+            "prog.l12-aux",
+            source("Viper", None, Some(12), None, Some("aux")),
+        ),
+        (
+            "prog.l12_precondition",
+            source("Viper", None, Some(12), None, Some("precondition")),
+        ),
+        (
+            "prog.getter_over_tuple2",
+            source("Viper", None, None, None, Some("getter_over_tuple2")),
+        ),
+        (
+            "$Seq[Int]_prog.seq_drop_over_update_1",
+            source("Viper", None, None, None, Some("seq_drop_over_update_1")),
+        ),
+        (
+            "$Set[ShStruct2[Ref, Ref]]_prog.in_union_in_one",
+            source("Viper", None, None, None, Some("in_union_in_one")),
+        ),
+        (
+            "qp.fvfValDef3",
+            source("Viper", None, None, None, Some("fvfValDef")),
+        ),
+        (
+            "quant-u-12",
+            source("Viper", None, None, None, Some("quant-u")),
+        ),
+        ("quant-u-x", None),
+        ("Seq_prog.empty", None),
+    ]);
+}
+
+#[test]
+fn decode_fstar() {
+    check(&[
+        (
+            "equation_FStar.List.Tot.Base.length",
+            source(
+                "F*",
+                Some("FStar.List.Tot.Base"),
+                None,
+                None,
+                Some("equation"),
+            ),
+        ),
+        (
+            "equation_with_fuel_FStar.List.Tot.Base.length",
+            source(
+                "F*",
+                Some("FStar.List.Tot.Base"),
+                None,
+                None,
+                Some("equation_with_fuel"),
+            ),
+        ),
+        (
+            "typing_Prims.op_Addition",
+            source("F*", Some("Prims"), None, None, Some("typing")),
+        ),
+        ("typing_op_Addition", None),
+        ("typing_prims.op_Addition", None),
+    ]);
+}
+
+#[test]
+fn decode_other() {
+    check(&[("loop1", None), ("injectivity", None), ("k!12", None)]);
+}
+
+/// The `:qid`s in a problem, without `|` quotes.
+fn qids(input: &str) -> Vec<&str> {
+    input
+        .split(":qid ")
+        .skip(1)
+        .filter_map(|rest| match rest.strip_prefix('|') {
+            Some(quoted) => quoted.split_once('|').map(|(qid, _)| qid),
+            None => rest.split([' ', ')', '\n']).next(),
+        })
+        .collect()
+}
+
+/// Every program quantifier and Silicon axiom in the Viper based problems
+/// is decoded.
+#[test]
+fn decode_smt_problems() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../smt-problems");
+    let decoders = QidDecoders::default();
+    let mut decoded = 0;
+    for tool in ["Viper", "Gobra", "Prusti", "Scion"] {
+        for entry in std::fs::read_dir(dir.join(tool)).unwrap() {
+            let input = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+            for qid in qids(&input) {
+                let silicon = ["prog.", "qp.", "quant-u-"]
+                    .iter()
+                    .any(|prefix| qid.starts_with(prefix))
+                    || qid.starts_with('$') && qid.contains("_prog.");
+                if !silicon {
+                    continue;
+                }
+                let source = decoders.decode(qid);
+                assert_eq!(source.map(|s| s.tool), Some("Viper".to_string()), "{qid}");
+                decoded += 1;
+            }
+        }
+    }
+    assert!(decoded > 0);
+}
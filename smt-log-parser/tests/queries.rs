@@ -0,0 +1,98 @@
+//! Splits a log with several `check-sat` calls into queries.
+#![cfg(feature = "analysis")]
+
+use smt_log_parser::{
+    analysis::{InstGraph, LogInfo, QuantifierAnalysis},
+    items::{QueryIdx, QueryResult},
+    LogParser, Z3Parser,
+};
+
+/// Three queries: the first is unsat after one instantiation, the second sat
+/// after two and the log ends during the third. The constant `d` is asserted
+/// between the second and third query.
+const LOG: &str = "\
+[tool-version] Z3 4.12.2
+[mk-app] #1 true
+[mk-app] #2 false
+[mk-var] #3 0
+[mk-app] #4 f #3
+[mk-app] #5 g #3
+[mk-app] #6 = #4 #5
+[mk-app] #7 pattern #4
+[mk-quant] #8 q 1 #7 #6
+[attach-var-names] #8 (|x| ; |Int|)
+[mk-app] #9 c
+[attach-enode] #9 0
+[begin-check] 0
+[mk-app] #10 f #9
+[attach-enode] #10 0
+[new-match] 0x1 #8 #7 #9 ; #10
+[mk-app] #11 g #9
+[mk-app] #12 = #10 #11
+[instance] 0x1 #12 ; 1
+[attach-enode] #11 1
+[end-of-instance]
+[query-done] unsat
+[push] 0
+[begin-check] 1
+[new-match] 0x2 #8 #7 #9 ; #10
+[instance] 0x2 #12 ; 1
+[end-of-instance]
+[mk-app] #13 f #11
+[attach-enode] #13 0
+[new-match] 0x3 #8 #7 #11 ; #13
+[mk-app] #14 g #11
+[mk-app] #15 = #13 #14
+[instance] 0x3 #15 ; 2
+[attach-enode] #14 2
+[end-of-instance]
+[query-done] sat
+[mk-app] #16 d
+[attach-enode] #16 0
+[begin-check] 1
+";
+
+#[test]
+fn split_queries() {
+    let parser = Z3Parser::from_str(LOG).process_all().unwrap();
+    assert!(parser.diagnostics().is_empty());
+    let queries = parser.queries();
+    assert_eq!(queries.len(), 3);
+    let results: Vec<_> = queries.iter().map(|q| q.result).collect();
+    assert_eq!(
+        results,
+        [Some(QueryResult::Unsat), Some(QueryResult::Sat), None]
+    );
+    let scopes: Vec<_> = queries.iter().map(|q| q.scope).collect();
+    assert_eq!(scopes, [0, 1, 1]);
+    assert!(queries.iter().all(|q| q.start_line > 0));
+    assert_eq!(queries.last().unwrap().end_line, None);
+
+    // Items created before the first `[begin-check]` belong to it.
+    let items: Vec<_> = queries
+        .keys()
+        .map(|query| parser.query_items(query))
+        .collect();
+    let insts: Vec<_> = items.iter().map(|r| r.insts.len()).collect();
+    assert_eq!(insts, [1, 2, 0]);
+    let enodes: Vec<_> = items.iter().map(|r| r.enodes.len()).collect();
+    assert_eq!(enodes, [3, 2, 1]);
+    assert_eq!(items[0].insts.start, 0);
+    assert_eq!(items[2].enodes.end, parser.item_counts().enodes);
+    let d = parser.item_counts().enodes - 1;
+    assert_eq!(parser.enode_query(d.into()), Some(QueryIdx::from(2)));
+    assert_eq!(queries.raw[1].end.unwrap().enodes, d);
+
+    let quant_insts: Vec<_> = queries
+        .keys()
+        .map(|query| LogInfo::new_for_query(&parser, query).quants.0.raw.clone())
+        .collect();
+    assert_eq!(quant_insts, [[1], [2], [0]]);
+
+    let mut inst_graph = InstGraph::new(&parser).unwrap();
+    inst_graph.initialise_inst_succs_and_preds(&parser);
+    let second = QuantifierAnalysis::new_for_query(&parser, &inst_graph, QueryIdx::from(1));
+    assert_eq!(second.raw[0].insts, 2);
+    let all = QuantifierAnalysis::new(&parser, &inst_graph);
+    assert_eq!(all.raw[0].insts, 3);
+}
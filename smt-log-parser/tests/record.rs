@@ -0,0 +1,132 @@
+//! Runs `record` with a stub in place of z3, which writes a canned log.
+#![cfg(all(unix, feature = "analysis"))]
+
+use std::{
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+    time::{Duration, Instant},
+};
+
+use smt_log_parser::{analysis::LogInfo, synth::SynthConfig, LogParser, Z3Parser};
+
+/// A fresh directory containing the canned log and an (empty) input file.
+fn setup(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("slp-record-{name}-{}", std::process::id()));
+    std::fs::remove_dir_all(&dir).ok();
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("canned.log"),
+        SynthConfig::default().generate().log,
+    )
+    .unwrap();
+    std::fs::write(dir.join("input.smt2"), "(check-sat)\n").unwrap();
+    dir
+}
+
+/// Writes a stub z3 which records its arguments and then runs `body` with
+/// `$LOG` set to the value of `trace-file-name`.
+fn stub(dir: &Path, body: &str) -> PathBuf {
+    let script = format!(
+        r#"#!/bin/sh
+echo "$@" > "{dir}/args"
+for arg in "$@"; do
+    case "$arg" in
+        trace-file-name=*) LOG="${{arg#trace-file-name=}}" ;;
+    esac
+done
+{body}
+"#,
+        dir = dir.display()
+    );
+    let path = dir.join("z3");
+    std::fs::write(&path, script).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+fn record(dir: &Path, z3: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_smt-log-parser"))
+        .arg("record")
+        .arg(dir.join("input.smt2"))
+        .arg("--z3")
+        .arg(z3)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn canned_insts(dir: &Path) -> u64 {
+    let log = std::fs::read_to_string(dir.join("canned.log")).unwrap();
+    let parser = Z3Parser::from_str(&log).process_all().unwrap();
+    LogInfo::new(&parser).inst.insts
+}
+
+#[test]
+fn record_complete_log() {
+    let dir = setup("complete");
+    let z3 = stub(
+        &dir,
+        r#"cat "$(dirname "$0")/canned.log" > "$LOG"; echo unsat"#,
+    );
+    let output = record(&dir, &z3, &["-t", "5", "-m", "7", "--", "smt.mbqi=false"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{output:?}");
+    assert!(stdout.starts_with("unsat\n"), "{stdout}");
+    let insts = format!("no-instantiations: {}\n", canned_insts(&dir));
+    assert!(stdout.contains(&insts), "{stdout}");
+    assert!(stdout.contains("dependencies="), "{stdout}");
+    assert_eq!(
+        std::fs::read(dir.join("input.log")).unwrap(),
+        std::fs::read(dir.join("canned.log")).unwrap()
+    );
+
+    let args = std::fs::read_to_string(dir.join("args")).unwrap();
+    let log = dir.join("input.log");
+    let expected = format!(
+        "trace=true proof=true trace-file-name={} -T:5 smt.qi.max_instances=7 smt.mbqi=false {}\n",
+        log.display(),
+        dir.join("input.smt2").display()
+    );
+    assert_eq!(args, expected);
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+/// The log is written slowly and z3 crashes before writing `[eof]`.
+#[test]
+fn record_truncated_log() {
+    let dir = setup("truncated");
+    let z3 = stub(
+        &dir,
+        r#"grep -v '^\[eof\]' "$(dirname "$0")/canned.log" | while read -r line; do
+    echo "$line" >> "$LOG"
+done
+sleep 0.3
+exit 3"#,
+    );
+    let output = record(&dir, &z3, &[]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{output:?}");
+    assert!(stderr.contains("z3 exited with"), "{stderr}");
+    let insts = format!("no-instantiations: {}\n", canned_insts(&dir));
+    assert!(stdout.contains(&insts), "{stdout}");
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+/// A z3 which ignores its timeout is killed.
+#[test]
+fn record_timeout() {
+    let dir = setup("timeout");
+    let z3 = stub(
+        &dir,
+        r#"head -n 20 "$(dirname "$0")/canned.log" > "$LOG"; exec sleep 60"#,
+    );
+    let start = Instant::now();
+    let output = record(&dir, &z3, &["--timeout", "1"]);
+    assert!(start.elapsed() < Duration::from_secs(30), "{output:?}");
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("no-instantiations: "), "{stdout}");
+    std::fs::remove_dir_all(&dir).ok();
+}
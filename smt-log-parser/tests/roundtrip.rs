@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+
+use smt_log_parser::{
+    analysis::InstGraph,
+    items::{InstIdx, ItemCounts},
+    parsers::z3::writer::TraceWriter,
+    synth::SynthConfig,
+    Z3Parser,
+};
+
+mod common;
+
+use common::parse;
+
+/// The statistics which must not change when writing a log back out.
+#[derive(Debug, PartialEq)]
+struct Stats {
+    counts: ItemCounts,
+    quantifiers: usize,
+    /// The number of instantiations of each quantifier (by name), `None`
+    /// for theory-solving.
+    quant_insts: HashMap<Option<String>, usize>,
+    /// The depth and whether it is still active of each frame.
+    frames: Vec<(usize, bool)>,
+    /// The generation of each instantiation and how many e-nodes it yields.
+    insts: Vec<(Option<u32>, usize)>,
+    matching_loops: usize,
+}
+
+impl Stats {
+    fn of(mut parser: Z3Parser) -> Self {
+        let mut quant_insts = HashMap::new();
+        for idx in parser.instantiations().keys() {
+            let name = quant_name(&parser, idx).map(str::to_string);
+            *quant_insts.entry(name).or_insert(0) += 1;
+        }
+        let mut graph = InstGraph::new(&parser).unwrap();
+        let matching_loops = graph.search_matching_loops(&mut parser);
+        Self {
+            // The CDCL search is not written.
+            counts: ItemCounts {
+                decisions: 0,
+                assignments: 0,
+                conflicts: 0,
+                ..parser.item_counts()
+            },
+            quantifiers: parser.quantifiers().len(),
+            quant_insts,
+            frames: parser
+                .stack_frames()
+                .iter()
+                .map(|f| (f.depth, f.active))
+                .collect(),
+            insts: parser
+                .instantiations()
+                .iter()
+                .map(|i| (i.z3_generation, i.yields_terms.len()))
+                .collect(),
+            matching_loops,
+        }
+    }
+}
+
+/// The name of the instantiated quantifier, if any.
+fn quant_name(parser: &Z3Parser, inst: InstIdx) -> Option<&str> {
+    let quant = parser[parser[inst].match_].kind.quant_idx()?;
+    let name = parser.quantifiers()[quant].kind.user_name()?;
+    Some(&parser.strings[*name])
+}
+
+fn roundtrip(log: &str) {
+    let parser = parse(log);
+    let written = TraceWriter::new(&parser).write_string().unwrap();
+    let reparsed = parse(&written);
+    // Writing is deterministic and loses nothing the second time around.
+    assert_eq!(TraceWriter::new(&reparsed).write_string().unwrap(), written);
+    assert_eq!(Stats::of(parser), Stats::of(reparsed));
+}
+
+#[test]
+fn roundtrip_default() {
+    roundtrip(&SynthConfig::default().generate().log);
+}
+
+#[test]
+fn roundtrip_matching_loops() {
+    for len in 1..=8 {
+        let config = SynthConfig {
+            matching_loops: vec![len],
+            ..Default::default()
+        };
+        roundtrip(&config.generate().log);
+    }
+}
+
+#[test]
+fn roundtrip_random() {
+    for seed in common::seeds(128) {
+        let synth = SynthConfig::random(seed).generate();
+        let result = std::panic::catch_unwind(|| roundtrip(&synth.log));
+        if let Err(err) = result {
+            eprintln!(
+                "Failed for seed {seed} (rerun with SLP_SYNTH_SEED={seed}): {:?}",
+                synth.config
+            );
+            std::panic::resume_unwind(err);
+        }
+    }
+}
+
+/// Writing only some instantiations gives a valid log which contains exactly
+/// those instantiations.
+#[test]
+fn extract_random() {
+    for seed in common::seeds(128) {
+        let synth = SynthConfig::random(seed).generate();
+        let parser = parse(&synth.log);
+        // Every other instantiation.
+        let selected: Vec<_> = parser
+            .instantiations()
+            .keys()
+            .filter(|i| usize::from(*i) % 2 == 0)
+            .collect();
+        let written = TraceWriter::new(&parser)
+            .only_insts(selected.iter().copied())
+            .write_string()
+            .unwrap();
+        let extracted = parse(&written);
+        assert_eq!(extracted.instantiations().len(), selected.len(), "{seed}");
+        for (inst, &original) in extracted.instantiations().keys().zip(&selected) {
+            assert_eq!(
+                quant_name(&extracted, inst),
+                quant_name(&parser, original),
+                "{seed}"
+            );
+        }
+
+        // Including all ancestors never loses an instantiation.
+        let all = TraceWriter::new(&parser)
+            .only_insts(selected.iter().copied())
+            .with_ancestors()
+            .write_string()
+            .unwrap();
+        let all = parse(&all);
+        assert!(all.instantiations().len() >= selected.len(), "{seed}");
+    }
+}
+
+/// The third instantiation matches a term yielded by the first, the second
+/// is unrelated to both.
+const CHAIN: &str = "\
+[tool-version] Z3 4.12.2
+[mk-app] #1 true
+[mk-app] #2 false
+[mk-var] #3 0
+[mk-app] #4 f #3
+[mk-app] #5 g #3
+[mk-app] #6 f #5
+[mk-app] #7 = #4 #6
+[mk-app] #8 pattern #4
+[mk-quant] #9 q 1 #8 #7
+[attach-var-names] #9 (|x| ; |Int|)
+[mk-app] #10 c
+[attach-enode] #10 0
+[mk-app] #11 f #10
+[attach-enode] #11 0
+[new-match] 0x1 #9 #8 #10 ; #11
+[mk-app] #12 g #10
+[mk-app] #13 f #12
+[mk-app] #14 = #11 #13
+[instance] 0x1 #14 ; 1
+[attach-enode] #12 1
+[attach-enode] #13 1
+[end-of-instance]
+[mk-app] #15 d
+[attach-enode] #15 0
+[mk-app] #16 f #15
+[attach-enode] #16 0
+[new-match] 0x2 #9 #8 #15 ; #16
+[mk-app] #17 g #15
+[mk-app] #18 f #17
+[mk-app] #19 = #16 #18
+[instance] 0x2 #19 ; 1
+[attach-enode] #17 1
+[attach-enode] #18 1
+[end-of-instance]
+[new-match] 0x3 #9 #8 #12 ; #13
+[mk-app] #20 g #12
+[mk-app] #21 f #20
+[mk-app] #22 = #13 #21
+[instance] 0x3 #22 ; 2
+[attach-enode] #20 2
+[attach-enode] #21 2
+[end-of-instance]
+[eof]
+";
+
+#[test]
+fn roundtrip_chain() {
+    roundtrip(CHAIN);
+}
+
+/// Extracting the last instantiation writes the e-node it matched on without
+/// the instantiation which yielded it, unless its ancestors are included.
+#[test]
+fn extract_chain() {
+    let parser = parse(CHAIN);
+    let last = parser.instantiations().last_key().unwrap();
+    let alone = TraceWriter::new(&parser).only_insts([last]);
+    let alone = parse(&alone.write_string().unwrap());
+    assert_eq!(alone.instantiations().len(), 1);
+    // The bound `g(c)`, the matched `f(g(c))` and the two yielded e-nodes.
+    assert_eq!(alone.item_counts().enodes, 4);
+    let ancestors = TraceWriter::new(&parser)
+        .only_insts([last])
+        .with_ancestors();
+    let ancestors = ancestors.write_string().unwrap();
+    assert!(!ancestors.contains("#15 d"), "{ancestors}");
+    let ancestors = parse(&ancestors);
+    let generations: Vec<_> = ancestors
+        .instantiations()
+        .iter()
+        .map(|i| i.z3_generation)
+        .collect();
+    assert_eq!(generations, [Some(1), Some(2)]);
+}
@@ -0,0 +1,120 @@
+//! Parses SMT-LIB inputs and relates their quantifiers to a log.
+
+use std::path::{Path, PathBuf};
+
+use smt_log_parser::{
+    items::QuantIdx,
+    parsers::smt2::{Binder, Smt2Problem},
+    LogParser, Z3Parser,
+};
+
+fn smt2_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            smt2_files(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "smt2") {
+            files.push(path);
+        }
+    }
+}
+
+/// Every problem in `smt-problems` parses, and the span of each quantifier
+/// points at its text.
+#[test]
+fn parse_smt_problems() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../smt-problems");
+    let mut files = Vec::new();
+    smt2_files(&dir, &mut files);
+    files.sort();
+    assert!(!files.is_empty());
+    let mut quantifiers = 0;
+    for file in files {
+        let input = std::fs::read_to_string(&file).unwrap();
+        let problem =
+            Smt2Problem::parse(&input).unwrap_or_else(|err| panic!("{}: {err:?}", file.display()));
+        for quant in &problem.quantifiers {
+            let span = &quant.span;
+            assert_eq!(&input[span.bytes.clone()], quant.text);
+            let line = input[..span.bytes.start].matches('\n').count() + 1;
+            assert_eq!(span.start.line, line, "{}", file.display());
+            assert!(span.start <= span.end);
+            let binder = match quant.binder {
+                Binder::Forall => "(forall",
+                Binder::Exists => "(exists",
+            };
+            assert!(quant.text.starts_with(binder), "{}", quant.text);
+            assert!(!quant.vars.is_empty(), "{}", quant.text);
+        }
+        quantifiers += problem.quantifiers.len();
+    }
+    assert!(quantifiers > 0);
+}
+
+const PROBLEM: &str = "\
+(declare-fun f (Int) Int)
+(assert (forall ((x Int)) (! (> (f x) 0) :qid |ax 1| :pattern ((f x)) :weight 3)))
+(assert (forall ((y Int) (z Int)) (! (= (f y) (f z)) :pattern ((f y) (f z)) :pattern ((f z)))))
+(assert (forall ((w Int)) (> (f w) 1)))
+(assert (exists ((v Int)) (> (f v) 2)))
+";
+
+#[test]
+fn parse_attributes() {
+    let problem = Smt2Problem::parse(PROBLEM).unwrap();
+    assert_eq!(problem.asserts, 4);
+    assert_eq!(problem.functions["f"].params, ["Int"]);
+    let [named, multi, unnamed, exists] = &problem.quantifiers[..] else {
+        panic!("{:?}", problem.quantifiers);
+    };
+    assert_eq!(named.qid.as_deref(), Some("ax 1"));
+    assert_eq!(named.weight, Some(3));
+    assert_eq!(named.vars, [("x".to_string(), "Int".to_string())]);
+    assert_eq!(named.patterns, [["(f x)"]]);
+    assert_eq!(named.span.start.line, 2);
+
+    assert_eq!(multi.qid, None);
+    assert_eq!(multi.weight, None);
+    assert_eq!(multi.vars.len(), 2);
+    assert_eq!(multi.patterns, [vec!["(f y)", "(f z)"], vec!["(f z)"]]);
+
+    assert!(unnamed.patterns.is_empty());
+    assert_eq!(unnamed.binder, Binder::Forall);
+    assert_eq!(unnamed.span.start.line, 4);
+    assert_eq!(exists.binder, Binder::Exists);
+}
+
+/// Named quantifiers are found by their `:qid`, unnamed ones by their
+/// variables and the line in their `k!<line>` name.
+#[test]
+fn map_quantifiers() {
+    let log = "\
+[tool-version] Z3 4.12.2
+[mk-app] #1 true
+[mk-app] #2 false
+[mk-var] #3 0
+[mk-app] #4 f #3
+[mk-app] #5 pattern #4
+[mk-app] #6 0
+[mk-app] #7 > #4 #6
+[mk-quant] #8 k!5 1 #7
+[attach-var-names] #8 (|v| ; |Int|)
+[mk-quant] #9 k!4 1 #7
+[attach-var-names] #9 (|w| ; |Int|)
+[mk-quant] #10 |ax 1| 1 #5 #7
+[attach-var-names] #10 (|x| ; |Int|)
+[mk-quant] #11 k!9 2 #7
+[attach-var-names] #11 (|y| ; |Int|) (|z| ; |Int|)
+[mk-quant] #12 missing 1 #5 #7
+[eof]
+";
+    let parser = Z3Parser::from_str(log).process_all().unwrap();
+    assert!(parser.diagnostics().is_empty());
+    let problem = Smt2Problem::parse(PROBLEM).unwrap();
+    let map = problem.map_quantifiers(&parser);
+    let found: Vec<_> = (0..parser.quantifiers().len())
+        .map(|idx| map.quants[QuantIdx::from(idx)])
+        .collect();
+    // The quantifier on line 3 is the only one binding two variables.
+    assert_eq!(found, [Some(3), Some(2), Some(0), Some(1), None]);
+}
@@ -0,0 +1,149 @@
+//! Saves synthetic logs as snapshots and loads them again.
+#![cfg(feature = "serde")]
+
+use std::process::Command;
+
+use petgraph::visit::EdgeRef;
+use smt_log_parser::{
+    analysis::{InstGraph, LogInfo},
+    items::ItemCounts,
+    synth::{SynthConfig, SynthLog},
+    Z3Parser,
+};
+
+mod common;
+
+fn parse(synth: &SynthLog) -> Z3Parser {
+    common::parse(&synth.log)
+}
+
+/// The statistics which must not change when saving and loading.
+#[derive(Debug, PartialEq)]
+struct Stats {
+    counts: ItemCounts,
+    queries: usize,
+    quant_insts: Vec<u64>,
+    cdcl: [u64; 5],
+}
+
+impl Stats {
+    fn of(parser: &Z3Parser) -> Self {
+        let info = LogInfo::new(parser);
+        Self {
+            counts: parser.item_counts(),
+            queries: parser.queries().len(),
+            quant_insts: info.quants.0.iter().copied().collect(),
+            cdcl: [
+                info.cdcl.decisions,
+                info.cdcl.conflicts,
+                info.cdcl.inst_decisions,
+                info.cdcl.inst_conflicts,
+                info.cdcl.learned_literals,
+            ],
+        }
+    }
+}
+
+/// The nodes (with their costs) and edges of the graph.
+fn graph_summary(graph: &InstGraph) -> (Vec<String>, Vec<(usize, usize, String)>) {
+    let nodes = graph
+        .raw
+        .graph
+        .node_weights()
+        .map(|node| format!("{node:?}"))
+        .collect();
+    let edges = graph
+        .raw
+        .graph
+        .edge_references()
+        .map(|edge| {
+            let (from, to) = (edge.source().index(), edge.target().index());
+            (from, to, format!("{:?}", edge.weight()))
+        })
+        .collect();
+    (nodes, edges)
+}
+
+#[test]
+fn snapshot_roundtrip() {
+    for seed in 0..32 {
+        let synth = SynthConfig::random(seed).generate();
+        let parser = parse(&synth);
+        let graph = InstGraph::new(&parser).unwrap();
+
+        let mut bytes = Vec::new();
+        parser.save_snapshot_with(&mut bytes, Some(&graph)).unwrap();
+        let (loaded, loaded_graph) = Z3Parser::load_snapshot_with_graph(&bytes[..]).unwrap();
+        assert_eq!(Stats::of(&parser), Stats::of(&loaded), "seed {seed}");
+        let loaded_graph = loaded_graph.unwrap();
+        assert_eq!(graph_summary(&graph), graph_summary(&loaded_graph));
+
+        // The graph is skipped when only the parser is loaded.
+        let loaded = Z3Parser::load_snapshot(&bytes[..]).unwrap();
+        assert_eq!(Stats::of(&parser), Stats::of(&loaded), "seed {seed}");
+
+        let mut bytes = Vec::new();
+        parser.save_snapshot(&mut bytes).unwrap();
+        let (_, loaded_graph) = Z3Parser::load_snapshot_with_graph(&bytes[..]).unwrap();
+        assert!(loaded_graph.is_none());
+    }
+}
+
+/// The terms created when searching for matching loops are reused after
+/// loading a snapshot, rather than being created a second time.
+#[test]
+fn snapshot_synthetic_terms() {
+    for seed in 0..8 {
+        let synth = SynthConfig {
+            seed,
+            matching_loops: vec![3],
+            ..Default::default()
+        }
+        .generate();
+        let mut parser = parse(&synth);
+        let mut graph = InstGraph::new(&parser).unwrap();
+        assert_eq!(graph.search_matching_loops(&mut parser), 1, "seed {seed}");
+        let terms = parser.item_counts().terms;
+        assert!(terms > parse(&synth).item_counts().terms, "seed {seed}");
+
+        let mut bytes = Vec::new();
+        parser.save_snapshot(&mut bytes).unwrap();
+        let mut loaded = Z3Parser::load_snapshot(&bytes[..]).unwrap();
+        let mut graph = InstGraph::new(&loaded).unwrap();
+        assert_eq!(graph.search_matching_loops(&mut loaded), 1, "seed {seed}");
+        assert_eq!(loaded.item_counts().terms, terms, "seed {seed}");
+    }
+}
+
+/// The commands give the same output for a snapshot (with its stored graph)
+/// as for the log.
+#[test]
+fn snapshot_command() {
+    let dir = std::env::temp_dir().join(format!("slp-snapshot-{}", std::process::id()));
+    std::fs::remove_dir_all(&dir).ok();
+    std::fs::create_dir_all(&dir).unwrap();
+    let log = dir.join("synth.log");
+    let synth = SynthConfig {
+        conflicts: 2,
+        ..Default::default()
+    };
+    std::fs::write(&log, synth.generate().log).unwrap();
+
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_smt-log-parser"))
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let log = log.to_str().unwrap();
+    let snapshot = dir.join("synth.slpsnap");
+    let written = run(&["snapshot", "--graph", log]);
+    assert_eq!(written.trim(), snapshot.display().to_string());
+    let snapshot = snapshot.to_str().unwrap();
+    for command in ["stats", "matching-loops", "dependencies"] {
+        assert_eq!(run(&[command, log]), run(&[command, snapshot]), "{command}");
+    }
+    std::fs::remove_dir_all(&dir).ok();
+}
@@ -0,0 +1,149 @@
+//! Runs `split` on logs with several queries.
+
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use smt_log_parser::items::BlameKind;
+
+mod common;
+
+use common::parse;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("slp-{name}-{}", std::process::id()));
+    std::fs::remove_dir_all(&dir).ok();
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn split(logfile: &Path) {
+    let output = Command::new(env!("CARGO_BIN_EXE_smt-log-parser"))
+        .arg("split")
+        .arg(logfile)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+}
+
+/// The second query matches on an e-node and modulo an equality which an
+/// instantiation of the first query yielded.
+const LOG: &str = "\
+[tool-version] Z3 4.12.2
+[mk-app] #1 true
+[mk-app] #2 false
+[mk-var] #3 0
+[mk-app] #4 f #3
+[mk-app] #5 g #3
+[mk-app] #6 d
+[mk-app] #7 = #5 #6
+[mk-app] #8 pattern #4
+[mk-quant] #9 q 1 #8 #7
+[attach-var-names] #9 (|x| ; |Int|)
+[mk-app] #10 c
+[attach-enode] #10 0
+[attach-enode] #6 0
+[begin-check] 0
+[mk-app] #11 f #10
+[attach-enode] #11 0
+[new-match] 0x1 #9 #8 #10 ; #11
+[mk-app] #12 g #10
+[mk-app] #13 = #12 #6
+[instance] 0x1 #13 ; 1
+[attach-enode] #12 1
+[attach-enode] #13 1
+[eq-expl] #12 lit #13 ; #6
+[end-of-instance]
+[query-done] unknown
+[begin-check] 0
+[mk-app] #14 f #12
+[attach-enode] #14 0
+[new-match] 0x2 #9 #8 #12 ; #14
+[mk-app] #15 g #12
+[mk-app] #16 = #15 #6
+[instance] 0x2 #16 ; 2
+[attach-enode] #15 2
+[end-of-instance]
+[new-match] 0x3 #9 #8 #6 ; #14 (#12 #6)
+[mk-app] #17 g #6
+[mk-app] #18 = #17 #6
+[instance] 0x3 #18 ; 2
+[end-of-instance]
+[query-done] unsat
+[eof]
+";
+
+#[test]
+fn split_yielded() {
+    let dir = temp_dir("split-yielded");
+    let logfile = dir.join("yielded.log");
+    std::fs::write(&logfile, LOG).unwrap();
+    split(&logfile);
+
+    let first = std::fs::read_to_string(dir.join("yielded.query0.log")).unwrap();
+    let first = parse(&first);
+    assert_eq!(first.instantiations().len(), 1);
+    let second = std::fs::read_to_string(dir.join("yielded.query1.log")).unwrap();
+    assert!(!second.contains("0x1"), "{second}");
+    let second = parse(&second);
+    assert_eq!(second.instantiations().len(), 2);
+    // The e-nodes `g(c)` and `g(c) = d` yielded in the first query are kept
+    // as ground e-nodes, along with the equality they explain.
+    let counts = second.item_counts();
+    assert_eq!(counts.enodes, 7);
+    assert_eq!(counts.given_eqs, 1);
+    let blamed = second.instantiations().iter().map(|inst| {
+        let match_ = &second[inst.match_];
+        match_
+            .blamed
+            .iter()
+            .any(|b| matches!(b, BlameKind::Equality { .. }))
+    });
+    assert_eq!(blamed.collect::<Vec<_>>(), [false, true]);
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+/// Each part parses on its own and contains exactly the instantiations of its
+/// query, along with all e-nodes created before it.
+#[cfg(feature = "synth")]
+#[test]
+fn split_queries() {
+    use smt_log_parser::synth::SynthConfig;
+
+    let dir = temp_dir("split");
+    for seed in 0..8 {
+        let config = SynthConfig {
+            seed,
+            queries: 3,
+            conflicts: 2,
+            ..SynthConfig::default()
+        };
+        let synth = config.generate();
+        let logfile = dir.join(format!("synth{seed}.log"));
+        std::fs::write(&logfile, &synth.log).unwrap();
+        let parser = parse(&synth.log);
+        assert_eq!(parser.queries().len(), 3);
+
+        split(&logfile);
+        for (idx, _) in parser.queries().iter_enumerated() {
+            let path = dir.join(format!("synth{seed}.query{}.log", usize::from(idx)));
+            let part = std::fs::read_to_string(&path).unwrap();
+            let reparsed = parse(&part);
+            let items = parser.query_items(idx);
+            assert_eq!(reparsed.queries().len(), 1, "{}", path.display());
+            assert_eq!(
+                reparsed.instantiations().len(),
+                items.insts.len(),
+                "{}",
+                path.display()
+            );
+            assert_eq!(reparsed.conflicts().len(), items.conflicts.len());
+            // The e-nodes yielded by earlier instantiations are kept as
+            // ground e-nodes.
+            let enodes = items.enodes.end;
+            assert_eq!(reparsed.item_counts().enodes, enodes, "{}", path.display());
+        }
+    }
+    std::fs::remove_dir_all(&dir).ok();
+}
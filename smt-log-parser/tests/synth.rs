@@ -0,0 +1,358 @@
+use std::collections::HashMap;
+
+use petgraph::visit::Dfs;
+use smt_log_parser::{
+    analysis::{
+        CostModel, InstGraph, InstUsage, LintAnalysis, LintConfig, LintLevel, LintRule,
+        PopAnalysis, ProofAnalysis, QuantifierAnalysis, MAX_EVIDENCE,
+    },
+    display_with::{DisplayConfiguration, DisplayCtxt, DisplayWithCtxt, SymbolReplacement},
+    formatter::TermDisplayContext,
+    items::QuantIdx,
+    synth::{SynthConfig, SynthLog},
+    Z3Parser,
+};
+
+mod common;
+
+fn parse(synth: &SynthLog) -> Z3Parser {
+    common::parse_with::<Z3Parser>(&synth.log, true)
+}
+
+fn check(synth: &SynthLog) {
+    let expected = &synth.expected;
+    let mut parser = parse(synth);
+
+    let counts = parser.item_counts();
+    assert_eq!(parser.quantifiers().len(), expected.quantifiers);
+    assert_eq!(counts.insts, expected.insts);
+    assert_eq!(counts.enodes, expected.enodes);
+    assert_eq!(counts.given_eqs, expected.given_eqs);
+    assert_eq!(parser.stack_frames().len(), expected.frames);
+    let popped = parser.stack_frames().iter().filter(|f| !f.active).count();
+    assert_eq!(popped, expected.popped_frames);
+
+    let mut quant_insts = HashMap::new();
+    let mut theory_solving = 0;
+    for inst in parser.instantiations() {
+        let kind = &parser[inst.match_].kind;
+        match kind.quant_idx() {
+            Some(qidx) => {
+                let name = parser.quantifiers()[qidx].kind.user_name().unwrap();
+                *quant_insts.entry(&parser.strings[*name]).or_insert(0) += 1;
+            }
+            None => theory_solving += 1,
+        }
+    }
+    assert_eq!(theory_solving, expected.theory_solving);
+    for (name, insts) in &expected.quant_insts {
+        let found = quant_insts.get(name.as_str()).copied().unwrap_or(0);
+        assert_eq!(found, *insts, "instantiations of {name}");
+    }
+
+    let pops = PopAnalysis::new(&parser);
+    assert_eq!(pops.total.insts, expected.lost_insts as u64);
+
+    let proofs = ProofAnalysis::new(&parser);
+    assert_eq!(proofs.refutations.len(), expected.refutations);
+    assert_eq!(proofs.refuted_queries(), expected.refutations);
+    let total = proofs.total();
+    assert_eq!(total.used, expected.used_insts as u64);
+    assert_eq!(total.wasted, expected.wasted_insts as u64);
+    let unknown = expected.insts - expected.used_insts - expected.wasted_insts;
+    assert_eq!(total.unknown, unknown as u64);
+
+    let provenance = parser.provenance().unwrap();
+    for (idx, _) in parser.instantiations().iter_enumerated() {
+        let pos = provenance.inst(idx).unwrap();
+        let line = synth.log[pos.byte_offset..].lines().next().unwrap();
+        assert!(line.starts_with("[instance]"), "{line}");
+    }
+
+    let mut graph = InstGraph::new(&parser).unwrap();
+    let loops = graph.search_matching_loops(&mut parser);
+    assert_eq!(loops, expected.matching_loops);
+}
+
+#[test]
+fn synth_default() {
+    check(&SynthConfig::default().generate());
+}
+
+/// Only the instantiations of a refuted query are used or wasted, those of
+/// the other queries stay unknown.
+#[test]
+fn synth_proofs() {
+    let (mut used, mut wasted, mut unrefuted) = (0, 0, 0);
+    for seed in 0..16 {
+        for queries in [0, 3] {
+            let config = SynthConfig {
+                seed,
+                quantifiers: 2,
+                instantiations: 6,
+                matching_loops: vec![3],
+                theory_solving: 2,
+                queries,
+                proofs: true,
+                ..Default::default()
+            };
+            let synth = config.generate();
+            check(&synth);
+            let expected = &synth.expected;
+            used += expected.used_insts;
+            wasted += expected.wasted_insts;
+            unrefuted += (queries.max(1) > expected.refutations) as usize;
+        }
+    }
+    assert!(used > 0 && wasted > 0 && unrefuted > 0);
+}
+
+#[test]
+fn synth_matching_loops() {
+    for len in 1..=8 {
+        let config = SynthConfig {
+            matching_loops: vec![len],
+            ..Default::default()
+        };
+        check(&config.generate());
+    }
+}
+
+#[test]
+fn synth_alternating_loops() {
+    for len in 1..=8 {
+        let config = SynthConfig {
+            matching_loops: Vec::new(),
+            alternating_loops: vec![len],
+            ..Default::default()
+        };
+        check(&config.generate());
+    }
+    let config = SynthConfig {
+        alternating_loops: vec![4, 6],
+        ..Default::default()
+    };
+    check(&config.generate());
+}
+
+/// The terms matched by a matching loop are generalised to iterated
+/// functions (or chains of functions) rather than `_`.
+#[test]
+fn synth_iterated_terms() {
+    let config = SynthConfig {
+        matching_loops: vec![6],
+        alternating_loops: vec![6],
+        ..Default::default()
+    };
+    let mut parser = parse(&config.generate());
+    let mut graph = InstGraph::new(&parser).unwrap();
+    assert_eq!(graph.search_matching_loops(&mut parser), 2);
+    let ctxt = DisplayCtxt {
+        parser: &parser,
+        term_display: &TermDisplayContext::basic(),
+        config: DisplayConfiguration {
+            display_term_ids: false,
+            display_quantifier_name: false,
+            replace_symbols: SymbolReplacement::None,
+            #[cfg(feature = "display_html")]
+            html: false,
+            enode_char_limit: None,
+            ast_depth_limit: None,
+            numeral_radix: Default::default(),
+            numeral_width: 0,
+        },
+    };
+    let matched: Vec<_> = graph
+        .analysis
+        .matching_loop_explanations
+        .iter()
+        .flat_map(|explanation| &explanation.matched)
+        .map(|term| term.with(&ctxt).to_string())
+        .collect();
+    assert!(matched.iter().any(|t| t.contains("g0^k(")), "{matched:?}");
+    assert!(
+        matched.iter().any(|t| t.contains("(t0∘s0)^k(")),
+        "{matched:?}"
+    );
+    assert!(!matched.iter().any(|t| t.contains('_')), "{matched:?}");
+}
+
+/// Every cost model gives finite costs, at least the instantiation's own
+/// weight, and the ranking by cost follows the active model.
+#[test]
+fn synth_cost_models() {
+    let synth = SynthConfig {
+        matching_loops: vec![5],
+        alternating_loops: vec![4],
+        equalities: 2,
+        proofs: true,
+        queries: 1,
+        ..Default::default()
+    }
+    .generate();
+    let parser = parse(&synth);
+    let proofs = ProofAnalysis::new(&parser);
+    let mut graph = InstGraph::new(&parser).unwrap();
+    for model in CostModel::ALL {
+        graph.set_cost_model(&parser, model);
+        assert_eq!(graph.cost_model, model);
+        for node in graph.raw.graph.node_weights() {
+            assert!(node.cost.is_finite() && node.cost >= 0.0, "{model:?}");
+            let Some(iidx) = node.kind().inst() else {
+                continue;
+            };
+            let weight = match model {
+                CostModel::ENodes => parser[iidx].yields_terms.len() as f64,
+                CostModel::Generation => 1.0 + parser[iidx].z3_generation.unwrap_or(0) as f64,
+                CostModel::Uniform | CostModel::Descendants => 1.0,
+                CostModel::Proof => (proofs.usage[iidx] == InstUsage::UsedInProof) as u8 as f64,
+                _ => 0.0,
+            };
+            assert!(node.cost >= weight - 1e-9, "{model:?}");
+        }
+        if model == CostModel::Descendants {
+            // Each descendant is counted once, however many paths lead to it.
+            let raw = &*graph.raw.graph;
+            for idx in raw.node_indices() {
+                let mut dfs = Dfs::new(raw, idx);
+                let mut descendants = 0;
+                while let Some(next) = dfs.next(raw) {
+                    let node = &raw[next];
+                    descendants += (node.kind().inst().is_some() && !node.disabled()) as u32;
+                }
+                assert_eq!(raw[idx].cost, descendants as f64);
+            }
+        }
+        if model == CostModel::Proof {
+            let used = proofs.total().used as f64;
+            assert!(used > 0.0);
+            let max = graph.raw.graph.node_weights().map(|node| node.cost);
+            assert!(max.fold(0.0, f64::max) <= used);
+        }
+        let costs: Vec<_> = graph
+            .analysis
+            .cost
+            .iter()
+            .map(|&idx| graph.raw[idx].cost)
+            .collect();
+        assert!(costs.windows(2).all(|w| w[0] >= w[1]), "{model:?}");
+    }
+}
+
+/// Each (alternating) matching loop is a strongly connected component of the
+/// quantifier graph, and the transitive dependencies agree whether or not
+/// they are bounded.
+#[test]
+fn synth_quantifier_cycles() {
+    let synth = SynthConfig {
+        matching_loops: vec![5],
+        alternating_loops: vec![4, 3],
+        ..Default::default()
+    }
+    .generate();
+    let parser = parse(&synth);
+    let mut inst_graph = InstGraph::new(&parser).unwrap();
+    inst_graph.initialise_inst_succs_and_preds(&parser);
+    let analysis = QuantifierAnalysis::new(&parser, &inst_graph);
+    let graph = analysis.graph();
+    let name = |qidx: QuantIdx| &parser.strings[*parser[qidx].kind.user_name().unwrap()];
+    let mut cycles: Vec<Vec<_>> = graph
+        .cycles()
+        .into_iter()
+        .map(|scc| scc.into_iter().map(name).collect())
+        .collect();
+    cycles.sort();
+    assert_eq!(
+        cycles,
+        [vec!["alt0a", "alt0b"], vec!["alt1a", "alt1b"], vec!["ml0"]]
+    );
+
+    for node in graph.graph.node_indices() {
+        let costs: f64 = graph
+            .graph
+            .edges_directed(node, petgraph::Direction::Incoming)
+            .map(|edge| edge.weight().costs)
+            .sum();
+        assert!(costs <= graph.graph[node].costs + 1e-9);
+    }
+
+    let unbounded = analysis.calculate_transitive(None);
+    assert_eq!(
+        unbounded.raw,
+        analysis.calculate_transitive(Some(u32::MAX)).raw
+    );
+    for (qidx, deps) in unbounded.iter_enumerated() {
+        let cyclic = name(qidx).starts_with("ml") || name(qidx).starts_with("alt");
+        assert_eq!(deps.contains(&qidx), cyclic, "{}", name(qidx));
+    }
+}
+
+/// With the lowest thresholds every finding is backed by instantiations of
+/// the quantifier (and trigger) it is about, and rules can be disabled.
+#[test]
+fn synth_lints() {
+    let mut config = LintConfig::default();
+    for rule in LintRule::ALL {
+        config[rule].threshold = match rule {
+            LintRule::CommonTrigger => 0.0,
+            LintRule::RepeatedBindings => 2.0,
+            _ => 1.0,
+        };
+    }
+    let mut found = HashMap::new();
+    for seed in 0..32 {
+        let parser = parse(&SynthConfig::random(seed).generate());
+        let mut inst_graph = InstGraph::new(&parser).unwrap();
+        inst_graph.initialise_inst_succs_and_preds(&parser);
+        let lints = LintAnalysis::new(&parser, &inst_graph, &config);
+        assert!(lints.windows(2).all(|w| w[0].level >= w[1].level));
+        for lint in lints.iter() {
+            *found.entry(lint.rule).or_insert(0) += 1;
+            assert!(lint.value >= config[lint.rule].threshold, "{lint:?}");
+            assert!(!lint.evidence.is_empty() || lint.rule == LintRule::CommonTrigger);
+            assert!(lint.evidence.len() <= MAX_EVIDENCE);
+            for &iidx in &lint.evidence {
+                let kind = &parser[parser[iidx].match_].kind;
+                assert_eq!(kind.quant_idx(), Some(lint.quant), "{lint:?}");
+                if lint.trigger.is_some() {
+                    assert_eq!(kind.pattern(), lint.trigger, "{lint:?}");
+                }
+                let children = &inst_graph.raw[iidx].inst_children.nodes;
+                match lint.rule {
+                    LintRule::UnusedYields => assert!(children.is_empty()),
+                    LintRule::MbqiOnly => assert!(kind.is_mbqi()),
+                    _ => (),
+                }
+            }
+        }
+
+        let mut allow_all = config.clone();
+        for rule in LintRule::ALL {
+            allow_all[rule].level = LintLevel::Allow;
+        }
+        assert!(LintAnalysis::new(&parser, &inst_graph, &allow_all).is_empty());
+    }
+    for rule in [
+        LintRule::EqualityChains,
+        LintRule::UnusedYields,
+        LintRule::CommonTrigger,
+        LintRule::RepeatedBindings,
+    ] {
+        assert!(found.contains_key(&rule), "{rule:?} never reported");
+    }
+}
+
+#[test]
+fn synth_random() {
+    for seed in common::seeds(256) {
+        let synth = SynthConfig::random(seed).generate();
+        let result = std::panic::catch_unwind(|| check(&synth));
+        if let Err(err) = result {
+            eprintln!(
+                "Failed for seed {seed} (rerun with SLP_SYNTH_SEED={seed}): {:?}",
+                synth.config
+            );
+            std::panic::resume_unwind(err);
+        }
+    }
+}
@@ -0,0 +1,201 @@
+//! Decodes the values of `[attach-meaning]`s.
+
+use std::{cmp::Ordering, fmt};
+
+use smt_log_parser::items::{Radix, Value};
+
+mod common;
+
+fn int(value: i64) -> Value {
+    Value::Int(num_bigint::BigInt::from(value).into())
+}
+
+fn rational(numer: i64, denom: i64) -> Value {
+    Value::Rational {
+        numer: num_bigint::BigInt::from(numer).into(),
+        denom: num_bigint::BigInt::from(denom).into(),
+    }
+}
+
+fn bit_vec(value: i64, width: u32) -> Value {
+    Value::BitVec {
+        value: num_bigint::BigInt::from(value).into(),
+        width,
+    }
+}
+
+#[test]
+fn parse_arith() {
+    let cases = [
+        ("3", Some(int(3))),
+        ("-3", Some(int(-3))),
+        ("(- 3)", Some(int(-3))),
+        ("(- (- 3))", Some(int(3))),
+        ("(/ 1 2)", Some(rational(1, 2))),
+        ("(/ (- 1) 2)", Some(rational(-1, 2))),
+        ("(/ 1 (- 2))", Some(rational(-1, 2))),
+        ("(- (/ 1 2))", Some(rational(-1, 2))),
+        ("(/ 4 2)", Some(int(2))),
+        ("(/ 6 4)", Some(rational(3, 2))),
+        ("1/3", Some(rational(1, 3))),
+        ("1.5", Some(rational(3, 2))),
+        ("0.50", Some(rational(1, 2))),
+        ("2.0", Some(int(2))),
+        ("(- 1.25)", Some(rational(-5, 4))),
+        // Division by zero is kept as logged.
+        ("(/ 1 0)", Some(rational(1, 0))),
+        ("x", None),
+        ("(/ 1)", None),
+        ("1.x", None),
+    ];
+    for (value, expected) in cases {
+        assert_eq!(Value::parse("arith", value), expected, "{value}");
+    }
+    assert_eq!(Value::parse("int", "(- 7)"), Some(int(-7)));
+    assert_eq!(Value::parse("real", "(/ 7 2)"), Some(rational(7, 2)));
+}
+
+#[test]
+fn parse_bv() {
+    let cases = [
+        ("#x0f", Some(bit_vec(15, 8))),
+        ("#x0000000000000001", Some(bit_vec(1, 64))),
+        ("#b101", Some(bit_vec(5, 3))),
+        ("#b0", Some(bit_vec(0, 1))),
+        ("(_ bv5 8)", Some(bit_vec(5, 8))),
+        ("(_ bv255 8)", Some(bit_vec(255, 8))),
+        ("5bv8", Some(bit_vec(5, 8))),
+        // The value does not fit in the width.
+        ("(_ bv256 8)", None),
+        ("(_ bv5)", None),
+        ("#xzz", None),
+        ("#b102", None),
+    ];
+    for (value, expected) in cases {
+        assert_eq!(Value::parse("bv", value), expected, "{value}");
+    }
+}
+
+#[test]
+fn parse_other() {
+    let cases = [
+        ("basic", "true", Some(Value::Bool(true))),
+        ("basic", "false", Some(Value::Bool(false))),
+        ("seq", r#""abc""#, Some(Value::String("abc".to_string()))),
+        (
+            "seq",
+            r#""a""b""#,
+            Some(Value::String(r#"a"b"#.to_string())),
+        ),
+        // Bit-vector literals are understood in any theory.
+        ("basic", "#x1", Some(bit_vec(1, 4))),
+        ("datatype", "3", None),
+    ];
+    for (theory, value, expected) in cases {
+        assert_eq!(Value::parse(theory, value), expected, "{theory} {value}");
+    }
+}
+
+#[test]
+fn parse_comparison() {
+    let cases = [
+        ("> 1000", Some((Ordering::Greater, int(1000)))),
+        ("<(- 3)", Some((Ordering::Less, int(-3)))),
+        ("= 1/2", Some((Ordering::Equal, rational(1, 2)))),
+        ("=#x0f", Some((Ordering::Equal, bit_vec(15, 8)))),
+        ("< (_ bv5 8)", Some((Ordering::Less, bit_vec(5, 8)))),
+        ("1000", None),
+        ("> x", None),
+        // Only numerals can be compared.
+        ("= true", None),
+    ];
+    for (comparison, expected) in cases {
+        assert_eq!(
+            Value::parse_comparison(comparison),
+            expected,
+            "{comparison}"
+        );
+    }
+}
+
+struct Padded<'a>(&'a Value, Radix, u32);
+
+impl fmt::Display for Padded<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt_radix(f, self.1, self.2)
+    }
+}
+
+#[test]
+fn fmt_width() {
+    let cases = [
+        (int(42), Radix::Decimal, 0, "42"),
+        (int(42), Radix::Decimal, 4, "0042"),
+        (int(-42), Radix::Decimal, 4, "-0042"),
+        (int(255), Radix::Hexadecimal, 4, "0x00ff"),
+        (rational(1, 2), Radix::Binary, 2, "0b01/0b10"),
+        (bit_vec(15, 8), Radix::Hexadecimal, 0, "#x0f"),
+        (bit_vec(15, 8), Radix::Hexadecimal, 4, "#x000f"),
+        (bit_vec(5, 4), Radix::Binary, 2, "#b0101"),
+        (bit_vec(5, 8), Radix::Decimal, 3, "(_ bv005 8)"),
+    ];
+    for (value, radix, width, expected) in cases {
+        assert_eq!(Padded(&value, radix, width).to_string(), expected);
+    }
+}
+
+/// `q` is instantiated with `5`, `2000` and `(- 3)`.
+const LOG: &str = "\
+[tool-version] Z3 4.12.2
+[mk-app] #1 true
+[mk-app] #2 false
+[mk-var] #3 0
+[mk-app] #4 p #3
+[mk-app] #5 pattern #4
+[mk-quant] #6 q 1 #5 #4
+[attach-var-names] #6 (|x| ; |Int|)
+[mk-app] #7 5
+[attach-meaning] #7 arith 5
+[attach-enode] #7 0
+[mk-app] #8 p #7
+[attach-enode] #8 0
+[mk-app] #9 2000
+[attach-meaning] #9 arith 2000
+[attach-enode] #9 0
+[mk-app] #10 p #9
+[attach-enode] #10 0
+[mk-app] #11 -3
+[attach-meaning] #11 arith (- 3)
+[attach-enode] #11 0
+[mk-app] #12 p #11
+[attach-enode] #12 0
+[new-match] 0x1 #6 #5 #7 ; #8
+[instance] 0x1 #8 ; 1
+[end-of-instance]
+[new-match] 0x2 #6 #5 #9 ; #10
+[instance] 0x2 #10 ; 1
+[end-of-instance]
+[new-match] 0x3 #6 #5 #11 ; #12
+[instance] 0x3 #12 ; 1
+[end-of-instance]
+[eof]
+";
+
+#[test]
+fn binds_value() {
+    let parser = common::parse(LOG);
+    let binding = |comparison: &str| -> Vec<_> {
+        let (ordering, value) = Value::parse_comparison(comparison).unwrap();
+        parser
+            .instantiations()
+            .keys()
+            .filter(|&iidx| parser.binds_value(iidx, ordering, &value))
+            .map(usize::from)
+            .collect()
+    };
+    assert_eq!(binding("> 1000"), [1]);
+    assert_eq!(binding("< 0"), [2]);
+    assert_eq!(binding("= 5"), [0]);
+    assert_eq!(binding("> (- 3)"), [0, 1]);
+    assert!(binding("= 7/2").is_empty());
+}